@@ -1,6 +1,6 @@
 use crate::TARGET_SAMPLE_RATE;
 use crate::cpal;
-use crate::cpal::traits::{DeviceTrait, HostTrait};
+use crate::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use crate::cpal::{Sample, SampleFormat, SupportedStreamConfig, SupportedStreamConfigRange};
 use crate::error::AudioError;
 use anyhow::Context;
@@ -12,6 +12,11 @@ use std::cell::RefCell;
 use std::fmt::{Debug, Display, Formatter};
 use tracing::instrument;
 
+mod virtual_device;
+
+pub use virtual_device::OutputBuffer;
+use virtual_device::{VirtualDevice, VirtualStream};
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum DeviceType {
     Input,
@@ -29,9 +34,32 @@ impl Display for DeviceType {
 
 pub struct StreamDevice {
     pub(crate) device_type: DeviceType,
-    pub(crate) device: cpal::Device,
+    pub(crate) backend: DeviceBackend,
     pub(crate) config: cpal::StreamConfig,
-    pub(crate) sample_format: SampleFormat,
+}
+
+pub(crate) enum DeviceBackend {
+    Cpal {
+        device: cpal::Device,
+        sample_format: SampleFormat,
+    },
+    Virtual(VirtualDevice),
+}
+
+/// Running stream of a [`StreamDevice`], stopped once dropped.
+pub(crate) enum DeviceStream {
+    Cpal(cpal::Stream),
+    Virtual(VirtualStream),
+}
+
+impl DeviceStream {
+    pub(crate) fn play(&self) -> Result<(), AudioError> {
+        match self {
+            DeviceStream::Cpal(stream) => stream.play()?,
+            DeviceStream::Virtual(stream) => stream.play(),
+        }
+        Ok(())
+    }
 }
 
 impl StreamDevice {
@@ -42,7 +70,16 @@ impl StreamDevice {
 
     #[inline]
     pub fn name(&self) -> String {
-        self.device.name().unwrap_or_default()
+        match &self.backend {
+            DeviceBackend::Cpal { device, .. } => device.name().unwrap_or_default(),
+            DeviceBackend::Virtual(device) => device.name().to_string(),
+        }
+    }
+
+    /// Returns whether this device is a virtual device not backed by any sound card.
+    #[inline]
+    pub fn is_virtual(&self) -> bool {
+        matches!(self.backend, DeviceBackend::Virtual(_))
     }
 
     #[inline]
@@ -58,44 +95,58 @@ impl StreamDevice {
     #[instrument(level = "trace", skip(data_callback, error_callback), err)]
     pub(crate) fn build_input_stream<D, E>(
         &self,
-        data_callback: D,
+        mut data_callback: D,
         error_callback: E,
-    ) -> Result<cpal::Stream, cpal::BuildStreamError>
+    ) -> Result<DeviceStream, AudioError>
     where
-        D: FnMut(&[f32], &cpal::InputCallbackInfo) + Send + 'static,
+        D: FnMut(&[f32]) + Send + 'static,
         E: FnMut(cpal::StreamError) + Send + 'static,
     {
         debug_assert!(matches!(self.device_type, DeviceType::Input));
 
-        match self.sample_format {
-            SampleFormat::F32 => self.device.build_input_stream::<f32, _, _>(
+        let (device, sample_format) = match &self.backend {
+            DeviceBackend::Cpal {
+                device,
+                sample_format,
+            } => (device, *sample_format),
+            DeviceBackend::Virtual(device) => {
+                return device
+                    .build_input_stream(&self.config, data_callback)
+                    .map(DeviceStream::Virtual);
+            }
+        };
+
+        let stream = match sample_format {
+            SampleFormat::F32 => device.build_input_stream::<f32, _, _>(
                 &self.config,
-                data_callback,
+                move |input: &[f32], _| data_callback(input),
                 error_callback,
                 None,
             ),
             SampleFormat::I16 => {
-                self.build_f32_input_stream::<i16, _, _>(data_callback, error_callback)
+                self.build_f32_input_stream::<i16, _, _>(device, data_callback, error_callback)
             }
             SampleFormat::U16 => {
-                self.build_f32_input_stream::<u16, _, _>(data_callback, error_callback)
+                self.build_f32_input_stream::<u16, _, _>(device, data_callback, error_callback)
             }
             other => Err(cpal::BuildStreamError::BackendSpecific {
                 err: cpal::BackendSpecificError {
                     description: format!("Unsupported input sample format: {other:?}"),
                 },
             }),
-        }
+        }?;
+        Ok(DeviceStream::Cpal(stream))
     }
 
     fn build_f32_input_stream<T, D, E>(
         &self,
+        device: &cpal::Device,
         mut data_callback: D,
         error_callback: E,
     ) -> Result<cpal::Stream, cpal::BuildStreamError>
     where
         T: Sample<Float = f32> + cpal::SizedSample + 'static,
-        D: FnMut(&[f32], &cpal::InputCallbackInfo) + Send + 'static,
+        D: FnMut(&[f32]) + Send + 'static,
         E: FnMut(cpal::StreamError) + Send + 'static,
     {
         let buf: RefCell<Vec<f32>> = RefCell::new(Vec::new());
@@ -103,9 +154,9 @@ impl StreamDevice {
             buf.borrow_mut().reserve(n as usize);
         }
 
-        self.device.build_input_stream::<T, _, _>(
+        device.build_input_stream::<T, _, _>(
             &self.config,
-            move |input: &[T], info| {
+            move |input: &[T], _| {
                 let mut b = buf.borrow_mut();
                 if b.len() != input.len() {
                     b.resize(input.len(), 0.0f32);
@@ -113,7 +164,7 @@ impl StreamDevice {
                 for (dst, &src) in b.iter_mut().zip(input.iter()) {
                     *dst = src.to_float_sample();
                 }
-                data_callback(&b, info);
+                data_callback(&b);
             },
             error_callback,
             None,
@@ -123,44 +174,58 @@ impl StreamDevice {
     #[instrument(level = "trace", skip(data_callback, error_callback), err)]
     pub(crate) fn build_output_stream<D, E>(
         &self,
-        data_callback: D,
+        mut data_callback: D,
         error_callback: E,
-    ) -> Result<cpal::Stream, cpal::BuildStreamError>
+    ) -> Result<DeviceStream, AudioError>
     where
-        D: FnMut(&mut [f32], &cpal::OutputCallbackInfo) + Send + 'static,
+        D: FnMut(&mut [f32]) + Send + 'static,
         E: FnMut(cpal::StreamError) + Send + 'static,
     {
         debug_assert!(matches!(self.device_type, DeviceType::Output));
 
-        match self.sample_format {
-            SampleFormat::F32 => self.device.build_output_stream::<f32, _, _>(
+        let (device, sample_format) = match &self.backend {
+            DeviceBackend::Cpal {
+                device,
+                sample_format,
+            } => (device, *sample_format),
+            DeviceBackend::Virtual(device) => {
+                return device
+                    .build_output_stream(&self.config, data_callback, error_callback)
+                    .map(DeviceStream::Virtual);
+            }
+        };
+
+        let stream = match sample_format {
+            SampleFormat::F32 => device.build_output_stream::<f32, _, _>(
                 &self.config,
-                data_callback,
+                move |output: &mut [f32], _| data_callback(output),
                 error_callback,
                 None,
             ),
             SampleFormat::I16 => {
-                self.build_f32_output_stream::<i16, _, _>(data_callback, error_callback)
+                self.build_f32_output_stream::<i16, _, _>(device, data_callback, error_callback)
             }
             SampleFormat::U16 => {
-                self.build_f32_output_stream::<u16, _, _>(data_callback, error_callback)
+                self.build_f32_output_stream::<u16, _, _>(device, data_callback, error_callback)
             }
             other => Err(cpal::BuildStreamError::BackendSpecific {
                 err: cpal::BackendSpecificError {
                     description: format!("Unsupported output sample format: {other:?}"),
                 },
             }),
-        }
+        }?;
+        Ok(DeviceStream::Cpal(stream))
     }
 
     fn build_f32_output_stream<T, D, E>(
         &self,
+        device: &cpal::Device,
         mut data_callback: D,
         error_callback: E,
     ) -> Result<cpal::Stream, cpal::BuildStreamError>
     where
         T: cpal::SizedSample + cpal::FromSample<f32> + 'static,
-        D: FnMut(&mut [f32], &cpal::OutputCallbackInfo) + Send + 'static,
+        D: FnMut(&mut [f32]) + Send + 'static,
         E: FnMut(cpal::StreamError) + Send + 'static,
     {
         let buf: RefCell<Vec<f32>> = RefCell::new(Vec::new());
@@ -168,14 +233,14 @@ impl StreamDevice {
             buf.borrow_mut().reserve(n as usize);
         }
 
-        self.device.build_output_stream::<T, _, _>(
+        device.build_output_stream::<T, _, _>(
             &self.config,
-            move |output: &mut [T], info| {
+            move |output: &mut [T], _| {
                 let mut b = buf.borrow_mut();
                 if b.len() != output.len() {
                     b.resize(output.len(), 0.0f32);
                 }
-                data_callback(&mut b, info);
+                data_callback(&mut b);
                 for (dst, &src) in output.iter_mut().zip(b.iter()) {
                     *dst = src.to_sample::<T>();
                 }
@@ -223,14 +288,23 @@ impl StreamDevice {
 
 impl Debug for StreamDevice {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "StreamDevice {{ device_type: {}, device: {}, config: {:?}, sample_format: {:?} }}",
-            self.device_type,
-            self.device.name().unwrap_or_default(),
-            self.config,
-            self.sample_format
-        )
+        match &self.backend {
+            DeviceBackend::Cpal { sample_format, .. } => write!(
+                f,
+                "StreamDevice {{ device_type: {}, device: {}, config: {:?}, sample_format: {:?} }}",
+                self.device_type,
+                self.name(),
+                self.config,
+                sample_format
+            ),
+            DeviceBackend::Virtual(_) => write!(
+                f,
+                "StreamDevice {{ device_type: {}, device: {} (virtual), config: {:?} }}",
+                self.device_type,
+                self.name(),
+                self.config
+            ),
+        }
     }
 }
 
@@ -250,9 +324,11 @@ impl DeviceSelector {
         Ok((
             StreamDevice {
                 device_type,
-                device,
+                backend: DeviceBackend::Cpal {
+                    device,
+                    sample_format: stream_config.sample_format(),
+                },
                 config: stream_config.config(),
-                sample_format: stream_config.sample_format(),
             },
             is_fallback,
         ))
@@ -0,0 +1,367 @@
+use crate::TARGET_SAMPLE_RATE;
+use crate::cpal;
+use crate::device::{DeviceBackend, DeviceType, StreamDevice};
+use crate::error::AudioError;
+use crate::sources::AudioSource;
+use anyhow::Context;
+use parking_lot::Mutex;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Interval in which virtual devices invoke their data callback, emulating the buffer period of
+/// a sound card.
+const VIRTUAL_DEVICE_PERIOD: Duration = Duration::from_millis(10);
+
+type WavFileWriter = hound::WavWriter<BufWriter<File>>;
+
+/// Shared buffer collecting the mixed output of a virtual playback device created via
+/// [`StreamDevice::buffer_output`].
+///
+/// Samples are stored interleaved, using the channel count the device was created with.
+#[derive(Debug, Clone, Default)]
+pub struct OutputBuffer(Arc<Mutex<Vec<f32>>>);
+
+impl OutputBuffer {
+    /// Returns the number of samples written so far.
+    pub fn len(&self) -> usize {
+        self.0.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().is_empty()
+    }
+
+    /// Returns a copy of all samples written so far.
+    pub fn samples(&self) -> Vec<f32> {
+        self.0.lock().clone()
+    }
+
+    /// Removes and returns all samples written so far.
+    pub fn take(&self) -> Vec<f32> {
+        std::mem::take(&mut *self.0.lock())
+    }
+}
+
+/// Device without any sound card attached, reading its input from or writing its output to
+/// memory or WAV files, driven by a timer thread instead of cpal callbacks.
+pub(crate) struct VirtualDevice {
+    name: String,
+    kind: VirtualDeviceKind,
+}
+
+enum VirtualDeviceKind {
+    WavInput { samples: Arc<[f32]>, looped: bool },
+    GeneratorInput(Mutex<Option<Box<dyn AudioSource>>>),
+    WavOutput(Mutex<Option<WavFileWriter>>),
+    BufferOutput(OutputBuffer),
+}
+
+impl StreamDevice {
+    /// Creates a virtual capture device reading its samples from the WAV file at `path`.
+    ///
+    /// The file's sample rate and channel count are used as device config, so captured audio is
+    /// resampled and downmixed just like for a physical device. Once all samples have been read,
+    /// the device either starts over (if `looped` is set) or continues with silence.
+    pub fn wav_input(path: impl AsRef<Path>, looped: bool) -> Result<Self, AudioError> {
+        let path = path.as_ref();
+        let mut reader = hound::WavReader::open(path)
+            .with_context(|| format!("Failed to open WAV file {}", path.display()))?;
+        let spec = reader.spec();
+
+        let samples = match spec.sample_format {
+            hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>(),
+            hound::SampleFormat::Int => {
+                let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .samples::<i32>()
+                    .map(|sample| sample.map(|s| s as f32 / scale))
+                    .collect::<Result<Vec<_>, _>>()
+            }
+        }
+        .with_context(|| format!("Failed to read samples from WAV file {}", path.display()))?;
+
+        tracing::debug!(
+            path = %path.display(),
+            sample_rate = spec.sample_rate,
+            channels = spec.channels,
+            samples = samples.len(),
+            "Loaded WAV file for virtual input device"
+        );
+
+        Ok(Self::virtual_device(
+            DeviceType::Input,
+            format!("wav:{}", path.display()),
+            VirtualDeviceKind::WavInput {
+                samples: samples.into(),
+                looped,
+            },
+            spec.sample_rate,
+            spec.channels,
+        ))
+    }
+
+    /// Creates a mono virtual capture device producing its samples from the given [`AudioSource`],
+    /// e.g. a [`crate::sources::waveform::WaveformSource`].
+    ///
+    /// The source is started once the device is used for a stream and must generate samples at
+    /// `sample_rate`.
+    pub fn generator_input(source: Box<dyn AudioSource>, sample_rate: u32) -> Self {
+        Self::virtual_device(
+            DeviceType::Input,
+            "generator".to_string(),
+            VirtualDeviceKind::GeneratorInput(Mutex::new(Some(source))),
+            sample_rate,
+            1,
+        )
+    }
+
+    /// Creates a virtual playback device writing the mixed output to a 32-bit float WAV file at
+    /// `path`, sampled at [`TARGET_SAMPLE_RATE`].
+    ///
+    /// The file is finalized once the stream using the device is dropped.
+    pub fn wav_output(path: impl AsRef<Path>, channels: u16) -> Result<Self, AudioError> {
+        let path = path.as_ref();
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate: TARGET_SAMPLE_RATE,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let writer = hound::WavWriter::create(path, spec)
+            .with_context(|| format!("Failed to create WAV file {}", path.display()))?;
+
+        Ok(Self::virtual_device(
+            DeviceType::Output,
+            format!("wav:{}", path.display()),
+            VirtualDeviceKind::WavOutput(Mutex::new(Some(writer))),
+            TARGET_SAMPLE_RATE,
+            channels,
+        ))
+    }
+
+    /// Creates a virtual playback device collecting the mixed output in memory, sampled at
+    /// [`TARGET_SAMPLE_RATE`].
+    ///
+    /// Returns the device alongside a handle to the [`OutputBuffer`] receiving its samples.
+    pub fn buffer_output(channels: u16) -> (Self, OutputBuffer) {
+        let buffer = OutputBuffer::default();
+        let device = Self::virtual_device(
+            DeviceType::Output,
+            "buffer".to_string(),
+            VirtualDeviceKind::BufferOutput(buffer.clone()),
+            TARGET_SAMPLE_RATE,
+            channels,
+        );
+        (device, buffer)
+    }
+
+    fn virtual_device(
+        device_type: DeviceType,
+        name: String,
+        kind: VirtualDeviceKind,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Self {
+        let period_frames =
+            (sample_rate as u128 * VIRTUAL_DEVICE_PERIOD.as_millis() / 1000).max(1) as u32;
+
+        Self {
+            device_type,
+            backend: DeviceBackend::Virtual(VirtualDevice { name, kind }),
+            config: cpal::StreamConfig {
+                channels,
+                sample_rate: cpal::SampleRate(sample_rate),
+                buffer_size: cpal::BufferSize::Fixed(period_frames),
+            },
+        }
+    }
+}
+
+impl VirtualDevice {
+    #[inline]
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn build_input_stream<D>(
+        &self,
+        config: &cpal::StreamConfig,
+        mut data_callback: D,
+    ) -> Result<VirtualStream, AudioError>
+    where
+        D: FnMut(&[f32]) + Send + 'static,
+    {
+        let mut buf = vec![0.0f32; Self::period_samples(config)];
+
+        match &self.kind {
+            VirtualDeviceKind::WavInput { samples, looped } => {
+                let samples = samples.clone();
+                let looped = *looped;
+                let mut pos = 0usize;
+
+                VirtualStream::spawn(move || {
+                    for sample in buf.iter_mut() {
+                        if pos == samples.len() && looped {
+                            pos = 0;
+                        }
+                        *sample = samples.get(pos).copied().unwrap_or(0.0);
+                        pos = (pos + 1).min(samples.len());
+                    }
+                    data_callback(&buf);
+                })
+            }
+            VirtualDeviceKind::GeneratorInput(source) => {
+                let mut source = source
+                    .lock()
+                    .take()
+                    .context("Virtual generator input device is already in use")?;
+                source.start();
+
+                VirtualStream::spawn(move || {
+                    buf.fill(0.0);
+                    source.mix_into(&mut buf);
+                    data_callback(&buf);
+                })
+            }
+            _ => Err(AudioError::Other(anyhow::anyhow!(
+                "Virtual device {} does not support capturing",
+                self.name
+            ))),
+        }
+    }
+
+    pub(crate) fn build_output_stream<D, E>(
+        &self,
+        config: &cpal::StreamConfig,
+        mut data_callback: D,
+        mut error_callback: E,
+    ) -> Result<VirtualStream, AudioError>
+    where
+        D: FnMut(&mut [f32]) + Send + 'static,
+        E: FnMut(cpal::StreamError) + Send + 'static,
+    {
+        let mut buf = vec![0.0f32; Self::period_samples(config)];
+
+        match &self.kind {
+            VirtualDeviceKind::WavOutput(writer) => {
+                let mut writer = writer
+                    .lock()
+                    .take()
+                    .context("Virtual WAV output device is already in use")?;
+                let mut failed = false;
+
+                VirtualStream::spawn(move || {
+                    data_callback(&mut buf);
+                    if failed {
+                        return;
+                    }
+                    for &sample in buf.iter() {
+                        if let Err(err) = writer.write_sample(sample) {
+                            failed = true;
+                            error_callback(cpal::StreamError::BackendSpecific {
+                                err: cpal::BackendSpecificError {
+                                    description: format!("Failed to write WAV sample: {err}"),
+                                },
+                            });
+                            break;
+                        }
+                    }
+                })
+            }
+            VirtualDeviceKind::BufferOutput(buffer) => {
+                let buffer = buffer.clone();
+
+                VirtualStream::spawn(move || {
+                    data_callback(&mut buf);
+                    buffer.0.lock().extend_from_slice(&buf);
+                })
+            }
+            _ => Err(AudioError::Other(anyhow::anyhow!(
+                "Virtual device {} does not support playback",
+                self.name
+            ))),
+        }
+    }
+
+    fn period_samples(config: &cpal::StreamConfig) -> usize {
+        let frames = match config.buffer_size {
+            cpal::BufferSize::Fixed(n) => n as usize,
+            cpal::BufferSize::Default => {
+                (config.sample_rate.0 as u128 * VIRTUAL_DEVICE_PERIOD.as_millis() / 1000) as usize
+            }
+        };
+        frames * config.channels as usize
+    }
+}
+
+/// Timer thread invoking a virtual device's data callback once per [`VIRTUAL_DEVICE_PERIOD`].
+///
+/// Just like a cpal stream, the callback is only invoked after [`VirtualStream::play`] has been
+/// called. Dropping the stream stops and joins the thread, releasing everything the callback owns.
+pub(crate) struct VirtualStream {
+    playing: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl VirtualStream {
+    fn spawn<F>(mut tick: F) -> Result<Self, AudioError>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let playing = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let playing = playing.clone();
+            let stopped = stopped.clone();
+            std::thread::Builder::new()
+                .name("vacs-virtual-device".to_string())
+                .spawn(move || {
+                    let mut next = Instant::now();
+                    while !stopped.load(Ordering::Relaxed) {
+                        if playing.load(Ordering::Relaxed) {
+                            tick();
+                        }
+
+                        next += VIRTUAL_DEVICE_PERIOD;
+                        let now = Instant::now();
+                        if next > now {
+                            std::thread::sleep(next - now);
+                        } else {
+                            // fell behind, skip missed periods instead of bursting to catch up
+                            next = now;
+                        }
+                    }
+                })
+                .context("Failed to spawn virtual device thread")?
+        };
+
+        Ok(Self {
+            playing,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn play(&self) {
+        self.playing.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for VirtualStream {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take()
+            && thread.join().is_err()
+        {
+            tracing::warn!("Virtual device thread panicked");
+        }
+    }
+}
@@ -1,6 +1,4 @@
-use crate::cpal;
-use crate::cpal::traits::StreamTrait;
-use crate::device::{DeviceType, StreamDevice};
+use crate::device::{DeviceStream, DeviceType, StreamDevice};
 use crate::dsp::{MicProcessor, downmix_interleaved_to_mono};
 use crate::error::AudioError;
 use crate::{EncodedAudioFrame, FRAME_SIZE, TARGET_SAMPLE_RATE};
@@ -32,7 +30,7 @@ const INPUT_VOLUME_OPS_PER_DATA_CALLBACK: usize = 16;
 type InputVolumeOp = Box<dyn Fn(&mut f32) + Send>;
 
 pub struct CaptureStream {
-    _stream: cpal::Stream,
+    _stream: DeviceStream,
     volume_ops: parking_lot::Mutex<ringbuf::HeapProd<InputVolumeOp>>,
     muted: Arc<AtomicBool>,
     cancel: Option<CancellationToken>,
@@ -63,7 +61,7 @@ impl CaptureStream {
         let mut mono_buf: Vec<f32> = Vec::with_capacity(MIN_INPUT_BUFFER_SIZE);
 
         let stream = device.build_input_stream(
-            move |input: &[f32], _| {
+            move |input: &[f32]| {
                 // downmix to mono if necessary
                 let mono: &[f32] = if device.config.channels > 1 {
                     downmix_interleaved_to_mono(
@@ -251,7 +249,7 @@ impl CaptureStream {
             HeapRb::<InputVolumeOp>::new(INPUT_VOLUME_OPS_CAPACITY).split();
 
         let stream = device.build_input_stream(
-            move |input: &[f32], _| {
+            move |input: &[f32]| {
                 for _ in 0..INPUT_VOLUME_OPS_PER_DATA_CALLBACK {
                     if let Some(op) = ops_cons.try_pop() {
                         op(&mut volume);
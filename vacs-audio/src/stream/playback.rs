@@ -1,6 +1,4 @@
-use crate::cpal;
-use crate::cpal::traits::StreamTrait;
-use crate::device::{DeviceType, StreamDevice};
+use crate::device::{DeviceStream, DeviceType, StreamDevice};
 use crate::error::AudioError;
 use crate::mixer::Mixer;
 use crate::sources::{AudioSource, AudioSourceId};
@@ -21,7 +19,7 @@ const MIXER_OPS_CAPACITY: usize = 256;
 const MIXER_OPS_PER_DATA_CALLBACK: usize = 32;
 
 pub struct PlaybackStream {
-    _stream: cpal::Stream,
+    _stream: DeviceStream,
     mixer_ops: Mutex<ringbuf::HeapProd<MixerOp>>,
     next_audio_source_id: atomic::AtomicUsize,
     deafened: Arc<AtomicBool>,
@@ -43,7 +41,7 @@ impl PlaybackStream {
         let deafened_clone = deafened.clone();
 
         let stream = device.build_output_stream(
-            move |output, _| {
+            move |output: &mut [f32]| {
                 for _ in 0..MIXER_OPS_PER_DATA_CALLBACK {
                     if let Some(op) = ops_cons.try_pop() {
                         op(&mut mixer);
@@ -0,0 +1,143 @@
+use std::time::Duration;
+use tokio::sync::mpsc;
+use vacs_audio::TARGET_SAMPLE_RATE;
+use vacs_audio::device::{DeviceType, StreamDevice};
+use vacs_audio::sources::waveform::{Waveform, WaveformSource, WaveformTone};
+use vacs_audio::stream::capture::CaptureStream;
+use vacs_audio::stream::playback::PlaybackStream;
+
+fn sine_source(sample_rate: u32, channels: usize) -> WaveformSource {
+    WaveformSource::single(
+        WaveformTone::new(440.0, Waveform::Sine, 0.5),
+        Duration::from_secs(10),
+        None,
+        Duration::from_millis(0),
+        sample_rate as f32,
+        channels,
+        1.0,
+    )
+}
+
+fn write_sine_wav(path: &std::path::Path, sample_rate: u32, duration: Duration) {
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    let frames = (duration.as_secs_f32() * sample_rate as f32) as usize;
+    for n in 0..frames {
+        let t = n as f32 / sample_rate as f32;
+        let sample =
+            ((2.0 * std::f32::consts::PI * 440.0 * t).sin() * 0.5 * i16::MAX as f32) as i16;
+        writer.write_sample(sample).unwrap();
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_buffer_output_receives_mixed_audio() {
+    let (device, buffer) = StreamDevice::buffer_output(2);
+    assert!(device.is_virtual());
+    assert!(matches!(device.device_type(), DeviceType::Output));
+    assert_eq!(device.sample_rate(), TARGET_SAMPLE_RATE);
+
+    let (error_tx, _error_rx) = mpsc::channel(1);
+    let playback = PlaybackStream::start(device, error_tx).unwrap();
+    assert_eq!(playback.channels(), 2);
+
+    let id = playback.add_audio_source(Box::new(sine_source(TARGET_SAMPLE_RATE, 2)));
+    playback.start_audio_source(id);
+
+    std::thread::sleep(Duration::from_millis(200));
+    drop(playback);
+
+    let samples = buffer.take();
+    assert!(!samples.is_empty());
+    assert_eq!(samples.len() % 2, 0);
+    assert!(samples.iter().any(|&s| s.abs() > 0.1));
+    assert!(samples.iter().all(|&s| (-1.0..=1.0).contains(&s)));
+}
+
+#[test]
+fn test_wav_output_is_finalized_on_drop() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("output.wav");
+
+    let device = StreamDevice::wav_output(&path, 1).unwrap();
+    let (error_tx, _error_rx) = mpsc::channel(1);
+    let playback = PlaybackStream::start(device, error_tx).unwrap();
+
+    let id = playback.add_audio_source(Box::new(sine_source(TARGET_SAMPLE_RATE, 1)));
+    playback.start_audio_source(id);
+
+    std::thread::sleep(Duration::from_millis(200));
+    drop(playback);
+
+    let mut reader = hound::WavReader::open(&path).unwrap();
+    assert_eq!(reader.spec().sample_rate, TARGET_SAMPLE_RATE);
+    assert_eq!(reader.spec().channels, 1);
+    let samples = reader
+        .samples::<f32>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert!(!samples.is_empty());
+    assert!(samples.iter().any(|&s| s.abs() > 0.1));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_generator_input_produces_encoded_frames() {
+    let device = StreamDevice::generator_input(
+        Box::new(sine_source(TARGET_SAMPLE_RATE, 1)),
+        TARGET_SAMPLE_RATE,
+    );
+    assert!(device.is_virtual());
+    assert!(matches!(device.device_type(), DeviceType::Input));
+    assert_eq!(device.channels(), 1);
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let (error_tx, _error_rx) = mpsc::channel(1);
+    let capture = CaptureStream::start(device, tx, 1.0, 1.0, error_tx, false).unwrap();
+
+    for _ in 0..5 {
+        let frame = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("Timed out waiting for encoded frame")
+            .expect("Capture stream closed");
+        assert!(!frame.is_empty());
+    }
+
+    capture.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_wav_input_is_resampled_and_downmixed() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("input.wav");
+    write_sine_wav(&path, 44_100, Duration::from_millis(500));
+
+    let device = StreamDevice::wav_input(&path, true).unwrap();
+    assert_eq!(device.sample_rate(), 44_100);
+    assert_eq!(device.channels(), 2);
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let (error_tx, _error_rx) = mpsc::channel(1);
+    let capture = CaptureStream::start(device, tx, 1.0, 1.0, error_tx, false).unwrap();
+
+    for _ in 0..5 {
+        let frame = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("Timed out waiting for encoded frame")
+            .expect("Capture stream closed");
+        assert!(!frame.is_empty());
+    }
+
+    capture.stop().await;
+}
+
+#[test]
+fn test_wav_input_missing_file() {
+    assert!(StreamDevice::wav_input("does-not-exist.wav", false).is_err());
+}
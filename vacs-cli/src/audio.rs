@@ -0,0 +1,196 @@
+use anyhow::{Context, bail};
+use bytes::Bytes;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::instrument;
+use vacs_audio::{EncodedAudioFrame, FRAME_DURATION_MS, TARGET_SAMPLE_RATE};
+
+const FRAME_SIZE: usize = TARGET_SAMPLE_RATE as usize * FRAME_DURATION_MS as usize / 1000;
+const MAX_OPUS_FRAME_SIZE: usize = 1275; // max size of an Opus frame according to RFC 6716 3.2.1.
+
+/// Reads a WAV file, downmixes it to mono and returns its samples as `f32` in `-1.0..=1.0`.
+///
+/// Only files recorded at [`TARGET_SAMPLE_RATE`] are supported, as no resampling is performed.
+#[instrument(level = "debug", err)]
+pub fn read_wav(path: &Path) -> anyhow::Result<Vec<f32>> {
+    let mut reader = hound::WavReader::open(path)
+        .with_context(|| format!("Failed to open WAV file {}", path.display()))?;
+    let spec = reader.spec();
+    if spec.sample_rate != TARGET_SAMPLE_RATE {
+        bail!(
+            "Unsupported sample rate {} in {}, expected {TARGET_SAMPLE_RATE}",
+            spec.sample_rate,
+            path.display()
+        );
+    }
+
+    let interleaved = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to read WAV samples")?,
+        hound::SampleFormat::Int => {
+            let scale = (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to read WAV samples")?
+        }
+    };
+
+    let channels = spec.channels.max(1) as usize;
+    Ok(interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect())
+}
+
+/// Spawns a task encoding the given WAV file into Opus frames, emitting one frame every
+/// [`FRAME_DURATION_MS`] to mimic a live capture device.
+///
+/// The task completes once the file has been transmitted (unless `looped` is set) or the
+/// `cancel` token is triggered and returns the number of frames sent.
+#[instrument(level = "debug", skip(tx, cancel), err)]
+pub fn spawn_wav_input(
+    path: PathBuf,
+    looped: bool,
+    tx: mpsc::Sender<EncodedAudioFrame>,
+    cancel: CancellationToken,
+) -> anyhow::Result<JoinHandle<u64>> {
+    let samples = read_wav(&path)?;
+    let mut encoder = opus::Encoder::new(
+        TARGET_SAMPLE_RATE,
+        opus::Channels::Mono,
+        opus::Application::Voip,
+    )
+    .context("Failed to create opus encoder")?;
+    encoder
+        .set_inband_fec(true)
+        .context("Failed to set opus inband fec")?;
+
+    Ok(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_millis(FRAME_DURATION_MS));
+        let mut encoded = vec![0u8; MAX_OPUS_FRAME_SIZE];
+        let mut frame = [0.0f32; FRAME_SIZE];
+        let mut sent = 0u64;
+
+        'outer: loop {
+            for chunk in samples.chunks(FRAME_SIZE) {
+                tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => break 'outer,
+                    _ = ticker.tick() => {}
+                }
+
+                frame[..chunk.len()].copy_from_slice(chunk);
+                frame[chunk.len()..].fill(0.0);
+
+                match encoder.encode_float(&frame, &mut encoded) {
+                    Ok(len) => {
+                        if tx
+                            .send(Bytes::copy_from_slice(&encoded[..len]))
+                            .await
+                            .is_err()
+                        {
+                            tracing::debug!("Audio input receiver dropped, stopping WAV input");
+                            break 'outer;
+                        }
+                        sent += 1;
+                    }
+                    Err(err) => {
+                        tracing::warn!(?err, "Failed to encode input audio frame");
+                    }
+                }
+            }
+
+            if !looped || samples.is_empty() {
+                break;
+            }
+        }
+
+        tracing::debug!(?sent, "WAV input finished");
+        sent
+    }))
+}
+
+/// Spawns a task decoding received Opus frames and writing them into a mono WAV file.
+///
+/// If no path is given, frames are only counted and discarded. The task completes once the
+/// sending half of `rx` is dropped or the `cancel` token is triggered and returns the number of
+/// frames received.
+#[instrument(level = "debug", skip(rx, cancel), err)]
+pub fn spawn_wav_output(
+    path: Option<PathBuf>,
+    mut rx: mpsc::Receiver<EncodedAudioFrame>,
+    cancel: CancellationToken,
+) -> anyhow::Result<JoinHandle<anyhow::Result<u64>>> {
+    let mut writer = match path {
+        Some(path) => Some(create_wav_writer(&path)?),
+        None => None,
+    };
+    let mut decoder = opus::Decoder::new(TARGET_SAMPLE_RATE, opus::Channels::Mono)
+        .context("Failed to create opus decoder")?;
+
+    Ok(tokio::spawn(async move {
+        let mut decoded = vec![0.0f32; FRAME_SIZE * 6]; // up to 120 ms per Opus packet
+        let mut received = 0u64;
+
+        loop {
+            let frame = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => match rx.try_recv() {
+                    Ok(frame) => frame,
+                    Err(_) => break,
+                },
+                frame = rx.recv() => match frame {
+                    Some(frame) => frame,
+                    None => break,
+                },
+            };
+
+            received += 1;
+            let Some(writer) = writer.as_mut() else {
+                continue;
+            };
+
+            match decoder.decode_float(&frame, &mut decoded, false) {
+                Ok(n) => {
+                    for &sample in &decoded[..n] {
+                        writer
+                            .write_sample(sample)
+                            .context("Failed to write WAV sample")?;
+                    }
+                }
+                Err(err) => {
+                    tracing::warn!(?err, "Failed to decode output audio frame");
+                }
+            }
+        }
+
+        if let Some(writer) = writer {
+            writer.finalize().context("Failed to finalize WAV file")?;
+        }
+
+        tracing::debug!(?received, "WAV output finished");
+        Ok(received)
+    }))
+}
+
+fn create_wav_writer(path: &Path) -> anyhow::Result<hound::WavWriter<BufWriter<File>>> {
+    hound::WavWriter::create(
+        path,
+        hound::WavSpec {
+            channels: 1,
+            sample_rate: TARGET_SAMPLE_RATE,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        },
+    )
+    .with_context(|| format!("Failed to create WAV file {}", path.display()))
+}
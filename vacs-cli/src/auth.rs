@@ -0,0 +1,31 @@
+use crate::http::ApiClient;
+use async_trait::async_trait;
+use vacs_signaling::auth::TokenProvider;
+use vacs_signaling::error::SignalingError;
+
+/// [`TokenProvider`] exchanging the configured API token for a short-lived WebSocket token.
+#[derive(Debug, Clone)]
+pub struct ApiTokenProvider {
+    api: ApiClient,
+}
+
+impl ApiTokenProvider {
+    pub fn new(api: ApiClient) -> Self {
+        Self { api }
+    }
+}
+
+#[async_trait]
+impl TokenProvider for ApiTokenProvider {
+    async fn get_token(&self) -> Result<String, SignalingError> {
+        tracing::debug!("Retrieving WebSocket auth token");
+        let token = self
+            .api
+            .ws_token()
+            .await
+            .map_err(|err| SignalingError::ProtocolError(err.to_string()))?;
+
+        tracing::debug!("Successfully retrieved WebSocket auth token");
+        Ok(token)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::ENCODED_AUDIO_FRAME_BUFFER_SIZE;
+use crate::audio::{spawn_wav_input, spawn_wav_output};
+use crate::cli::AudioArgs;
+use anyhow::Context;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use vacs_signaling::protocol::vatsim::ClientId;
+use vacs_signaling::protocol::ws::shared::CallId;
+use vacs_webrtc::Peer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CallEndedBy {
+    Local,
+    Remote,
+}
+
+/// Result of a completed call, printed after hanging up.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallSummary {
+    pub call_id: CallId,
+    pub peer_id: ClientId,
+    pub connected_ms: u128,
+    pub frames_sent: u64,
+    pub frames_received: u64,
+    pub ended_by: CallEndedBy,
+}
+
+/// Audio tasks attached to a connected [`Peer`], feeding it from and recording it to WAV files.
+pub(crate) struct CallMedia {
+    cancel: CancellationToken,
+    input_task: Option<JoinHandle<u64>>,
+    output_task: JoinHandle<anyhow::Result<u64>>,
+    frames_sent: u64,
+    connected_at: Instant,
+}
+
+impl CallMedia {
+    pub(crate) fn start(peer: &mut Peer, audio: &AudioArgs) -> anyhow::Result<Self> {
+        let cancel = CancellationToken::new();
+        let (input_tx, input_rx) = mpsc::channel(ENCODED_AUDIO_FRAME_BUFFER_SIZE);
+        let (output_tx, output_rx) = mpsc::channel(ENCODED_AUDIO_FRAME_BUFFER_SIZE);
+
+        let input_task = match &audio.input {
+            Some(path) => Some(spawn_wav_input(
+                path.clone(),
+                audio.loop_input,
+                input_tx,
+                cancel.child_token(),
+            )?),
+            None => None,
+        };
+        let output_task = spawn_wav_output(audio.output.clone(), output_rx, cancel.child_token())?;
+
+        peer.start(input_rx, output_tx)
+            .context("Failed to start WebRTC peer")?;
+
+        Ok(Self {
+            cancel,
+            input_task,
+            output_task,
+            frames_sent: 0,
+            connected_at: Instant::now(),
+        })
+    }
+
+    /// Resolves once the input file has been transmitted completely. Never resolves if no input
+    /// file is attached or it has already finished.
+    pub(crate) async fn input_finished(&mut self) {
+        match self.input_task.as_mut() {
+            Some(task) => {
+                self.frames_sent = task.await.unwrap_or_default();
+                self.input_task = None;
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Stops all audio tasks, returning the connected duration as well as the number of frames
+    /// sent and received.
+    pub(crate) async fn stop(mut self) -> anyhow::Result<(Duration, u64, u64)> {
+        let connected = self.connected_at.elapsed();
+        self.cancel.cancel();
+
+        if let Some(task) = self.input_task.take() {
+            self.frames_sent = task.await.context("Failed to join audio input task")?;
+        }
+        let frames_received = self
+            .output_task
+            .await
+            .context("Failed to join audio output task")??;
+
+        Ok((connected, self.frames_sent, frames_received))
+    }
+}
@@ -0,0 +1,145 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+use vacs_signaling::protocol::vatsim::{ClientId, PositionId, StationId};
+use vacs_signaling::protocol::ws::shared::CallTarget;
+
+/// Headless vacs client for load tests, end-to-end tests and bots.
+#[derive(Debug, Parser)]
+#[command(name = "vacs-cli", version)]
+pub struct Cli {
+    /// Base URL of the vacs server, e.g. `https://vacs.example.com`.
+    #[arg(long, env = "VACS_SERVER_URL")]
+    pub server_url: Url,
+
+    /// API token used to authenticate with the server.
+    #[arg(long, env = "VACS_API_TOKEN", hide_env_values = true)]
+    pub api_token: String,
+
+    /// Position to log in as, required if the VATSIM connection matches multiple positions.
+    #[arg(long)]
+    pub position_id: Option<String>,
+
+    /// Print results as JSON lines instead of human-readable text.
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+impl Cli {
+    pub fn position_id(&self) -> Option<PositionId> {
+        self.position_id.as_deref().map(PositionId::from)
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List all other clients currently connected to the server.
+    Clients,
+    /// List all currently covered stations.
+    Stations,
+    /// Place a call and exchange audio once it has been accepted.
+    Call {
+        /// Call target, formatted as `client:<cid>`, `position:<id>` or `station:<id>`.
+        #[arg(value_parser = parse_call_target)]
+        target: CallTarget,
+        /// Place the call with priority.
+        #[arg(long)]
+        prio: bool,
+        /// Seconds to wait for the call to be accepted.
+        #[arg(long, default_value_t = 30)]
+        ring_timeout_secs: u64,
+        #[command(flatten)]
+        audio: AudioArgs,
+    },
+    /// Wait for an incoming call, accept it and exchange audio.
+    Answer {
+        /// Only accept calls placed by this client.
+        #[arg(long)]
+        from: Option<String>,
+        /// Seconds to wait for an incoming call.
+        #[arg(long, default_value_t = 300)]
+        wait_timeout_secs: u64,
+        #[command(flatten)]
+        audio: AudioArgs,
+    },
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct AudioArgs {
+    /// WAV file (48 kHz) to transmit once the call is connected.
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+    /// Restart the input file once it ends instead of hanging up.
+    #[arg(long, requires = "input")]
+    pub loop_input: bool,
+    /// WAV file to record the received audio into.
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+    /// Hang up after the given number of seconds. Without it, the call ends once the input file
+    /// has been transmitted or the peer hangs up.
+    #[arg(long)]
+    pub duration_secs: Option<u64>,
+}
+
+impl AudioArgs {
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration_secs.map(Duration::from_secs)
+    }
+}
+
+pub fn parse_call_target(s: &str) -> Result<CallTarget, String> {
+    let (kind, id) = s
+        .split_once(':')
+        .ok_or_else(|| format!("invalid call target {s}, expected <kind>:<id>"))?;
+    if id.is_empty() {
+        return Err(format!("invalid call target {s}, id must not be empty"));
+    }
+
+    match kind.to_ascii_lowercase().as_str() {
+        "client" => Ok(CallTarget::Client(ClientId::from(id))),
+        "position" => Ok(CallTarget::Position(PositionId::from(id))),
+        "station" => Ok(CallTarget::Station(StationId::from(id))),
+        other => Err(format!(
+            "unknown call target kind {other}, expected client, position or station"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn verify_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn call_target() {
+        assert_eq!(
+            parse_call_target("client:1234567"),
+            Ok(CallTarget::Client(ClientId::from("1234567")))
+        );
+        assert_eq!(
+            parse_call_target("Position:LOWW_TWR"),
+            Ok(CallTarget::Position(PositionId::from("LOWW_TWR")))
+        );
+        assert_eq!(
+            parse_call_target("station:LOWW_APP"),
+            Ok(CallTarget::Station(StationId::from("LOWW_APP")))
+        );
+    }
+
+    #[test]
+    fn call_target_invalid() {
+        assert!(parse_call_target("1234567").is_err());
+        assert!(parse_call_target("client:").is_err());
+        assert!(parse_call_target("frequency:119.400").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::APP_USER_AGENT;
+use anyhow::{Context, bail};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use url::Url;
+use vacs_signaling::protocol::http::webrtc::IceConfig;
+use vacs_signaling::protocol::http::ws::WebSocketToken;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Minimal HTTP client for the parts of the vacs server API required by headless clients.
+///
+/// All requests are authenticated using an API token passed as `Authorization: Bearer` header.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    base_url: Url,
+    http_client: reqwest::Client,
+}
+
+impl ApiClient {
+    pub fn new(base_url: Url, api_token: &str) -> anyhow::Result<Self> {
+        let mut auth_value =
+            HeaderValue::from_str(&format!("Bearer {api_token}")).context("Invalid API token")?;
+        auth_value.set_sensitive(true);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, auth_value);
+
+        let http_client = reqwest::ClientBuilder::new()
+            .user_agent(APP_USER_AGENT)
+            .default_headers(headers)
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self {
+            base_url,
+            http_client,
+        })
+    }
+
+    /// Returns the WebSocket URL of the signaling endpoint, derived from the HTTP base URL.
+    pub fn ws_url(&self) -> anyhow::Result<String> {
+        let mut url = self.endpoint_url("ws")?;
+        let scheme = match url.scheme() {
+            "http" => "ws",
+            "https" => "wss",
+            other => bail!("Unsupported server URL scheme {other}"),
+        };
+        url.set_scheme(scheme)
+            .map_err(|_| anyhow::anyhow!("Failed to set WebSocket URL scheme"))?;
+        Ok(url.to_string())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn ws_token(&self) -> anyhow::Result<String> {
+        let token = self.get::<WebSocketToken>("ws/token").await?;
+        Ok(token.token)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn ice_config(&self) -> anyhow::Result<IceConfig> {
+        self.get::<IceConfig>("webrtc/ice-config").await
+    }
+
+    async fn get<R: DeserializeOwned>(&self, path: &str) -> anyhow::Result<R> {
+        let url = self.endpoint_url(path)?;
+
+        tracing::trace!(%url, "Performing HTTP GET request");
+        let response = self
+            .http_client
+            .get(url.clone())
+            .send()
+            .await
+            .with_context(|| format!("Failed to perform HTTP GET request to {url}"))?
+            .error_for_status()
+            .with_context(|| format!("HTTP GET request to {url} failed"))?;
+
+        response
+            .json::<R>()
+            .await
+            .with_context(|| format!("Failed to parse HTTP GET response from {url}"))
+    }
+
+    fn endpoint_url(&self, path: &str) -> anyhow::Result<Url> {
+        let mut base_url = self.base_url.clone();
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        base_url
+            .join(path)
+            .with_context(|| format!("Failed to build URL for {path}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn client(base_url: &str) -> ApiClient {
+        ApiClient::new(Url::parse(base_url).unwrap(), "token").unwrap()
+    }
+
+    #[test]
+    fn ws_url_http() {
+        assert_eq!(
+            client("http://localhost:3000").ws_url().unwrap(),
+            "ws://localhost:3000/ws"
+        );
+    }
+
+    #[test]
+    fn ws_url_https_with_path() {
+        assert_eq!(
+            client("https://vacs.example.com/api").ws_url().unwrap(),
+            "wss://vacs.example.com/api/ws"
+        );
+    }
+
+    #[test]
+    fn ws_url_unsupported_scheme() {
+        assert!(client("ftp://vacs.example.com").ws_url().is_err());
+    }
+}
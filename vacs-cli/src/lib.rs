@@ -0,0 +1,10 @@
+pub mod audio;
+pub mod auth;
+pub mod call;
+pub mod cli;
+pub mod http;
+pub mod session;
+
+pub const APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+pub(crate) const ENCODED_AUDIO_FRAME_BUFFER_SIZE: usize = 512;
@@ -0,0 +1,106 @@
+use clap::Parser;
+use serde::Serialize;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use vacs_cli::cli::{Cli, Command};
+use vacs_cli::http::ApiClient;
+use vacs_cli::session::Session;
+use vacs_signaling::protocol::vatsim::ClientId;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    rustls::crypto::aws_lc_rs::default_provider()
+        .install_default()
+        .expect("Failed to install rustls crypto provider");
+
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| "vacs_cli=info,vacs_signaling=warn,vacs_webrtc=warn".into());
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .init();
+
+    let cli = Cli::parse();
+
+    let shutdown_token = CancellationToken::new();
+    {
+        let shutdown_token = shutdown_token.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                tracing::info!("Received Ctrl+C, shutting down");
+                shutdown_token.cancel();
+            }
+        });
+    }
+
+    let api = ApiClient::new(cli.server_url.clone(), &cli.api_token)?;
+    let mut session = Session::connect(api, cli.position_id(), shutdown_token).await?;
+
+    let result = match &cli.command {
+        Command::Clients => session.list_clients().await.map(|clients| {
+            for client in clients {
+                print(cli.json, &client, || {
+                    format!(
+                        "{}\t{}\t{}\t{}",
+                        client.id,
+                        client.display_name,
+                        client.frequency,
+                        client
+                            .position_id
+                            .as_ref()
+                            .map(|p| p.to_string())
+                            .unwrap_or_default()
+                    )
+                });
+            }
+        }),
+        Command::Stations => session.list_stations().await.map(|stations| {
+            for station in stations {
+                print(cli.json, &station, || {
+                    format!("{}{}", station.id, if station.own { "\t(own)" } else { "" })
+                });
+            }
+        }),
+        Command::Call {
+            target,
+            prio,
+            ring_timeout_secs,
+            audio,
+        } => session
+            .place_call(
+                target.clone(),
+                *prio,
+                Duration::from_secs(*ring_timeout_secs),
+                audio,
+            )
+            .await
+            .map(|summary| print(cli.json, &summary, || format!("{summary:?}"))),
+        Command::Answer {
+            from,
+            wait_timeout_secs,
+            audio,
+        } => session
+            .answer_call(
+                from.as_deref().map(ClientId::from),
+                Duration::from_secs(*wait_timeout_secs),
+                audio,
+            )
+            .await
+            .map(|summary| print(cli.json, &summary, || format!("{summary:?}"))),
+    };
+
+    session.disconnect().await;
+    result
+}
+
+fn print<T: Serialize>(json: bool, value: &T, text: impl FnOnce() -> String) {
+    if json {
+        match serde_json::to_string(value) {
+            Ok(line) => println!("{line}"),
+            Err(err) => tracing::error!(?err, "Failed to serialize output"),
+        }
+    } else {
+        println!("{}", text());
+    }
+}
@@ -0,0 +1,473 @@
+use crate::auth::ApiTokenProvider;
+use crate::call::{CallEndedBy, CallMedia, CallSummary};
+use crate::cli::AudioArgs;
+use crate::http::ApiClient;
+use anyhow::{Context, bail};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tracing::instrument;
+use vacs_signaling::client::{SignalingClient, SignalingEvent};
+use vacs_signaling::protocol::vatsim::{ClientId, PositionId};
+use vacs_signaling::protocol::ws::client::{CallReject, CallRejectReason, ClientMessage};
+use vacs_signaling::protocol::ws::server::{ClientInfo, ServerMessage, StationInfo};
+use vacs_signaling::protocol::ws::shared::{
+    CallAccept, CallEnd, CallError, CallErrorReason, CallId, CallInvite, CallSource, CallTarget,
+    WebrtcAnswer, WebrtcIceCandidate, WebrtcOffer,
+};
+use vacs_signaling::transport::tokio::TokioTransport;
+use vacs_webrtc::{Peer, PeerConnectionState, PeerEvent};
+
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10);
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+const PEER_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A logged-in signaling session acting on behalf of a single client.
+pub struct Session {
+    client: SignalingClient<TokioTransport, ApiTokenProvider>,
+    events_rx: broadcast::Receiver<SignalingEvent>,
+    api: ApiClient,
+    client_info: ClientInfo,
+    shutdown_token: CancellationToken,
+}
+
+impl Session {
+    #[instrument(level = "info", skip_all, err)]
+    pub async fn connect(
+        api: ApiClient,
+        position_id: Option<PositionId>,
+        shutdown_token: CancellationToken,
+    ) -> anyhow::Result<Self> {
+        let transport = TokioTransport::new(&api.ws_url()?);
+        let client = SignalingClient::new(
+            transport,
+            ApiTokenProvider::new(api.clone()),
+            |_| async {},
+            shutdown_token.child_token(),
+            false,
+            LOGIN_TIMEOUT,
+            0,
+            None,
+            &tokio::runtime::Handle::current(),
+        );
+
+        let mut events_rx = client.subscribe();
+        client
+            .connect(position_id)
+            .await
+            .context("Failed to connect to signaling server")?;
+
+        let client_info = loop {
+            match events_rx.recv().await {
+                Ok(SignalingEvent::Connected { client_info, .. }) => break client_info,
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => bail!("Signaling client closed before login completed"),
+            }
+        };
+
+        tracing::info!(client_id = %client_info.id, "Logged in to signaling server");
+        Ok(Self {
+            client,
+            events_rx,
+            api,
+            client_info,
+            shutdown_token,
+        })
+    }
+
+    pub fn client_info(&self) -> &ClientInfo {
+        &self.client_info
+    }
+
+    pub async fn disconnect(self) {
+        self.client.disconnect().await;
+    }
+
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn list_clients(&mut self) -> anyhow::Result<Vec<ClientInfo>> {
+        self.send(ClientMessage::ListClients).await?;
+        self.wait_for(RESPONSE_TIMEOUT, |msg| match msg {
+            ServerMessage::ClientList(list) => Some(Ok(list.clients.clone())),
+            _ => None,
+        })
+        .await
+    }
+
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn list_stations(&mut self) -> anyhow::Result<Vec<StationInfo>> {
+        self.send(ClientMessage::ListStations).await?;
+        self.wait_for(RESPONSE_TIMEOUT, |msg| match msg {
+            ServerMessage::StationList(list) => Some(Ok(list.stations.clone())),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Places a call to `target`, waits for it to be accepted and exchanges audio until the call
+    /// ends.
+    #[instrument(level = "info", skip(self, audio), err)]
+    pub async fn place_call(
+        &mut self,
+        target: CallTarget,
+        prio: bool,
+        ring_timeout: Duration,
+        audio: &AudioArgs,
+    ) -> anyhow::Result<CallSummary> {
+        let call_id = CallId::new();
+        self.send(CallInvite {
+            call_id,
+            source: self.call_source(),
+            target,
+            prio,
+        })
+        .await?;
+        tracing::info!(%call_id, "Call placed, waiting for it to be accepted");
+
+        let accepted = self
+            .wait_for(ring_timeout, |msg| match msg {
+                ServerMessage::CallAccept(accept) if accept.call_id == call_id => {
+                    Some(Ok(accept.accepting_client_id.clone()))
+                }
+                ServerMessage::CallCancelled(cancelled) if cancelled.call_id == call_id => {
+                    Some(Err(anyhow::anyhow!(
+                        "Call was cancelled: {:?}",
+                        cancelled.reason
+                    )))
+                }
+                ServerMessage::CallError(error) if error.call_id == call_id => Some(Err(
+                    anyhow::anyhow!("Call failed: {:?} {:?}", error.reason, error.message),
+                )),
+                _ => None,
+            })
+            .await;
+        let peer_id = match accepted {
+            Ok(peer_id) => peer_id,
+            Err(err) => {
+                self.send_call_end(call_id).await;
+                return Err(err);
+            }
+        };
+        tracing::info!(%call_id, %peer_id, "Call accepted, establishing peer connection");
+
+        let (peer, peer_events_rx) = self.create_peer().await?;
+        let sdp = peer.create_offer().await?;
+        self.send(WebrtcOffer {
+            call_id,
+            from_client_id: self.client_info.id.clone(),
+            to_client_id: peer_id.clone(),
+            sdp,
+        })
+        .await?;
+
+        self.run_call(call_id, peer_id, peer, peer_events_rx, audio)
+            .await
+    }
+
+    /// Waits for an incoming call (optionally only from `from`), accepts it and exchanges audio
+    /// until the call ends.
+    #[instrument(level = "info", skip(self, audio), err)]
+    pub async fn answer_call(
+        &mut self,
+        from: Option<ClientId>,
+        wait_timeout: Duration,
+        audio: &AudioArgs,
+    ) -> anyhow::Result<CallSummary> {
+        tracing::info!("Waiting for incoming call");
+        let invite = self
+            .wait_for(wait_timeout, |msg| match msg {
+                ServerMessage::CallInvite(invite)
+                    if from
+                        .as_ref()
+                        .is_none_or(|from| invite.source.client_id == *from) =>
+                {
+                    Some(Ok(invite.clone()))
+                }
+                _ => None,
+            })
+            .await?;
+        let call_id = invite.call_id;
+        tracing::info!(%call_id, caller = %invite.source.client_id, "Received call, accepting");
+
+        self.send(CallAccept {
+            call_id,
+            accepting_client_id: self.client_info.id.clone(),
+        })
+        .await?;
+
+        let offer = self
+            .wait_for(PEER_CONNECT_TIMEOUT, |msg| match msg {
+                ServerMessage::WebrtcOffer(offer) if offer.call_id == call_id => {
+                    Some(Ok(offer.clone()))
+                }
+                ServerMessage::CallCancelled(cancelled) if cancelled.call_id == call_id => {
+                    Some(Err(anyhow::anyhow!(
+                        "Call was cancelled: {:?}",
+                        cancelled.reason
+                    )))
+                }
+                ServerMessage::CallEnd(end) if end.call_id == call_id => {
+                    Some(Err(anyhow::anyhow!("Call ended before it was connected")))
+                }
+                ServerMessage::CallError(error) if error.call_id == call_id => Some(Err(
+                    anyhow::anyhow!("Call failed: {:?} {:?}", error.reason, error.message),
+                )),
+                _ => None,
+            })
+            .await?;
+
+        let (peer, peer_events_rx) = self.create_peer().await?;
+        let sdp = peer.accept_offer(offer.sdp).await?;
+        self.send(WebrtcAnswer {
+            call_id,
+            from_client_id: self.client_info.id.clone(),
+            to_client_id: offer.from_client_id.clone(),
+            sdp,
+        })
+        .await?;
+
+        self.run_call(call_id, offer.from_client_id, peer, peer_events_rx, audio)
+            .await
+    }
+
+    async fn create_peer(&self) -> anyhow::Result<(Peer, broadcast::Receiver<PeerEvent>)> {
+        let ice_config = self
+            .api
+            .ice_config()
+            .await
+            .context("Failed to retrieve ICE config")?;
+        Peer::new(ice_config)
+            .await
+            .context("Failed to create WebRTC peer")
+    }
+
+    async fn run_call(
+        &mut self,
+        call_id: CallId,
+        peer_id: ClientId,
+        mut peer: Peer,
+        mut peer_events_rx: broadcast::Receiver<PeerEvent>,
+        audio: &AudioArgs,
+    ) -> anyhow::Result<CallSummary> {
+        let end_on_input = audio.input.is_some() && !audio.loop_input && audio.duration().is_none();
+        let mut media: Option<CallMedia> = None;
+
+        let connect_timeout = tokio::time::sleep(PEER_CONNECT_TIMEOUT);
+        tokio::pin!(connect_timeout);
+        let hangup = tokio::time::sleep(Duration::MAX);
+        tokio::pin!(hangup);
+
+        let result = loop {
+            tokio::select! {
+                biased;
+
+                _ = self.shutdown_token.cancelled() => {
+                    tracing::info!("Shutdown requested, hanging up");
+                    break Ok(CallEndedBy::Local);
+                }
+
+                _ = &mut connect_timeout, if media.is_none() => {
+                    break Err(anyhow::anyhow!("Timed out waiting for peer connection"));
+                }
+
+                _ = &mut hangup, if media.is_some() => {
+                    tracing::info!("Call duration elapsed, hanging up");
+                    break Ok(CallEndedBy::Local);
+                }
+
+                _ = async { media.as_mut().expect("media must be present").input_finished().await }, if end_on_input && media.is_some() => {
+                    tracing::info!("Input file transmitted, hanging up");
+                    break Ok(CallEndedBy::Local);
+                }
+
+                event = peer_events_rx.recv() => match event {
+                    Ok(PeerEvent::ConnectionState(PeerConnectionState::Connected)) => {
+                        if media.is_none() {
+                            tracing::info!(%call_id, %peer_id, "Call connected");
+                            match CallMedia::start(&mut peer, audio) {
+                                Ok(started) => media = Some(started),
+                                Err(err) => break Err(err),
+                            }
+                            if let Some(duration) = audio.duration() {
+                                hangup.as_mut().reset(tokio::time::Instant::now() + duration);
+                            }
+                        }
+                    }
+                    Ok(PeerEvent::ConnectionState(PeerConnectionState::Failed)) => {
+                        self.send_call_error(call_id, CallErrorReason::WebrtcFailure).await;
+                        break Err(anyhow::anyhow!("Peer connection failed"));
+                    }
+                    Ok(PeerEvent::ConnectionState(PeerConnectionState::Closed)) => {
+                        break Ok(CallEndedBy::Remote);
+                    }
+                    Ok(PeerEvent::ConnectionState(state)) => {
+                        tracing::debug!(?state, "Peer connection state changed");
+                    }
+                    Ok(PeerEvent::IceCandidate(candidate)) => {
+                        if let Err(err) = self.send(WebrtcIceCandidate {
+                            call_id,
+                            from_client_id: self.client_info.id.clone(),
+                            to_client_id: peer_id.clone(),
+                            candidate,
+                        }).await {
+                            tracing::warn!(?err, "Failed to send ICE candidate");
+                        }
+                    }
+                    Ok(PeerEvent::Error(err)) => {
+                        tracing::warn!(?err, "Received peer error");
+                    }
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(?n, "Lagged behind on peer events");
+                    }
+                    Err(RecvError::Closed) => {
+                        break Err(anyhow::anyhow!("Peer event channel closed"));
+                    }
+                },
+
+                event = self.events_rx.recv() => match event {
+                    Ok(SignalingEvent::Message(msg)) => match msg {
+                        ServerMessage::WebrtcAnswer(answer) if answer.call_id == call_id => {
+                            if let Err(err) = peer.accept_answer(answer.sdp).await {
+                                self.send_call_error(call_id, CallErrorReason::WebrtcFailure).await;
+                                break Err(err.into());
+                            }
+                        }
+                        ServerMessage::WebrtcIceCandidate(candidate) if candidate.call_id == call_id => {
+                            if let Err(err) = peer.add_remote_ice_candidate(candidate.candidate).await {
+                                tracing::warn!(?err, "Failed to add remote ICE candidate");
+                            }
+                        }
+                        ServerMessage::CallEnd(end) if end.call_id == call_id => {
+                            tracing::info!(ending_client_id = %end.ending_client_id, "Call ended by peer");
+                            break Ok(CallEndedBy::Remote);
+                        }
+                        ServerMessage::CallError(error) if error.call_id == call_id => {
+                            break Err(anyhow::anyhow!("Call failed: {:?} {:?}", error.reason, error.message));
+                        }
+                        ServerMessage::CallInvite(invite) => {
+                            tracing::debug!(call_id = %invite.call_id, "Rejecting incoming call while busy");
+                            if let Err(err) = self.send(CallReject {
+                                call_id: invite.call_id,
+                                rejecting_client_id: self.client_info.id.clone(),
+                                reason: CallRejectReason::Busy,
+                            }).await {
+                                tracing::warn!(?err, "Failed to reject incoming call");
+                            }
+                        }
+                        _ => {}
+                    },
+                    Ok(SignalingEvent::Error(err)) if err.is_fatal() => {
+                        break Err(anyhow::anyhow!("Signaling connection failed: {err}"));
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(?n, "Lagged behind on signaling events");
+                    }
+                    Err(RecvError::Closed) => {
+                        break Err(anyhow::anyhow!("Signaling event channel closed"));
+                    }
+                },
+            }
+        };
+
+        if matches!(result, Ok(CallEndedBy::Local)) {
+            self.send_call_end(call_id).await;
+        }
+
+        let (connected, frames_sent, frames_received) = match media {
+            Some(media) => media.stop().await?,
+            None => (Duration::ZERO, 0, 0),
+        };
+        if let Err(err) = peer.close().await {
+            tracing::warn!(?err, "Failed to close peer");
+        }
+
+        let ended_by = result?;
+        Ok(CallSummary {
+            call_id,
+            peer_id,
+            connected_ms: connected.as_millis(),
+            frames_sent,
+            frames_received,
+            ended_by,
+        })
+    }
+
+    /// Waits for the first server message `f` returns a result for, skipping all others.
+    async fn wait_for<T>(
+        &mut self,
+        timeout: Duration,
+        mut f: impl FnMut(&ServerMessage) -> Option<anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let event = tokio::select! {
+                biased;
+                _ = self.shutdown_token.cancelled() => bail!("Shutdown requested"),
+                _ = tokio::time::sleep_until(deadline) => bail!("Timed out waiting for server message"),
+                event = self.events_rx.recv() => event,
+            };
+
+            match event {
+                Ok(SignalingEvent::Message(ServerMessage::Error(error))) => {
+                    bail!("Server returned error: {:?}", error.reason);
+                }
+                Ok(SignalingEvent::Message(msg)) => {
+                    if let Some(result) = f(&msg) {
+                        return result;
+                    }
+                }
+                Ok(SignalingEvent::Error(err)) if err.is_fatal() => {
+                    bail!("Signaling connection failed: {err}");
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!(?n, "Lagged behind on signaling events");
+                }
+                Err(RecvError::Closed) => bail!("Signaling event channel closed"),
+            }
+        }
+    }
+
+    async fn send(&self, msg: impl Into<ClientMessage>) -> anyhow::Result<()> {
+        let msg = msg.into();
+        let variant = msg.variant();
+        self.client
+            .send(msg)
+            .await
+            .with_context(|| format!("Failed to send {variant} message"))
+    }
+
+    async fn send_call_end(&self, call_id: CallId) {
+        if let Err(err) = self
+            .send(CallEnd {
+                call_id,
+                ending_client_id: self.client_info.id.clone(),
+            })
+            .await
+        {
+            tracing::warn!(?err, "Failed to send call end");
+        }
+    }
+
+    async fn send_call_error(&self, call_id: CallId, reason: CallErrorReason) {
+        if let Err(err) = self
+            .send(CallError {
+                call_id,
+                reason,
+                message: None,
+            })
+            .await
+        {
+            tracing::warn!(?err, "Failed to send call error");
+        }
+    }
+
+    fn call_source(&self) -> CallSource {
+        CallSource {
+            client_id: self.client_info.id.clone(),
+            position_id: self.client_info.position_id.clone(),
+            station_id: None,
+        }
+    }
+}
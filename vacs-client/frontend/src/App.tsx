@@ -34,6 +34,7 @@ import {useProfileType} from "./stores/profile-store.ts";
 import Button from "./components/ui/Button.tsx";
 import {fetchCallConfig, fetchClientPageSettings} from "./stores/settings-store.ts";
 import {useZoomHotkey} from "./hooks/zoom-hotkey-hook.ts";
+import AnnouncementBanner from "./components/AnnouncementBanner.tsx";
 
 function App() {
     const connected = useConnectionStore(state => state.connectionState === "connected");
@@ -91,6 +92,7 @@ function App() {
                                 <Route path="/telephone" component={TelephonePage} />
                             </Route>
                         </Switch>
+                        <AnnouncementBanner />
                     </div>
                     {/* Right Button Row */}
                     <div className="w-24 h-full px-2 pb-6 flex flex-col justify-between">
@@ -0,0 +1,51 @@
+import {useEffect} from "preact/hooks";
+import {clsx} from "clsx";
+import {useAnnouncements, useAnnouncementsActions} from "../stores/announcements-store.ts";
+import type {AnnouncementSeverity} from "../../../../vacs-protocol/schema/protocol.ts";
+
+const EXPIRY_CHECK_INTERVAL_MS = 1000;
+
+const SEVERITY_CLASSES: Record<AnnouncementSeverity, string> = {
+    info: "bg-blue-100 border-blue-600",
+    warning: "bg-yellow-100 border-yellow-500",
+    critical: "bg-red-200 border-red-700",
+};
+
+function AnnouncementBanner() {
+    const announcements = useAnnouncements();
+    const {dismiss, removeExpired} = useAnnouncementsActions();
+
+    useEffect(() => {
+        if (announcements.length === 0) return;
+
+        const intervalId = setInterval(removeExpired, EXPIRY_CHECK_INTERVAL_MS);
+        return () => clearInterval(intervalId);
+    }, [announcements.length, removeExpired]);
+
+    return announcements.length > 0 ? (
+        <div className="z-40 absolute top-0 left-0 w-full p-1 flex flex-col gap-1 pointer-events-none">
+            {announcements.map(announcement => (
+                <div
+                    key={announcement.id}
+                    className={clsx(
+                        "pointer-events-auto flex flex-row items-center gap-2 border-2 rounded px-2 py-1",
+                        SEVERITY_CLASSES[announcement.severity],
+                    )}
+                >
+                    <p className="grow wrap-break-word">{announcement.message}</p>
+                    <button
+                        className="shrink-0 px-1 font-semibold cursor-pointer"
+                        title="Dismiss"
+                        onClick={() => dismiss(announcement.id)}
+                    >
+                        X
+                    </button>
+                </div>
+            ))}
+        </div>
+    ) : (
+        <></>
+    );
+}
+
+export default AnnouncementBanner;
@@ -1,6 +1,6 @@
 import {listen, UnlistenFn} from "@tauri-apps/api/event";
 import {useClientsStore} from "../stores/clients-store.ts";
-import {ClientInfo, ClientPageSettings, SessionInfo} from "../types/client.ts";
+import {ClientInfo, ClientPageSettings, ClientPresence, SessionInfo} from "../types/client.ts";
 import {useCallStore} from "../stores/call-store.ts";
 import {
     IncomingCallListEntry,
@@ -12,15 +12,18 @@ import {CallId, ClientId, PositionId} from "../types/generic.ts";
 import {useProfileStore} from "../stores/profile-store.ts";
 import {StationChange, StationInfo} from "../types/station.ts";
 import {useStationsStore} from "../stores/stations-store.ts";
-import {Call} from "../types/call.ts";
+import {Call, TextMessage} from "../types/call.ts";
 import {useErrorOverlayStore} from "../stores/error-overlay-store.ts";
 import {Profile} from "../types/profile.ts";
 import {navigate} from "wouter/use-browser-location";
 import {useSettingsStore} from "../stores/settings-store.ts";
 import {useFilterStore} from "../stores/filter-store.ts";
+import {useTextMessagesStore} from "../stores/text-messages-store.ts";
+import {useAnnouncementsStore} from "../stores/announcements-store.ts";
+import type {Announcement} from "../../../../vacs-protocol/schema/protocol.ts";
 
 export function setupSignalingListeners() {
-    const {setClients, addClient, removeClient} = useClientsStore.getState();
+    const {setClients, addClient, removeClient, setPresence} = useClientsStore.getState();
     const {setStations, addStationChanges, reset: resetStationsStore} = useStationsStore.getState();
     const {
         addIncomingCall,
@@ -35,6 +38,10 @@ export function setupSignalingListeners() {
         updateCall: updateCallInCallList,
         clearCallList,
     } = useCallListStore.getState().actions;
+    const {addIncomingMessage: addIncomingTextMessage, clear: clearTextMessages} =
+        useTextMessagesStore.getState().actions;
+    const {addAnnouncement, removeExpired: removeExpiredAnnouncements} =
+        useAnnouncementsStore.getState().actions;
     const {setConnectionState, setConnectionInfo, setPositionsToSelect} =
         useConnectionStore.getState();
     const {setProfile, reset: resetProfileStore} = useProfileStore.getState();
@@ -61,6 +68,10 @@ export function setupSignalingListeners() {
             listen("signaling:reconnecting", () => {
                 setConnectionState("connecting");
             }),
+            listen("signaling:calls-reset", () => {
+                resetCallStore();
+                clearCallList();
+            }),
             listen("signaling:disconnected", () => {
                 setConnectionState("disconnected");
                 setConnectionInfo({displayName: "", positionId: undefined, frequency: ""});
@@ -68,6 +79,8 @@ export function setupSignalingListeners() {
                 resetStationsStore();
                 resetCallStore();
                 clearCallList();
+                clearTextMessages();
+                removeExpiredAnnouncements();
                 resetProfileStore();
                 setFilter("");
             }),
@@ -90,6 +103,15 @@ export function setupSignalingListeners() {
             listen<ClientId>("signaling:client-disconnected", event => {
                 removeClient(event.payload);
             }),
+            listen<ClientPresence>("signaling:client-presence", event => {
+                setPresence(event.payload.clientId, event.payload.presence);
+            }),
+            listen<TextMessage>("signaling:text-message", event => {
+                addIncomingTextMessage(event.payload);
+            }),
+            listen<Announcement>("signaling:announcement", event => {
+                addAnnouncement(event.payload);
+            }),
             listen<ClientId>("signaling:client-not-found", event => {
                 removeClient(event.payload);
                 openErrorOverlay(
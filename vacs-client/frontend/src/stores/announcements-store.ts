@@ -0,0 +1,40 @@
+import {create} from "zustand/react";
+import type {Announcement} from "../../../../vacs-protocol/schema/protocol.ts";
+
+type AnnouncementsState = {
+    announcements: Announcement[];
+    actions: {
+        addAnnouncement: (announcement: Announcement) => void;
+        dismiss: (id: string) => void;
+        removeExpired: () => void;
+    };
+};
+
+export const useAnnouncementsStore = create<AnnouncementsState>()((set, get) => ({
+    announcements: [],
+    actions: {
+        addAnnouncement: announcement => {
+            if (isExpired(announcement)) return;
+            const announcements = get().announcements.filter(
+                a => a.id !== announcement.id && !isExpired(a),
+            );
+            announcements.push(announcement);
+            set({announcements});
+        },
+        dismiss: id => {
+            set({announcements: get().announcements.filter(a => a.id !== id)});
+        },
+        removeExpired: () => {
+            const announcements = get().announcements.filter(a => !isExpired(a));
+            if (announcements.length !== get().announcements.length) {
+                set({announcements});
+            }
+        },
+    },
+}));
+
+export const useAnnouncements = () => useAnnouncementsStore(state => state.announcements);
+export const useAnnouncementsActions = () => useAnnouncementsStore(state => state.actions);
+
+const isExpired = ({expiresAt}: Announcement): boolean =>
+    expiresAt !== undefined && expiresAt !== null && expiresAt * 1000 <= Date.now();
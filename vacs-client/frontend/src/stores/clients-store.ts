@@ -1,4 +1,4 @@
-import {ClientInfo} from "../types/client.ts";
+import {ClientInfo, Presence} from "../types/client.ts";
 import {create} from "zustand/react";
 import {ClientId} from "../types/generic.ts";
 
@@ -8,6 +8,7 @@ type ClientsState = {
     addClient: (client: ClientInfo) => void;
     getClientInfo: (cid: ClientId) => ClientInfo;
     removeClient: (cid: ClientId) => void;
+    setPresence: (cid: ClientId, presence: Presence) => void;
 };
 
 export const useClientsStore = create<ClientsState>()((set, get) => ({
@@ -36,4 +37,11 @@ export const useClientsStore = create<ClientsState>()((set, get) => ({
             clients: get().clients.filter(client => client.id !== cid),
         });
     },
+    setPresence: (cid, presence) => {
+        set({
+            clients: get().clients.map(client =>
+                client.id === cid ? {...client, presence} : client,
+            ),
+        });
+    },
 }));
@@ -0,0 +1,102 @@
+import {create} from "zustand/react";
+import {callSourceToTarget, CallTarget, TextMessage} from "../types/call.ts";
+import {ClientId} from "../types/generic.ts";
+
+export type TextMessageEntry = {
+    type: "IN" | "OUT";
+    time: string;
+    text: string;
+    clientId?: ClientId;
+};
+
+type TextMessagesState = {
+    // History of exchanged messages, keyed by the peer's target (see peerKey)
+    history: Map<string, TextMessageEntry[]>;
+    unread: Map<string, number>;
+    actions: {
+        addIncomingMessage: (message: TextMessage) => void;
+        addOutgoingMessage: (target: CallTarget, text: string) => void;
+        markRead: (target: CallTarget) => void;
+        clear: () => void;
+    };
+};
+
+const MAX_HISTORY_PER_PEER = 100;
+
+export const useTextMessagesStore = create<TextMessagesState>()((set, get) => ({
+    history: new Map(),
+    unread: new Map(),
+    actions: {
+        addIncomingMessage: (message: TextMessage) => {
+            const key = peerKey(callSourceToTarget(message.source));
+            const unread = new Map(get().unread);
+            unread.set(key, (unread.get(key) ?? 0) + 1);
+
+            set({
+                history: appendEntry(get().history, key, {
+                    type: "IN",
+                    time: now(),
+                    text: message.text,
+                    clientId: message.source.clientId,
+                }),
+                unread,
+            });
+        },
+        addOutgoingMessage: (target: CallTarget, text: string) => {
+            set({
+                history: appendEntry(get().history, peerKey(target), {
+                    type: "OUT",
+                    time: now(),
+                    text,
+                }),
+            });
+        },
+        markRead: (target: CallTarget) => {
+            const key = peerKey(target);
+            if (!get().unread.has(key)) return;
+
+            const unread = new Map(get().unread);
+            unread.delete(key);
+            set({unread});
+        },
+        clear: () => set({history: new Map(), unread: new Map()}),
+    },
+}));
+
+export const useTextMessageHistory = (target: CallTarget) =>
+    useTextMessagesStore(state => state.history.get(peerKey(target))) ?? [];
+
+export const useUnreadTextMessages = (target: CallTarget) =>
+    useTextMessagesStore(state => state.unread.get(peerKey(target)) ?? 0);
+
+export const useTotalUnreadTextMessages = () =>
+    useTextMessagesStore(state => Array.from(state.unread.values()).reduce((a, b) => a + b, 0));
+
+export const useTextMessagesActions = () => useTextMessagesStore(state => state.actions);
+
+export function peerKey(target: CallTarget): string {
+    if (target.station !== undefined) {
+        return `station:${target.station}`;
+    } else if (target.position !== undefined) {
+        return `position:${target.position}`;
+    }
+    return `client:${target.client}`;
+}
+
+function appendEntry(
+    history: Map<string, TextMessageEntry[]>,
+    key: string,
+    entry: TextMessageEntry,
+): Map<string, TextMessageEntry[]> {
+    const updated = new Map(history);
+    updated.set(key, [...(history.get(key) ?? []), entry].slice(-MAX_HISTORY_PER_PEER));
+    return updated;
+}
+
+function now(): string {
+    return new Date().toLocaleString("de-AT", {
+        hour: "2-digit",
+        minute: "2-digit",
+        timeZone: "UTC",
+    });
+}
@@ -19,6 +19,12 @@ export type Call = {
     prio: boolean;
 };
 
+export type TextMessage = {
+    source: CallSource;
+    target: CallTarget;
+    text: string;
+};
+
 export function callSourceToTarget(source: CallSource): CallTarget {
     if (source.stationId !== undefined) {
         return {station: source.stationId};
@@ -6,11 +6,20 @@ export type ClientInfo = {
     positionId: PositionId | undefined;
     displayName: string;
     frequency: string;
+    presence?: Presence;
+};
+
+export type Presence = "available" | "busy" | "away" | "doNotDisturb";
+
+export type ClientPresence = {
+    clientId: ClientId;
+    presence: Presence;
 };
 
 export type SessionInfo = {
     client: ClientInfo;
     profile: SessionProfile;
+    capabilities?: string[];
 };
 
 export function splitDisplayName(name: string): [string, string] {
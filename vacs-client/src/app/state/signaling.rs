@@ -20,7 +20,7 @@ use vacs_signaling::protocol::ws::server::{
     CallCancelReason, DisconnectReason, LoginFailureReason, ServerMessage, SessionProfile,
 };
 use vacs_signaling::protocol::ws::shared::{CallErrorReason, CallId, CallSource, ErrorReason};
-use vacs_signaling::protocol::ws::{client, server, shared};
+use vacs_signaling::protocol::ws::{Encoding, client, server, shared};
 use vacs_signaling::transport::tokio::TokioTransport;
 
 const INCOMING_CALLS_LIMIT: usize = 5;
@@ -416,18 +416,37 @@ impl AppStateInner {
             SignalingEvent::Connected {
                 client_info,
                 profile,
+                resumed,
+                capabilities,
             } => {
                 log::debug!(
-                    "Successfully connected to signaling server. Display name: {}, frequency: {}, profile: {profile}",
+                    "Successfully connected to signaling server. Display name: {}, frequency: {}, profile: {profile}, resumed: {resumed}",
                     &client_info.display_name,
                     &client_info.frequency,
                 );
 
+                if resumed {
+                    // Messages concerning calls changed in the meantime have been queued by the
+                    // server and are received after the session info, updating the kept calls.
+                    log::info!("Resumed previous signaling session, keeping calls");
+                } else {
+                    // Calls of a previous session kept during the reconnect have been ended by
+                    // the server.
+                    let state = app.state::<AppState>();
+                    let mut state = state.lock().await;
+                    state.cleanup_signaling(app).await;
+                    app.emit("signaling:calls-reset", Value::Null).ok();
+                }
+
                 app.emit(
                     "signaling:connected",
                     server::SessionInfo {
                         client: client_info,
                         profile: SessionProfile::Changed(profile),
+                        resume_token: None,
+                        encoding: Encoding::Json,
+                        compression: false,
+                        capabilities,
                     },
                 )
                 .ok();
@@ -437,7 +456,16 @@ impl AppStateInner {
                 if error.is_fatal() {
                     let state = app.state::<AppState>();
                     let mut state = state.lock().await;
-                    state.handle_signaling_connection_closed(app).await;
+
+                    // The server keeps a resumable session including its calls while the client
+                    // reconnects, they are only cleaned up if the session cannot be resumed.
+                    if error.can_reconnect() && state.signaling_client.is_resumable() {
+                        log::info!(
+                            "Signaling server connection lost, keeping calls while resuming session"
+                        );
+                    } else {
+                        state.handle_signaling_connection_closed(app).await;
+                    }
 
                     if let SignalingRuntimeError::Disconnected(Some(
                         DisconnectReason::AmbiguousVatsimPosition(positions),
@@ -729,29 +757,84 @@ impl AppStateInner {
 
                 state.set_remote_ice_candidate(&call_id, candidate).await;
             }
-            ServerMessage::ClientConnected(server::ClientConnected { client }) => {
+            ServerMessage::ClientConnected(server::ClientConnected { client, .. }) => {
                 log::trace!("Client connected: {client:?}");
 
                 app.emit("signaling:client-connected", client).ok();
             }
-            ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id }) => {
+            ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id, .. }) => {
                 log::trace!("Client disconnected: {client_id:?}");
 
                 app.emit("signaling:client-disconnected", client_id).ok();
             }
-            ServerMessage::ClientList(server::ClientList { clients }) => {
+            ServerMessage::ClientList(server::ClientList { clients, .. }) => {
                 log::trace!("Received client list: {} clients connected", clients.len());
 
                 app.emit("signaling:client-list", clients).ok();
             }
-            ServerMessage::ClientInfo(info) => {
-                log::trace!("Received client info: {info:?}");
+            ServerMessage::ClientChanges(server::ClientChanges { revision, changes }) => {
+                log::trace!(
+                    "Received {} client changes up to revision {revision}",
+                    changes.len()
+                );
+
+                for change in changes {
+                    match change {
+                        server::ClientChange::Connected { client }
+                        | server::ClientChange::Updated { client } => {
+                            app.emit("signaling:client-connected", client).ok();
+                        }
+                        server::ClientChange::Disconnected { client_id } => {
+                            app.emit("signaling:client-disconnected", client_id).ok();
+                        }
+                    }
+                }
+            }
+            ServerMessage::ClientPresence(presence) => {
+                log::trace!(
+                    "Client {} changed presence to {:?}",
+                    presence.client_id,
+                    presence.presence
+                );
 
-                app.emit("signaling:client-connected", info).ok();
+                app.emit("signaling:client-presence", presence).ok();
+            }
+            ServerMessage::TextMessage(message) => {
+                log::trace!(
+                    "Received text message from {:?} for {:?}",
+                    message.source,
+                    message.target
+                );
+
+                let state = app.state::<AppState>();
+                let state = state.lock().await;
+
+                let sender_id = &message.source.client_id;
+                if state.config.client.ignored.contains(sender_id) {
+                    log::trace!("Ignoring text message from {sender_id}");
+                    return;
+                }
+
+                app.emit("signaling:text-message", message).ok();
+            }
+            ServerMessage::Announcement(announcement) => {
+                log::trace!(
+                    "Received {:?} announcement {}",
+                    announcement.severity,
+                    announcement.id
+                );
+
+                app.emit("signaling:announcement", announcement).ok();
+            }
+            ServerMessage::ClientInfo(server::ClientInfoUpdate { client, .. }) => {
+                log::trace!("Received client info: {client:?}");
+
+                app.emit("signaling:client-connected", client).ok();
             }
             ref msg @ ServerMessage::SessionInfo(server::SessionInfo {
                 ref client,
                 ref profile,
+                ..
             }) => {
                 log::trace!("Received session info for client {client:?}: {profile}");
 
@@ -859,6 +942,26 @@ impl AppStateInner {
 
                     app.emit("signaling:client-not-found", client_id).ok();
                 }
+                ErrorReason::Muted { .. } => {
+                    log::warn!("Received muted error from signaling server");
+
+                    if let Some(call_id) = call_id {
+                        let state = app.state::<AppState>();
+                        let mut state = state.lock().await;
+
+                        state.cleanup_call(&call_id).await;
+                        state.remove_outgoing_call_id(&call_id);
+
+                        app.emit("signaling:force-call-end", call_id).ok();
+                    }
+                    app.emit::<FrontendError>(
+                        "error",
+                        FrontendError::from(Error::from(SignalingRuntimeError::ServerError(
+                            reason,
+                        ))),
+                    )
+                    .ok();
+                }
             },
             ServerMessage::Disconnected(_) | ServerMessage::LoginFailure(_) => {}
         }
@@ -230,6 +230,9 @@ fn format_signaling_error(err: &SignalingError) -> String {
             LoginFailureReason::IncompatibleProtocolVersion => {
                 "Login failed: Incompatible protocol version. Please check your client version."
             }
+            LoginFailureReason::Banned { reason, .. } => {
+                return format!("Login failed: You have been banned by a supervisor: {reason}");
+            }
         }
         .to_string(),
         SignalingError::Runtime(runtime_err) => match runtime_err {
@@ -246,6 +249,9 @@ fn format_signaling_error(err: &SignalingError) -> String {
                 ErrorReason::ClientNotFound => {
                     "Server error: Client not found.".to_string()
                 }
+                ErrorReason::Muted { reason, .. } => {
+                    format!("Muted: You have been muted by a supervisor: {reason}")
+                }
             },
             SignalingRuntimeError::Disconnected(reason) => match reason {
                 None => "Disconnected",
@@ -254,6 +260,10 @@ fn format_signaling_error(err: &SignalingError) -> String {
                 Some(DisconnectReason::AmbiguousVatsimPosition(_)) => {
                     "Disconnected: Multiple VATSIM positions matched your current position. Please select the correct position manually."
                 }
+                Some(DisconnectReason::Kicked { reason }) => {
+                    return format!("Disconnected: You have been disconnected by a supervisor: {reason}");
+                }
+                Some(DisconnectReason::ServerRestart { .. }) => "Disconnected: The server is restarting.",
             }.to_string(),
             _ => runtime_err.to_string(),
         },
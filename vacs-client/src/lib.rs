@@ -165,6 +165,8 @@ pub fn run() {
             signaling::commands::signaling_end_call,
             signaling::commands::signaling_get_ignored_clients,
             signaling::commands::signaling_remove_ignored_client,
+            signaling::commands::signaling_set_presence,
+            signaling::commands::signaling_send_text_message,
             signaling::commands::signaling_start_call,
             signaling::commands::signaling_terminate,
         ])
@@ -11,8 +11,8 @@ use std::collections::HashSet;
 use tauri::{AppHandle, Manager, State};
 use vacs_signaling::protocol::http::webrtc::IceConfig;
 use vacs_signaling::protocol::vatsim::{ClientId, PositionId};
-use vacs_signaling::protocol::ws::shared;
-use vacs_signaling::protocol::ws::shared::{CallId, CallSource, CallTarget};
+use vacs_signaling::protocol::ws::shared::{CallId, CallSource, CallTarget, Presence, TextMessage};
+use vacs_signaling::protocol::ws::{client, shared};
 
 #[tauri::command]
 #[vacs_macros::log_err]
@@ -137,6 +137,47 @@ pub async fn signaling_end_call(
     Ok(())
 }
 
+#[tauri::command]
+#[vacs_macros::log_err]
+pub async fn signaling_set_presence(
+    app_state: State<'_, AppState>,
+    presence: Presence,
+) -> Result<(), Error> {
+    log::debug!("Setting presence to {presence:?}");
+
+    let mut state = app_state.lock().await;
+    state
+        .send_signaling_message(client::SetPresence { presence })
+        .await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+#[vacs_macros::log_err]
+pub async fn signaling_send_text_message(
+    app_state: State<'_, AppState>,
+    target: CallTarget,
+    source: CallSource,
+    text: String,
+) -> Result<(), Error> {
+    log::debug!("Sending text message to {target:?} as {source:?}");
+
+    let message = TextMessage::new(source, target, text);
+    if !message.is_valid() {
+        return Err(anyhow::anyhow!(
+            "Text message must not be empty or longer than {} characters",
+            TextMessage::MAX_LENGTH
+        )
+        .into());
+    }
+
+    let mut state = app_state.lock().await;
+    state.send_signaling_message(message).await?;
+
+    Ok(())
+}
+
 #[tauri::command]
 #[vacs_macros::log_err]
 pub async fn signaling_get_ignored_clients(
@@ -0,0 +1,628 @@
+// Generated by vacs-protocol, do not edit manually.
+// vacs protocol version 2.0.0
+
+/**
+ * Unique identifier for a VATSIM client (CID).
+ */
+export type ClientId = string;
+
+/**
+ * Unique identifier for a VATSIM position.
+ */
+export type PositionId = string;
+
+/**
+ * Unique identifier for a VATSIM station.
+ */
+export type StationId = string;
+
+/**
+ * Represents a change in station status (online, offline, or handoff).
+ */
+export type StationChange = { "online": { 
+/**
+ * The ID of the station that came online.
+ */
+stationId: StationId, 
+/**
+ * The ID of the position that controls the station.
+ */
+positionId: PositionId, } } | { "handoff": { 
+/**
+ * The ID of the station being handed off.
+ */
+stationId: StationId, 
+/**
+ * The ID of the position handing off control over the station.
+ */
+fromPositionId: PositionId, 
+/**
+ * The ID of the position receiving control over the station.
+ */
+toPositionId: PositionId, } } | { "offline": { 
+/**
+ * The ID of the station that went offline.
+ */
+stationId: StationId, } };
+
+/**
+ * Unique identifier for a vacs profile.
+ */
+export type ProfileId = string;
+
+/**
+ * Representation of a VACS profile.
+ */
+export type Profile = { 
+/**
+ * The unique identifier for this profile.
+ */
+id: ProfileId, } & ({ "geo": GeoPageContainer } | { "tabbed": Array<Tab> });
+
+/**
+ * The specific configuration type of a profile.
+ */
+export type ProfileType = { "geo": GeoPageContainer } | { "tabbed": Array<Tab> };
+
+/**
+ * A page containing direct access keys for stations or clients.
+ */
+export type DirectAccessPage = { 
+/**
+ * The number of rows in the grid (> 0).
+ *
+ * The default layout is optimized for 6 rows. After a seventh row is added,
+ * the space in between the rows is slightly reduced and a scrollbar might
+ * appear automatically.
+ */
+rows: number, } & ({ 
+/**
+ * The list of keys on this page.
+ *
+ * Will always be non-empty.
+ */
+keys: Array<DirectAccessKey>, } | { 
+/**
+ * Configuration for the Client page.
+ */
+clientPage: ClientPageConfig, });
+
+/**
+ * The content of a direct access page.
+ */
+export type DirectAccessPageContent = { 
+/**
+ * The list of keys on this page.
+ *
+ * Will always be non-empty.
+ */
+keys: Array<DirectAccessKey>, } | { 
+/**
+ * Configuration for the Client page.
+ */
+clientPage: ClientPageConfig, };
+
+/**
+ * A single key on a direct access page.
+ */
+export type DirectAccessKey = { 
+/**
+ * The text label displayed on the key.
+ *
+ * Will always contain between 0 and 3 lines of text.
+ */
+label: Array<string>, 
+/**
+ * The optional station ID associated with this key.
+ *
+ * If [`DirectAccessKey::station_id`] and [`DirectAccessKey::page`] are `None`, the DA key will be displayed on the UI but will be non-functional.
+ * This field is mutually exclusive with [`DirectAccessKey::page`].
+ */
+stationId?: StationId | null, 
+/**
+ * The optional subpage associated with this key.
+ *
+ * If [`DirectAccessKey::station_id`] and [`DirectAccessKey::page`] are `None`, the DA key will be displayed on the UI but will be non-functional.
+ * This field is mutually exclusive with [`DirectAccessKey::station_id`].
+ */
+page?: DirectAccessPage | null, };
+
+/**
+ * Represents the currently active profile for a user session.
+ *
+ * The active profile determines which stations are considered "relevant" and thus which
+ * status updates (online/offline/handoff) are sent to the client.
+ */
+export type ActiveProfile = { "type": "specific", "profile": Profile } | { "type": "custom" } | { "type": "none" };
+
+/**
+ * Mode for controlling how frequencies are displayed on DA keys of the Client page.
+ */
+export type FrequencyDisplayMode = "HideAll" | "ShowAll";
+
+/**
+ * Mode for controlling how DA keys are grouped on the Client page.
+ */
+export type ClientGroupMode = "None" | "Fir" | "Icao" | "FirAndIcao";
+
+/**
+ * Configuration for the Client page, displaying all currently connected clients.
+ */
+export type ClientPageConfig = { 
+/**
+ * Optional list of callsign patterns to include.
+ *
+ * - If this list is empty, all clients are eligible to be shown (subject to `exclude`).
+ * - If this list is not empty, only clients matching at least one pattern are eligible to be shown.
+ *
+ * Glob syntax is supported: `"LO*"`, `"LOWW_*"`, `"*_APP"`, …
+ * Matching is case-insensitive.
+ *
+ * Example:
+ *   `["LO*", "EDDM_*", "EDMM_*"]`
+ */
+include?: Array<string>, 
+/**
+ * Optional list of callsign patterns to exclude.
+ *
+ * - Clients matching any pattern here are never shown, even if they match an `include` rule.
+ *
+ * Glob syntax is supported: `"LO*"`, `"LOWW_*"`, `"*_APP"`, …
+ * Matching is case-insensitive.
+ *
+ * Example:
+ *   `["*_TWR", "*_GND", "*_DEL"]`
+ */
+exclude?: Array<string>, 
+/**
+ * Optional ordered list of callsign patterns used to assign priority.
+ *
+ * The *first* matching pattern in the list determines the client's
+ * priority bucket. Earlier entries = higher priority.
+ *
+ * Glob syntax is supported: `"LO*"`, `"LOWW_*"`, `"*_APP"`, …
+ * Matching is case-insensitive.
+ *
+ * Example:
+ *   `["LOVV_*", "LOWW_*_APP", "LOWW_*_TWR", "LOWW_*"]`
+ */
+priority?: Array<string>, 
+/**
+ * Control how frequencies are displayed on the DA keys.
+ *
+ * - `ShowAll`: Show frequency for all clients (default).
+ * - `HideAll`: Never show frequencies.
+ */
+frequencies: FrequencyDisplayMode, 
+/**
+ * Control how DA keys are grouped.
+ *
+ * - `None`: Don't group.
+ * - `Fir`: Group by the first two letters (FIR) of the display name.
+ * - `FirAndIcao`: First, group by the first two letters (FIR), then by the first four letters
+ *   (ICAO code) of the display name (default).
+ * - `Icao`: Group by the first four letters (ICAO code) of the display name.
+ */
+grouping: ClientGroupMode, };
+
+export type GeoPageContainer = { 
+/**
+ * The height of the container.
+ *
+ * Must either be defined as a percentage or a rem value (e.g. "100%", "5rem").
+ */
+height?: string | null, 
+/**
+ * The width of the container.
+ *
+ * Must either be defined as a percentage or a rem value (e.g. "100%", "5rem").
+ */
+width?: string | null, 
+/**
+ * The padding for all sides (in rem).
+ */
+padding?: number | null, 
+/**
+ * The left padding (in rem).
+ */
+paddingLeft?: number | null, 
+/**
+ * The right padding (in rem).
+ */
+paddingRight?: number | null, 
+/**
+ * The top padding (in rem).
+ */
+paddingTop?: number | null, 
+/**
+ * The bottom padding (in rem).
+ */
+paddingBottom?: number | null, 
+/**
+ * The gap between children (in rem).
+ */
+gap?: number | null, 
+/**
+ * The justification of the content along the main axis.
+ */
+justifyContent?: JustifyContent | null, 
+/**
+ * The alignment of items along the cross-axis.
+ */
+alignItems?: AlignItems | null, 
+/**
+ * The direction of the flex container.
+ */
+direction: FlexDirection, 
+/**
+ * The children of this container.
+ */
+children: Array<GeoNode>, };
+
+export type JustifyContent = "start" | "end" | "space-between" | "space-around" | "space-evenly" | "center";
+
+export type AlignItems = "start" | "end" | "center";
+
+export type FlexDirection = "row" | "col";
+
+export type GeoNode = GeoPageContainer | GeoPageButton | GeoPageDivider;
+
+/**
+ * A button on a GEO profile page.
+ */
+export type GeoPageButton = { 
+/**
+ * The text label displayed on the button.
+ *
+ * Will always contain between 0 and 3 lines of text.
+ */
+label: Array<string>, 
+/**
+ * The size of the button (> 0, in rem).
+ */
+size: number, 
+/**
+ * The optional direct access page that opens when this button is clicked.
+ *
+ * If [`GeoPageButton::page`] and [`GeoPageButton::station_id`] are `None`, the button will be displayed and clickable on the UI, but will otherwise be non-functional.
+ * This field is mutually exclusive with [`GeoPageButton::station_id`].
+ */
+page?: DirectAccessPage | null, 
+/**
+ * The optional station ID associated with this button.
+ *
+ * If [`GeoPageButton::page`] and [`GeoPageButton::station_id`] are `None`, the button will be displayed and clickable on the UI, but will otherwise be non-functional.
+ * This field is mutually exclusive with [`GeoPageButton::page`].
+ */
+stationId?: StationId | null, };
+
+export type GeoPageDivider = { 
+/**
+ * The orientation of the divider.
+ */
+orientation: GeoPageDividerOrientation, 
+/**
+ * The thickness of the divider (> 0, in px).
+ */
+thickness: number, 
+/**
+ * The color of the divider (CSS color).
+ */
+color: string, 
+/**
+ * The oversize of the divider (> 0, in rem).
+ */
+oversize?: number | null, };
+
+export type GeoPageDividerOrientation = "horizontal" | "vertical";
+
+export type Tab = { 
+/**
+ * The label of the tab.
+ *
+ * Will always contain between 1 and 3 lines of text.
+ */
+label: Array<string>, 
+/**
+ * The direct access page that opens when this tab is clicked.
+ */
+page: DirectAccessPage, };
+
+export type ErrorResponse = { message: string, };
+
+export type InitVatsimLogin = { url: string, };
+
+export type AuthExchangeToken = { code: string, state: string, };
+
+export type UserInfo = { cid: ClientId, };
+
+export type AuthTokenResponse = { cid: ClientId, token: string, };
+
+export type Release = { version: string, required: boolean, url: string, signature: string, notes: string | null, pub_date: string | null, };
+
+export type ReleaseChannel = "stable" | "beta" | "rc";
+
+export type IceServer = { urls: Array<string>, username: string | null, credential: string | null, };
+
+export type IceConfig = { ice_servers: Array<IceServer>, 
+/**
+ * Expiry as Unix timestamp (seconds since epoch).
+ */
+expires_at?: number | null, };
+
+export type WebSocketToken = { token: string, };
+
+/**
+ * Wire encoding of [`ClientMessage`]s and [`ServerMessage`]s.
+ *
+ * The encoding is requested by the client in its [`client::Login`] and confirmed by the server in
+ * the [`server::SessionInfo`], after which both sides use it for all further messages. JSON
+ * messages are sent as websocket text frames and MessagePack messages as binary frames, so the
+ * receiver can always decode a message based on its frame type.
+ */
+export type Encoding = "json" | "messagePack";
+
+export type Message = { "type": "client" } & ClientMessage | { "type": "server" } & ServerMessage;
+
+export type ClientMessage = { "type": "login" } & Login | { "type": "logout" } | { "type": "callInvite" } & CallInvite | { "type": "callAccept" } & CallAccept | { "type": "callEnd" } & CallEnd | { "type": "callReject" } & CallReject | { "type": "callError" } & CallError | { "type": "webrtcOffer" } & WebrtcOffer | { "type": "webrtcAnswer" } & WebrtcAnswer | { "type": "webrtcIceCandidate" } & WebrtcIceCandidate | { "type": "listClients" } | { "type": "listClientChanges" } & ListClientChanges | { "type": "listStations" } | { "type": "setPresence" } & SetPresence | { "type": "textMessage" } & TextMessage | { "type": "kickClient" } & KickClient | { "type": "banClient" } & BanClient | { "type": "muteClient" } & MuteClient | { "type": "disconnect" } | { "type": "error" } & Error;
+
+export type Login = { token: string, protocolVersion: string, customProfile: boolean, positionId: PositionId | null, 
+/**
+ * Resume token of a previous session, received in its [`crate::ws::server::SessionInfo`].
+ * If the previous session is still within its grace period, it is resumed instead of
+ * creating a new one, keeping all calls and queued messages.
+ */
+resumeToken?: string | null, 
+/**
+ * Encoding requested for all messages after the login. The server confirms the encoding in
+ * its [`crate::ws::server::SessionInfo`], older servers fall back to JSON.
+ */
+encoding?: Encoding, 
+/**
+ * Requests compression of all messages sent by the server after the login, see
+ * [`crate::ws::compress`]. Confirmed in the [`crate::ws::server::SessionInfo`], as servers
+ * may have compression disabled.
+ *
+ * Compression is one-way: messages sent by the client are never compressed. It is applied on
+ * the application level since the websocket stack doesn't support permessage-deflate, and
+ * only pays off for large server messages like client and station lists.
+ */
+compression?: boolean, 
+/**
+ * Capabilities supported by the client. The negotiated subset is confirmed in the
+ * [`crate::ws::server::SessionInfo`].
+ */
+capabilities?: Capabilities, };
+
+export type CallReject = { callId: CallId, rejectingClientId: ClientId, reason: CallRejectReason, };
+
+export type CallRejectReason = "busy" | "doNotDisturb";
+
+/**
+ * Requests all roster changes since the given revision, e.g. to catch up after a brief
+ * reconnect. Answered with a [`crate::ws::server::ClientChanges`] or, if the revision is too old,
+ * a full [`crate::ws::server::ClientList`].
+ */
+export type ListClientChanges = { 
+/**
+ * Last roster revision known to the client.
+ */
+sinceRevision: number, };
+
+/**
+ * Sets the presence of the client, broadcast to all other clients as
+ * [`crate::ws::server::ClientPresence`].
+ */
+export type SetPresence = { presence: Presence, };
+
+/**
+ * Disconnects a client. Only accepted from supervisors.
+ */
+export type KickClient = { clientId: ClientId, reason: string, };
+
+/**
+ * Bans a client from logging in for the given duration, disconnecting it if it is connected.
+ * Only accepted from supervisors.
+ */
+export type BanClient = { clientId: ClientId, reason: string, durationSecs: number, };
+
+/**
+ * Prevents a client from placing calls and sending text messages for the given duration.
+ * Only accepted from supervisors.
+ */
+export type MuteClient = { clientId: ClientId, reason: string, durationSecs: number, };
+
+export type ServerMessage = { "type": "loginFailure" } & LoginFailure | { "type": "callInvite" } & CallInvite | { "type": "callAccept" } & CallAccept | { "type": "callEnd" } & CallEnd | { "type": "callCancelled" } & CallCancelled | { "type": "callError" } & CallError | { "type": "webrtcOffer" } & WebrtcOffer | { "type": "webrtcAnswer" } & WebrtcAnswer | { "type": "webrtcIceCandidate" } & WebrtcIceCandidate | { "type": "clientInfo" } & ClientInfoUpdate | { "type": "sessionInfo" } & SessionInfo | { "type": "clientConnected" } & ClientConnected | { "type": "clientDisconnected" } & ClientDisconnected | { "type": "clientList" } & ClientList | { "type": "clientChanges" } & ClientChanges | { "type": "clientPresence" } & ClientPresence | { "type": "textMessage" } & TextMessage | { "type": "announcement" } & Announcement | { "type": "stationList" } & StationList | { "type": "stationChanges" } & StationChanges | { "type": "disconnected" } & Disconnected | { "type": "error" } & Error;
+
+/**
+ * Notice broadcast by the server admins to all connected clients, e.g. announcing a server
+ * restart or a newly deployed dataset. Clients display it until it expires.
+ */
+export type Announcement = { 
+/**
+ * Unique ID, allowing clients to deduplicate announcements sent again after a reconnect.
+ */
+id: string, severity: AnnouncementSeverity, message: string, 
+/**
+ * Expiry as Unix timestamp (seconds since epoch). Until then, the announcement is also sent
+ * to clients connecting later. Announcements without expiry are only sent to the clients
+ * connected at the time and shown until dismissed.
+ */
+expiresAt?: number | null, };
+
+export type AnnouncementSeverity = "info" | "warning" | "critical";
+
+export type LoginFailure = { reason: LoginFailureReason, };
+
+export type LoginFailureReason = "unauthorized" | "duplicateId" | "invalidCredentials" | "noActiveVatsimConnection" | { "ambiguousVatsimPosition": Array<PositionId> } | "invalidVatsimPosition" | "timeout" | "incompatibleProtocolVersion" | { "banned": { reason: string, expiresAt: number, } };
+
+export type Disconnected = { reason: DisconnectReason, };
+
+export type DisconnectReason = "terminated" | "noActiveVatsimConnection" | { "ambiguousVatsimPosition": Array<PositionId> } | { "kicked": { reason: string, } } | { "serverRestart": { retryAfter: number, } };
+
+export type CallCancelled = { callId: CallId, reason: CallCancelReason, };
+
+export type CallCancelReason = { "answeredElsewhere": ClientId } | "callerCancelled" | "disconnected" | { "errored": CallErrorReason } | { "rejected": CallRejectReason };
+
+export type SessionProfile = { "type": "unchanged" } | { "type": "changed", "activeProfile": ActiveProfile };
+
+export type SessionInfo = { client: ClientInfo, profile: SessionProfile, 
+/**
+ * Token allowing the client to resume this session after losing its connection. Only
+ * included in the initial session info sent after login, which contains the same token
+ * again if a previous session was resumed.
+ */
+resumeToken?: string | null, 
+/**
+ * Encoding used for all messages sent after the login, including this one. Confirms the
+ * encoding requested in the client's [`crate::ws::client::Login`] and, like the resume
+ * token, only set in the initial session info.
+ */
+encoding?: Encoding, 
+/**
+ * Whether all messages sent after this session info are compressed, see
+ * [`crate::ws::compress`]. Only set in the initial session info.
+ */
+compression?: boolean, 
+/**
+ * Capabilities negotiated for this session, i.e. the capabilities requested in the client's
+ * [`crate::ws::client::Login`] which are supported by the server. Only set in the initial
+ * session info.
+ */
+capabilities?: Capabilities, };
+
+export type ClientInfo = { id: ClientId, displayName: string, frequency: string, positionId?: PositionId | null, presence?: Presence, };
+
+/**
+ * Updated info of a connected client, broadcast when its position or frequency changes.
+ */
+export type ClientInfoUpdate = { 
+/**
+ * Roster revision of this change, see [`ClientChanges`].
+ */
+revision: number, id: ClientId, displayName: string, frequency: string, positionId?: PositionId | null, presence?: Presence, };
+
+export type ClientConnected = { client: ClientInfo, 
+/**
+ * Roster revision of this change, see [`ClientChanges`].
+ */
+revision: number, };
+
+export type ClientDisconnected = { clientId: ClientId, 
+/**
+ * Roster revision of this change, see [`ClientChanges`].
+ */
+revision: number, };
+
+export type ClientList = { clients: Array<ClientInfo>, 
+/**
+ * Roster revision the list is up to date with. Changes since this revision can be requested
+ * using [`crate::ws::client::ListClientChanges`].
+ */
+revision: number, };
+
+/**
+ * Represents a change to the roster of connected clients.
+ */
+export type ClientChange = { "connected": { 
+/**
+ * Info of the connected client.
+ */
+client: ClientInfo, } } | { "updated": { 
+/**
+ * Updated info of the client.
+ */
+client: ClientInfo, } } | { "disconnected": { 
+/**
+ * The ID of the disconnected client.
+ */
+clientId: ClientId, } };
+
+/**
+ * Changes to the roster since the revision requested using
+ * [`crate::ws::client::ListClientChanges`].
+ *
+ * Every roster change increments the server's roster revision, which is included in the
+ * [`ClientConnected`], [`ClientDisconnected`] and [`ClientInfoUpdate`] broadcasts as well as in
+ * the [`ClientList`]. If the requested revision is too old to be covered by the server's change
+ * history, a full [`ClientList`] is sent instead.
+ */
+export type ClientChanges = { 
+/**
+ * Current roster revision, including all listed changes.
+ */
+revision: number, 
+/**
+ * Changes in the order they were applied, excluding changes of the requesting client.
+ */
+changes: Array<ClientChange>, };
+
+/**
+ * Changed presence of a connected client, broadcast after it sent a
+ * [`crate::ws::client::SetPresence`]. Recorded in the roster as [`ClientChange::Updated`].
+ */
+export type ClientPresence = { clientId: ClientId, presence: Presence, 
+/**
+ * Roster revision of this change, see [`ClientChanges`].
+ */
+revision: number, };
+
+export type StationInfo = { id: StationId, own: boolean, };
+
+export type StationList = { stations: Array<StationInfo>, };
+
+export type StationChanges = { changes: Array<StationChange>, };
+
+/**
+ * Optional protocol feature, negotiated during login.
+ *
+ * Clients list the capabilities they support in their [`crate::ws::client::Login`], the server
+ * replies with the subset it supports as well in the initial [`crate::ws::server::SessionInfo`].
+ * Message variants belonging to a capability are only exchanged if it was negotiated, so new
+ * message types can be rolled out without forcing all clients to update at once.
+ */
+export type Capability = "client-changes" | "presence" | "text-messages" | "announcements" | "unknown";
+
+/**
+ * Set of [`Capability`]s supported by one side or negotiated for a session.
+ */
+export type Capabilities = Array<Capability>;
+
+/**
+ * Availability of a controller for calls, set by the client and shown to all other clients.
+ */
+export type Presence = "available" | "busy" | "away" | "doNotDisturb";
+
+/**
+ * Short text message between controllers, e.g. to coordinate a runway release.
+ *
+ * Routed like a [`crate::ws::shared::CallInvite`]: the server delivers it to every client
+ * covering the target.
+ */
+export type TextMessage = { source: CallSource, target: CallTarget, text: string, };
+
+export type CallId = string;
+
+export type CallSource = { clientId: ClientId, positionId?: PositionId | null, stationId?: StationId | null, };
+
+export type CallTarget = { "client": ClientId } | { "position": PositionId } | { "station": StationId };
+
+export type CallInvite = { callId: CallId, source: CallSource, target: CallTarget, prio: boolean, };
+
+export type CallAccept = { callId: CallId, acceptingClientId: ClientId, };
+
+export type CallEnd = { callId: CallId, endingClientId: ClientId, };
+
+export type CallError = { callId: CallId, reason: CallErrorReason, message?: string | null, };
+
+export type CallErrorReason = "targetNotFound" | "callActive" | "webrtcFailure" | "audioFailure" | "callFailure" | "signalingFailure" | "autoHangup" | "other";
+
+export type Error = { reason: ErrorReason, clientId?: ClientId | null, callId?: CallId | null, };
+
+export type ErrorReason = "malformedMessage" | { "internal": string } | "peerConnection" | { "unexpectedMessage": string } | { "rateLimited": { retry_after_secs: number, } } | "clientNotFound" | { "muted": { reason: string, expiresAt: number, } };
+
+export type WebrtcOffer = { callId: CallId, fromClientId: ClientId, toClientId: ClientId, sdp: string, };
+
+export type WebrtcAnswer = { callId: CallId, fromClientId: ClientId, toClientId: ClientId, sdp: string, };
+
+export type WebrtcIceCandidate = { callId: CallId, fromClientId: ClientId, toClientId: ClientId, candidate: string, };
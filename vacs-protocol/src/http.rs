@@ -10,6 +10,7 @@ pub mod webrtc;
 pub mod ws;
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 pub struct ErrorResponse {
     pub message: String,
 }
@@ -2,6 +2,8 @@
 pub mod http;
 #[cfg(feature = "profile")]
 pub mod profile;
+#[cfg(feature = "schema")]
+pub mod schema;
 #[cfg(feature = "vatsim")]
 pub mod vatsim;
 #[cfg(feature = "ws")]
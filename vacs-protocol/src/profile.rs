@@ -9,11 +9,13 @@ use serde::{Deserialize, Serialize};
 
 /// Unique identifier for a vacs profile.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[repr(transparent)]
 pub struct ProfileId(String);
 
 /// Representation of a VACS profile.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct Profile {
     /// The unique identifier for this profile.
@@ -26,6 +28,7 @@ pub struct Profile {
 
 /// The specific configuration type of a profile.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub enum ProfileType {
     /// A GEO profile with a container-based layout.
@@ -39,6 +42,7 @@ pub enum ProfileType {
 
 /// A page containing direct access keys for stations or clients.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct DirectAccessPage {
     /// The number of rows in the grid (> 0).
@@ -55,6 +59,7 @@ pub struct DirectAccessPage {
 
 /// The content of a direct access page.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase", untagged)]
 pub enum DirectAccessPageContent {
     /// A page containing a grid of direct access keys.
@@ -75,6 +80,7 @@ pub enum DirectAccessPageContent {
 
 /// A single key on a direct access page.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct DirectAccessKey {
     /// The text label displayed on the key.
@@ -137,6 +143,8 @@ impl ProfileReference for Profile {}
 /// The active profile determines which stations are considered "relevant" and thus which
 /// status updates (online/offline/handoff) are sent to the client.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[cfg_attr(feature = "schema", ts(concrete(T = Profile)))]
 #[serde(rename_all = "camelCase", tag = "type", content = "profile")]
 pub enum ActiveProfile<T: ProfileReference> {
     /// A specific, pre-defined profile is active.
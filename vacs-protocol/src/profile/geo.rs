@@ -3,6 +3,7 @@ use crate::vatsim::StationId;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct GeoPageContainer {
     /// The height of the container.
@@ -57,6 +58,7 @@ pub struct GeoPageContainer {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "kebab-case")]
 pub enum JustifyContent {
     /// The items are packed flush to each other toward the start edge of the alignment container in the main axis.
@@ -80,6 +82,7 @@ pub enum JustifyContent {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "kebab-case")]
 pub enum AlignItems {
     /// The items are packed flush to each other toward the start edge of the alignment container in the appropriate axis.
@@ -92,6 +95,7 @@ pub enum AlignItems {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "kebab-case")]
 pub enum FlexDirection {
     /// The flex container's main axis is the same as the text direction.
@@ -101,6 +105,7 @@ pub enum FlexDirection {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(untagged)]
 pub enum GeoNode {
     /// A recursive container for grouping other nodes.
@@ -113,6 +118,7 @@ pub enum GeoNode {
 
 /// A button on a GEO profile page.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct GeoPageButton {
     /// The text label displayed on the button.
@@ -140,6 +146,7 @@ pub struct GeoPageButton {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 pub struct GeoPageDivider {
     /// The orientation of the divider.
     pub orientation: GeoPageDividerOrientation,
@@ -156,6 +163,7 @@ pub struct GeoPageDivider {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "kebab-case")]
 pub enum GeoPageDividerOrientation {
     /// The divider runs horizontally.
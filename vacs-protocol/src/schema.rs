@@ -0,0 +1,214 @@
+//! JSON Schema and TypeScript definitions of all public protocol types.
+//!
+//! The generated definitions are checked in at `vacs-protocol/schema`, so the frontend and
+//! third-party tools can use them without building vacs. They are verified by a test, which fails
+//! as soon as the checked-in definitions drift from the Rust types. After changing any protocol
+//! type, regenerate them by running:
+//!
+//! ```sh
+//! VACS_UPDATE_SCHEMA=1 cargo test -p vacs-protocol --features schema
+//! ```
+use schemars::generate::SchemaSettings;
+use serde_json::json;
+
+/// Path of the checked-in JSON Schema, relative to the crate root.
+pub const JSON_SCHEMA_PATH: &str = "schema/protocol.schema.json";
+/// Path of the checked-in TypeScript definitions, relative to the crate root.
+pub const TYPESCRIPT_PATH: &str = "schema/protocol.ts";
+
+/// Invokes the given macro with all public protocol types.
+macro_rules! protocol_types {
+    ($callback:ident) => {
+        $callback! {
+            crate::vatsim::ClientId,
+            crate::vatsim::PositionId,
+            crate::vatsim::StationId,
+            crate::vatsim::StationChange,
+            crate::profile::ProfileId,
+            crate::profile::Profile,
+            crate::profile::ProfileType,
+            crate::profile::DirectAccessPage,
+            crate::profile::DirectAccessPageContent,
+            crate::profile::DirectAccessKey,
+            crate::profile::ActiveProfile<crate::profile::Profile>,
+            crate::profile::client_page::FrequencyDisplayMode,
+            crate::profile::client_page::ClientGroupMode,
+            crate::profile::client_page::ClientPageConfig,
+            crate::profile::geo::GeoPageContainer,
+            crate::profile::geo::JustifyContent,
+            crate::profile::geo::AlignItems,
+            crate::profile::geo::FlexDirection,
+            crate::profile::geo::GeoNode,
+            crate::profile::geo::GeoPageButton,
+            crate::profile::geo::GeoPageDivider,
+            crate::profile::geo::GeoPageDividerOrientation,
+            crate::profile::tabbed::Tab,
+            crate::http::ErrorResponse,
+            crate::http::auth::InitVatsimLogin,
+            crate::http::auth::AuthExchangeToken,
+            crate::http::auth::UserInfo,
+            crate::http::auth::AuthTokenResponse,
+            crate::http::version::Release,
+            crate::http::version::ReleaseChannel,
+            crate::http::webrtc::IceServer,
+            crate::http::webrtc::IceConfig,
+            crate::http::ws::WebSocketToken,
+            crate::ws::Encoding,
+            crate::ws::Message,
+            crate::ws::client::ClientMessage,
+            crate::ws::client::Login,
+            crate::ws::client::CallReject,
+            crate::ws::client::CallRejectReason,
+            crate::ws::client::ListClientChanges,
+            crate::ws::client::SetPresence,
+            crate::ws::client::KickClient,
+            crate::ws::client::BanClient,
+            crate::ws::client::MuteClient,
+            crate::ws::server::ServerMessage,
+            crate::ws::server::Announcement,
+            crate::ws::server::AnnouncementSeverity,
+            crate::ws::server::LoginFailure,
+            crate::ws::server::LoginFailureReason,
+            crate::ws::server::Disconnected,
+            crate::ws::server::DisconnectReason,
+            crate::ws::server::CallCancelled,
+            crate::ws::server::CallCancelReason,
+            crate::ws::server::SessionProfile,
+            crate::ws::server::SessionInfo,
+            crate::ws::server::ClientInfo,
+            crate::ws::server::ClientInfoUpdate,
+            crate::ws::server::ClientConnected,
+            crate::ws::server::ClientDisconnected,
+            crate::ws::server::ClientList,
+            crate::ws::server::ClientChange,
+            crate::ws::server::ClientChanges,
+            crate::ws::server::ClientPresence,
+            crate::ws::server::StationInfo,
+            crate::ws::server::StationList,
+            crate::ws::server::StationChanges,
+            crate::ws::shared::Capability,
+            crate::ws::shared::Capabilities,
+            crate::ws::shared::Presence,
+            crate::ws::shared::TextMessage,
+            crate::ws::shared::CallId,
+            crate::ws::shared::CallSource,
+            crate::ws::shared::CallTarget,
+            crate::ws::shared::CallInvite,
+            crate::ws::shared::CallAccept,
+            crate::ws::shared::CallEnd,
+            crate::ws::shared::CallError,
+            crate::ws::shared::CallErrorReason,
+            crate::ws::shared::Error,
+            crate::ws::shared::ErrorReason,
+            crate::ws::shared::WebrtcOffer,
+            crate::ws::shared::WebrtcAnswer,
+            crate::ws::shared::WebrtcIceCandidate,
+        }
+    };
+}
+
+/// Generates a JSON Schema containing the definitions of all public protocol types in `$defs`.
+pub fn json_schema() -> serde_json::Value {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    let mut inlined = serde_json::Map::new();
+
+    macro_rules! add_definitions {
+        ($($ty:ty),* $(,)?) => {
+            $(
+                let schema = generator.subschema_for::<$ty>();
+                if schema.get("$ref").is_none() {
+                    inlined.insert(
+                        <$ty as schemars::JsonSchema>::schema_name().into_owned(),
+                        schema.to_value(),
+                    );
+                }
+            )*
+        };
+    }
+    protocol_types!(add_definitions);
+
+    let mut definitions = generator.take_definitions(true);
+    definitions.extend(inlined);
+
+    let mut schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "vacs-protocol",
+        "description": format!("vacs protocol version {}", crate::VACS_PROTOCOL_VERSION),
+        "$defs": definitions,
+    });
+    sort_keys(&mut schema);
+    schema
+}
+
+/// Recursively sorts all object keys, so the generated schema is stable regardless of whether
+/// `serde_json` preserves insertion order.
+fn sort_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.sort_keys();
+            map.values_mut().for_each(sort_keys);
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
+
+/// Generates TypeScript declarations of all public protocol types.
+pub fn typescript() -> String {
+    let mut out = format!(
+        "// Generated by vacs-protocol, do not edit manually.\n// vacs protocol version {}\n",
+        crate::VACS_PROTOCOL_VERSION
+    );
+
+    macro_rules! add_declarations {
+        ($($ty:ty),* $(,)?) => {
+            $(
+                out.push('\n');
+                if let Some(docs) = <$ty as ts_rs::TS>::docs() {
+                    out.push_str(&docs);
+                }
+                out.push_str("export ");
+                out.push_str(&<$ty as ts_rs::TS>::decl());
+                out.push('\n');
+            )*
+        };
+    }
+    protocol_types!(add_declarations);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn check_generated(path: &str, generated: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+
+        if std::env::var_os("VACS_UPDATE_SCHEMA").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, generated).unwrap();
+            return;
+        }
+
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "{} is out of date, regenerate it by running `VACS_UPDATE_SCHEMA=1 cargo test -p vacs-protocol --features schema`",
+            path.display()
+        );
+    }
+
+    #[test]
+    fn json_schema_is_up_to_date() {
+        let mut generated = serde_json::to_string_pretty(&json_schema()).unwrap();
+        generated.push('\n');
+        check_generated(JSON_SCHEMA_PATH, &generated);
+    }
+
+    #[test]
+    fn typescript_is_up_to_date() {
+        check_generated(TYPESCRIPT_PATH, &typescript());
+    }
+}
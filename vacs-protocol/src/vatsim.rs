@@ -2,21 +2,25 @@ use serde::{Deserialize, Serialize};
 
 /// Unique identifier for a VATSIM client (CID).
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[repr(transparent)]
 pub struct ClientId(String);
 
 /// Unique identifier for a VATSIM position.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[repr(transparent)]
 pub struct PositionId(String);
 
 /// Unique identifier for a VATSIM station.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[repr(transparent)]
 pub struct StationId(String);
 
 /// Represents a change in station status (online, offline, or handoff).
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub enum StationChange {
     /// A station has come online.
@@ -5,9 +5,36 @@ pub mod shared;
 use crate::ws::client::ClientMessage;
 use crate::ws::server::ServerMessage;
 use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// Maximum size of a decompressed message, protecting receivers against decompression bombs.
+pub const MAX_DECOMPRESSED_MESSAGE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Wire encoding of [`ClientMessage`]s and [`ServerMessage`]s.
+///
+/// The encoding is requested by the client in its [`client::Login`] and confirmed by the server in
+/// the [`server::SessionInfo`], after which both sides use it for all further messages. JSON
+/// messages are sent as websocket text frames and MessagePack messages as binary frames, so the
+/// receiver can always decode a message based on its frame type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub enum Encoding {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl Encoding {
+    pub fn is_json(&self) -> bool {
+        matches!(self, Encoding::Json)
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase", tag = "type")]
+#[allow(clippy::large_enum_variant)]
 pub enum Message {
     Client(ClientMessage),
     Server(ServerMessage),
@@ -38,3 +65,461 @@ impl From<ServerMessage> for Message {
         Self::Server(value)
     }
 }
+
+/// Compresses an encoded message using DEFLATE.
+///
+/// Compression is negotiated at login (see [`client::Login::compression`]) and applied per message
+/// by vacs itself instead of relying on the permessage-deflate websocket extension, so it does not
+/// depend on the websocket implementations of server and client. Compressed messages are always
+/// sent as binary frames, containing the message in the negotiated [`Encoding`].
+pub fn compress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder =
+        flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Decompresses a message compressed with [`compress`], failing if the decompressed message
+/// exceeds [`MAX_DECOMPRESSED_MESSAGE_SIZE`].
+pub fn decompress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    flate2::read::DeflateDecoder::new(data)
+        .take(MAX_DECOMPRESSED_MESSAGE_SIZE + 1)
+        .read_to_end(&mut decompressed)?;
+    if decompressed.len() as u64 > MAX_DECOMPRESSED_MESSAGE_SIZE {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Decompressed message exceeds maximum size",
+        ));
+    }
+    Ok(decompressed)
+}
+
+/// Serializes a message to MessagePack, using maps with field names for structs and the same
+/// human-readable representation of values as JSON. This keeps the format compatible with
+/// internally tagged and flattened types, which require self-describing input.
+pub(crate) fn to_msgpack<T: Serialize>(value: &T) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    let mut buf = Vec::new();
+    value.serialize(
+        &mut rmp_serde::Serializer::new(&mut buf)
+            .with_struct_map()
+            .with_human_readable(),
+    )?;
+    Ok(buf)
+}
+
+pub(crate) fn from_msgpack<'a, T: Deserialize<'a>>(
+    b: &'a [u8],
+) -> Result<T, rmp_serde::decode::Error> {
+    T::deserialize(&mut rmp_serde::Deserializer::from_read_ref(b).with_human_readable())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{ActiveProfile, Profile, ProfileId, ProfileType};
+    use crate::vatsim::{ClientId, PositionId, StationChange, StationId};
+    use crate::ws::shared::{
+        CallAccept, CallEnd, CallError, CallErrorReason, CallId, CallInvite, CallSource,
+        CallTarget, Capabilities, Error, ErrorReason, Presence, TextMessage, WebrtcAnswer,
+        WebrtcIceCandidate, WebrtcOffer,
+    };
+
+    fn call_invite() -> CallInvite {
+        CallInvite {
+            call_id: CallId::new(),
+            source: CallSource::new(ClientId::from("client1"))
+                .with_position(PositionId::from("LOVV_CTR"))
+                .with_station(StationId::from("LOWW_APP")),
+            target: CallTarget::Station(StationId::from("LOWW_TWR")),
+            prio: true,
+        }
+    }
+
+    fn client_info() -> server::ClientInfo {
+        server::ClientInfo {
+            id: ClientId::from("client1"),
+            display_name: "LOVV_CTR".to_string(),
+            frequency: "132.600".to_string(),
+            position_id: Some(PositionId::from("LOVV_CTR")),
+            presence: Presence::DoNotDisturb,
+        }
+    }
+
+    fn webrtc_offer() -> WebrtcOffer {
+        WebrtcOffer {
+            call_id: CallId::new(),
+            from_client_id: ClientId::from("client1"),
+            to_client_id: ClientId::from("client2"),
+            sdp: "v=0".to_string(),
+        }
+    }
+
+    fn webrtc_answer() -> WebrtcAnswer {
+        WebrtcAnswer {
+            call_id: CallId::new(),
+            from_client_id: ClientId::from("client2"),
+            to_client_id: ClientId::from("client1"),
+            sdp: "v=0".to_string(),
+        }
+    }
+
+    fn webrtc_ice_candidate() -> WebrtcIceCandidate {
+        WebrtcIceCandidate {
+            call_id: CallId::new(),
+            from_client_id: ClientId::from("client1"),
+            to_client_id: ClientId::from("client2"),
+            candidate: "candidate:1 1 udp 2122260223 192.0.2.1 54321 typ host".to_string(),
+        }
+    }
+
+    fn text_message() -> TextMessage {
+        TextMessage::new(
+            CallSource::new(ClientId::from("client1")).with_station(StationId::from("LOWW_TWR")),
+            CallTarget::Station(StationId::from("LOWW_APP")),
+            "request release RWY 29",
+        )
+    }
+
+    fn error() -> Error {
+        Error::new(ErrorReason::RateLimited {
+            retry_after_secs: 5,
+        })
+        .with_client_id(ClientId::from("client2"))
+        .with_call_id(CallId::new())
+    }
+
+    fn client_messages() -> Vec<ClientMessage> {
+        let messages = vec![
+            ClientMessage::Login(client::Login {
+                token: "token".to_string(),
+                protocol_version: crate::VACS_PROTOCOL_VERSION.to_string(),
+                custom_profile: false,
+                position_id: Some(PositionId::from("LOVV_CTR")),
+                resume_token: Some("resume".to_string()),
+                encoding: Encoding::MessagePack,
+                compression: true,
+                capabilities: Capabilities::all(),
+            }),
+            ClientMessage::Logout,
+            ClientMessage::CallInvite(call_invite()),
+            ClientMessage::CallAccept(CallAccept {
+                call_id: CallId::new(),
+                accepting_client_id: ClientId::from("client2"),
+            }),
+            ClientMessage::CallEnd(CallEnd::new(CallId::new(), ClientId::from("client1"))),
+            ClientMessage::CallReject(client::CallReject {
+                call_id: CallId::new(),
+                rejecting_client_id: ClientId::from("client2"),
+                reason: client::CallRejectReason::Busy,
+            }),
+            ClientMessage::CallError(CallError {
+                call_id: CallId::new(),
+                reason: CallErrorReason::WebrtcFailure,
+                message: Some("ICE failed".to_string()),
+            }),
+            ClientMessage::WebrtcOffer(webrtc_offer()),
+            ClientMessage::WebrtcAnswer(webrtc_answer()),
+            ClientMessage::WebrtcIceCandidate(webrtc_ice_candidate()),
+            ClientMessage::ListClients,
+            ClientMessage::ListClientChanges(client::ListClientChanges { since_revision: 3 }),
+            ClientMessage::ListStations,
+            ClientMessage::SetPresence(client::SetPresence {
+                presence: Presence::Away,
+            }),
+            ClientMessage::TextMessage(text_message()),
+            ClientMessage::KickClient(client::KickClient {
+                client_id: ClientId::from("client2"),
+                reason: "Spamming".to_string(),
+            }),
+            ClientMessage::BanClient(client::BanClient {
+                client_id: ClientId::from("client2"),
+                reason: "Spamming".to_string(),
+                duration_secs: 3600,
+            }),
+            ClientMessage::MuteClient(client::MuteClient {
+                client_id: ClientId::from("client2"),
+                reason: "Spamming".to_string(),
+                duration_secs: 600,
+            }),
+            ClientMessage::Disconnect,
+            ClientMessage::Error(error()),
+        ];
+
+        // Fails to compile if a variant is added, so it has to be covered above.
+        for message in &messages {
+            match message {
+                ClientMessage::Login(_)
+                | ClientMessage::Logout
+                | ClientMessage::CallInvite(_)
+                | ClientMessage::CallAccept(_)
+                | ClientMessage::CallEnd(_)
+                | ClientMessage::CallReject(_)
+                | ClientMessage::CallError(_)
+                | ClientMessage::WebrtcOffer(_)
+                | ClientMessage::WebrtcAnswer(_)
+                | ClientMessage::WebrtcIceCandidate(_)
+                | ClientMessage::ListClients
+                | ClientMessage::ListClientChanges(_)
+                | ClientMessage::ListStations
+                | ClientMessage::SetPresence(_)
+                | ClientMessage::TextMessage(_)
+                | ClientMessage::KickClient(_)
+                | ClientMessage::BanClient(_)
+                | ClientMessage::MuteClient(_)
+                | ClientMessage::Disconnect
+                | ClientMessage::Error(_) => {}
+            }
+        }
+        messages
+    }
+
+    fn server_messages() -> Vec<ServerMessage> {
+        let messages = vec![
+            ServerMessage::LoginFailure(
+                server::LoginFailureReason::AmbiguousVatsimPosition(vec![
+                    PositionId::from("LOVV_CTR"),
+                    PositionId::from("LOVV_E_CTR"),
+                ])
+                .into(),
+            ),
+            ServerMessage::CallInvite(call_invite()),
+            ServerMessage::CallAccept(CallAccept {
+                call_id: CallId::new(),
+                accepting_client_id: ClientId::from("client2"),
+            }),
+            ServerMessage::CallEnd(CallEnd::new(CallId::new(), ClientId::from("client2"))),
+            ServerMessage::CallCancelled(server::CallCancelled::new(
+                CallId::new(),
+                server::CallCancelReason::AnsweredElsewhere(ClientId::from("client3")),
+            )),
+            ServerMessage::CallError(CallError {
+                call_id: CallId::new(),
+                reason: CallErrorReason::TargetNotFound,
+                message: None,
+            }),
+            ServerMessage::WebrtcOffer(webrtc_offer()),
+            ServerMessage::WebrtcAnswer(webrtc_answer()),
+            ServerMessage::WebrtcIceCandidate(webrtc_ice_candidate()),
+            ServerMessage::ClientInfo(server::ClientInfoUpdate {
+                client: client_info(),
+                revision: 3,
+            }),
+            ServerMessage::SessionInfo(server::SessionInfo {
+                client: client_info(),
+                profile: server::SessionProfile::Changed(ActiveProfile::Specific(Profile {
+                    id: ProfileId::from("LOVV"),
+                    profile_type: ProfileType::Tabbed(vec![]),
+                })),
+                resume_token: Some("resume".to_string()),
+                encoding: Encoding::MessagePack,
+                compression: true,
+                capabilities: Capabilities::all(),
+            }),
+            ServerMessage::ClientConnected(client_info().into()),
+            ServerMessage::ClientDisconnected(ClientId::from("client1").into()),
+            ServerMessage::ClientList(vec![client_info()].into()),
+            ServerMessage::ClientChanges(server::ClientChanges {
+                revision: 7,
+                changes: vec![
+                    server::ClientChange::Connected {
+                        client: client_info(),
+                    },
+                    server::ClientChange::Updated {
+                        client: client_info(),
+                    },
+                    server::ClientChange::Disconnected {
+                        client_id: ClientId::from("client1"),
+                    },
+                ],
+            }),
+            ServerMessage::ClientPresence(server::ClientPresence {
+                client_id: ClientId::from("client1"),
+                presence: Presence::Busy,
+                revision: 8,
+            }),
+            ServerMessage::TextMessage(text_message()),
+            ServerMessage::Announcement(
+                server::Announcement::new(
+                    server::AnnouncementSeverity::Warning,
+                    "Server restarts in 10 minutes",
+                )
+                .with_expiry(1_700_000_000),
+            ),
+            ServerMessage::StationList(
+                vec![server::StationInfo {
+                    id: StationId::from("LOWW_TWR"),
+                    own: true,
+                }]
+                .into(),
+            ),
+            ServerMessage::StationChanges(
+                vec![
+                    StationChange::Online {
+                        station_id: StationId::from("LOWW_TWR"),
+                        position_id: PositionId::from("LOWW_TWR"),
+                    },
+                    StationChange::Handoff {
+                        station_id: StationId::from("LOWW_APP"),
+                        from_position_id: PositionId::from("LOWW_APP"),
+                        to_position_id: PositionId::from("LOVV_CTR"),
+                    },
+                    StationChange::Offline {
+                        station_id: StationId::from("LOWW_GND"),
+                    },
+                ]
+                .into(),
+            ),
+            ServerMessage::Disconnected(server::DisconnectReason::Terminated.into()),
+            ServerMessage::Disconnected(
+                server::DisconnectReason::ServerRestart { retry_after: 5 }.into(),
+            ),
+            ServerMessage::Disconnected(
+                server::DisconnectReason::Kicked {
+                    reason: "Spamming".to_string(),
+                }
+                .into(),
+            ),
+            ServerMessage::Error(error()),
+        ];
+
+        // Fails to compile if a variant is added, so it has to be covered above.
+        for message in &messages {
+            match message {
+                ServerMessage::LoginFailure(_)
+                | ServerMessage::CallInvite(_)
+                | ServerMessage::CallAccept(_)
+                | ServerMessage::CallEnd(_)
+                | ServerMessage::CallCancelled(_)
+                | ServerMessage::CallError(_)
+                | ServerMessage::WebrtcOffer(_)
+                | ServerMessage::WebrtcAnswer(_)
+                | ServerMessage::WebrtcIceCandidate(_)
+                | ServerMessage::ClientInfo(_)
+                | ServerMessage::SessionInfo(_)
+                | ServerMessage::ClientConnected(_)
+                | ServerMessage::ClientDisconnected(_)
+                | ServerMessage::ClientList(_)
+                | ServerMessage::ClientChanges(_)
+                | ServerMessage::ClientPresence(_)
+                | ServerMessage::TextMessage(_)
+                | ServerMessage::Announcement(_)
+                | ServerMessage::StationList(_)
+                | ServerMessage::StationChanges(_)
+                | ServerMessage::Disconnected(_)
+                | ServerMessage::Error(_) => {}
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn client_messages_json_round_trip() {
+        for message in client_messages() {
+            let serialized = message.serialize().unwrap();
+            assert_eq!(ClientMessage::deserialize(&serialized).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn client_messages_msgpack_round_trip() {
+        for message in client_messages() {
+            let serialized = message.serialize_msgpack().unwrap();
+            assert_eq!(
+                ClientMessage::deserialize_msgpack(&serialized).unwrap(),
+                message,
+                "{} failed to round-trip",
+                message.variant()
+            );
+        }
+    }
+
+    #[test]
+    fn server_messages_json_round_trip() {
+        for message in server_messages() {
+            let serialized = message.serialize().unwrap();
+            assert_eq!(ServerMessage::deserialize(&serialized).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn server_messages_msgpack_round_trip() {
+        for message in server_messages() {
+            let serialized = message.serialize_msgpack().unwrap();
+            assert_eq!(
+                ServerMessage::deserialize_msgpack(&serialized).unwrap(),
+                message,
+                "{} failed to round-trip",
+                message.variant()
+            );
+        }
+    }
+
+    #[test]
+    fn msgpack_is_smaller_than_json() {
+        let message = ServerMessage::ClientList(vec![client_info(); 50].into());
+        assert!(message.serialize_msgpack().unwrap().len() < message.serialize().unwrap().len());
+    }
+
+    #[test]
+    fn compression_round_trip() {
+        let message = ServerMessage::ClientList(vec![client_info(); 50].into());
+        let serialized = message.serialize().unwrap();
+        let compressed = compress(serialized.as_bytes()).unwrap();
+        assert!(compressed.len() < serialized.len() / 4);
+        assert_eq!(decompress(&compressed).unwrap(), serialized.as_bytes());
+    }
+
+    #[test]
+    fn decompress_rejects_oversized_messages() {
+        let data = vec![0u8; MAX_DECOMPRESSED_MESSAGE_SIZE as usize + 1];
+        let compressed = compress(&data).unwrap();
+        assert!(decompress(&compressed).is_err());
+    }
+
+    #[test]
+    fn decompress_rejects_invalid_data() {
+        assert!(decompress(b"not deflate").is_err());
+    }
+
+    #[test]
+    fn login_encoding_defaults_to_json() {
+        let login = client::Login {
+            token: "token".to_string(),
+            protocol_version: crate::VACS_PROTOCOL_VERSION.to_string(),
+            custom_profile: false,
+            position_id: None,
+            resume_token: None,
+            encoding: Encoding::Json,
+            compression: false,
+            capabilities: Capabilities::default(),
+        };
+        let serialized = ClientMessage::Login(login.clone()).serialize().unwrap();
+        assert!(!serialized.contains("encoding"));
+        assert!(!serialized.contains("compression"));
+        assert!(!serialized.contains("capabilities"));
+        assert_eq!(
+            ClientMessage::deserialize(&serialized).unwrap(),
+            ClientMessage::Login(login)
+        );
+    }
+
+    #[test]
+    fn client_info_update_is_flat() {
+        let message = ServerMessage::ClientInfo(server::ClientInfoUpdate {
+            client: client_info(),
+            revision: 3,
+        });
+        let serialized = message.serialize().unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(value["id"], "client1");
+        assert_eq!(value["revision"], 3);
+
+        let legacy = serialized.replace(",\"revision\":3", "");
+        assert_eq!(
+            ServerMessage::deserialize(&legacy).unwrap(),
+            ServerMessage::ClientInfo(client_info().into())
+        );
+    }
+}
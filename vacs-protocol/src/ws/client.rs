@@ -1,16 +1,21 @@
 pub mod auth;
 pub mod calls;
+pub mod moderation;
+pub mod network;
 
 pub use auth::*;
 pub use calls::*;
+pub use moderation::*;
+pub use network::*;
 
 use crate::ws::shared::{
-    CallAccept, CallEnd, CallError, CallInvite, Error, WebrtcAnswer, WebrtcIceCandidate,
-    WebrtcOffer,
+    CallAccept, CallEnd, CallError, CallInvite, Capability, Error, TextMessage, WebrtcAnswer,
+    WebrtcIceCandidate, WebrtcOffer,
 };
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum ClientMessage {
     Login(Login),
@@ -24,7 +29,13 @@ pub enum ClientMessage {
     WebrtcAnswer(WebrtcAnswer),
     WebrtcIceCandidate(WebrtcIceCandidate),
     ListClients,
+    ListClientChanges(ListClientChanges),
     ListStations,
+    SetPresence(SetPresence),
+    TextMessage(TextMessage),
+    KickClient(KickClient),
+    BanClient(BanClient),
+    MuteClient(MuteClient),
     Disconnect,
     Error(Error),
 }
@@ -42,6 +53,14 @@ impl ClientMessage {
         serde_json::from_str(s)
     }
 
+    pub fn serialize_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        crate::ws::to_msgpack(self)
+    }
+
+    pub fn deserialize_msgpack(b: &[u8]) -> Result<Self, rmp_serde::decode::Error> {
+        crate::ws::from_msgpack(b)
+    }
+
     pub const fn variant(&self) -> &'static str {
         match self {
             ClientMessage::Login(_) => "Login",
@@ -55,9 +74,25 @@ impl ClientMessage {
             ClientMessage::WebrtcAnswer(_) => "WebrtcAnswer",
             ClientMessage::WebrtcIceCandidate(_) => "WebrtcIceCandidate",
             ClientMessage::ListClients => "ListClients",
+            ClientMessage::ListClientChanges(_) => "ListClientChanges",
             ClientMessage::ListStations => "ListStations",
+            ClientMessage::SetPresence(_) => "SetPresence",
+            ClientMessage::TextMessage(_) => "TextMessage",
+            ClientMessage::KickClient(_) => "KickClient",
+            ClientMessage::BanClient(_) => "BanClient",
+            ClientMessage::MuteClient(_) => "MuteClient",
             ClientMessage::Disconnect => "Disconnect",
             ClientMessage::Error(_) => "Error",
         }
     }
+
+    /// Capability which has to be negotiated for this message to be sent, if any.
+    pub const fn required_capability(&self) -> Option<Capability> {
+        match self {
+            ClientMessage::ListClientChanges(_) => Some(Capability::ClientChanges),
+            ClientMessage::SetPresence(_) => Some(Capability::Presence),
+            ClientMessage::TextMessage(_) => Some(Capability::TextMessages),
+            _ => None,
+        }
+    }
 }
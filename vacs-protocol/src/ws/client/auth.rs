@@ -1,14 +1,39 @@
 use crate::vatsim::PositionId;
+use crate::ws::Encoding;
 use crate::ws::client::ClientMessage;
+use crate::ws::shared::Capabilities;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct Login {
     pub token: String,
     pub protocol_version: String,
     pub custom_profile: bool,
     pub position_id: Option<PositionId>,
+    /// Resume token of a previous session, received in its [`crate::ws::server::SessionInfo`].
+    /// If the previous session is still within its grace period, it is resumed instead of
+    /// creating a new one, keeping all calls and queued messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_token: Option<String>,
+    /// Encoding requested for all messages after the login. The server confirms the encoding in
+    /// its [`crate::ws::server::SessionInfo`], older servers fall back to JSON.
+    #[serde(default, skip_serializing_if = "Encoding::is_json")]
+    pub encoding: Encoding,
+    /// Requests compression of all messages sent by the server after the login, see
+    /// [`crate::ws::compress`]. Confirmed in the [`crate::ws::server::SessionInfo`], as servers
+    /// may have compression disabled.
+    ///
+    /// Compression is one-way: messages sent by the client are never compressed. It is applied on
+    /// the application level since the websocket stack doesn't support permessage-deflate, and
+    /// only pays off for large server messages like client and station lists.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub compression: bool,
+    /// Capabilities supported by the client. The negotiated subset is confirmed in the
+    /// [`crate::ws::server::SessionInfo`].
+    #[serde(default, skip_serializing_if = "Capabilities::is_empty")]
+    pub capabilities: Capabilities,
 }
 
 impl From<Login> for ClientMessage {
@@ -4,12 +4,18 @@ use crate::ws::shared::CallId;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub enum CallRejectReason {
     Busy,
+    /// Rejected by the server, as the callee's presence is [`crate::ws::shared::Presence::DoNotDisturb`]
+    /// and the call was not a priority call. Callers without the presence capability receive
+    /// [`CallRejectReason::Busy`] instead.
+    DoNotDisturb,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct CallReject {
     pub call_id: CallId,
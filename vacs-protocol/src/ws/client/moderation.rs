@@ -0,0 +1,54 @@
+use crate::vatsim::ClientId;
+use crate::ws::client::ClientMessage;
+use serde::{Deserialize, Serialize};
+
+/// Disconnects a client. Only accepted from supervisors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct KickClient {
+    pub client_id: ClientId,
+    pub reason: String,
+}
+
+impl From<KickClient> for ClientMessage {
+    fn from(value: KickClient) -> Self {
+        Self::KickClient(value)
+    }
+}
+
+/// Bans a client from logging in for the given duration, disconnecting it if it is connected.
+/// Only accepted from supervisors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct BanClient {
+    pub client_id: ClientId,
+    pub reason: String,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub duration_secs: u64,
+}
+
+impl From<BanClient> for ClientMessage {
+    fn from(value: BanClient) -> Self {
+        Self::BanClient(value)
+    }
+}
+
+/// Prevents a client from placing calls and sending text messages for the given duration.
+/// Only accepted from supervisors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct MuteClient {
+    pub client_id: ClientId,
+    pub reason: String,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub duration_secs: u64,
+}
+
+impl From<MuteClient> for ClientMessage {
+    fn from(value: MuteClient) -> Self {
+        Self::MuteClient(value)
+    }
+}
@@ -0,0 +1,36 @@
+use crate::ws::client::ClientMessage;
+use crate::ws::shared::Presence;
+use serde::{Deserialize, Serialize};
+
+/// Requests all roster changes since the given revision, e.g. to catch up after a brief
+/// reconnect. Answered with a [`crate::ws::server::ClientChanges`] or, if the revision is too old,
+/// a full [`crate::ws::server::ClientList`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct ListClientChanges {
+    /// Last roster revision known to the client.
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub since_revision: u64,
+}
+
+impl From<ListClientChanges> for ClientMessage {
+    fn from(value: ListClientChanges) -> Self {
+        Self::ListClientChanges(value)
+    }
+}
+
+/// Sets the presence of the client, broadcast to all other clients as
+/// [`crate::ws::server::ClientPresence`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct SetPresence {
+    pub presence: Presence,
+}
+
+impl From<SetPresence> for ClientMessage {
+    fn from(value: SetPresence) -> Self {
+        Self::SetPresence(value)
+    }
+}
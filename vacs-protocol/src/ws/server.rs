@@ -1,19 +1,23 @@
+pub mod announcement;
 pub mod auth;
 pub mod calls;
 pub mod network;
 
+pub use announcement::*;
 pub use auth::*;
 pub use calls::*;
 pub use network::*;
 
 use crate::ws::shared::{
-    CallAccept, CallEnd, CallError, CallInvite, Error, WebrtcAnswer, WebrtcIceCandidate,
-    WebrtcOffer,
+    CallAccept, CallEnd, CallError, CallInvite, Capability, Error, TextMessage, WebrtcAnswer,
+    WebrtcIceCandidate, WebrtcOffer,
 };
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase", tag = "type")]
+#[allow(clippy::large_enum_variant)] // SessionInfo is only sent once per session
 pub enum ServerMessage {
     LoginFailure(LoginFailure),
     CallInvite(CallInvite),
@@ -24,11 +28,15 @@ pub enum ServerMessage {
     WebrtcOffer(WebrtcOffer),
     WebrtcAnswer(WebrtcAnswer),
     WebrtcIceCandidate(WebrtcIceCandidate),
-    ClientInfo(ClientInfo),
+    ClientInfo(ClientInfoUpdate),
     SessionInfo(SessionInfo),
     ClientConnected(ClientConnected),
     ClientDisconnected(ClientDisconnected),
     ClientList(ClientList),
+    ClientChanges(ClientChanges),
+    ClientPresence(ClientPresence),
+    TextMessage(TextMessage),
+    Announcement(Announcement),
     StationList(StationList),
     StationChanges(StationChanges),
     Disconnected(Disconnected),
@@ -48,6 +56,14 @@ impl ServerMessage {
         serde_json::from_str(s)
     }
 
+    pub fn serialize_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        crate::ws::to_msgpack(self)
+    }
+
+    pub fn deserialize_msgpack(b: &[u8]) -> Result<Self, rmp_serde::decode::Error> {
+        crate::ws::from_msgpack(b)
+    }
+
     pub const fn variant(&self) -> &'static str {
         match self {
             ServerMessage::LoginFailure(_) => "LoginFailure",
@@ -64,10 +80,25 @@ impl ServerMessage {
             ServerMessage::ClientConnected(_) => "ClientConnected",
             ServerMessage::ClientDisconnected(_) => "ClientDisconnected",
             ServerMessage::ClientList(_) => "ClientList",
+            ServerMessage::ClientChanges(_) => "ClientChanges",
+            ServerMessage::ClientPresence(_) => "ClientPresence",
+            ServerMessage::TextMessage(_) => "TextMessage",
+            ServerMessage::Announcement(_) => "Announcement",
             ServerMessage::StationList(_) => "StationList",
             ServerMessage::StationChanges(_) => "StationChanges",
             ServerMessage::Disconnected(_) => "Disconnected",
             ServerMessage::Error(_) => "Error",
         }
     }
+
+    /// Capability which has to be negotiated for this message to be sent, if any.
+    pub const fn required_capability(&self) -> Option<Capability> {
+        match self {
+            ServerMessage::ClientChanges(_) => Some(Capability::ClientChanges),
+            ServerMessage::ClientPresence(_) => Some(Capability::Presence),
+            ServerMessage::TextMessage(_) => Some(Capability::TextMessages),
+            ServerMessage::Announcement(_) => Some(Capability::Announcements),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,83 @@
+use crate::ws::server::ServerMessage;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub enum AnnouncementSeverity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+/// Notice broadcast by the server admins to all connected clients, e.g. announcing a server
+/// restart or a newly deployed dataset. Clients display it until it expires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct Announcement {
+    /// Unique ID, allowing clients to deduplicate announcements sent again after a reconnect.
+    pub id: Uuid,
+    pub severity: AnnouncementSeverity,
+    pub message: String,
+    /// Expiry as Unix timestamp (seconds since epoch). Until then, the announcement is also sent
+    /// to clients connecting later. Announcements without expiry are only sent to the clients
+    /// connected at the time and shown until dismissed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", ts(as = "Option<f64>"))]
+    pub expires_at: Option<u64>,
+}
+
+impl Announcement {
+    pub fn new(severity: AnnouncementSeverity, message: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            severity,
+            message: message.into(),
+            expires_at: None,
+        }
+    }
+
+    pub fn with_expiry(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Whether the announcement has expired at the given Unix timestamp (seconds since epoch).
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// Whether the announcement has expired.
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.is_expired_at(now)
+    }
+}
+
+impl From<Announcement> for ServerMessage {
+    fn from(value: Announcement) -> Self {
+        Self::Announcement(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expiry() {
+        let announcement = Announcement::new(AnnouncementSeverity::Warning, "restart");
+        assert!(!announcement.is_expired_at(u64::MAX));
+
+        let announcement = announcement.with_expiry(100);
+        assert!(!announcement.is_expired_at(99));
+        assert!(announcement.is_expired_at(100));
+    }
+}
@@ -3,6 +3,7 @@ use crate::ws::server::ServerMessage;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub enum LoginFailureReason {
     Unauthorized,
@@ -13,23 +14,44 @@ pub enum LoginFailureReason {
     InvalidVatsimPosition,
     Timeout,
     IncompatibleProtocolVersion,
+    /// The client has been banned by a supervisor until `expires_at` (Unix timestamp in seconds).
+    #[serde(rename_all = "camelCase")]
+    Banned {
+        reason: String,
+        #[cfg_attr(feature = "schema", ts(type = "number"))]
+        expires_at: u64,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub enum DisconnectReason {
     Terminated,
     NoActiveVatsimConnection,
     AmbiguousVatsimPosition(Vec<PositionId>),
+    /// The client has been disconnected by a supervisor.
+    Kicked {
+        reason: String,
+    },
+    /// The server is shutting down for a restart. Clients should reconnect after `retry_after`
+    /// seconds instead of treating the disconnect as fatal.
+    #[serde(rename_all = "camelCase")]
+    ServerRestart {
+        #[cfg_attr(feature = "schema", ts(type = "number"))]
+        retry_after: u64,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct LoginFailure {
     pub reason: LoginFailureReason,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct Disconnected {
     pub reason: DisconnectReason,
@@ -5,6 +5,7 @@ use crate::ws::shared::{CallErrorReason, CallId};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub enum CallCancelReason {
     AnsweredElsewhere(ClientId),
@@ -15,6 +16,7 @@ pub enum CallCancelReason {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct CallCancelled {
     pub call_id: CallId,
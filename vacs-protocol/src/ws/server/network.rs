@@ -1,9 +1,12 @@
 use crate::profile::{ActiveProfile, Profile};
 use crate::vatsim::{ClientId, PositionId, StationChange, StationId};
+use crate::ws::Encoding;
 use crate::ws::server::ServerMessage;
+use crate::ws::shared::{Capabilities, Presence};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase", tag = "type", content = "activeProfile")]
 pub enum SessionProfile {
     Unchanged,
@@ -11,6 +14,7 @@ pub enum SessionProfile {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct ClientInfo {
     pub id: ClientId,
@@ -18,16 +22,52 @@ pub struct ClientInfo {
     pub frequency: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub position_id: Option<PositionId>,
+    #[serde(default, skip_serializing_if = "Presence::is_available")]
+    pub presence: Presence,
+}
+
+/// Updated info of a connected client, broadcast when its position or frequency changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct ClientInfoUpdate {
+    #[serde(flatten)]
+    pub client: ClientInfo,
+    /// Roster revision of this change, see [`ClientChanges`].
+    #[serde(default)]
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub revision: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct SessionInfo {
     pub client: ClientInfo,
     pub profile: SessionProfile,
+    /// Token allowing the client to resume this session after losing its connection. Only
+    /// included in the initial session info sent after login, which contains the same token
+    /// again if a previous session was resumed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_token: Option<String>,
+    /// Encoding used for all messages sent after the login, including this one. Confirms the
+    /// encoding requested in the client's [`crate::ws::client::Login`] and, like the resume
+    /// token, only set in the initial session info.
+    #[serde(default, skip_serializing_if = "Encoding::is_json")]
+    pub encoding: Encoding,
+    /// Whether all messages sent after this session info are compressed, see
+    /// [`crate::ws::compress`]. Only set in the initial session info.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub compression: bool,
+    /// Capabilities negotiated for this session, i.e. the capabilities requested in the client's
+    /// [`crate::ws::client::Login`] which are supported by the server. Only set in the initial
+    /// session info.
+    #[serde(default, skip_serializing_if = "Capabilities::is_empty")]
+    pub capabilities: Capabilities,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct StationInfo {
     pub id: StationId,
@@ -35,30 +75,105 @@ pub struct StationInfo {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct ClientConnected {
     pub client: ClientInfo,
+    /// Roster revision of this change, see [`ClientChanges`].
+    #[serde(default)]
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub revision: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct ClientDisconnected {
     pub client_id: ClientId,
+    /// Roster revision of this change, see [`ClientChanges`].
+    #[serde(default)]
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub revision: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct ClientList {
     pub clients: Vec<ClientInfo>,
+    /// Roster revision the list is up to date with. Changes since this revision can be requested
+    /// using [`crate::ws::client::ListClientChanges`].
+    #[serde(default)]
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub revision: u64,
+}
+
+/// Represents a change to the roster of connected clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub enum ClientChange {
+    /// A client has connected.
+    #[serde(rename_all = "camelCase")]
+    Connected {
+        /// Info of the connected client.
+        client: ClientInfo,
+    },
+    /// The info of a connected client has changed.
+    #[serde(rename_all = "camelCase")]
+    Updated {
+        /// Updated info of the client.
+        client: ClientInfo,
+    },
+    /// A client has disconnected.
+    #[serde(rename_all = "camelCase")]
+    Disconnected {
+        /// The ID of the disconnected client.
+        client_id: ClientId,
+    },
+}
+
+/// Changes to the roster since the revision requested using
+/// [`crate::ws::client::ListClientChanges`].
+///
+/// Every roster change increments the server's roster revision, which is included in the
+/// [`ClientConnected`], [`ClientDisconnected`] and [`ClientInfoUpdate`] broadcasts as well as in
+/// the [`ClientList`]. If the requested revision is too old to be covered by the server's change
+/// history, a full [`ClientList`] is sent instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct ClientChanges {
+    /// Current roster revision, including all listed changes.
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub revision: u64,
+    /// Changes in the order they were applied, excluding changes of the requesting client.
+    pub changes: Vec<ClientChange>,
+}
+
+/// Changed presence of a connected client, broadcast after it sent a
+/// [`crate::ws::client::SetPresence`]. Recorded in the roster as [`ClientChange::Updated`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct ClientPresence {
+    pub client_id: ClientId,
+    pub presence: Presence,
+    /// Roster revision of this change, see [`ClientChanges`].
+    #[serde(default)]
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub revision: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct StationList {
     pub stations: Vec<StationInfo>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct StationChanges {
     pub changes: Vec<StationChange>,
@@ -79,9 +194,30 @@ impl From<ActiveProfile<Profile>> for SessionProfile {
     }
 }
 
+impl From<ClientInfo> for ClientInfoUpdate {
+    fn from(client: ClientInfo) -> Self {
+        Self {
+            client,
+            revision: 0,
+        }
+    }
+}
+
+impl From<ClientInfoUpdate> for ServerMessage {
+    fn from(value: ClientInfoUpdate) -> Self {
+        Self::ClientInfo(value)
+    }
+}
+
+impl From<ClientPresence> for ServerMessage {
+    fn from(value: ClientPresence) -> Self {
+        Self::ClientPresence(value)
+    }
+}
+
 impl From<ClientInfo> for ServerMessage {
     fn from(value: ClientInfo) -> Self {
-        Self::ClientInfo(value)
+        Self::ClientInfo(value.into())
     }
 }
 
@@ -93,7 +229,10 @@ impl From<SessionInfo> for ServerMessage {
 
 impl From<ClientInfo> for ClientConnected {
     fn from(client: ClientInfo) -> Self {
-        Self { client }
+        Self {
+            client,
+            revision: 0,
+        }
     }
 }
 
@@ -105,7 +244,10 @@ impl From<ClientConnected> for ServerMessage {
 
 impl From<ClientId> for ClientDisconnected {
     fn from(client_id: ClientId) -> Self {
-        Self { client_id }
+        Self {
+            client_id,
+            revision: 0,
+        }
     }
 }
 
@@ -117,7 +259,10 @@ impl From<ClientDisconnected> for ServerMessage {
 
 impl From<Vec<ClientInfo>> for ClientList {
     fn from(clients: Vec<ClientInfo>) -> Self {
-        Self { clients }
+        Self {
+            clients,
+            revision: 0,
+        }
     }
 }
 
@@ -133,6 +278,12 @@ impl From<Vec<ClientInfo>> for ServerMessage {
     }
 }
 
+impl From<ClientChanges> for ServerMessage {
+    fn from(value: ClientChanges) -> Self {
+        Self::ClientChanges(value)
+    }
+}
+
 impl From<Vec<StationInfo>> for StationList {
     fn from(stations: Vec<StationInfo>) -> Self {
         Self { stations }
@@ -1,7 +1,13 @@
 pub mod calls;
+pub mod capabilities;
 pub mod errors;
+pub mod presence;
+pub mod text;
 pub mod webrtc;
 
 pub use calls::*;
+pub use capabilities::*;
 pub use errors::*;
+pub use presence::*;
+pub use text::*;
 pub use webrtc::*;
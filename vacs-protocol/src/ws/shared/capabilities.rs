@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Optional protocol feature, negotiated during login.
+///
+/// Clients list the capabilities they support in their [`crate::ws::client::Login`], the server
+/// replies with the subset it supports as well in the initial [`crate::ws::server::SessionInfo`].
+/// Message variants belonging to a capability are only exchanged if it was negotiated, so new
+/// message types can be rolled out without forcing all clients to update at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "kebab-case")]
+pub enum Capability {
+    /// Incremental roster updates, see [`crate::ws::client::ListClientChanges`].
+    ClientChanges,
+    /// Presence of other clients, see [`crate::ws::client::SetPresence`] and
+    /// [`crate::ws::server::ClientPresence`].
+    Presence,
+    /// Text messages between clients, see [`crate::ws::shared::TextMessage`].
+    TextMessages,
+    /// Announcements of the server admins, see [`crate::ws::server::Announcement`].
+    Announcements,
+    /// A capability unknown to this version of vacs, never negotiated.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Capability {
+    /// All capabilities supported by this version of vacs.
+    pub const ALL: &'static [Capability] = &[
+        Capability::ClientChanges,
+        Capability::Presence,
+        Capability::TextMessages,
+        Capability::Announcements,
+    ];
+}
+
+/// Set of [`Capability`]s supported by one side or negotiated for a session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(transparent)]
+pub struct Capabilities(BTreeSet<Capability>);
+
+impl Capabilities {
+    /// All capabilities supported by this version of vacs.
+    pub fn all() -> Self {
+        Self::from_iter(Capability::ALL.iter().copied())
+    }
+
+    #[inline]
+    pub fn contains(&self, capability: Capability) -> bool {
+        self.0.contains(&capability)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Capability> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Returns the capabilities contained in both sets, ignoring [`Capability::Unknown`].
+    pub fn negotiate(&self, other: &Capabilities) -> Capabilities {
+        self.0
+            .intersection(&other.0)
+            .copied()
+            .filter(|capability| *capability != Capability::Unknown)
+            .collect()
+    }
+
+    /// Whether a message requiring the given capability may be exchanged.
+    #[inline]
+    pub fn allows(&self, required: Option<Capability>) -> bool {
+        required.is_none_or(|capability| self.contains(capability))
+    }
+}
+
+impl FromIterator<Capability> for Capabilities {
+    fn from_iter<T: IntoIterator<Item = Capability>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<const N: usize> From<[Capability; N]> for Capabilities {
+    fn from(value: [Capability; N]) -> Self {
+        Self::from_iter(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiate() {
+        let server = Capabilities::all();
+        let client = Capabilities::from([Capability::ClientChanges, Capability::Unknown]);
+        assert_eq!(
+            server.negotiate(&client),
+            Capabilities::from([Capability::ClientChanges])
+        );
+        assert!(server.negotiate(&Capabilities::default()).is_empty());
+    }
+
+    #[test]
+    fn unknown_capabilities_are_ignored() {
+        let capabilities: Capabilities =
+            serde_json::from_str(r#"["client-changes","conference"]"#).unwrap();
+        assert_eq!(
+            capabilities,
+            Capabilities::from([Capability::ClientChanges, Capability::Unknown])
+        );
+        assert!(
+            Capabilities::all()
+                .negotiate(&capabilities)
+                .contains(Capability::ClientChanges)
+        );
+    }
+}
@@ -5,17 +5,30 @@ use crate::ws::shared::CallId;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub enum ErrorReason {
     MalformedMessage,
     Internal(String),
     PeerConnection,
     UnexpectedMessage(String),
-    RateLimited { retry_after_secs: u64 },
+    RateLimited {
+        #[cfg_attr(feature = "schema", ts(type = "number"))]
+        retry_after_secs: u64,
+    },
     ClientNotFound,
+    /// The client has been muted by a supervisor until `expires_at` (Unix timestamp in seconds)
+    /// and may not place calls or send text messages.
+    #[serde(rename_all = "camelCase")]
+    Muted {
+        reason: String,
+        #[cfg_attr(feature = "schema", ts(type = "number"))]
+        expires_at: u64,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct Error {
     pub reason: ErrorReason,
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Availability of a controller for calls, set by the client and shown to all other clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub enum Presence {
+    #[default]
+    Available,
+    Busy,
+    Away,
+    /// Only priority calls are put through, all other call invites are rejected by the server.
+    DoNotDisturb,
+}
+
+impl Presence {
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        *self == Presence::Available
+    }
+
+    /// Whether a call invite with the given priority is put through to a client with this
+    /// presence.
+    #[inline]
+    pub fn accepts_call(&self, prio: bool) -> bool {
+        prio || *self != Presence::DoNotDisturb
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_call() {
+        assert!(Presence::Available.accepts_call(false));
+        assert!(Presence::Busy.accepts_call(false));
+        assert!(Presence::Away.accepts_call(false));
+        assert!(!Presence::DoNotDisturb.accepts_call(false));
+        assert!(Presence::DoNotDisturb.accepts_call(true));
+    }
+}
@@ -0,0 +1,47 @@
+use crate::ws::client::ClientMessage;
+use crate::ws::server::ServerMessage;
+use crate::ws::shared::{CallSource, CallTarget};
+use serde::{Deserialize, Serialize};
+
+/// Short text message between controllers, e.g. to coordinate a runway release.
+///
+/// Routed like a [`crate::ws::shared::CallInvite`]: the server delivers it to every client
+/// covering the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct TextMessage {
+    pub source: CallSource,
+    pub target: CallTarget,
+    pub text: String,
+}
+
+impl TextMessage {
+    /// Maximum length of the text in bytes, longer messages are rejected by the server.
+    pub const MAX_LENGTH: usize = 256;
+
+    pub fn new(source: CallSource, target: CallTarget, text: impl Into<String>) -> Self {
+        Self {
+            source,
+            target,
+            text: text.into(),
+        }
+    }
+
+    /// Whether the text is neither blank nor longer than [`TextMessage::MAX_LENGTH`].
+    pub fn is_valid(&self) -> bool {
+        !self.text.trim().is_empty() && self.text.len() <= Self::MAX_LENGTH
+    }
+}
+
+impl From<TextMessage> for ClientMessage {
+    fn from(value: TextMessage) -> Self {
+        Self::TextMessage(value)
+    }
+}
+
+impl From<TextMessage> for ServerMessage {
+    fn from(value: TextMessage) -> Self {
+        Self::TextMessage(value)
+    }
+}
@@ -5,6 +5,7 @@ use crate::ws::shared::CallId;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct WebrtcOffer {
     pub call_id: CallId,
@@ -14,6 +15,7 @@ pub struct WebrtcOffer {
 }
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct WebrtcAnswer {
     pub call_id: CallId,
@@ -23,6 +25,7 @@ pub struct WebrtcAnswer {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema, ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct WebrtcIceCandidate {
     pub call_id: CallId,
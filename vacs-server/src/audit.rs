@@ -0,0 +1,275 @@
+pub mod file;
+pub mod memory;
+
+use crate::audit::file::FileAuditSink;
+use crate::audit::memory::MemoryAuditSink;
+use crate::auth::roles::Role;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::server::AnnouncementSeverity;
+
+/// Method a user authenticated with via the HTTP API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthMethod {
+    /// VATSIM OAuth authorization code flow.
+    #[serde(rename = "oauth")]
+    OAuth,
+    /// VATSIM access token exchanged for an API token.
+    #[serde(rename = "accessToken")]
+    AccessToken,
+}
+
+/// Security-relevant event recorded in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum AuditEvent {
+    Login {
+        method: AuthMethod,
+    },
+    LoginFailed {
+        method: AuthMethod,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ip: Option<IpAddr>,
+    },
+    Logout,
+    ApiTokenIssued,
+    ApiTokenRevoked,
+    /// Coverage dataset reload triggered via GitHub Actions.
+    DatasetReloaded {
+        #[serde(rename = "ref")]
+        git_ref: String,
+        sha: String,
+        /// Subject of the OIDC token used to trigger the reload.
+        subject: String,
+    },
+    AnnouncementSent {
+        severity: AnnouncementSeverity,
+        message: String,
+        /// Subject of the OIDC token used to send the announcement.
+        subject: String,
+    },
+    RolesAssigned {
+        roles: Vec<Role>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ClientKicked {
+        reason: String,
+        /// Whether the client was connected at the time.
+        connected: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    ClientBanned {
+        reason: String,
+        expires_at: u64,
+    },
+    ClientUnbanned,
+    #[serde(rename_all = "camelCase")]
+    ClientMuted {
+        reason: String,
+        expires_at: u64,
+    },
+    ClientUnmuted,
+}
+
+impl AuditEvent {
+    /// Whether the event was caused by a supervisor moderation action.
+    pub fn is_moderation(&self) -> bool {
+        matches!(
+            self,
+            AuditEvent::ClientKicked { .. }
+                | AuditEvent::ClientBanned { .. }
+                | AuditEvent::ClientUnbanned
+                | AuditEvent::ClientMuted { .. }
+                | AuditEvent::ClientUnmuted
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    /// Unix timestamp in seconds.
+    pub timestamp: u64,
+    /// User performing the action, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<ClientId>,
+    /// User the action was performed on, if different from the actor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<ClientId>,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+/// Filter for querying the audit log.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    /// Only return records with this CID as actor or target.
+    pub cid: Option<ClientId>,
+    /// Only return records at or after this Unix timestamp in seconds.
+    pub since: Option<u64>,
+    /// Only return records at or before this Unix timestamp in seconds.
+    pub until: Option<u64>,
+    /// Maximum number of records returned. Returns all matching records if omitted.
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        self.cid.as_ref().is_none_or(|cid| {
+            record.actor.as_ref() == Some(cid) || record.target.as_ref() == Some(cid)
+        }) && self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp <= until)
+    }
+}
+
+/// Append-only storage for audit records.
+#[async_trait::async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn append(&self, record: &AuditRecord) -> anyhow::Result<()>;
+    /// Returns all records matching the query, newest first.
+    async fn query(&self, query: &AuditQuery) -> anyhow::Result<Vec<AuditRecord>>;
+}
+
+/// Audit log recording security-relevant events to the configured [`AuditSink`].
+#[derive(Clone)]
+pub struct AuditLog {
+    sink: Arc<dyn AuditSink>,
+}
+
+impl AuditLog {
+    pub fn new(sink: Arc<dyn AuditSink>) -> Self {
+        Self { sink }
+    }
+
+    /// Records an event. Failures to persist the record are logged, but never fail the
+    /// action being audited.
+    pub async fn record(
+        &self,
+        actor: Option<&ClientId>,
+        target: Option<&ClientId>,
+        event: AuditEvent,
+    ) {
+        let record = AuditRecord {
+            timestamp: unix_now(),
+            actor: actor.cloned(),
+            target: target.cloned(),
+            event,
+        };
+        tracing::info!(
+            actor = ?record.actor,
+            target = ?record.target,
+            event = ?record.event,
+            "Audit event recorded"
+        );
+
+        if let Err(err) = self.sink.append(&record).await {
+            tracing::error!(?err, "Failed to append audit record");
+        }
+    }
+
+    pub async fn query(&self, query: &AuditQuery) -> anyhow::Result<Vec<AuditRecord>> {
+        self.sink.query(query).await
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditSinkType {
+    /// Keeps the most recent records in memory. Records are lost on restart.
+    #[default]
+    Memory,
+    /// Appends records as JSON lines to a file.
+    File,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditConfig {
+    pub sink: AuditSinkType,
+    /// Path of the audit log file, required for the file sink.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+impl AuditConfig {
+    pub fn create_sink(&self) -> anyhow::Result<Arc<dyn AuditSink>> {
+        match self.sink {
+            AuditSinkType::Memory => Ok(Arc::new(MemoryAuditSink::default())),
+            AuditSinkType::File => {
+                let path = self
+                    .path
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("Missing audit log path"))?;
+                Ok(Arc::new(FileAuditSink::open(path)?))
+            }
+        }
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn record(timestamp: u64, actor: &str, target: Option<&str>) -> AuditRecord {
+        AuditRecord {
+            timestamp,
+            actor: Some(ClientId::from(actor)),
+            target: target.map(ClientId::from),
+            event: AuditEvent::Logout,
+        }
+    }
+
+    #[test]
+    fn serialize_record() {
+        let record = AuditRecord {
+            timestamp: 42,
+            actor: Some(ClientId::from("sup")),
+            target: Some(ClientId::from("client1")),
+            event: AuditEvent::ClientBanned {
+                reason: "Abuse".to_string(),
+                expires_at: 100,
+            },
+        };
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            json,
+            r#"{"timestamp":42,"actor":"sup","target":"client1","event":"clientBanned","reason":"Abuse","expiresAt":100}"#
+        );
+        assert_eq!(serde_json::from_str::<AuditRecord>(&json).unwrap(), record);
+    }
+
+    #[test]
+    fn query_matches_actor_or_target() {
+        let query = AuditQuery {
+            cid: Some(ClientId::from("client1")),
+            ..Default::default()
+        };
+        assert!(query.matches(&record(1, "client1", None)));
+        assert!(query.matches(&record(1, "sup", Some("client1"))));
+        assert!(!query.matches(&record(1, "sup", Some("client2"))));
+    }
+
+    #[test]
+    fn query_matches_time_range() {
+        let query = AuditQuery {
+            since: Some(10),
+            until: Some(20),
+            ..Default::default()
+        };
+        assert!(!query.matches(&record(9, "client1", None)));
+        assert!(query.matches(&record(10, "client1", None)));
+        assert!(query.matches(&record(20, "client1", None)));
+        assert!(!query.matches(&record(21, "client1", None)));
+    }
+}
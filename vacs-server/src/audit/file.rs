@@ -0,0 +1,135 @@
+use crate::audit::{AuditQuery, AuditRecord, AuditSink};
+use anyhow::Context;
+use parking_lot::Mutex;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Audit sink appending records as JSON lines to a file.
+///
+/// The file is only ever appended to. Rotation and retention are left to external tooling.
+#[derive(Debug, Clone)]
+pub struct FileAuditSink {
+    path: PathBuf,
+    file: Arc<Mutex<File>>,
+}
+
+impl FileAuditSink {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open audit log {path:?}"))?;
+        tracing::info!(?path, "Opened audit log");
+
+        Ok(Self {
+            path,
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for FileAuditSink {
+    async fn append(&self, record: &AuditRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(record).context("Failed to serialize audit record")?;
+        line.push(b'\n');
+
+        let file = self.file.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut file = file.lock();
+            file.write_all(&line)?;
+            file.flush()
+        })
+        .await
+        .context("Audit log writer panicked")?
+        .context("Failed to write audit record")
+    }
+
+    async fn query(&self, query: &AuditQuery) -> anyhow::Result<Vec<AuditRecord>> {
+        let path = self.path.clone();
+        let query = query.clone();
+        tokio::task::spawn_blocking(move || {
+            let file =
+                File::open(&path).with_context(|| format!("Failed to open audit log {path:?}"))?;
+
+            let mut records = Vec::new();
+            for line in BufReader::new(file).lines() {
+                let line = line.context("Failed to read audit log")?;
+                if line.is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<AuditRecord>(&line) {
+                    Ok(record) if query.matches(&record) => records.push(record),
+                    Ok(_) => {}
+                    Err(err) => tracing::warn!(?err, "Skipping malformed audit record"),
+                }
+            }
+
+            records.reverse();
+            records.truncate(query.limit.unwrap_or(usize::MAX));
+            Ok(records)
+        })
+        .await
+        .context("Audit log reader panicked")?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEvent;
+    use pretty_assertions::assert_eq;
+    use vacs_protocol::vatsim::ClientId;
+
+    fn record(timestamp: u64, actor: &str) -> AuditRecord {
+        AuditRecord {
+            timestamp,
+            actor: Some(ClientId::from(actor)),
+            target: None,
+            event: AuditEvent::ApiTokenRevoked,
+        }
+    }
+
+    #[tokio::test]
+    async fn appends_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+
+        let sink = FileAuditSink::open(&path).unwrap();
+        sink.append(&record(1, "client1")).await.unwrap();
+        sink.append(&record(2, "client2")).await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+
+        // reopening must not truncate existing records
+        let sink = FileAuditSink::open(&path).unwrap();
+        sink.append(&record(3, "client1")).await.unwrap();
+
+        let records = sink
+            .query(&AuditQuery {
+                cid: Some(ClientId::from("client1")),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(records, vec![record(3, "client1"), record(1, "client1")]);
+    }
+
+    #[tokio::test]
+    async fn query_skips_malformed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+        std::fs::write(&path, "not json\n").unwrap();
+
+        let sink = FileAuditSink::open(&path).unwrap();
+        sink.append(&record(1, "client1")).await.unwrap();
+
+        let records = sink.query(&AuditQuery::default()).await.unwrap();
+        assert_eq!(records, vec![record(1, "client1")]);
+    }
+}
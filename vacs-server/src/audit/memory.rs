@@ -0,0 +1,102 @@
+use crate::audit::{AuditQuery, AuditRecord, AuditSink};
+use crate::config;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+
+/// Audit sink keeping the most recent records in memory.
+#[derive(Debug)]
+pub struct MemoryAuditSink {
+    records: Mutex<VecDeque<AuditRecord>>,
+}
+
+impl MemoryAuditSink {
+    /// Maximum number of records kept.
+    pub const MAX_RECORDS: usize = config::AUDIT_LOG_MEMORY_CAPACITY;
+}
+
+impl Default for MemoryAuditSink {
+    fn default() -> Self {
+        Self {
+            records: Mutex::new(VecDeque::with_capacity(Self::MAX_RECORDS)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for MemoryAuditSink {
+    async fn append(&self, record: &AuditRecord) -> anyhow::Result<()> {
+        let mut records = self.records.lock();
+        if records.len() == Self::MAX_RECORDS {
+            records.pop_front();
+        }
+        records.push_back(record.clone());
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> anyhow::Result<Vec<AuditRecord>> {
+        Ok(self
+            .records
+            .lock()
+            .iter()
+            .rev()
+            .filter(|record| query.matches(record))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEvent;
+    use pretty_assertions::assert_eq;
+    use vacs_protocol::vatsim::ClientId;
+
+    fn record(timestamp: u64, actor: &str) -> AuditRecord {
+        AuditRecord {
+            timestamp,
+            actor: Some(ClientId::from(actor)),
+            target: None,
+            event: AuditEvent::ApiTokenIssued,
+        }
+    }
+
+    #[tokio::test]
+    async fn query_returns_newest_first() {
+        let sink = MemoryAuditSink::default();
+        sink.append(&record(1, "client1")).await.unwrap();
+        sink.append(&record(2, "client2")).await.unwrap();
+        sink.append(&record(3, "client1")).await.unwrap();
+
+        let records = sink
+            .query(&AuditQuery {
+                cid: Some(ClientId::from("client1")),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(records, vec![record(3, "client1"), record(1, "client1")]);
+
+        let records = sink
+            .query(&AuditQuery {
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(records, vec![record(3, "client1")]);
+    }
+
+    #[tokio::test]
+    async fn drops_oldest_records() {
+        let sink = MemoryAuditSink::default();
+        for n in 0..MemoryAuditSink::MAX_RECORDS as u64 + 1 {
+            sink.append(&record(n, "client1")).await.unwrap();
+        }
+
+        let records = sink.query(&AuditQuery::default()).await.unwrap();
+        assert_eq!(records.len(), MemoryAuditSink::MAX_RECORDS);
+        assert_eq!(records.last().unwrap().timestamp, 1);
+    }
+}
@@ -1,3 +1,6 @@
+pub mod dev;
 pub mod extractor;
 pub mod layer;
+pub mod roles;
+pub mod scopes;
 pub mod users;
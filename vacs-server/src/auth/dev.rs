@@ -0,0 +1,144 @@
+use crate::config::DevIdentityConfig;
+use anyhow::Context;
+use dashmap::DashMap;
+use oauth2::CsrfToken;
+use reqwest::Url;
+use uuid::Uuid;
+use vacs_protocol::vatsim::ClientId;
+
+/// In-process stand-in for VATSIM Connect, allowing the full login flow to be exercised locally
+/// without OAuth credentials or network access.
+///
+/// Only the configured CIDs can log in. Authorization codes are single-use, access tokens are
+/// valid until the server restarts.
+#[derive(Debug)]
+pub struct DevIdentityProvider {
+    cids: Vec<ClientId>,
+    authorize_url: Url,
+    redirect_url: String,
+    codes: DashMap<String, ClientId>,
+    access_tokens: DashMap<String, ClientId>,
+}
+
+impl DevIdentityProvider {
+    pub fn new(config: &DevIdentityConfig, redirect_url: &str) -> anyhow::Result<Self> {
+        let authorize_url = Url::parse(&config.base_url)
+            .and_then(|url| url.join("auth/dev/authorize"))
+            .context("Invalid dev identity provider base URL")?;
+        tracing::warn!(cids = ?config.cids, "Using dev identity provider, do not use in production");
+
+        Ok(Self {
+            cids: config.cids.clone(),
+            authorize_url,
+            redirect_url: redirect_url.to_string(),
+            codes: DashMap::new(),
+            access_tokens: DashMap::new(),
+        })
+    }
+
+    pub fn cids(&self) -> &[ClientId] {
+        &self.cids
+    }
+
+    pub fn authorize_url(&self) -> (Url, CsrfToken) {
+        let csrf_token = CsrfToken::new_random();
+        let mut url = self.authorize_url.clone();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("redirect_uri", &self.redirect_url)
+            .append_pair("state", csrf_token.secret());
+        (url, csrf_token)
+    }
+
+    /// URL of the authorization endpoint logging the user in as `cid` directly.
+    pub fn login_url(&self, cid: &ClientId, state: &str) -> Url {
+        let mut url = self.authorize_url.clone();
+        url.query_pairs_mut()
+            .append_pair("state", state)
+            .append_pair("cid", cid.as_str());
+        url
+    }
+
+    /// Issues an authorization code for the CID and returns the URL the user is redirected to.
+    /// Returns `None` if the CID is not configured.
+    pub fn authorize(&self, cid: &ClientId, state: &str) -> Option<Url> {
+        if !self.cids.contains(cid) {
+            return None;
+        }
+
+        let code = Uuid::now_v7().to_string();
+        let mut url = Url::parse(&self.redirect_url).ok()?;
+        url.query_pairs_mut()
+            .append_pair("code", &code)
+            .append_pair("state", state);
+        self.codes.insert(code, cid.clone());
+        Some(url)
+    }
+
+    /// Exchanges an authorization code for an access token. Codes can only be exchanged once.
+    pub fn exchange_code(&self, code: &str) -> Option<String> {
+        let (_, cid) = self.codes.remove(code)?;
+        let access_token = Uuid::now_v7().to_string();
+        self.access_tokens.insert(access_token.clone(), cid);
+        Some(access_token)
+    }
+
+    pub fn user(&self, access_token: &str) -> Option<ClientId> {
+        self.access_tokens
+            .get(access_token)
+            .map(|cid| cid.value().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn provider() -> DevIdentityProvider {
+        DevIdentityProvider::new(
+            &DevIdentityConfig {
+                base_url: "http://localhost:3000".to_string(),
+                cids: vec![ClientId::from("10000001")],
+            },
+            "vacs://auth/vatsim/callback",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn authorize_url() {
+        let (url, csrf_token) = provider().authorize_url();
+        assert_eq!(url.path(), "/auth/dev/authorize");
+        assert!(
+            url.query_pairs()
+                .any(|(k, v)| k == "state" && v == csrf_token.secret().as_str())
+        );
+    }
+
+    #[test]
+    fn login_flow() {
+        let provider = provider();
+        let cid = ClientId::from("10000001");
+
+        let redirect = provider.authorize(&cid, "state").unwrap();
+        assert_eq!(redirect.scheme(), "vacs");
+        let code = redirect
+            .query_pairs()
+            .find_map(|(k, v)| (k == "code").then(|| v.to_string()))
+            .unwrap();
+
+        let access_token = provider.exchange_code(&code).unwrap();
+        assert_eq!(provider.user(&access_token), Some(cid));
+        // codes are single-use
+        assert_eq!(provider.exchange_code(&code), None);
+    }
+
+    #[test]
+    fn authorize_unknown_cid() {
+        assert_eq!(
+            provider().authorize(&ClientId::from("10000002"), "state"),
+            None
+        );
+    }
+}
@@ -1,42 +1,45 @@
+use crate::auth::roles::{RequiredRole, Roles};
+use crate::auth::scopes::{self, RequiredScope, Scopes};
 use crate::auth::users::User;
 use crate::http::error::AppError;
 use crate::state::AppState;
 use axum::extract::FromRequestParts;
 use axum::http::header::AUTHORIZATION;
 use axum::http::request::Parts;
+use std::marker::PhantomData;
 use std::sync::Arc;
 use vacs_protocol::vatsim::ClientId;
 
 /// An authenticated user, resolved from either:
 /// 1. An `Authorization: Bearer <api_token>` header (API token flow), or
 /// 2. A session cookie (standard OAuth flow via `axum_login`).
+///
+/// Requires full access: API tokens restricted to scopes are rejected with `403 Forbidden`. Routes
+/// accessible with scoped tokens use [`Scoped`] instead.
 pub struct AuthenticatedUser {
     pub user: User,
     /// The API token used to authenticate, if any. `None` for session-based auth.
     pub api_token: Option<String>,
+    /// Scopes the API token is restricted to. `None` for session-based auth and unrestricted
+    /// tokens.
+    pub scopes: Option<Scopes>,
 }
 
 impl AuthenticatedUser {
     pub fn cid(&self) -> &ClientId {
         &self.user.cid
     }
-}
-
-impl FromRequestParts<Arc<AppState>> for AuthenticatedUser {
-    type Rejection = AppError;
 
-    async fn from_request_parts(
-        parts: &mut Parts,
-        state: &Arc<AppState>,
-    ) -> Result<Self, Self::Rejection> {
+    async fn authenticate(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, AppError> {
         if let Some(token) = extract_bearer_token(parts) {
-            if let Some(cid) = state.verify_api_token(&token).await.map_err(|err| {
+            if let Some(grant) = state.verify_api_token(&token).await.map_err(|err| {
                 tracing::warn!(?err, "Failed to verify API token");
                 AppError::Unauthorized("Invalid token".to_string())
             })? {
                 return Ok(Self {
-                    user: User { cid },
+                    user: User { cid: grant.cid },
                     api_token: Some(token),
+                    scopes: grant.scopes,
                 });
             }
 
@@ -57,13 +60,113 @@ impl FromRequestParts<Arc<AppState>> for AuthenticatedUser {
             Some(user) => Ok(Self {
                 user,
                 api_token: None,
+                scopes: None,
             }),
             None => Err(AppError::Unauthorized("Not authenticated".to_string())),
         }
     }
 }
 
-fn extract_bearer_token(parts: &Parts) -> Option<String> {
+impl FromRequestParts<Arc<AppState>> for AuthenticatedUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let user = Self::authenticate(parts, state).await?;
+
+        if user.scopes.is_some() {
+            tracing::debug!(cid = %user.cid(), scopes = ?user.scopes, "API token lacks full access");
+            return Err(AppError::Forbidden(
+                "API token is restricted to scopes".to_string(),
+            ));
+        }
+
+        Ok(user)
+    }
+}
+
+/// An authenticated user whose credentials grant the scope required by `S`, e.g.
+/// `Scoped<require::WsLogin>`. Sessions and unrestricted API tokens grant every scope.
+///
+/// Rejects unauthenticated requests with `401 Unauthorized` and API tokens lacking the required
+/// scope with `403 Forbidden`.
+pub struct Scoped<S> {
+    pub user: AuthenticatedUser,
+    _scope: PhantomData<S>,
+}
+
+impl<S> Scoped<S> {
+    pub fn cid(&self) -> &ClientId {
+        self.user.cid()
+    }
+}
+
+impl<S: RequiredScope + Send + Sync> FromRequestParts<Arc<AppState>> for Scoped<S> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let user = AuthenticatedUser::authenticate(parts, state).await?;
+
+        if !scopes::grants(user.scopes.as_ref(), S::SCOPE) {
+            tracing::debug!(cid = %user.cid(), required = %S::SCOPE, "API token lacks required scope");
+            return Err(AppError::Forbidden(format!(
+                "API token lacks scope {}",
+                S::SCOPE
+            )));
+        }
+
+        Ok(Self {
+            user,
+            _scope: PhantomData,
+        })
+    }
+}
+
+/// An authenticated user holding the role required by `R`, e.g. `Authorized<require::Supervisor>`.
+///
+/// Rejects unauthenticated requests with `401 Unauthorized` and users lacking the required role
+/// with `403 Forbidden`.
+pub struct Authorized<R> {
+    pub user: AuthenticatedUser,
+    pub roles: Roles,
+    _role: PhantomData<R>,
+}
+
+impl<R> Authorized<R> {
+    pub fn cid(&self) -> &ClientId {
+        self.user.cid()
+    }
+}
+
+impl<R: RequiredRole + Send + Sync> FromRequestParts<Arc<AppState>> for Authorized<R> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let user = AuthenticatedUser::from_request_parts(parts, state).await?;
+        let roles = state.user_roles(user.cid()).await?;
+
+        if !roles.has(R::ROLE) {
+            tracing::debug!(cid = %user.cid(), required = ?R::ROLE, "User lacks required role");
+            return Err(AppError::Forbidden("Insufficient role".to_string()));
+        }
+
+        Ok(Self {
+            user,
+            roles,
+            _role: PhantomData,
+        })
+    }
+}
+
+pub(crate) fn extract_bearer_token(parts: &Parts) -> Option<String> {
     parts
         .headers
         .get(AUTHORIZATION)?
@@ -128,6 +231,7 @@ mod tests {
 
     mod authenticated_user {
         use super::*;
+        use crate::audit::memory::MemoryAuditSink;
         use crate::config::AppConfig;
         use crate::ice::provider::stun::StunOnlyProvider;
         use crate::ratelimit::RateLimiters;
@@ -140,7 +244,7 @@ mod tests {
         use vacs_vatsim::data_feed::mock::MockDataFeed;
         use vacs_vatsim::slurper::SlurperClient;
 
-        fn test_state() -> Arc<AppState> {
+        pub(super) fn test_state() -> Arc<AppState> {
             let (_, shutdown_rx) = watch::channel(());
             Arc::new(AppState::new(
                 AppConfig::default(),
@@ -153,6 +257,7 @@ mod tests {
                 shutdown_rx,
                 Arc::new(StunOnlyProvider::default()),
                 None,
+                Arc::new(MemoryAuditSink::default()),
             ))
         }
 
@@ -189,4 +294,112 @@ mod tests {
             assert!(matches!(result, Err(AppError::Unauthorized(_))));
         }
     }
+
+    mod authorized {
+        use super::authenticated_user::test_state;
+        use super::*;
+        use crate::auth::roles::{Role, require};
+        use crate::store::memory::MemoryStore;
+
+        #[tokio::test]
+        async fn user_with_role_is_authorized() {
+            let state = test_state();
+            state
+                .set_user_roles(&ClientId::from("cid0"), vec![Role::Supervisor])
+                .await
+                .unwrap();
+            let token = MemoryStore::test_api_token(0);
+            let mut parts = parts_with_header(AUTHORIZATION, &format!("Bearer {token}"));
+
+            let result =
+                Authorized::<require::Supervisor>::from_request_parts(&mut parts, &state).await;
+
+            assert_eq!(result.unwrap().cid().as_str(), "cid0");
+        }
+
+        #[tokio::test]
+        async fn user_without_role_is_forbidden() {
+            let state = test_state();
+            let token = MemoryStore::test_api_token(0);
+            let mut parts = parts_with_header(AUTHORIZATION, &format!("Bearer {token}"));
+
+            let result =
+                Authorized::<require::Supervisor>::from_request_parts(&mut parts, &state).await;
+
+            assert!(matches!(result, Err(AppError::Forbidden(_))));
+        }
+
+        #[tokio::test]
+        async fn unauthenticated_user_is_unauthorized() {
+            let state = test_state();
+            let mut parts = parts_without_auth();
+
+            let result = Authorized::<require::Admin>::from_request_parts(&mut parts, &state).await;
+
+            assert!(matches!(result, Err(AppError::Unauthorized(_))));
+        }
+    }
+
+    mod scoped {
+        use super::authenticated_user::test_state;
+        use super::*;
+        use crate::auth::scopes::{Scope, require};
+
+        async fn scoped_token(state: &AppState, scopes: &[Scope]) -> String {
+            let (token, _) = state
+                .generate_api_token(
+                    "cid0",
+                    "Test",
+                    Some(Scopes::from_iter(scopes.iter().copied())),
+                )
+                .await
+                .unwrap();
+            token
+        }
+
+        #[tokio::test]
+        async fn token_with_scope_is_accepted() {
+            let state = test_state();
+            let token = scoped_token(&state, &[Scope::WsLogin]).await;
+            let mut parts = parts_with_header(AUTHORIZATION, &format!("Bearer {token}"));
+
+            let result = Scoped::<require::WsLogin>::from_request_parts(&mut parts, &state).await;
+
+            assert_eq!(result.unwrap().cid().as_str(), "cid0");
+        }
+
+        #[tokio::test]
+        async fn token_without_scope_is_forbidden() {
+            let state = test_state();
+            let token = scoped_token(&state, &[Scope::CoverageRead]).await;
+            let mut parts = parts_with_header(AUTHORIZATION, &format!("Bearer {token}"));
+
+            let result = Scoped::<require::WsLogin>::from_request_parts(&mut parts, &state).await;
+
+            assert!(matches!(result, Err(AppError::Forbidden(_))));
+        }
+
+        #[tokio::test]
+        async fn unrestricted_token_is_accepted() {
+            let state = test_state();
+            let token = MemoryStore::test_api_token(0);
+            let mut parts = parts_with_header(AUTHORIZATION, &format!("Bearer {token}"));
+
+            let result =
+                Scoped::<require::CallsWrite>::from_request_parts(&mut parts, &state).await;
+
+            assert!(result.is_ok());
+        }
+
+        #[tokio::test]
+        async fn scoped_token_is_rejected_by_authenticated_user() {
+            let state = test_state();
+            let token = scoped_token(&state, &[Scope::WsLogin]).await;
+            let mut parts = parts_with_header(AUTHORIZATION, &format!("Bearer {token}"));
+
+            let result = AuthenticatedUser::from_request_parts(&mut parts, &state).await;
+
+            assert!(matches!(result, Err(AppError::Forbidden(_))));
+        }
+    }
 }
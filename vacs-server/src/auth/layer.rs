@@ -1,7 +1,10 @@
+use crate::auth::dev::DevIdentityProvider;
 use crate::auth::users::Backend;
-use crate::auth::users::mock::MockBackend;
-use crate::config::AppConfig;
-use crate::http::session::{setup_memory_session_manager, setup_redis_session_manager};
+use crate::config::{AppConfig, IdentityProviderType};
+use crate::http::session::{
+    setup_memory_session_manager, setup_redis_session_manager, setup_sqlite_session_manager,
+};
+use crate::store::sqlite::SqliteStore;
 use anyhow::Context;
 use axum_login::{AuthManagerLayer, AuthManagerLayerBuilder};
 use oauth2::basic::BasicClient;
@@ -19,21 +22,7 @@ pub async fn setup_auth_layer(
 ) -> anyhow::Result<AuthManagerLayer<Backend, RedisStore<Pool>, SignedCookie>> {
     tracing::debug!("Setting up authentication layer");
 
-    let client = BasicClient::new(ClientId::new(config.auth.oauth.client_id.clone()))
-        .set_client_secret(ClientSecret::new(config.auth.oauth.client_secret.clone()))
-        .set_auth_uri(AuthUrl::new(config.auth.oauth.auth_url.clone()).context("Invalid auth URL")?)
-        .set_token_uri(
-            TokenUrl::new(config.auth.oauth.token_url.clone()).context("Invalid token URL")?,
-        )
-        .set_redirect_uri(
-            RedirectUrl::new(config.auth.oauth.redirect_url.clone())
-                .context("Invalid redirect URL")?,
-        );
-    let backend = Backend::new(
-        client,
-        config.vatsim.user_service.user_details_endpoint_url.clone(),
-    )?;
-
+    let backend = create_backend(config)?;
     let session_layer = setup_redis_session_manager(config, redis_pool).await?;
 
     tracing::debug!("Authentication layer setup complete");
@@ -41,15 +30,56 @@ pub async fn setup_auth_layer(
 }
 
 #[instrument(level = "debug", skip_all, err)]
-pub async fn setup_mock_auth_layer(
+pub async fn setup_sqlite_auth_layer(
     config: &AppConfig,
-) -> anyhow::Result<AuthManagerLayer<MockBackend, MemoryStore, SignedCookie>> {
-    tracing::debug!("Setting up mock authentication layer");
+    sqlite_store: SqliteStore,
+) -> anyhow::Result<AuthManagerLayer<Backend, SqliteStore, SignedCookie>> {
+    tracing::debug!("Setting up SQLite authentication layer");
 
-    let backend = MockBackend::default();
+    let backend = create_backend(config)?;
+    let session_layer = setup_sqlite_session_manager(config, sqlite_store).await?;
 
+    tracing::debug!("SQLite authentication layer setup complete");
+    Ok(AuthManagerLayerBuilder::new(backend, session_layer).build())
+}
+
+#[instrument(level = "debug", skip_all, err)]
+pub async fn setup_memory_auth_layer(
+    config: &AppConfig,
+) -> anyhow::Result<AuthManagerLayer<Backend, MemoryStore, SignedCookie>> {
+    tracing::debug!("Setting up memory authentication layer");
+
+    let backend = create_backend(config)?;
     let session_layer = setup_memory_session_manager(config).await?;
 
-    tracing::debug!("Mock authentication layer setup complete");
+    tracing::debug!("Memory authentication layer setup complete");
     Ok(AuthManagerLayerBuilder::new(backend, session_layer).build())
 }
+
+fn create_backend(config: &AppConfig) -> anyhow::Result<Backend> {
+    match config.auth.identity_provider {
+        IdentityProviderType::Vatsim => {
+            let client = BasicClient::new(ClientId::new(config.auth.oauth.client_id.clone()))
+                .set_client_secret(ClientSecret::new(config.auth.oauth.client_secret.clone()))
+                .set_auth_uri(
+                    AuthUrl::new(config.auth.oauth.auth_url.clone()).context("Invalid auth URL")?,
+                )
+                .set_token_uri(
+                    TokenUrl::new(config.auth.oauth.token_url.clone())
+                        .context("Invalid token URL")?,
+                )
+                .set_redirect_uri(
+                    RedirectUrl::new(config.auth.oauth.redirect_url.clone())
+                        .context("Invalid redirect URL")?,
+                );
+            Backend::new(
+                client,
+                config.vatsim.user_service.user_details_endpoint_url.clone(),
+            )
+        }
+        IdentityProviderType::Dev => Ok(Backend::dev(DevIdentityProvider::new(
+            &config.auth.dev_identity,
+            &config.auth.oauth.redirect_url,
+        )?)),
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Server-side role granting access to privileged features.
+///
+/// Roles are assigned by CID, either statically in the config (`auth.roles`) or at runtime via the
+/// store. Admins implicitly hold every other role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    Admin,
+    /// VATSIM supervisor, allowed to use moderation features.
+    Supervisor,
+    /// Facility engineer, allowed to preview coverage datasets.
+    FacilityEngineer,
+}
+
+impl Role {
+    /// Whether this role grants the access of the `required` role.
+    pub fn grants(&self, required: Role) -> bool {
+        *self == Role::Admin || *self == required
+    }
+}
+
+/// Set of roles held by a user.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Roles(HashSet<Role>);
+
+impl Roles {
+    pub fn has(&self, required: Role) -> bool {
+        self.0.iter().any(|role| role.grants(required))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Role> {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<Role> for Roles {
+    fn from_iter<T: IntoIterator<Item = Role>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<Role> for Roles {
+    fn extend<T: IntoIterator<Item = Role>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+/// Role required by an [`Authorized`](crate::auth::extractor::Authorized) extractor.
+pub trait RequiredRole {
+    const ROLE: Role;
+}
+
+/// Marker types for use with [`Authorized`](crate::auth::extractor::Authorized).
+pub mod require {
+    use super::{RequiredRole, Role};
+
+    pub struct Admin;
+    pub struct Supervisor;
+    pub struct FacilityEngineer;
+
+    impl RequiredRole for Admin {
+        const ROLE: Role = Role::Admin;
+    }
+
+    impl RequiredRole for Supervisor {
+        const ROLE: Role = Role::Supervisor;
+    }
+
+    impl RequiredRole for FacilityEngineer {
+        const ROLE: Role = Role::FacilityEngineer;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_grants_all_roles() {
+        let roles = Roles::from_iter([Role::Admin]);
+        assert!(roles.has(Role::Admin));
+        assert!(roles.has(Role::Supervisor));
+        assert!(roles.has(Role::FacilityEngineer));
+    }
+
+    #[test]
+    fn roles_grant_only_themselves() {
+        let roles = Roles::from_iter([Role::Supervisor]);
+        assert!(roles.has(Role::Supervisor));
+        assert!(!roles.has(Role::Admin));
+        assert!(!roles.has(Role::FacilityEngineer));
+        assert!(!Roles::default().has(Role::Supervisor));
+    }
+
+    #[test]
+    fn deserialize() {
+        let roles: Roles = serde_json::from_str(r#"["supervisor","facility-engineer"]"#).unwrap();
+        assert_eq!(
+            roles,
+            Roles::from_iter([Role::Supervisor, Role::FacilityEngineer])
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Scope restricting what an API token may be used for.
+///
+/// Tokens without scopes grant the same full access as a session. Tokens with scopes are only
+/// accepted by routes requiring one of their scopes via [`Scoped`](crate::auth::extractor::Scoped)
+/// and are rejected everywhere else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Scope {
+    /// Read station coverage, e.g. for stream overlays or facility dashboards.
+    #[serde(rename = "coverage:read")]
+    CoverageRead,
+    /// Place and accept calls.
+    #[serde(rename = "calls:write")]
+    CallsWrite,
+    /// Log in to the websocket server.
+    #[serde(rename = "ws:login")]
+    WsLogin,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Scope::CoverageRead => "coverage:read",
+            Scope::CallsWrite => "calls:write",
+            Scope::WsLogin => "ws:login",
+        })
+    }
+}
+
+/// Set of scopes granted to an API token.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Scopes(HashSet<Scope>);
+
+impl Scopes {
+    pub fn contains(&self, scope: Scope) -> bool {
+        self.0.contains(&scope)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Scope> {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<Scope> for Scopes {
+    fn from_iter<T: IntoIterator<Item = Scope>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Whether `scopes` grant `scope`. `None` stands for unrestricted access.
+pub fn grants(scopes: Option<&Scopes>, scope: Scope) -> bool {
+    scopes.is_none_or(|scopes| scopes.contains(scope))
+}
+
+/// Scope required by a [`Scoped`](crate::auth::extractor::Scoped) extractor.
+pub trait RequiredScope {
+    const SCOPE: Scope;
+}
+
+/// Marker types for use with [`Scoped`](crate::auth::extractor::Scoped).
+pub mod require {
+    use super::{RequiredScope, Scope};
+
+    pub struct CoverageRead;
+    pub struct CallsWrite;
+    pub struct WsLogin;
+
+    impl RequiredScope for CoverageRead {
+        const SCOPE: Scope = Scope::CoverageRead;
+    }
+
+    impl RequiredScope for CallsWrite {
+        const SCOPE: Scope = Scope::CallsWrite;
+    }
+
+    impl RequiredScope for WsLogin {
+        const SCOPE: Scope = Scope::WsLogin;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unrestricted_grants_all_scopes() {
+        assert!(grants(None, Scope::CoverageRead));
+        assert!(grants(None, Scope::CallsWrite));
+        assert!(grants(None, Scope::WsLogin));
+    }
+
+    #[test]
+    fn scopes_grant_only_themselves() {
+        let scopes = Scopes::from_iter([Scope::WsLogin]);
+        assert!(grants(Some(&scopes), Scope::WsLogin));
+        assert!(!grants(Some(&scopes), Scope::CallsWrite));
+        assert!(!grants(Some(&Scopes::default()), Scope::WsLogin));
+    }
+
+    #[test]
+    fn deserialize() {
+        let scopes: Scopes = serde_json::from_str(r#"["coverage:read","ws:login"]"#).unwrap();
+        assert_eq!(
+            scopes,
+            Scopes::from_iter([Scope::CoverageRead, Scope::WsLogin])
+        );
+        assert_eq!(Scope::CallsWrite.to_string(), "calls:write");
+    }
+}
@@ -1,4 +1,5 @@
 use crate::APP_USER_AGENT;
+use crate::auth::dev::DevIdentityProvider;
 use crate::http::error::AppError;
 use anyhow::Context;
 use axum_login::{AuthUser, AuthnBackend, UserId};
@@ -8,6 +9,7 @@ use oauth2::{
 };
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tracing::instrument;
 use vacs_protocol::vatsim::ClientId;
 
@@ -45,9 +47,20 @@ pub type VatsimOAuthClient =
 
 #[derive(Debug, Clone)]
 pub struct Backend {
+    provider: IdentityProvider,
+}
+
+#[derive(Debug, Clone)]
+enum IdentityProvider {
+    Vatsim(VatsimConnect),
+    Dev(Arc<DevIdentityProvider>),
+}
+
+#[derive(Debug, Clone)]
+struct VatsimConnect {
     client: VatsimOAuthClient,
     http_client: reqwest::Client,
-    vatsim_user_details_endpoint_url: String,
+    user_details_endpoint_url: String,
 }
 
 impl Backend {
@@ -56,24 +69,80 @@ impl Backend {
         vatsim_user_details_endpoint_url: String,
     ) -> anyhow::Result<Self> {
         Ok(Self {
-            client,
-            http_client: reqwest::ClientBuilder::new()
-                .user_agent(APP_USER_AGENT)
-                .build()
-                .context("Failed to build HTTP client")?,
-            vatsim_user_details_endpoint_url,
+            provider: IdentityProvider::Vatsim(VatsimConnect {
+                client,
+                http_client: reqwest::ClientBuilder::new()
+                    .user_agent(APP_USER_AGENT)
+                    .build()
+                    .context("Failed to build HTTP client")?,
+                user_details_endpoint_url: vatsim_user_details_endpoint_url,
+            }),
         })
     }
 
+    /// Creates a backend authenticating users against the in-process [`DevIdentityProvider`]
+    /// instead of VATSIM Connect.
+    pub fn dev(provider: DevIdentityProvider) -> Self {
+        Self {
+            provider: IdentityProvider::Dev(Arc::new(provider)),
+        }
+    }
+
+    /// Returns the dev identity provider, if the backend is using one.
+    pub fn dev_provider(&self) -> Option<&DevIdentityProvider> {
+        match &self.provider {
+            IdentityProvider::Dev(provider) => Some(provider),
+            IdentityProvider::Vatsim(_) => None,
+        }
+    }
+
     pub fn authorize_url(&self) -> (Url, CsrfToken) {
-        self.client.authorize_url(CsrfToken::new_random).url()
+        match &self.provider {
+            IdentityProvider::Vatsim(connect) => {
+                connect.client.authorize_url(CsrfToken::new_random).url()
+            }
+            IdentityProvider::Dev(provider) => provider.authorize_url(),
+        }
+    }
+
+    async fn exchange_code(&self, code: String) -> Result<String, AppError> {
+        match &self.provider {
+            IdentityProvider::Vatsim(connect) => {
+                tracing::trace!("Exchanging code for VATSIM access token");
+                let token = connect
+                    .client
+                    .exchange_code(AuthorizationCode::new(code))
+                    .request_async(&ReqwestClient(&connect.http_client))
+                    .await
+                    .context("Failed to exchange code")
+                    .map_err(|err| {
+                        tracing::warn!(?err, "Failed to exchange code for VATSIM access token");
+                        AppError::Unauthorized("Invalid code".to_string())
+                    })?;
+
+                Ok(token.access_token().secret().to_string())
+            }
+            IdentityProvider::Dev(provider) => provider
+                .exchange_code(&code)
+                .ok_or_else(|| AppError::Unauthorized("Invalid code".to_string())),
+        }
     }
 
     async fn fetch_user_details(&self, access_token: &str) -> Result<User, AppError> {
+        let connect = match &self.provider {
+            IdentityProvider::Vatsim(connect) => connect,
+            IdentityProvider::Dev(provider) => {
+                let cid = provider
+                    .user(access_token)
+                    .ok_or_else(|| AppError::Unauthorized("Invalid access token".to_string()))?;
+                return Ok(User { cid });
+            }
+        };
+
         tracing::trace!(?access_token, "Fetching user details");
-        let response = self
+        let response = connect
             .http_client
-            .get(self.vatsim_user_details_endpoint_url.clone())
+            .get(connect.user_details_endpoint_url.clone())
             .bearer_auth(access_token)
             .send()
             .await
@@ -116,19 +185,7 @@ impl AuthnBackend for Backend {
                     return Ok(None);
                 }
 
-                tracing::trace!("Exchanging code for VATSIM access token");
-                let token = self
-                    .client
-                    .exchange_code(AuthorizationCode::new(code))
-                    .request_async(&ReqwestClient(&self.http_client))
-                    .await
-                    .context("Failed to exchange code")
-                    .map_err(|err| {
-                        tracing::warn!(?err, "Failed to exchange code for VATSIM access token");
-                        AppError::Unauthorized("Invalid code".to_string())
-                    })?;
-
-                token.access_token().secret().to_string()
+                self.exchange_code(code).await?
             }
             Credentials::AccessToken { access_token } => access_token,
         };
@@ -189,91 +246,3 @@ impl<'a, 'c> oauth2::AsyncHttpClient<'c> for ReqwestClient<'a> {
         })
     }
 }
-
-pub mod mock {
-    use super::*;
-    use dashmap::DashMap;
-
-    #[derive(Debug, Clone)]
-    pub struct MockBackend {
-        access_tokens: DashMap<String, String>,
-        user_details: DashMap<String, ConnectUserDetails>,
-    }
-
-    impl Default for MockBackend {
-        fn default() -> Self {
-            let access_tokens = DashMap::new();
-            let user_details = DashMap::new();
-
-            for i in 0..=5 {
-                access_tokens.insert(format!("code{i}"), format!("token{i}"));
-                user_details.insert(
-                    format!("token{i}"),
-                    ConnectUserDetails {
-                        data: ConnectUserDetailsData {
-                            cid: ClientId::from(format!("cid{i}")),
-                        },
-                    },
-                );
-                user_details.insert(
-                    format!("access_token{i}"),
-                    ConnectUserDetails {
-                        data: ConnectUserDetailsData {
-                            cid: ClientId::from(format!("cid{i}")),
-                        },
-                    },
-                );
-            }
-            Self {
-                access_tokens,
-                user_details,
-            }
-        }
-    }
-
-    impl AuthnBackend for MockBackend {
-        type User = User;
-        type Credentials = Credentials;
-        type Error = AppError;
-
-        async fn authenticate(
-            &self,
-            creds: Self::Credentials,
-        ) -> Result<Option<Self::User>, Self::Error> {
-            let access_token = match creds {
-                Credentials::OAuthCode {
-                    code,
-                    stored_state,
-                    received_state,
-                } => {
-                    if stored_state != received_state {
-                        return Ok(None);
-                    }
-
-                    let Some(token) = self.access_tokens.get(&code).map(|t| t.clone()) else {
-                        return Err(AppError::Unauthorized("Invalid code".to_string()));
-                    };
-                    token
-                }
-                Credentials::AccessToken { access_token } => access_token,
-            };
-
-            let Some(user_details) = self.user_details.get(&access_token).map(|d| d.clone()) else {
-                return Err(AppError::Unauthorized("Invalid access token".to_string()));
-            };
-
-            Ok(Some(User {
-                cid: user_details.data.cid,
-            }))
-        }
-
-        async fn get_user(
-            &self,
-            user_id: &UserId<Self>,
-        ) -> Result<Option<Self::User>, Self::Error> {
-            Ok(Some(User {
-                cid: user_id.clone(),
-            }))
-        }
-    }
-}
@@ -0,0 +1,327 @@
+pub mod memory;
+pub mod redis;
+
+use anyhow::Context;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::instrument;
+use uuid::Uuid;
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::client::ClientMessage;
+use vacs_protocol::ws::server::{self, ClientChange, DisconnectReason, ServerMessage};
+
+/// Channel all instances of the cluster subscribe to.
+const BROADCAST_CHANNEL: &str = "vacs.cluster";
+
+/// Publish/subscribe transport connecting the instances of a cluster.
+#[async_trait::async_trait]
+pub trait ClusterBroker: Send + Sync {
+    async fn publish(&self, channel: &str, payload: Bytes) -> anyhow::Result<()>;
+    /// Subscribes to the given channels, returning a receiver for all payloads published to
+    /// them. The subscription ends when the receiver is dropped.
+    async fn subscribe(
+        &self,
+        channels: &[String],
+    ) -> anyhow::Result<mpsc::UnboundedReceiver<Bytes>>;
+}
+
+/// Message exchanged between the instances of a cluster.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[allow(clippy::large_enum_variant)] // messages are serialized right away
+pub enum ClusterMessage {
+    /// Announcement to broadcast to all clients of the receiving instance.
+    Announcement { announcement: server::Announcement },
+    /// Change to the roster of clients connected to the origin instance.
+    RosterChange { change: ClientChange },
+    /// Message for a client connected to the receiving instance.
+    #[serde(rename_all = "camelCase")]
+    Direct {
+        client_id: ClientId,
+        message: ServerMessage,
+    },
+    /// Message of a client connected to the origin instance concerning a call hosted by the
+    /// receiving instance.
+    #[serde(rename_all = "camelCase")]
+    CallMessage {
+        client_id: ClientId,
+        message: ClientMessage,
+    },
+    /// Asks the receiving instance to disconnect one of its clients, e.g. when kicked by a
+    /// supervisor connected to the origin instance.
+    #[serde(rename_all = "camelCase")]
+    Disconnect {
+        client_id: ClientId,
+        reason: DisconnectReason,
+    },
+    /// Asks the receiving instance to hand over the session of one of its clients, which
+    /// reconnected to the origin instance with the given resume token.
+    #[serde(rename_all = "camelCase")]
+    ResumeRequest {
+        client_id: ClientId,
+        resume_token: String,
+    },
+    /// Answers a [`ClusterMessage::ResumeRequest`] with the messages queued while the session
+    /// was detached, after recording the receiving instance as owner of the client. `None` if
+    /// there is no session to resume.
+    #[serde(rename_all = "camelCase")]
+    ResumeResponse {
+        client_id: ClientId,
+        pending: Option<Vec<ServerMessage>>,
+    },
+    /// Asks all instances to announce their connected clients, sent by instances joining the
+    /// cluster.
+    SyncRequest,
+    /// The origin instance is alive, published periodically. Instances not sending heartbeats
+    /// for [`ClusterConfig::instance_timeout`] are considered gone.
+    Heartbeat,
+    /// The origin instance is leaving the cluster, all of its clients are disconnected.
+    Leave,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClusterEnvelope {
+    /// ID of the instance the message originates from.
+    pub origin: String,
+    #[serde(flatten)]
+    pub message: ClusterMessage,
+}
+
+/// Connection of a server instance to the cluster.
+///
+/// Clients stay connected to a single instance, which hosts their session and the calls they
+/// initiate. Broadcasts and roster changes are published to all instances, messages for clients
+/// of other instances are routed to the instance owning the client, as recorded in the store.
+/// Clients reconnecting to another instance after a connection loss resume their session there,
+/// as it is handed over by the previous instance.
+pub struct Cluster {
+    instance_id: String,
+    broker: Arc<dyn ClusterBroker>,
+}
+
+impl Cluster {
+    pub fn new(instance_id: impl Into<String>, broker: Arc<dyn ClusterBroker>) -> Self {
+        Self {
+            instance_id: instance_id.into(),
+            broker,
+        }
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Publishes a message to the given instance, or all other instances if `None`.
+    #[instrument(level = "trace", skip(self, message), err)]
+    pub async fn publish(
+        &self,
+        instance_id: Option<&str>,
+        message: ClusterMessage,
+    ) -> anyhow::Result<()> {
+        let envelope = ClusterEnvelope {
+            origin: self.instance_id.clone(),
+            message,
+        };
+        let payload =
+            serde_json::to_vec(&envelope).context("Failed to serialize cluster message")?;
+        let channel = match instance_id {
+            Some(instance_id) => instance_channel(instance_id),
+            None => BROADCAST_CHANNEL.to_string(),
+        };
+        self.broker
+            .publish(&channel, Bytes::from(payload))
+            .await
+            .context("Failed to publish cluster message")
+    }
+
+    /// Subscribes to messages published to all instances and to this instance. Messages
+    /// originating from this instance are skipped.
+    #[instrument(level = "debug", skip(self), fields(instance_id = %self.instance_id), err)]
+    pub async fn subscribe(&self) -> anyhow::Result<mpsc::UnboundedReceiver<ClusterEnvelope>> {
+        let mut payload_rx = self
+            .broker
+            .subscribe(&[
+                BROADCAST_CHANNEL.to_string(),
+                instance_channel(&self.instance_id),
+            ])
+            .await
+            .context("Failed to subscribe to cluster channels")?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let instance_id = self.instance_id.clone();
+        tokio::spawn(async move {
+            while let Some(payload) = payload_rx.recv().await {
+                match serde_json::from_slice::<ClusterEnvelope>(&payload) {
+                    Ok(envelope) if envelope.origin == instance_id => {}
+                    Ok(envelope) => {
+                        if tx.send(envelope).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => tracing::warn!(?err, "Failed to deserialize cluster message"),
+                }
+            }
+        });
+        Ok(rx)
+    }
+}
+
+impl std::fmt::Debug for Cluster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cluster")
+            .field("instance_id", &self.instance_id)
+            .finish_non_exhaustive()
+    }
+}
+
+fn instance_channel(instance_id: &str) -> String {
+    format!("{BROADCAST_CHANNEL}.{instance_id}")
+}
+
+/// Tracks when the other instances of the cluster were last heard of.
+#[derive(Debug, Default)]
+pub struct ClusterInstances {
+    last_seen: HashMap<String, Instant>,
+}
+
+impl ClusterInstances {
+    /// Records a message of the given instance. Returns whether the instance was not known
+    /// before.
+    pub fn seen(&mut self, instance_id: &str) -> bool {
+        self.last_seen
+            .insert(instance_id.to_string(), Instant::now())
+            .is_none()
+    }
+
+    pub fn remove(&mut self, instance_id: &str) {
+        self.last_seen.remove(instance_id);
+    }
+
+    /// Removes and returns all instances not heard of within the given timeout.
+    pub fn take_stale(&mut self, timeout: Duration) -> Vec<String> {
+        let mut stale = Vec::new();
+        self.last_seen.retain(|instance_id, last_seen| {
+            if last_seen.elapsed() < timeout {
+                true
+            } else {
+                stale.push(instance_id.clone());
+                false
+            }
+        });
+        stale
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterConfig {
+    /// Run as one of multiple instances sharing clients via Redis pub/sub.
+    pub enabled: bool,
+    /// Unique ID of this instance. A random ID is generated on startup if omitted.
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    /// Interval in which this instance announces itself to the cluster and refreshes the
+    /// ownership of its clients and calls.
+    pub heartbeat_interval: Duration,
+    /// Time after which an instance not sending heartbeats is considered gone. Its clients are
+    /// removed and may log in to another instance. Also used as TTL of client and call ownership
+    /// records.
+    pub instance_timeout: Duration,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            instance_id: None,
+            heartbeat_interval: Duration::from_secs(5),
+            instance_timeout: Duration::from_secs(15),
+        }
+    }
+}
+
+impl ClusterConfig {
+    pub fn instance_id(&self) -> String {
+        self.instance_id
+            .clone()
+            .unwrap_or_else(|| Uuid::now_v7().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::memory::MemoryBroker;
+    use pretty_assertions::assert_eq;
+    use vacs_protocol::ws::shared::ErrorReason;
+
+    fn cluster(instance_id: &str, broker: &MemoryBroker) -> Cluster {
+        Cluster::new(instance_id, Arc::new(broker.clone()))
+    }
+
+    #[tokio::test]
+    async fn publish_to_all_instances() {
+        let broker = MemoryBroker::default();
+        let a = cluster("a", &broker);
+        let b = cluster("b", &broker);
+        let mut a_rx = a.subscribe().await.unwrap();
+        let mut b_rx = b.subscribe().await.unwrap();
+
+        a.publish(None, ClusterMessage::SyncRequest).await.unwrap();
+        b.publish(None, ClusterMessage::SyncRequest).await.unwrap();
+
+        assert_eq!(
+            a_rx.recv().await.unwrap(),
+            ClusterEnvelope {
+                origin: "b".to_string(),
+                message: ClusterMessage::SyncRequest,
+            }
+        );
+        assert_eq!(b_rx.recv().await.unwrap().origin, "a");
+        assert!(a_rx.try_recv().is_err());
+        assert!(b_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn publish_to_instance() {
+        let broker = MemoryBroker::default();
+        let a = cluster("a", &broker);
+        let b = cluster("b", &broker);
+        let c = cluster("c", &broker);
+        let mut b_rx = b.subscribe().await.unwrap();
+        let mut c_rx = c.subscribe().await.unwrap();
+
+        let message = ClusterMessage::Direct {
+            client_id: ClientId::from("client1"),
+            message: vacs_protocol::ws::shared::Error::new(ErrorReason::ClientNotFound).into(),
+        };
+        a.publish(Some("b"), message.clone()).await.unwrap();
+
+        assert_eq!(b_rx.recv().await.unwrap().message, message);
+        assert!(c_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn stale_instances_are_taken() {
+        let timeout = Duration::from_millis(100);
+        let mut instances = ClusterInstances::default();
+        assert!(instances.seen("a"));
+        assert!(instances.seen("b"));
+        assert!(!instances.seen("a"));
+        assert!(instances.take_stale(timeout).is_empty());
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        instances.seen("b");
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(instances.take_stale(timeout), vec!["a"]);
+        assert!(instances.seen("a"));
+
+        instances.remove("b");
+        tokio::time::sleep(timeout).await;
+        assert_eq!(instances.take_stale(timeout), vec!["a"]);
+    }
+}
@@ -0,0 +1,38 @@
+use crate::cluster::ClusterBroker;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// In-process broker connecting instances running in the same process, e.g. in tests. Clones
+/// share the same subscriptions.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBroker {
+    subscribers: Arc<Mutex<HashMap<String, Vec<mpsc::UnboundedSender<Bytes>>>>>,
+}
+
+#[async_trait::async_trait]
+impl ClusterBroker for MemoryBroker {
+    async fn publish(&self, channel: &str, payload: Bytes) -> anyhow::Result<()> {
+        if let Some(subscribers) = self.subscribers.lock().get_mut(channel) {
+            subscribers.retain(|tx| tx.send(payload.clone()).is_ok());
+        }
+        Ok(())
+    }
+
+    async fn subscribe(
+        &self,
+        channels: &[String],
+    ) -> anyhow::Result<mpsc::UnboundedReceiver<Bytes>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut subscribers = self.subscribers.lock();
+        for channel in channels {
+            subscribers
+                .entry(channel.clone())
+                .or_default()
+                .push(tx.clone());
+        }
+        Ok(rx)
+    }
+}
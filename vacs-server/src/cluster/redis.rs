@@ -0,0 +1,88 @@
+use crate::cluster::ClusterBroker;
+use crate::config::RedisConfig;
+use anyhow::Context;
+use bytes::Bytes;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tower_sessions_redis_store::fred::interfaces::{ClientLike, EventInterface, PubsubInterface};
+use tower_sessions_redis_store::fred::prelude::{Config, Pool};
+use tower_sessions_redis_store::fred::types::Builder;
+use tracing::instrument;
+
+/// Broker connecting instances via Redis pub/sub.
+///
+/// Messages are published via the shared connection pool, while each subscription uses a
+/// dedicated connection as Redis does not allow other commands on subscribed connections.
+#[derive(Debug)]
+pub struct RedisBroker {
+    pool: Pool,
+    config: Config,
+}
+
+impl RedisBroker {
+    #[instrument(level = "trace", skip(pool), err)]
+    pub fn new(redis_config: &RedisConfig, pool: Pool) -> anyhow::Result<Self> {
+        let config = Config::from_url_centralized(&redis_config.addr)
+            .context("Failed to create redis subscriber config")?;
+        Ok(Self { pool, config })
+    }
+}
+
+#[async_trait::async_trait]
+impl ClusterBroker for RedisBroker {
+    #[instrument(level = "trace", skip(self, payload), err)]
+    async fn publish(&self, channel: &str, payload: Bytes) -> anyhow::Result<()> {
+        self.pool
+            .next()
+            .publish::<(), _, _>(channel, payload)
+            .await
+            .context("Failed to publish message to redis")
+    }
+
+    #[instrument(level = "debug", skip(self), err)]
+    async fn subscribe(
+        &self,
+        channels: &[String],
+    ) -> anyhow::Result<mpsc::UnboundedReceiver<Bytes>> {
+        let subscriber = Builder::from_config(self.config.clone())
+            .build()
+            .context("Failed to create redis subscriber")?;
+        subscriber
+            .init()
+            .await
+            .context("Failed to connect to redis")?;
+
+        let mut message_rx = subscriber.message_rx();
+        subscriber
+            .subscribe(channels.to_vec())
+            .await
+            .context("Failed to subscribe to redis channels")?;
+        tracing::info!(?channels, "Subscribed to redis channels");
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let message = match message_rx.recv().await {
+                    Ok(message) => message,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Redis subscriber lagged, messages were dropped");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                match message.value.convert::<Bytes>() {
+                    Ok(payload) => {
+                        if tx.send(payload).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => tracing::warn!(?err, "Received invalid redis message"),
+                }
+            }
+            if let Err(err) = subscriber.quit().await {
+                tracing::debug!(?err, "Failed to close redis subscriber");
+            }
+        });
+        Ok(rx)
+    }
+}
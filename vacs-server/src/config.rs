@@ -1,24 +1,42 @@
+use crate::audit::AuditConfig;
+use crate::auth::roles::Role;
+use crate::cluster::ClusterConfig;
 use crate::ice::IceConfig;
 use crate::ratelimit::RateLimitersConfig;
 use crate::release::catalog::CatalogConfig;
+use crate::sfu::SfuConfig;
 use anyhow::Context;
 use axum_client_ip::ClientIpSource;
 use config::{Config, Environment, File};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 use std::time::Duration;
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::shared::{Capabilities, Capability};
 
 pub const BROADCAST_CHANNEL_CAPACITY: usize = 100;
 pub const CLIENT_CHANNEL_CAPACITY: usize = 100;
+pub const ROSTER_HISTORY_CAPACITY: usize = 1000;
+pub const AUDIT_LOG_MEMORY_CAPACITY: usize = 1000;
+pub const AUDIT_QUERY_MAX_LIMIT: usize = 1000;
+pub const API_TOKEN_LAST_USED_RESOLUTION: Duration = Duration::from_secs(60);
+pub const API_TOKEN_NAME_MAX_LENGTH: usize = 64;
 pub const CLIENT_WEBSOCKET_TASK_CHANNEL_CAPACITY: usize = 100;
 pub const CLIENT_WEBSOCKET_PING_INTERVAL: Duration = Duration::from_secs(10);
 pub const CLIENT_WEBSOCKET_PONG_TIMEOUT: Duration = Duration::from_secs(30);
 pub const SERVER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+pub const SESSION_TAKEOVER_TIMEOUT: Duration = Duration::from_secs(2);
+pub const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+pub const DRAIN_DISCONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+pub const CLUSTER_RESUME_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct AppConfig {
     pub server: ServerConfig,
     pub redis: RedisConfig,
+    #[serde(default)]
+    pub store: StoreConfig,
     pub session: SessionConfig,
     pub auth: AuthConfig,
     pub vatsim: VatsimConfig,
@@ -26,6 +44,12 @@ pub struct AppConfig {
     pub rate_limiters: RateLimitersConfig,
     pub ice: IceConfig,
     pub admin: AdminConfig,
+    #[serde(default)]
+    pub sfu: SfuConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub cluster: ClusterConfig,
 }
 
 impl AppConfig {
@@ -44,14 +68,25 @@ impl AppConfig {
             .try_deserialize::<Self>()
             .context("Failed to deserialize config")?;
 
-        if config.auth.oauth.client_id.is_empty() {
+        if config.auth.identity_provider == IdentityProviderType::Dev {
+            if config.auth.dev_identity.cids.is_empty() {
+                anyhow::bail!("Dev identity provider has no CIDs configured");
+            }
+        } else if config.auth.oauth.client_id.is_empty() {
             anyhow::bail!("OAuth client ID is empty");
         } else if config.auth.oauth.client_secret.is_empty() {
             anyhow::bail!("OAuth client secret is empty");
-        } else if config.session.signing_key.is_empty() {
+        }
+
+        if config.session.signing_key.is_empty() {
             anyhow::bail!("Session signing key is empty");
         }
 
+        let replay_speed = config.vatsim.data_feed_replay_speed;
+        if !replay_speed.is_finite() || replay_speed <= 0.0 {
+            anyhow::bail!("Data feed replay speed must be a positive number, got {replay_speed}");
+        }
+
         Ok(config)
     }
 }
@@ -72,6 +107,25 @@ pub struct ServerConfig {
     pub client_ip_source: ClientIpSource,
     #[serde(default)]
     pub debug_endpoints: bool,
+    /// Compress messages sent to clients requesting compression at login.
+    pub websocket_compression: bool,
+    /// Capabilities withheld from clients during login, e.g. while rolling out a new feature.
+    #[serde(default)]
+    pub disabled_capabilities: Vec<Capability>,
+    /// Maximum time to wait for active calls to end before disconnecting clients on shutdown.
+    pub drain_timeout_secs: u64,
+    /// Delay after which clients disconnected on shutdown are asked to reconnect.
+    pub drain_retry_after_secs: u64,
+}
+
+impl ServerConfig {
+    /// Capabilities offered to clients during login.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities::all()
+            .iter()
+            .filter(|capability| !self.disabled_capabilities.contains(capability))
+            .collect()
+    }
 }
 
 impl Default for ServerConfig {
@@ -81,6 +135,10 @@ impl Default for ServerConfig {
             metrics_bind_addr: "0.0.0.0:9200".to_string(),
             client_ip_source: ClientIpSource::ConnectInfo,
             debug_endpoints: false,
+            websocket_compression: true,
+            disabled_capabilities: Vec::new(),
+            drain_timeout_secs: 20,
+            drain_retry_after_secs: 5,
         }
     }
 }
@@ -98,6 +156,32 @@ impl Default for RedisConfig {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StoreConfig {
+    /// Backend storing tokens and sessions.
+    pub backend: StoreType,
+    /// Path of the database file used by the [`StoreType::Sqlite`] backend.
+    pub sqlite_path: String,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            backend: StoreType::default(),
+            sqlite_path: "/var/lib/vacs-server/data/vacs.db".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StoreType {
+    /// Redis, configured via [`RedisConfig`]. Required for running multiple instances.
+    #[default]
+    Redis,
+    /// SQLite database on the local disk, for single-instance deployments without Redis.
+    Sqlite,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SessionConfig {
     pub secure: bool,
@@ -120,16 +204,64 @@ impl Default for SessionConfig {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AuthConfig {
     pub login_flow_timeout_millis: u64,
+    /// Time a client's session is kept after losing its connection, allowing the client to
+    /// resume it using its resume token. Set to 0 to disable session resumption.
+    pub session_resume_grace_period_secs: u64,
+    /// Identity provider users log in with via the HTTP API.
+    #[serde(default)]
+    pub identity_provider: IdentityProviderType,
     pub oauth: OAuthConfig,
+    /// Configuration of the dev identity provider, only used if it is selected.
+    #[serde(default)]
+    pub dev_identity: DevIdentityConfig,
     pub api_token: ApiTokenConfig,
+    /// Roles statically assigned by CID, in addition to the roles assigned in the store.
+    #[serde(default)]
+    pub roles: HashMap<ClientId, Vec<Role>>,
 }
 
 impl Default for AuthConfig {
     fn default() -> Self {
         Self {
             login_flow_timeout_millis: 10000,
+            session_resume_grace_period_secs: 30,
+            identity_provider: IdentityProviderType::default(),
             oauth: OAuthConfig::default(),
+            dev_identity: DevIdentityConfig::default(),
             api_token: ApiTokenConfig::default(),
+            roles: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IdentityProviderType {
+    /// VATSIM Connect, configured via [`OAuthConfig`].
+    #[default]
+    Vatsim,
+    /// In-process stand-in for VATSIM Connect for local development and integration tests,
+    /// configured via [`DevIdentityConfig`]. Anyone can log in as any of the configured CIDs,
+    /// so this must never be used in production.
+    Dev,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DevIdentityConfig {
+    /// Base URL the server is reachable at, used to build the authorization URL of the dev
+    /// identity provider.
+    pub base_url: String,
+    /// CIDs users can log in as.
+    pub cids: Vec<ClientId>,
+}
+
+impl Default for DevIdentityConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:3000".to_string(),
+            // CIDs of the VATSIM Connect development environment test accounts
+            cids: (10000001..=10000010)
+                .map(|cid| ClientId::from(cid.to_string()))
+                .collect(),
         }
     }
 }
@@ -175,6 +307,16 @@ pub struct VatsimConfig {
     pub slurper_base_url: String,
     pub data_feed_url: String,
     pub data_feed_timeout: Duration,
+    /// Directory to record fetched data feed snapshots to, allowing them to be replayed later.
+    #[serde(default)]
+    pub data_feed_record_dir: Option<String>,
+    /// Directory of recorded data feed snapshots to replay instead of fetching the live data
+    /// feed, e.g. to reproduce coverage issues locally.
+    #[serde(default)]
+    pub data_feed_replay_dir: Option<String>,
+    /// Playback speed of the data feed replay, e.g. `10.0` for ten times real-time. Must be
+    /// finite and greater than zero.
+    pub data_feed_replay_speed: f64,
     pub controller_update_interval: Duration,
     /// Path to the dataset coverage directory. Must be a **subdirectory** of
     /// the volume mount - not the volume root itself - so that the dataset
@@ -194,6 +336,9 @@ impl Default for VatsimConfig {
             slurper_base_url: "https://slurper.vatsim.net".to_string(),
             data_feed_url: "https://data.vatsim.net/v3/vatsim-data.json".to_string(),
             data_feed_timeout: Duration::from_secs(2),
+            data_feed_record_dir: None,
+            data_feed_replay_dir: None,
+            data_feed_replay_speed: 1.0,
             controller_update_interval: Duration::from_secs(30),
             coverage_dir: "/var/lib/vacs-server/data/coverage".to_string(),
         }
@@ -238,6 +383,8 @@ pub struct AdminConfig {
     /// `repo:<owner>/<repo>:environment:<environment_name>`
     /// e.g. `repo:vacs-project/vacs-data:environment:production`
     pub oidc_allowed_sub: String,
+    /// JWKS endpoint providing the keys GitHub OIDC tokens are signed with.
+    pub oidc_jwks_url: String,
     /// Configuration for the dataset repository. If omitted, the server
     /// will only load the dataset from the local `coverage_dir` on disk
     /// and the admin reload endpoint will be unavailable.
@@ -250,6 +397,8 @@ impl Default for AdminConfig {
         Self {
             oidc_audience: "https://vacs.network".to_string(),
             oidc_allowed_sub: String::new(),
+            oidc_jwks_url: "https://token.actions.githubusercontent.com/.well-known/jwks"
+                .to_string(),
             dataset: None,
         }
     }
@@ -237,13 +237,7 @@ impl DatasetManager {
 
         // Validate by loading - this catches any schema / parse errors before
         // we touch the on-disk copy.
-        let dataset_path = dataset_dir.to_string_lossy().to_string();
-        tracing::info!(%dataset_path, "Validating downloaded dataset");
-
-        let network = tokio::task::spawn_blocking(move || Network::load_from_dir(&dataset_path))
-            .await
-            .context("Dataset load task panicked")?
-            .map_err(|errs| anyhow::anyhow!("Failed to parse dataset: {errs:?}"))?;
+        let network = Self::load_dataset(&dataset_dir).await?;
 
         // Atomically swap the on-disk coverage directory.
         let cov = self.coverage_dir.clone();
@@ -259,6 +253,27 @@ impl DatasetManager {
         Ok(network)
     }
 
+    /// Download and validate the dataset for a given git ref without installing it.
+    ///
+    /// Returns the loaded [`Network`], allowing a dataset to be previewed
+    /// before it gets deployed.
+    #[instrument(level = "info", skip(self))]
+    pub async fn fetch_preview(&self, ref_name: &str) -> Result<Network> {
+        let temp_dir = self.download_and_extract(ref_name).await?;
+        let dataset_dir = Self::find_dataset_dir(temp_dir.path())?;
+        Self::load_dataset(&dataset_dir).await
+    }
+
+    async fn load_dataset(dataset_dir: &Path) -> Result<Network> {
+        let dataset_path = dataset_dir.to_string_lossy().to_string();
+        tracing::info!(%dataset_path, "Validating downloaded dataset");
+
+        tokio::task::spawn_blocking(move || Network::load_from_dir(&dataset_path))
+            .await
+            .context("Dataset load task panicked")?
+            .map_err(|errs| anyhow::anyhow!("Failed to parse dataset: {errs:?}"))
+    }
+
     /// Synchronise the dataset on startup.
     ///
     /// 1. Resolve the `deployed/<env>` tag to a commit SHA.
@@ -102,6 +102,8 @@ pub enum AppError {
     BadRequest(String),
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
     #[error("Not Found")]
     NotFound,
     #[error("Too Many Requests: retry after {0}")]
@@ -123,6 +125,10 @@ impl IntoResponse for AppError {
                 ProblemDetails::new(StatusCode::UNAUTHORIZED.as_u16(), "Unauthorized")
                     .with_detail(&msg)
             }
+            AppError::Forbidden(msg) => {
+                tracing::debug!(?msg, "Forbidden");
+                ProblemDetails::new(StatusCode::FORBIDDEN.as_u16(), "Forbidden").with_detail(&msg)
+            }
             AppError::NotFound => ProblemDetails::new(StatusCode::NOT_FOUND.as_u16(), "Not Found"),
             AppError::TooManyRequests(retry_after_secs) => {
                 tracing::debug!(?retry_after_secs, "Too Many Requests");
@@ -1,4 +1,5 @@
 use crate::config::AppConfig;
+use crate::store::sqlite::SqliteStore;
 use tower_sessions::cookie::{Key, SameSite, time};
 use tower_sessions::service::SignedCookie;
 use tower_sessions::{Expiry, MemoryStore, SessionManagerLayer, SessionStore};
@@ -14,6 +15,14 @@ pub async fn setup_redis_session_manager(
     Ok(configure_session_manager(config, session_store))
 }
 
+#[instrument(level = "info", skip_all, err)]
+pub async fn setup_sqlite_session_manager(
+    config: &AppConfig,
+    sqlite_store: SqliteStore,
+) -> anyhow::Result<SessionManagerLayer<SqliteStore, SignedCookie>> {
+    Ok(configure_session_manager(config, sqlite_store))
+}
+
 #[instrument(level = "info", skip_all, err)]
 pub async fn setup_memory_session_manager(
     config: &AppConfig,
@@ -1,6 +1,8 @@
 use crate::ice::provider::IceConfigProvider;
 use crate::ice::provider::cloudflare::CloudflareIceProvider;
+use crate::ice::provider::composite::CompositeIceProvider;
 use crate::ice::provider::stun::StunOnlyProvider;
+use crate::ice::provider::turn::SelfHostedTurnProvider;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Duration;
@@ -17,19 +19,36 @@ pub enum IceError {
     Timeout(String),
 }
 
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IceConfigProviderType {
     #[default]
     StunOnly,
     Cloudflare,
+    SelfHostedTurn,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IceConfig {
     pub provider: IceConfigProviderType,
+    /// Providers tried in order if the primary provider fails or times out.
+    #[serde(default)]
+    pub fallback_providers: Vec<IceConfigProviderType>,
+    /// Timeout for a single provider request before falling back to the next provider.
+    #[serde(default)]
+    pub provider_timeout: Option<Duration>,
     pub stun_servers: Option<Vec<String>>,
     pub cloudflare_turn_key_id: Option<String>,
     pub cloudflare_turn_key_api_token: Option<String>,
+    /// Host of the self-hosted TURN server, used for the TURN and TURNS URLs.
+    #[serde(default)]
+    pub turn_server_host: Option<String>,
+    #[serde(default)]
+    pub turn_server_port: Option<u16>,
+    #[serde(default)]
+    pub turn_server_tls_port: Option<u16>,
+    /// Secret shared with the self-hosted TURN server for minting TURN REST API credentials.
+    #[serde(default)]
+    pub turn_shared_secret: Option<String>,
     pub turn_credential_ttl: Option<Duration>,
 }
 
@@ -37,12 +56,18 @@ impl Default for IceConfig {
     fn default() -> Self {
         Self {
             provider: IceConfigProviderType::StunOnly,
+            fallback_providers: Vec::new(),
+            provider_timeout: Some(CompositeIceProvider::DEFAULT_TIMEOUT),
             stun_servers: Some(vec![
                 "stun:stun.cloudflare.com:3478".to_string(),
                 "stun:stun.cloudflare.com:53".to_string(),
             ]),
             cloudflare_turn_key_api_token: None,
             cloudflare_turn_key_id: None,
+            turn_server_host: None,
+            turn_server_port: None,
+            turn_server_tls_port: None,
+            turn_shared_secret: None,
             turn_credential_ttl: Some(Self::DEFAULT_TURN_CREDENTIAL_TTL),
         }
     }
@@ -52,7 +77,23 @@ impl IceConfig {
     const DEFAULT_TURN_CREDENTIAL_TTL: Duration = Duration::from_hours(6);
 
     pub fn create_provider(&self) -> Result<Arc<dyn IceConfigProvider>, IceError> {
-        match self.provider {
+        let backends = std::iter::once(self.provider)
+            .chain(self.fallback_providers.iter().copied())
+            .map(|provider_type| Ok((provider_type, self.create_backend(provider_type)?)))
+            .collect::<Result<Vec<_>, IceError>>()?;
+
+        Ok(Arc::new(CompositeIceProvider::new(
+            backends,
+            self.provider_timeout
+                .unwrap_or(CompositeIceProvider::DEFAULT_TIMEOUT),
+        )))
+    }
+
+    fn create_backend(
+        &self,
+        provider_type: IceConfigProviderType,
+    ) -> Result<Arc<dyn IceConfigProvider>, IceError> {
+        match provider_type {
             IceConfigProviderType::StunOnly => {
                 if let Some(stun_servers) = self.stun_servers.clone() {
                     Ok(Arc::new(StunOnlyProvider::new(stun_servers)))
@@ -79,6 +120,24 @@ impl IceConfig {
                     )),
                 }
             }
+            IceConfigProviderType::SelfHostedTurn => {
+                match (&self.turn_server_host, &self.turn_shared_secret) {
+                    (Some(host), Some(shared_secret)) => Ok(Arc::new(SelfHostedTurnProvider::new(
+                        host,
+                        self.turn_server_port
+                            .unwrap_or(SelfHostedTurnProvider::DEFAULT_PORT),
+                        self.turn_server_tls_port
+                            .unwrap_or(SelfHostedTurnProvider::DEFAULT_TLS_PORT),
+                        shared_secret,
+                        self.turn_credential_ttl
+                            .unwrap_or(Self::DEFAULT_TURN_CREDENTIAL_TTL)
+                            .as_secs(),
+                    )?)),
+                    _ => Err(IceError::Config(
+                        "Missing self-hosted TURN server host or shared secret".to_string(),
+                    )),
+                }
+            }
         }
     }
 }
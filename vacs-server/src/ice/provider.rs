@@ -1,5 +1,7 @@
 pub mod cloudflare;
+pub mod composite;
 pub mod stun;
+pub mod turn;
 
 use crate::ice::IceError;
 use vacs_protocol::http::webrtc::IceConfig;
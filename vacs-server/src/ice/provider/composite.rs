@@ -0,0 +1,344 @@
+use crate::ice::provider::IceConfigProvider;
+use crate::ice::{IceConfigProviderType, IceError};
+use crate::metrics::IceMetrics;
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::fmt::{Debug, Formatter};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use tracing::instrument;
+use vacs_protocol::http::webrtc::IceConfig;
+use vacs_protocol::vatsim::ClientId;
+
+const ICE_CONFIG_CACHE_SIZE: usize = 4096;
+
+/// Provider trying an ordered list of backends until one returns an ICE config.
+///
+/// Each backend is queried with a timeout. Successful configs with an expiry are cached per user
+/// and served again until [`CompositeIceProvider::REFRESH_AFTER_TTL_FRACTION`] of their TTL has
+/// elapsed, avoiding repeated credential generation. If all backends fail, a cached config is returned
+/// until it expires.
+pub struct CompositeIceProvider {
+    backends: Vec<(IceConfigProviderType, Arc<dyn IceConfigProvider>)>,
+    timeout: Duration,
+    cache: Mutex<LruCache<ClientId, CachedIceConfig>>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedIceConfig {
+    config: IceConfig,
+    /// Unix timestamp in seconds after which a fresh config is requested from the backends.
+    refresh_at: u64,
+}
+
+impl Debug for CompositeIceProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompositeIceProvider")
+            .field(
+                "backends",
+                &self.backends.iter().map(|(t, _)| t).collect::<Vec<_>>(),
+            )
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CompositeIceProvider {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+    /// Fraction of a cached config's TTL after which it is refreshed.
+    pub const REFRESH_AFTER_TTL_FRACTION: f64 = 0.5;
+
+    pub fn new(
+        backends: Vec<(IceConfigProviderType, Arc<dyn IceConfigProvider>)>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            backends,
+            timeout,
+            cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(ICE_CONFIG_CACHE_SIZE).unwrap(),
+            )),
+        }
+    }
+
+    fn cached(&self, user_id: &ClientId, now: u64) -> Option<IceConfig> {
+        self.cache
+            .lock()
+            .get(user_id)
+            .filter(|cached| cached.refresh_at > now)
+            .map(|cached| cached.config.clone())
+    }
+
+    fn cached_unexpired(&self, user_id: &ClientId, now: u64) -> Option<IceConfig> {
+        self.cache
+            .lock()
+            .get(user_id)
+            .filter(|cached| cached.config.expires_at.is_some_and(|e| e > now))
+            .map(|cached| cached.config.clone())
+    }
+
+    fn store(&self, user_id: &ClientId, config: &IceConfig, now: u64) {
+        if let Some(expires_at) = config.expires_at {
+            let ttl = expires_at.saturating_sub(now);
+            let refresh_at = now + (ttl as f64 * Self::REFRESH_AFTER_TTL_FRACTION) as u64;
+            self.cache.lock().put(
+                user_id.clone(),
+                CachedIceConfig {
+                    config: config.clone(),
+                    refresh_at,
+                },
+            );
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IceConfigProvider for CompositeIceProvider {
+    #[instrument(level = "debug", skip(self), err)]
+    async fn get_ice_config(&self, user_id: &ClientId) -> Result<IceConfig, IceError> {
+        let now = UNIX_EPOCH.elapsed().unwrap_or_default().as_secs();
+
+        if let Some(config) = self.cached(user_id, now) {
+            tracing::trace!("Serving cached ICE config");
+            IceMetrics::served_from_cache(false);
+            return Ok(config);
+        }
+
+        let mut last_err = None;
+        for (provider_type, backend) in &self.backends {
+            match tokio::time::timeout(self.timeout, backend.get_ice_config(user_id)).await {
+                Ok(Ok(config)) => {
+                    tracing::trace!(?provider_type, "Serving ICE config from provider");
+                    IceMetrics::served_by_provider(*provider_type);
+                    self.store(user_id, &config, now);
+                    return Ok(config);
+                }
+                Ok(Err(err)) => {
+                    tracing::warn!(?err, ?provider_type, "ICE config provider failed");
+                    IceMetrics::provider_failure(*provider_type, false);
+                    last_err = Some(err);
+                }
+                Err(_) => {
+                    tracing::warn!(?provider_type, timeout = ?self.timeout, "ICE config provider timed out");
+                    IceMetrics::provider_failure(*provider_type, true);
+                    last_err = Some(IceError::Timeout(format!("{provider_type:?}")));
+                }
+            }
+        }
+
+        if let Some(config) = self.cached_unexpired(user_id, now) {
+            tracing::warn!("All ICE config providers failed, serving cached ICE config");
+            IceMetrics::served_from_cache(true);
+            return Ok(config);
+        }
+
+        Err(last_err
+            .unwrap_or_else(|| IceError::Config("No ICE config providers configured".to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::{assert_eq, assert_matches};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use vacs_protocol::http::webrtc::IceServer;
+
+    #[derive(Debug)]
+    enum Behavior {
+        Succeed(Option<u64>),
+        Fail,
+        Hang,
+    }
+
+    #[derive(Debug)]
+    struct MockProvider {
+        behavior: Mutex<Behavior>,
+        url: &'static str,
+        calls: AtomicUsize,
+    }
+
+    impl MockProvider {
+        fn new(url: &'static str, behavior: Behavior) -> Arc<Self> {
+            Arc::new(Self {
+                behavior: Mutex::new(behavior),
+                url,
+                calls: AtomicUsize::new(0),
+            })
+        }
+
+        fn set_behavior(&self, behavior: Behavior) {
+            *self.behavior.lock() = behavior;
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl IceConfigProvider for MockProvider {
+        async fn get_ice_config(&self, _user_id: &ClientId) -> Result<IceConfig, IceError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let expires_at = match *self.behavior.lock() {
+                Behavior::Succeed(expires_at) => Some(expires_at),
+                Behavior::Fail => return Err(IceError::Provider("mock failure".to_string())),
+                Behavior::Hang => None,
+            };
+            let Some(expires_at) = expires_at else {
+                return std::future::pending().await;
+            };
+            Ok(IceConfig {
+                ice_servers: vec![IceServer::from(self.url.to_string())],
+                expires_at,
+            })
+        }
+    }
+
+    fn now() -> u64 {
+        UNIX_EPOCH.elapsed().unwrap().as_secs()
+    }
+
+    fn composite(backends: &[&Arc<MockProvider>]) -> CompositeIceProvider {
+        CompositeIceProvider::new(
+            backends
+                .iter()
+                .map(|b| {
+                    (
+                        IceConfigProviderType::Cloudflare,
+                        Arc::clone(b) as Arc<dyn IceConfigProvider>,
+                    )
+                })
+                .collect(),
+            Duration::from_millis(50),
+        )
+    }
+
+    fn served_url(config: &IceConfig) -> &str {
+        &config.ice_servers[0].urls[0]
+    }
+
+    #[tokio::test]
+    async fn first_provider_serves() {
+        let primary = MockProvider::new("turn:primary", Behavior::Succeed(None));
+        let fallback = MockProvider::new("turn:fallback", Behavior::Succeed(None));
+        let provider = composite(&[&primary, &fallback]);
+
+        let config = provider
+            .get_ice_config(&ClientId::from("client1"))
+            .await
+            .unwrap();
+        assert_eq!(served_url(&config), "turn:primary");
+        assert_eq!(fallback.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_error() {
+        let primary = MockProvider::new("turn:primary", Behavior::Fail);
+        let fallback = MockProvider::new("turn:fallback", Behavior::Succeed(None));
+        let provider = composite(&[&primary, &fallback]);
+
+        let config = provider
+            .get_ice_config(&ClientId::from("client1"))
+            .await
+            .unwrap();
+        assert_eq!(served_url(&config), "turn:fallback");
+        assert_eq!(primary.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_timeout() {
+        let primary = MockProvider::new("turn:primary", Behavior::Hang);
+        let fallback = MockProvider::new("turn:fallback", Behavior::Succeed(None));
+        let provider = composite(&[&primary, &fallback]);
+
+        let config = provider
+            .get_ice_config(&ClientId::from("client1"))
+            .await
+            .unwrap();
+        assert_eq!(served_url(&config), "turn:fallback");
+    }
+
+    #[tokio::test]
+    async fn all_providers_fail() {
+        let primary = MockProvider::new("turn:primary", Behavior::Fail);
+        let fallback = MockProvider::new("turn:fallback", Behavior::Hang);
+        let provider = composite(&[&primary, &fallback]);
+
+        let result = provider.get_ice_config(&ClientId::from("client1")).await;
+        assert_matches!(result, Err(IceError::Timeout(_)));
+    }
+
+    #[tokio::test]
+    async fn serves_cached_config_while_valid() {
+        let primary = MockProvider::new("turn:primary", Behavior::Succeed(Some(now() + 7200)));
+        let provider = composite(&[&primary]);
+        let user_id = ClientId::from("client1");
+
+        let first = provider.get_ice_config(&user_id).await.unwrap();
+        let second = provider.get_ice_config(&user_id).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(primary.calls(), 1);
+
+        // cache is per user
+        provider
+            .get_ice_config(&ClientId::from("client2"))
+            .await
+            .unwrap();
+        assert_eq!(primary.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn serves_cached_short_lived_config() {
+        let primary = MockProvider::new("turn:primary", Behavior::Succeed(Some(now() + 600)));
+        let provider = composite(&[&primary]);
+        let user_id = ClientId::from("client1");
+
+        provider.get_ice_config(&user_id).await.unwrap();
+        provider.get_ice_config(&user_id).await.unwrap();
+        assert_eq!(primary.calls(), 1);
+
+        let refresh_at = provider.cache.lock().get(&user_id).unwrap().refresh_at;
+        assert!(refresh_at.abs_diff(now() + 300) <= 1);
+    }
+
+    #[tokio::test]
+    async fn refreshes_config_close_to_expiry() {
+        let primary = MockProvider::new("turn:primary", Behavior::Succeed(Some(now() + 60)));
+        let provider = composite(&[&primary]);
+        let user_id = ClientId::from("client1");
+
+        provider.get_ice_config(&user_id).await.unwrap();
+        provider.cache.lock().get_mut(&user_id).unwrap().refresh_at = now();
+        provider.get_ice_config(&user_id).await.unwrap();
+        assert_eq!(primary.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn serves_stale_cached_config_if_all_providers_fail() {
+        let primary = MockProvider::new("turn:primary", Behavior::Succeed(Some(now() + 60)));
+        let provider = composite(&[&primary]);
+        let user_id = ClientId::from("client1");
+
+        let first = provider.get_ice_config(&user_id).await.unwrap();
+        provider.cache.lock().get_mut(&user_id).unwrap().refresh_at = now();
+        primary.set_behavior(Behavior::Fail);
+
+        let second = provider.get_ice_config(&user_id).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(primary.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn does_not_serve_expired_config() {
+        let primary = MockProvider::new("turn:primary", Behavior::Succeed(Some(now() - 1)));
+        let provider = composite(&[&primary]);
+        let user_id = ClientId::from("client1");
+
+        provider.get_ice_config(&user_id).await.unwrap();
+        primary.set_behavior(Behavior::Fail);
+
+        assert!(provider.get_ice_config(&user_id).await.is_err());
+    }
+}
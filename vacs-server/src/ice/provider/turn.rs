@@ -0,0 +1,195 @@
+use crate::ice::IceError;
+use crate::ice::provider::IceConfigProvider;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use std::fmt::{Debug, Formatter};
+use std::time::UNIX_EPOCH;
+use tracing::instrument;
+use vacs_protocol::http::webrtc::{IceConfig, IceServer};
+use vacs_protocol::vatsim::ClientId;
+
+/// Provides credentials for a self-hosted TURN server (e.g. coturn) using the TURN REST API
+/// scheme, where the server and this provider share a secret instead of storing per-user
+/// credentials.
+///
+/// The username is `<expiry>:<user_id>` and the credential the base64 encoded HMAC-SHA1 of the
+/// username, keyed with the shared secret. The TURN server validates both without any
+/// communication with vacs-server (coturn: `use-auth-secret` and `static-auth-secret`).
+#[derive(Clone)]
+pub struct SelfHostedTurnProvider {
+    host: String,
+    port: u16,
+    tls_port: u16,
+    shared_secret: String,
+    ttl: u64,
+}
+
+impl Debug for SelfHostedTurnProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SelfHostedTurnProvider")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls_port", &self.tls_port)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SelfHostedTurnProvider {
+    pub const DEFAULT_PORT: u16 = 3478;
+    pub const DEFAULT_TLS_PORT: u16 = 5349;
+
+    pub fn new(
+        host: impl Into<String>,
+        port: u16,
+        tls_port: u16,
+        shared_secret: impl Into<String>,
+        ttl: u64,
+    ) -> Result<Self, IceError> {
+        let host = host.into();
+        let shared_secret = shared_secret.into();
+        if host.is_empty() {
+            return Err(IceError::Config("TURN server host is empty".to_string()));
+        } else if shared_secret.is_empty() {
+            return Err(IceError::Config(
+                "TURN server shared secret is empty".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            host,
+            port,
+            tls_port,
+            shared_secret,
+            ttl,
+        })
+    }
+
+    fn urls(&self) -> Vec<String> {
+        vec![
+            format!("turn:{}:{}?transport=udp", self.host, self.port),
+            format!("turn:{}:{}?transport=tcp", self.host, self.port),
+            format!("turns:{}:{}?transport=tcp", self.host, self.tls_port),
+        ]
+    }
+
+    fn credentials(&self, user_id: &ClientId, expiry: u64) -> Result<(String, String), IceError> {
+        let username = format!("{expiry}:{user_id}");
+
+        let mut mac = Hmac::<Sha1>::new_from_slice(self.shared_secret.as_bytes())
+            .map_err(|e| IceError::Provider(format!("Failed to initialize HMAC: {e}")))?;
+        mac.update(username.as_bytes());
+        let credential = STANDARD.encode(mac.finalize().into_bytes());
+
+        Ok((username, credential))
+    }
+
+    fn ice_config(&self, user_id: &ClientId, now: u64) -> Result<IceConfig, IceError> {
+        let expiry = now + self.ttl;
+        let (username, credential) = self.credentials(user_id, expiry)?;
+
+        Ok(IceConfig {
+            ice_servers: vec![
+                IceServer::from(format!("stun:{}:{}", self.host, self.port)),
+                IceServer::new(self.urls()).with_auth(username, credential),
+            ],
+            expires_at: Some(expiry),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl IceConfigProvider for SelfHostedTurnProvider {
+    #[instrument(level = "debug", err)]
+    async fn get_ice_config(&self, user_id: &ClientId) -> Result<IceConfig, IceError> {
+        tracing::debug!("Providing self-hosted TURN ICE config");
+        self.ice_config(user_id, UNIX_EPOCH.elapsed().unwrap_or_default().as_secs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn provider() -> SelfHostedTurnProvider {
+        SelfHostedTurnProvider::new(
+            "turn.example.com",
+            SelfHostedTurnProvider::DEFAULT_PORT,
+            SelfHostedTurnProvider::DEFAULT_TLS_PORT,
+            "secret",
+            21600,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn credentials() {
+        let (username, credential) = provider()
+            .credentials(&ClientId::from("1234567"), 1700021600)
+            .unwrap();
+        assert_eq!(username, "1700021600:1234567");
+        assert_eq!(credential, "8Gi3QfgiZ6dMeSFCYY31eGqZPCU=");
+    }
+
+    #[test]
+    fn ice_config() {
+        let config = provider()
+            .ice_config(&ClientId::from("1234567"), 1700000000)
+            .unwrap();
+
+        assert_eq!(config.expires_at, Some(1700021600));
+        assert_eq!(config.ice_servers.len(), 2);
+        assert_eq!(
+            config.ice_servers[0].urls,
+            vec!["stun:turn.example.com:3478".to_string()]
+        );
+        assert_eq!(config.ice_servers[0].username, None);
+
+        let turn = &config.ice_servers[1];
+        assert_eq!(
+            turn.urls,
+            vec![
+                "turn:turn.example.com:3478?transport=udp".to_string(),
+                "turn:turn.example.com:3478?transport=tcp".to_string(),
+                "turns:turn.example.com:5349?transport=tcp".to_string(),
+            ]
+        );
+        assert_eq!(turn.username.as_deref(), Some("1700021600:1234567"));
+        assert_eq!(
+            turn.credential.as_deref(),
+            Some("8Gi3QfgiZ6dMeSFCYY31eGqZPCU=")
+        );
+    }
+
+    #[test]
+    fn credentials_differ_per_user() {
+        let provider = provider();
+        let (_, credential1) = provider
+            .credentials(&ClientId::from("1"), 1700000000)
+            .unwrap();
+        let (_, credential2) = provider
+            .credentials(&ClientId::from("2"), 1700000000)
+            .unwrap();
+        assert_ne!(credential1, credential2);
+    }
+
+    #[test]
+    fn missing_configuration() {
+        assert!(SelfHostedTurnProvider::new("", 3478, 5349, "secret", 60).is_err());
+        assert!(SelfHostedTurnProvider::new("turn.example.com", 3478, 5349, "", 60).is_err());
+    }
+
+    #[tokio::test]
+    async fn get_ice_config_expiry() {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+        let config = provider()
+            .get_ice_config(&ClientId::from("1234567"))
+            .await
+            .unwrap();
+        let expires_at = config.expires_at.unwrap();
+        assert!(expires_at >= now + 21600 && expires_at <= now + 21600 + 5);
+    }
+}
@@ -1,5 +1,7 @@
+pub mod audit;
 pub mod auth;
 pub mod build;
+pub mod cluster;
 pub mod config;
 pub mod dataset;
 pub mod http;
@@ -8,6 +10,7 @@ pub mod metrics;
 pub mod ratelimit;
 pub mod release;
 pub mod routes;
+pub mod sfu;
 pub mod state;
 pub mod store;
 #[cfg(feature = "test-utils")]
@@ -1,12 +1,15 @@
+use anyhow::Context;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::signal;
 use tokio::sync::watch;
 use tracing_subscriber::Layer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use vacs_server::auth::layer::setup_auth_layer;
+use vacs_server::auth::layer::{setup_auth_layer, setup_sqlite_auth_layer};
 use vacs_server::build::BuildInfo;
-use vacs_server::config::AppConfig;
+use vacs_server::cluster::Cluster;
+use vacs_server::cluster::redis::RedisBroker;
+use vacs_server::config::{AppConfig, StoreType};
 use vacs_server::dataset::DatasetManager;
 use vacs_server::metrics::NetworkDatasetMetrics;
 use vacs_server::metrics::setup_prometheus_metric_layer;
@@ -17,8 +20,9 @@ use vacs_server::routes::{create_app, create_metrics_app};
 use vacs_server::state::AppState;
 use vacs_server::store::Store;
 use vacs_server::store::redis::RedisStore;
+use vacs_server::store::sqlite::SqliteStore;
 use vacs_vatsim::coverage::network::Network;
-use vacs_vatsim::data_feed::VatsimDataFeed;
+use vacs_vatsim::data_feed::{DataFeed, ReplayDataFeed, VatsimDataFeed};
 use vacs_vatsim::slurper::SlurperClient;
 
 #[tokio::main]
@@ -54,19 +58,31 @@ async fn main() -> anyhow::Result<()> {
     let policy = Policy::new(&config.updates.policy_path)?;
     let updates = UpdateChecker::new(config.updates.catalog.to_catalog().await?, policy);
 
-    let redis_store = RedisStore::new(&config.redis).await?;
-    let redis_pool = redis_store.get_pool().clone();
-
     let slurper = SlurperClient::new(config.vatsim.slurper_base_url.as_str())?;
-    let data_feed = Arc::new(VatsimDataFeed::new(
-        config.vatsim.data_feed_url.as_str(),
-        config.vatsim.data_feed_timeout,
-    )?);
-
-    let rate_limiters = RateLimiters::from(config.rate_limiters);
+    let data_feed: Arc<dyn DataFeed> = match &config.vatsim.data_feed_replay_dir {
+        Some(dir) => {
+            tracing::warn!(
+                ?dir,
+                "Replaying recorded VATSIM data feed instead of live data"
+            );
+            Arc::new(ReplayDataFeed::load(dir)?.with_speed(config.vatsim.data_feed_replay_speed))
+        }
+        None => {
+            let mut data_feed = VatsimDataFeed::new(
+                config.vatsim.data_feed_url.as_str(),
+                config.vatsim.data_feed_timeout,
+            )?;
+            if let Some(dir) = &config.vatsim.data_feed_record_dir {
+                data_feed = data_feed.with_recorder(dir)?;
+            }
+            Arc::new(data_feed)
+        }
+    };
 
     let ice_config_provider = config.ice.create_provider()?;
 
+    let audit_sink = config.audit.create_sink()?;
+
     let (prom_layer, prom_handle) = setup_prometheus_metric_layer();
 
     let (shutdown_tx, shutdown_rx) = watch::channel(());
@@ -109,10 +125,42 @@ async fn main() -> anyhow::Result<()> {
         network.profiles_count(),
     );
 
-    let app_state = Arc::new(AppState::new(
+    let (store, app, redis_pool) = match config.store.backend {
+        StoreType::Redis => {
+            let redis_store = RedisStore::new(&config.redis).await?;
+            let redis_pool = redis_store.get_pool().clone();
+            let auth_layer = setup_auth_layer(&config, redis_pool.clone()).await?;
+            let app = create_app(
+                auth_layer,
+                Some(prom_layer),
+                config.server.client_ip_source.clone(),
+                config.server.debug_endpoints,
+            );
+            (Store::Redis(redis_store), app, Some(redis_pool))
+        }
+        StoreType::Sqlite => {
+            let sqlite_store = SqliteStore::open(config.store.sqlite_path.as_ref())?;
+            let auth_layer = setup_sqlite_auth_layer(&config, sqlite_store.clone()).await?;
+            let app = create_app(
+                auth_layer,
+                Some(prom_layer),
+                config.server.client_ip_source.clone(),
+                config.server.debug_endpoints,
+            );
+            (Store::Sqlite(sqlite_store), app, None)
+        }
+    };
+
+    let mut rate_limiters = RateLimiters::from(config.rate_limiters);
+    if config.rate_limiters.use_store {
+        tracing::info!("Keeping rate limits in store");
+        rate_limiters = rate_limiters.with_store(store.clone());
+    }
+
+    let mut app_state = AppState::new(
         config.clone(),
         updates,
-        Store::Redis(redis_store),
+        store,
         slurper,
         data_feed,
         network,
@@ -120,16 +168,17 @@ async fn main() -> anyhow::Result<()> {
         shutdown_rx.clone(),
         ice_config_provider,
         dataset_manager,
-    ));
-
-    let auth_layer = setup_auth_layer(&config, redis_pool).await?;
-
-    let app = create_app(
-        auth_layer,
-        Some(prom_layer),
-        config.server.client_ip_source.clone(),
-        config.server.debug_endpoints,
+        audit_sink,
     );
+    if config.cluster.enabled {
+        let redis_pool = redis_pool.context("Clustering requires the Redis store backend")?;
+        let broker = RedisBroker::new(&config.redis, redis_pool)?;
+        let instance_id = config.cluster.instance_id();
+        tracing::info!(?instance_id, "Running as cluster instance");
+        app_state = app_state.with_cluster(Cluster::new(instance_id, Arc::new(broker)));
+    }
+    let app_state = Arc::new(app_state);
+
     let listener = tokio::net::TcpListener::bind(config.server.bind_addr).await?;
     tracing::info!(bind_addr = ?listener.local_addr(), "Started main listener");
 
@@ -142,15 +191,24 @@ async fn main() -> anyhow::Result<()> {
         config.vatsim.controller_update_interval,
     );
 
+    let cluster_task = if config.cluster.enabled {
+        Some(AppState::start_cluster_task(app_state.clone()).await?)
+    } else {
+        None
+    };
+
+    let mut metrics_shutdown_rx = shutdown_rx.clone();
     let metrics_server = axum::serve(metrics_listener, metrics_app.into_make_service())
-        .with_graceful_shutdown(shutdown_signal(shutdown_tx.clone()));
+        .with_graceful_shutdown(async move {
+            let _ = metrics_shutdown_rx.changed().await;
+        });
 
     let server = axum::serve(
         listener,
-        app.with_state(app_state)
+        app.with_state(app_state.clone())
             .into_make_service_with_connect_info::<SocketAddr>(),
     )
-    .with_graceful_shutdown(shutdown_signal(shutdown_tx));
+    .with_graceful_shutdown(shutdown_signal(app_state, shutdown_tx));
 
     tokio::try_join!(metrics_server, server)?;
 
@@ -158,10 +216,16 @@ async fn main() -> anyhow::Result<()> {
         tracing::warn!(?err, "Controller update task finished with error");
     }
 
+    if let Some(cluster_task) = cluster_task
+        && let Err(err) = cluster_task.await
+    {
+        tracing::warn!(?err, "Cluster task finished with error");
+    }
+
     Ok(())
 }
 
-async fn shutdown_signal(shutdown_tx: watch::Sender<()>) {
+async fn shutdown_signal(app_state: Arc<AppState>, shutdown_tx: watch::Sender<()>) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -186,6 +250,8 @@ async fn shutdown_signal(shutdown_tx: watch::Sender<()>) {
 
     tracing::info!("Shutdown signal received, terminating gracefully...");
 
+    app_state.drain().await;
+
     shutdown_tx
         .send(())
         .expect("Failed to send shutdown signal");
@@ -1,6 +1,7 @@
 pub mod guards;
 mod labels;
 
+use crate::ice::IceConfigProviderType;
 use crate::metrics::labels::AsMetricLabel;
 use crate::release::catalog::BundleType;
 use axum_prometheus::metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
@@ -89,6 +90,8 @@ pub fn register_metrics() {
     ProfileMetrics::register();
     VatsimSyncMetrics::register();
     NetworkDatasetMetrics::register();
+    SfuMetrics::register();
+    IceMetrics::register();
 }
 
 pub struct ClientMetrics;
@@ -104,6 +107,11 @@ impl ClientMetrics {
         counter!("vacs_clients_login_failures_total", "reason" => label).increment(1);
     }
 
+    pub fn detached_session(resumed: bool) {
+        let label = if resumed { "resumed" } else { "expired" };
+        counter!("vacs_clients_detached_sessions_total", "outcome" => label).increment(1);
+    }
+
     fn register() {
         describe_gauge!(
             "vacs_clients_connected",
@@ -125,6 +133,11 @@ impl ClientMetrics {
             Unit::Count,
             "Login failures by reason"
         );
+        describe_counter!(
+            "vacs_clients_detached_sessions_total",
+            Unit::Count,
+            "Sessions kept after a connection loss, labeled by resumed/expired"
+        );
         describe_counter!(
             "vacs_clients_disconnects_total",
             Unit::Count,
@@ -221,6 +234,17 @@ impl MessageMetrics {
         counter!("vacs_messages_malformed_total").increment(1);
     }
 
+    pub fn compressed(uncompressed_bytes: usize, compressed_bytes: usize) {
+        counter!("vacs_message_compression_bytes_total", "stage" => "uncompressed")
+            .increment(uncompressed_bytes as u64);
+        counter!("vacs_message_compression_bytes_total", "stage" => "compressed")
+            .increment(compressed_bytes as u64);
+        if uncompressed_bytes > 0 {
+            histogram!("vacs_message_compression_ratio")
+                .record(compressed_bytes as f64 / uncompressed_bytes as f64);
+        }
+    }
+
     fn register() {
         describe_counter!(
             "vacs_messages_total",
@@ -237,6 +261,15 @@ impl MessageMetrics {
             Unit::Bytes,
             "Size of WebSocket messages in bytes, by direction (sent/received)"
         );
+        describe_counter!(
+            "vacs_message_compression_bytes_total",
+            Unit::Bytes,
+            "Bytes of compressed messages sent, by stage (uncompressed/compressed)"
+        );
+        describe_histogram!(
+            "vacs_message_compression_ratio",
+            "Ratio of compressed to uncompressed size of sent messages"
+        );
     }
 }
 
@@ -451,3 +484,75 @@ impl NetworkDatasetMetrics {
         );
     }
 }
+
+pub struct SfuMetrics;
+
+impl SfuMetrics {
+    pub fn session_opened() {
+        gauge!("vacs_sfu_sessions_active").increment(1);
+        counter!("vacs_sfu_sessions_total").increment(1);
+    }
+
+    pub fn session_closed() {
+        gauge!("vacs_sfu_sessions_active").decrement(1);
+    }
+
+    pub fn session_failed() {
+        counter!("vacs_sfu_session_failures_total").increment(1);
+    }
+
+    fn register() {
+        describe_gauge!(
+            "vacs_sfu_sessions_active",
+            Unit::Count,
+            "Number of calls currently routed through the SFU"
+        );
+        describe_counter!(
+            "vacs_sfu_sessions_total",
+            Unit::Count,
+            "Total number of calls routed through the SFU"
+        );
+        describe_counter!(
+            "vacs_sfu_session_failures_total",
+            Unit::Count,
+            "SFU sessions that failed to open, falling back to peer-to-peer media"
+        );
+    }
+}
+
+pub struct IceMetrics;
+
+impl IceMetrics {
+    pub fn served_by_provider(provider: IceConfigProviderType) {
+        counter!("vacs_ice_config_requests_total", "source" => provider.as_metric_label())
+            .increment(1);
+    }
+
+    pub fn served_from_cache(stale: bool) {
+        let source = if stale { "stale_cache" } else { "cache" };
+        counter!("vacs_ice_config_requests_total", "source" => source).increment(1);
+    }
+
+    pub fn provider_failure(provider: IceConfigProviderType, timeout: bool) {
+        let reason = if timeout { "timeout" } else { "error" };
+        counter!(
+            "vacs_ice_provider_failures_total",
+            "provider" => provider.as_metric_label(),
+            "reason" => reason
+        )
+        .increment(1);
+    }
+
+    fn register() {
+        describe_counter!(
+            "vacs_ice_config_requests_total",
+            Unit::Count,
+            "ICE config requests served, by provider or cache"
+        );
+        describe_counter!(
+            "vacs_ice_provider_failures_total",
+            Unit::Count,
+            "Failed ICE config provider requests, by provider and reason"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::ice::IceConfigProviderType;
 use crate::metrics::guards::CallAttemptOutcome;
 use crate::release::catalog::BundleType;
 use vacs_protocol::http::version::ReleaseChannel;
@@ -15,6 +16,8 @@ impl AsMetricLabel for DisconnectReason {
             DisconnectReason::Terminated => "terminated",
             DisconnectReason::NoActiveVatsimConnection => "no_active_vatsim_connection",
             DisconnectReason::AmbiguousVatsimPosition(_) => "ambiguous_vatsim_position",
+            DisconnectReason::Kicked { .. } => "kicked",
+            DisconnectReason::ServerRestart { .. } => "server_restart",
         }
     }
 }
@@ -39,6 +42,7 @@ impl AsMetricLabel for LoginFailureReason {
             LoginFailureReason::InvalidVatsimPosition => "invalid_vatsim_position",
             LoginFailureReason::Timeout => "timeout",
             LoginFailureReason::IncompatibleProtocolVersion => "incompatible_protocol_version",
+            LoginFailureReason::Banned { .. } => "banned",
         }
     }
 }
@@ -99,7 +103,13 @@ impl AsMetricLabel for ClientMessage {
             ClientMessage::WebrtcAnswer(_) => "webrtc_answer",
             ClientMessage::WebrtcIceCandidate(_) => "webrtc_ice_candidate",
             ClientMessage::ListClients => "list_clients",
+            ClientMessage::ListClientChanges(_) => "list_client_changes",
             ClientMessage::ListStations => "list_stations",
+            ClientMessage::SetPresence(_) => "set_presence",
+            ClientMessage::TextMessage(_) => "text_message",
+            ClientMessage::KickClient(_) => "kick_client",
+            ClientMessage::BanClient(_) => "ban_client",
+            ClientMessage::MuteClient(_) => "mute_client",
             ClientMessage::Disconnect => "disconnect",
             ClientMessage::Error(_) => "error",
         }
@@ -123,6 +133,10 @@ impl AsMetricLabel for ServerMessage {
             ServerMessage::ClientConnected(_) => "client_connected",
             ServerMessage::ClientDisconnected(_) => "client_disconnected",
             ServerMessage::ClientList(_) => "client_list",
+            ServerMessage::ClientChanges(_) => "client_changes",
+            ServerMessage::ClientPresence(_) => "client_presence",
+            ServerMessage::TextMessage(_) => "text_message",
+            ServerMessage::Announcement(_) => "announcement",
             ServerMessage::StationList(_) => "station_list",
             ServerMessage::StationChanges(_) => "station_changes",
             ServerMessage::Disconnected(_) => "disconnected",
@@ -140,6 +154,7 @@ impl AsMetricLabel for ErrorReason {
             ErrorReason::UnexpectedMessage(_) => "unexpected_message",
             ErrorReason::RateLimited { .. } => "rate_limited",
             ErrorReason::ClientNotFound => "client_not_found",
+            ErrorReason::Muted { .. } => "muted",
         }
     }
 }
@@ -178,3 +193,13 @@ impl AsMetricLabel for CallSource {
         }
     }
 }
+
+impl AsMetricLabel for IceConfigProviderType {
+    fn as_metric_label(&self) -> &'static str {
+        match self {
+            IceConfigProviderType::StunOnly => "stun_only",
+            IceConfigProviderType::Cloudflare => "cloudflare",
+            IceConfigProviderType::SelfHostedTurn => "self_hosted_turn",
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::metrics::ErrorMetrics;
+use crate::store::{Store, StoreBackend};
 use axum_client_ip::ClientIp;
 use governor::clock::{Clock, QuantaClock};
 use governor::middleware::NoOpMiddleware;
@@ -51,6 +52,7 @@ impl Policy {
 }
 
 type KeyedLimiter<K> = RateLimiter<K, DefaultKeyedStateStore<K>, QuantaClock, NoOpMiddleware>;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct Key(pub String);
@@ -104,69 +106,143 @@ impl Deref for Key {
     }
 }
 
+/// Rate limit applied to each key separately.
+#[derive(Debug)]
+struct Limit {
+    name: &'static str,
+    quota: Quota,
+    limiter: KeyedLimiter<Key>,
+}
+
+impl Limit {
+    fn new(name: &'static str, quota: Quota) -> Self {
+        Self {
+            name,
+            quota,
+            limiter: KeyedLimiter::<Key>::keyed(quota),
+        }
+    }
+
+    fn from_policy(name: &'static str, policy: &Policy) -> Option<Self> {
+        policy.enabled.then(|| Self::new(name, policy.quota()))
+    }
+
+    fn per_minute(name: &'static str, per_minute: u32) -> Option<Self> {
+        NonZero::new(per_minute).map(|val| Self::new(name, Quota::per_minute(val).allow_burst(val)))
+    }
+
+    fn check_local(&self, key: &Key) -> Result<(), Duration> {
+        self.limiter
+            .check_key(key)
+            .map_err(|not_until| not_until.wait_time_from(self.limiter.clock().now()))
+    }
+
+    /// Checks the limit against the state kept in the store, falling back to the local state if
+    /// the store is unavailable.
+    async fn check_store(&self, store: &Store, key: &Key) -> Result<(), Duration> {
+        match store
+            .check_rate_limit(
+                format!("ratelimit.{}.{}", self.name, key.0).as_str(),
+                self.quota.replenish_interval(),
+                self.quota.burst_size(),
+            )
+            .await
+        {
+            Ok(None) => Ok(()),
+            Ok(Some(wait)) => Err(wait),
+            Err(err) => {
+                tracing::warn!(
+                    ?err,
+                    limit = self.name,
+                    "Failed to check rate limit in store, falling back to local limit"
+                );
+                self.check_local(key)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct RateLimiters {
-    call_invite: Option<KeyedLimiter<Key>>,
-    call_invite_per_minute: Option<KeyedLimiter<Key>>,
-    failed_auth: Option<KeyedLimiter<Key>>,
-    failed_auth_per_minute: Option<KeyedLimiter<Key>>,
-    version_update: Option<KeyedLimiter<Key>>,
-    version_update_per_minute: Option<KeyedLimiter<Key>>,
-    vatsim_token: Option<KeyedLimiter<Key>>,
-    vatsim_token_per_minute: Option<KeyedLimiter<Key>>,
+    call_invite: Option<Limit>,
+    call_invite_per_minute: Option<Limit>,
+    failed_auth: Option<Limit>,
+    failed_auth_per_minute: Option<Limit>,
+    version_update: Option<Limit>,
+    version_update_per_minute: Option<Limit>,
+    vatsim_token: Option<Limit>,
+    vatsim_token_per_minute: Option<Limit>,
+    text_message: Option<Limit>,
+    text_message_per_minute: Option<Limit>,
+    webrtc_signaling: Option<Limit>,
+    webrtc_signaling_per_minute: Option<Limit>,
+    list_request: Option<Limit>,
+    list_request_per_minute: Option<Limit>,
+    /// Store keeping the state of all limits. The state is kept in process memory if `None`.
+    store: Option<Store>,
 }
 
 impl RateLimiters {
-    #[inline]
-    pub fn check_call_invite(&self, key: impl Into<Key>) -> Result<(), Duration> {
+    /// Keeps the state of all limits in the given store, so limits are shared between all
+    /// instances using the store and persist across restarts.
+    pub fn with_store(mut self, store: Store) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    pub async fn check_call_invite(&self, key: impl Into<Key>) -> Result<(), Duration> {
         let key = key.into();
-        Self::check(&self.call_invite_per_minute, "call_invite_per_minute", &key)
-            .and_then(|_| Self::check(&self.call_invite, "call_invite", &key))
+        self.check(&self.call_invite_per_minute, &key).await?;
+        self.check(&self.call_invite, &key).await
     }
 
-    #[inline]
-    pub fn check_failed_auth(&self, key: impl Into<Key>) -> Result<(), Duration> {
+    pub async fn check_failed_auth(&self, key: impl Into<Key>) -> Result<(), Duration> {
         let key = key.into();
-        Self::check(&self.failed_auth_per_minute, "failed_auth_per_minute", &key)
-            .and_then(|_| Self::check(&self.failed_auth, "failed_auth", &key))
+        self.check(&self.failed_auth_per_minute, &key).await?;
+        self.check(&self.failed_auth, &key).await
     }
 
-    #[inline]
-    pub fn check_version_update(&self, key: impl Into<Key>) -> Result<(), Duration> {
+    pub async fn check_version_update(&self, key: impl Into<Key>) -> Result<(), Duration> {
         let key = key.into();
-        Self::check(
-            &self.version_update_per_minute,
-            "version_update_per_minute",
-            &key,
-        )
-        .and_then(|_| Self::check(&self.version_update, "version_update", &key))
+        self.check(&self.version_update_per_minute, &key).await?;
+        self.check(&self.version_update, &key).await
     }
 
-    #[inline]
-    pub fn check_vatsim_token(&self, key: impl Into<Key>) -> Result<(), Duration> {
+    pub async fn check_vatsim_token(&self, key: impl Into<Key>) -> Result<(), Duration> {
         let key = key.into();
-        Self::check(
-            &self.vatsim_token_per_minute,
-            "vatsim_token_per_minute",
-            &key,
-        )
-        .and_then(|_| Self::check(&self.vatsim_token, "vatsim_token", &key))
-    }
-
-    #[inline]
-    fn check(
-        limiter: &Option<KeyedLimiter<Key>>,
-        limit_name: impl Into<String>,
-        key: &Key,
-    ) -> Result<(), Duration> {
-        if let Some(limiter) = limiter {
-            limiter.check_key(key).map_err(|not_until| {
-                ErrorMetrics::rate_limit_exceeded(limit_name);
-                not_until.wait_time_from(limiter.clock().now())
-            })
-        } else {
-            Ok(())
-        }
+        self.check(&self.vatsim_token_per_minute, &key).await?;
+        self.check(&self.vatsim_token, &key).await
+    }
+
+    pub async fn check_text_message(&self, key: impl Into<Key>) -> Result<(), Duration> {
+        let key = key.into();
+        self.check(&self.text_message_per_minute, &key).await?;
+        self.check(&self.text_message, &key).await
+    }
+
+    /// Checks the limit shared by WebRTC offers, answers and ICE candidates.
+    pub async fn check_webrtc_signaling(&self, key: impl Into<Key>) -> Result<(), Duration> {
+        let key = key.into();
+        self.check(&self.webrtc_signaling_per_minute, &key).await?;
+        self.check(&self.webrtc_signaling, &key).await
+    }
+
+    /// Checks the limit shared by requests listing clients, client changes and stations.
+    pub async fn check_list_request(&self, key: impl Into<Key>) -> Result<(), Duration> {
+        let key = key.into();
+        self.check(&self.list_request_per_minute, &key).await?;
+        self.check(&self.list_request, &key).await
+    }
+
+    async fn check(&self, limit: &Option<Limit>, key: &Key) -> Result<(), Duration> {
+        let Some(limit) = limit else {
+            return Ok(());
+        };
+        let result = match &self.store {
+            Some(store) => limit.check_store(store, key).await,
+            None => limit.check_local(key),
+        };
+        result.inspect_err(|_| ErrorMetrics::rate_limit_exceeded(limit.name))
     }
 }
 
@@ -174,6 +250,9 @@ impl RateLimiters {
 #[serde(default)]
 pub struct RateLimitersConfig {
     pub enabled: bool,
+    /// Keep the state of all limits in the store instead of process memory, sharing limits
+    /// between instances and across restarts.
+    pub use_store: bool,
     pub call_invite: Policy,
     pub call_invite_per_minute: u32,
     pub failed_auth: Policy,
@@ -182,12 +261,19 @@ pub struct RateLimitersConfig {
     pub version_update_per_minute: u32,
     pub vatsim_token: Policy,
     pub vatsim_token_per_minute: u32,
+    pub text_message: Policy,
+    pub text_message_per_minute: u32,
+    pub webrtc_signaling: Policy,
+    pub webrtc_signaling_per_minute: u32,
+    pub list_request: Policy,
+    pub list_request_per_minute: u32,
 }
 
 impl Default for RateLimitersConfig {
     fn default() -> Self {
         Self {
             enabled: true,
+            use_store: false,
             call_invite: Policy::new(10, nonzero!(3u32)),
             call_invite_per_minute: 20,
             failed_auth: Policy::new(60, nonzero!(5u32)).disabled(),
@@ -196,6 +282,12 @@ impl Default for RateLimitersConfig {
             version_update_per_minute: 60,
             vatsim_token: Policy::new(30, nonzero!(3u32)),
             vatsim_token_per_minute: 10,
+            text_message: Policy::new(2, nonzero!(5u32)),
+            text_message_per_minute: 30,
+            webrtc_signaling: Policy::new(1, nonzero!(50u32)),
+            webrtc_signaling_per_minute: 600,
+            list_request: Policy::new(1, nonzero!(10u32)),
+            list_request_per_minute: 120,
         }
     }
 }
@@ -203,87 +295,105 @@ impl Default for RateLimitersConfig {
 impl From<RateLimitersConfig> for RateLimiters {
     fn from(value: RateLimitersConfig) -> Self {
         if !value.enabled {
-            return Self {
-                call_invite: None,
-                call_invite_per_minute: None,
-                failed_auth: None,
-                failed_auth_per_minute: None,
-                version_update: None,
-                version_update_per_minute: None,
-                vatsim_token: None,
-                vatsim_token_per_minute: None,
-            };
+            return Self::default();
         }
 
-        let call_invite = if value.call_invite.enabled {
-            Some(KeyedLimiter::<Key>::keyed(value.call_invite.quota()))
-        } else {
-            None
-        };
-        let call_invite_per_minute = if value.call_invite_per_minute > 0 {
-            let val =
-                NonZero::new(value.call_invite_per_minute).expect("invalid call_invite_per_minute");
-            Some(KeyedLimiter::<Key>::keyed(
-                Quota::per_minute(val).allow_burst(val),
-            ))
-        } else {
-            None
-        };
+        Self {
+            call_invite: Limit::from_policy("call_invite", &value.call_invite),
+            call_invite_per_minute: Limit::per_minute(
+                "call_invite_per_minute",
+                value.call_invite_per_minute,
+            ),
+            failed_auth: Limit::from_policy("failed_auth", &value.failed_auth),
+            failed_auth_per_minute: Limit::per_minute(
+                "failed_auth_per_minute",
+                value.failed_auth_per_minute,
+            ),
+            version_update: Limit::from_policy("version_update", &value.version_update),
+            version_update_per_minute: Limit::per_minute(
+                "version_update_per_minute",
+                value.version_update_per_minute,
+            ),
+            vatsim_token: Limit::from_policy("vatsim_token", &value.vatsim_token),
+            vatsim_token_per_minute: Limit::per_minute(
+                "vatsim_token_per_minute",
+                value.vatsim_token_per_minute,
+            ),
+            text_message: Limit::from_policy("text_message", &value.text_message),
+            text_message_per_minute: Limit::per_minute(
+                "text_message_per_minute",
+                value.text_message_per_minute,
+            ),
+            webrtc_signaling: Limit::from_policy("webrtc_signaling", &value.webrtc_signaling),
+            webrtc_signaling_per_minute: Limit::per_minute(
+                "webrtc_signaling_per_minute",
+                value.webrtc_signaling_per_minute,
+            ),
+            list_request: Limit::from_policy("list_request", &value.list_request),
+            list_request_per_minute: Limit::per_minute(
+                "list_request_per_minute",
+                value.list_request_per_minute,
+            ),
+            store: None,
+        }
+    }
+}
 
-        let failed_auth = if value.failed_auth.enabled {
-            Some(KeyedLimiter::<Key>::keyed(value.failed_auth.quota()))
-        } else {
-            None
-        };
-        let failed_auth_per_minute = if value.failed_auth_per_minute > 0 {
-            let val =
-                NonZero::new(value.failed_auth_per_minute).expect("invalid failed_auth_per_minute");
-            Some(KeyedLimiter::<Key>::keyed(
-                Quota::per_minute(val).allow_burst(val),
-            ))
-        } else {
-            None
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::memory::MemoryStore;
 
-        let version_update = if value.version_update.enabled {
-            Some(KeyedLimiter::<Key>::keyed(value.version_update.quota()))
-        } else {
-            None
-        };
-        let version_update_per_minute = if value.version_update_per_minute > 0 {
-            let val = NonZero::new(value.version_update_per_minute)
-                .expect("invalid version_update_per_minute");
-            Some(KeyedLimiter::<Key>::keyed(
-                Quota::per_minute(val).allow_burst(val),
-            ))
-        } else {
-            None
-        };
+    fn config() -> RateLimitersConfig {
+        RateLimitersConfig {
+            text_message: Policy::new(60, nonzero!(2u32)),
+            text_message_per_minute: 0,
+            ..Default::default()
+        }
+    }
 
-        let vatsim_token = if value.vatsim_token.enabled {
-            Some(KeyedLimiter::<Key>::keyed(value.vatsim_token.quota()))
-        } else {
-            None
-        };
-        let vatsim_token_per_minute = if value.vatsim_token_per_minute > 0 {
-            let val = NonZero::new(value.vatsim_token_per_minute)
-                .expect("invalid vatsim_token_per_minute");
-            Some(KeyedLimiter::<Key>::keyed(
-                Quota::per_minute(val).allow_burst(val),
-            ))
-        } else {
-            None
-        };
+    #[tokio::test]
+    async fn local_limit() {
+        let limiters = RateLimiters::from(config());
+        assert!(limiters.check_text_message("client1").await.is_ok());
+        assert!(limiters.check_text_message("client1").await.is_ok());
 
-        Self {
-            call_invite,
-            call_invite_per_minute,
-            failed_auth,
-            failed_auth_per_minute,
-            version_update,
-            version_update_per_minute,
-            vatsim_token,
-            vatsim_token_per_minute,
+        let wait = limiters.check_text_message("client1").await.unwrap_err();
+        assert!(wait > Duration::from_secs(58) && wait <= Duration::from_secs(60));
+        assert!(limiters.check_text_message("client2").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn store_limit_is_shared() {
+        let store = MemoryStore::default();
+        let a = RateLimiters::from(config()).with_store(Store::Memory(store.clone()));
+        let b = RateLimiters::from(config()).with_store(Store::Memory(store));
+
+        assert!(a.check_text_message("client1").await.is_ok());
+        assert!(b.check_text_message("client1").await.is_ok());
+
+        let wait = a.check_text_message("client1").await.unwrap_err();
+        assert!(wait > Duration::from_secs(58) && wait <= Duration::from_secs(60));
+        assert!(b.check_text_message("client1").await.is_err());
+        assert!(b.check_text_message("client2").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn disabled_limits() {
+        let limiters = RateLimiters::from(RateLimitersConfig {
+            enabled: false,
+            ..config()
+        });
+        for _ in 0..10 {
+            assert!(limiters.check_text_message("client1").await.is_ok());
+        }
+
+        let limiters = RateLimiters::from(RateLimitersConfig {
+            text_message: config().text_message.disabled(),
+            ..config()
+        });
+        for _ in 0..10 {
+            assert!(limiters.check_text_message("client1").await.is_ok());
         }
     }
 }
@@ -1,6 +1,8 @@
 mod admin;
 mod auth;
+mod coverage;
 mod debug;
+mod moderation;
 mod root;
 mod version;
 mod webrtc;
@@ -36,6 +38,8 @@ where
     let mut app = Router::new()
         .nest("/admin", admin::routes())
         .nest("/auth", auth::routes())
+        .nest("/coverage", coverage::routes())
+        .nest("/moderation", moderation::routes())
         .nest("/ws", ws::routes().merge(crate::ws::routes()))
         .nest("/version", version::routes())
         .nest("/webrtc", webrtc::routes())
@@ -1,30 +1,116 @@
 use crate::state::AppState;
 use axum::Router;
-use axum::routing::post;
+use axum::routing::{get, post};
 use std::sync::Arc;
 
 pub fn routes() -> Router<Arc<AppState>> {
-    Router::new().route("/dataset/reload", post(post::reload_dataset))
+    Router::new()
+        .route("/dataset/reload", post(post::reload_dataset))
+        .route("/dataset/preview", post(post::preview_dataset))
+        .route("/announcements", post(post::announce))
+        .route("/roles/{cid}", get(get::user_roles).put(put::user_roles))
+        .route("/audit", get(get::audit_log))
+}
+
+mod get {
+    use crate::audit::{AuditQuery, AuditRecord};
+    use crate::auth::extractor::Authorized;
+    use crate::auth::roles::{Roles, require};
+    use crate::config;
+    use crate::http::ApiResult;
+    use crate::state::AppState;
+    use axum::Json;
+    use axum::extract::{Path, Query, State};
+    use std::sync::Arc;
+    use vacs_protocol::vatsim::ClientId;
+
+    pub async fn audit_log(
+        _auth: Authorized<require::Admin>,
+        State(state): State<Arc<AppState>>,
+        Query(mut query): Query<AuditQuery>,
+    ) -> ApiResult<Vec<AuditRecord>> {
+        query.limit = Some(
+            query
+                .limit
+                .unwrap_or(config::AUDIT_QUERY_MAX_LIMIT)
+                .min(config::AUDIT_QUERY_MAX_LIMIT),
+        );
+        let records = state.audit.query(&query).await?;
+        Ok(Json(records))
+    }
+
+    pub async fn user_roles(
+        _auth: Authorized<require::Admin>,
+        State(state): State<Arc<AppState>>,
+        Path(cid): Path<String>,
+    ) -> ApiResult<Roles> {
+        let roles = state.user_roles(&ClientId::from(cid)).await?;
+        Ok(Json(roles))
+    }
+}
+
+mod put {
+    use crate::audit::AuditEvent;
+    use crate::auth::extractor::Authorized;
+    use crate::auth::roles::{Role, Roles, require};
+    use crate::http::ApiResult;
+    use crate::state::AppState;
+    use axum::Json;
+    use axum::extract::{Path, State};
+    use serde::Deserialize;
+    use std::sync::Arc;
+    use tracing::instrument;
+    use vacs_protocol::vatsim::ClientId;
+
+    /// Request body for assigning roles. Replaces all roles previously assigned in the store.
+    #[derive(Debug, Deserialize)]
+    pub struct UserRolesRequest {
+        pub roles: Vec<Role>,
+    }
+
+    #[instrument(level = "info", skip(auth, state), fields(admin = %auth.cid()))]
+    pub async fn user_roles(
+        auth: Authorized<require::Admin>,
+        State(state): State<Arc<AppState>>,
+        Path(cid): Path<String>,
+        Json(body): Json<UserRolesRequest>,
+    ) -> ApiResult<Roles> {
+        let cid = ClientId::from(cid);
+        tracing::info!(roles = ?body.roles, "Assigning user roles");
+        state.set_user_roles(&cid, body.roles.clone()).await?;
+        state
+            .audit
+            .record(
+                Some(auth.cid()),
+                Some(&cid),
+                AuditEvent::RolesAssigned { roles: body.roles },
+            )
+            .await;
+
+        let roles = state.user_roles(&cid).await?;
+        Ok(Json(roles))
+    }
 }
 
 mod post {
-    use crate::http::StatusCodeResult;
+    use crate::audit::AuditEvent;
+    use crate::auth::extractor::Authorized;
+    use crate::auth::roles::require;
     use crate::http::error::AppError;
+    use crate::http::{ApiResult, StatusCodeResult};
     use crate::state::AppState;
     use axum::Json;
     use axum::extract::State;
     use axum::http::{HeaderMap, StatusCode};
     use jsonwebtoken::{DecodingKey, Validation, decode, jwk::JwkSet};
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
     use std::sync::Arc;
-    use std::time::Duration;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
     use tracing::instrument;
+    use vacs_protocol::ws::server::{Announcement, AnnouncementSeverity};
 
     /// GitHub Actions OIDC issuer.
     const GITHUB_OIDC_ISSUER: &str = "https://token.actions.githubusercontent.com";
-    /// GitHub Actions OIDC JWKS endpoint.
-    const GITHUB_OIDC_JWKS_URL: &str =
-        "https://token.actions.githubusercontent.com/.well-known/jwks";
     /// Expected algorithm for GitHub Actions OIDC tokens.
     const GITHUB_OIDC_JWT_ALGORITHM: jsonwebtoken::Algorithm = jsonwebtoken::Algorithm::RS256;
     /// Timeout for fetching the JWKS from GitHub.
@@ -50,6 +136,37 @@ mod post {
         pub sha: Option<String>,
     }
 
+    /// Request body for the dataset preview endpoint.
+    #[derive(Debug, Deserialize)]
+    pub struct PreviewRequest {
+        /// The git ref to preview (tag, branch, or commit SHA).
+        #[serde(rename = "ref")]
+        pub git_ref: String,
+    }
+
+    /// Summary of a validated, but not installed dataset.
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DatasetPreview {
+        #[serde(rename = "ref")]
+        pub git_ref: String,
+        pub positions: usize,
+        pub stations: usize,
+        pub profiles: usize,
+    }
+
+    /// Request body for the announcement endpoint.
+    #[derive(Debug, Deserialize)]
+    pub struct AnnouncementRequest {
+        pub message: String,
+        #[serde(default)]
+        pub severity: AnnouncementSeverity,
+        /// Seconds until the announcement expires. If omitted, it is only sent to clients
+        /// connected at the time.
+        #[serde(default)]
+        pub expires_in_secs: Option<u64>,
+    }
+
     /// Verify that the request carries a valid GitHub Actions OIDC token.
     ///
     /// The token is expected as a Bearer token in the Authorization header.
@@ -59,10 +176,12 @@ mod post {
     /// 2. Issuer matches GitHub's OIDC issuer
     /// 3. Audience matches the configured expected audience
     /// 4. Subject matches the configured allowed subject (repo + environment)
+    ///
+    /// Returns the subject of the verified token.
     async fn verify_github_oidc(
         config: &crate::config::AdminConfig,
         headers: &HeaderMap,
-    ) -> Result<(), AppError> {
+    ) -> Result<String, AppError> {
         // Extract bearer token
         let token = headers
             .get("authorization")
@@ -95,7 +214,7 @@ mod post {
             })?;
 
         let jwks: JwkSet = client
-            .get(GITHUB_OIDC_JWKS_URL)
+            .get(&config.oidc_jwks_url)
             .send()
             .await
             .map_err(|err| {
@@ -151,7 +270,7 @@ mod post {
             aud = %claims.aud,
             "Authenticated via GitHub Actions OIDC token"
         );
-        Ok(())
+        Ok(claims.sub)
     }
 
     #[instrument(level = "info", skip(state, headers, body))]
@@ -160,7 +279,7 @@ mod post {
         headers: HeaderMap,
         Json(body): Json<ReloadRequest>,
     ) -> StatusCodeResult {
-        verify_github_oidc(&state.config.admin, &headers).await?;
+        let subject = verify_github_oidc(&state.config.admin, &headers).await?;
 
         let git_ref = &body.git_ref;
         let commit_sha = body.sha.as_deref().unwrap_or(git_ref);
@@ -184,6 +303,18 @@ mod post {
             })?;
 
         state.replace_network(network).await;
+        state
+            .audit
+            .record(
+                None,
+                None,
+                AuditEvent::DatasetReloaded {
+                    git_ref: git_ref.clone(),
+                    sha: commit_sha.to_string(),
+                    subject,
+                },
+            )
+            .await;
 
         tracing::info!(
             from = ?previous_sha,
@@ -193,4 +324,77 @@ mod post {
 
         Ok(StatusCode::OK)
     }
+
+    #[instrument(level = "info", skip(auth, state, body), fields(cid = %auth.cid()))]
+    pub async fn preview_dataset(
+        auth: Authorized<require::FacilityEngineer>,
+        State(state): State<Arc<AppState>>,
+        Json(body): Json<PreviewRequest>,
+    ) -> ApiResult<DatasetPreview> {
+        let git_ref = body.git_ref;
+        tracing::info!(%git_ref, "Dataset preview triggered");
+
+        let dataset = state.dataset.as_ref().ok_or_else(|| {
+            tracing::warn!("Dataset preview requested but no dataset repository is configured");
+            AppError::NotFound
+        })?;
+
+        let network = dataset.fetch_preview(&git_ref).await.map_err(|err| {
+            tracing::warn!(?err, %git_ref, "Failed to fetch and validate dataset preview");
+            AppError::BadRequest(format!("Invalid dataset: {err}"))
+        })?;
+
+        Ok(Json(DatasetPreview {
+            git_ref,
+            positions: network.positions_count(),
+            stations: network.stations_count(),
+            profiles: network.profiles_count(),
+        }))
+    }
+
+    #[instrument(level = "info", skip(state, headers, body))]
+    pub async fn announce(
+        State(state): State<Arc<AppState>>,
+        headers: HeaderMap,
+        Json(body): Json<AnnouncementRequest>,
+    ) -> ApiResult<Announcement> {
+        let subject = verify_github_oidc(&state.config.admin, &headers).await?;
+
+        if body.message.trim().is_empty() {
+            return Err(AppError::BadRequest(
+                "Announcement message must not be empty".to_string(),
+            ));
+        }
+
+        let mut announcement = Announcement::new(body.severity, body.message);
+        if let Some(expires_in_secs) = body.expires_in_secs {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|err| AppError::InternalServerError(anyhow::anyhow!(err)))?
+                .as_secs();
+            announcement = announcement.with_expiry(now.saturating_add(expires_in_secs));
+        }
+
+        tracing::info!(
+            id = %announcement.id,
+            severity = ?announcement.severity,
+            expires_at = ?announcement.expires_at,
+            "Broadcasting announcement"
+        );
+        state.clients.announce(announcement.clone());
+        state
+            .audit
+            .record(
+                None,
+                None,
+                AuditEvent::AnnouncementSent {
+                    severity: announcement.severity,
+                    message: announcement.message.clone(),
+                    subject,
+                },
+            )
+            .await;
+
+        Ok(Json(announcement))
+    }
 }
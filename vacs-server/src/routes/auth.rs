@@ -1,16 +1,29 @@
+use crate::audit::{AuditEvent, AuthMethod};
+use crate::auth::dev::DevIdentityProvider;
 use crate::auth::extractor::AuthenticatedUser;
 use crate::auth::users::{AuthSession, Credentials};
+use crate::config;
 use crate::http::ApiResult;
 use crate::http::error::AppError;
 use crate::state::AppState;
 use anyhow::Context;
-use axum::routing::{get, post};
+use axum::routing::{delete, get, post};
 use axum::{Json, Router};
 use std::sync::Arc;
 use tower_sessions::Session;
 use vacs_protocol::http::auth::UserInfo;
 
 const VATSIM_OAUTH_CSRF_TOKEN_KEY: &str = "vatsim.oauth.csrf_token";
+/// Name of API tokens issued via the VATSIM token exchange if the request has no User-Agent.
+const DEFAULT_API_TOKEN_NAME: &str = "API token";
+
+/// Truncates a token name to the maximum allowed length.
+fn api_token_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .take(config::API_TOKEN_NAME_MAX_LENGTH)
+        .collect()
+}
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
@@ -19,11 +32,31 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/vatsim/token", post(post::vatsim_token))
         .route("/user", get(get::user_info))
         .route("/logout", post(post::logout))
+        .route("/tokens", get(get::api_tokens).post(post::create_api_token))
+        .route("/tokens/{id}", delete(delete::revoke_api_token))
+        .route("/dev/authorize", get(get::dev_authorize))
+        .route("/dev/token", post(post::dev_token))
+        .route("/dev/user", get(get::dev_user))
+}
+
+/// Returns the dev identity provider, pretending its routes do not exist if it is not in use.
+fn dev_provider(auth_session: &AuthSession) -> Result<&DevIdentityProvider, AppError> {
+    auth_session
+        .backend
+        .dev_provider()
+        .ok_or(AppError::NotFound)
 }
 
 mod get {
     use super::*;
+    use crate::auth::extractor::extract_bearer_token;
+    use crate::state::tokens::ApiToken;
+    use axum::extract::{Query, State};
+    use axum::response::{Html, IntoResponse, Redirect, Response};
+    use serde::Deserialize;
+    use serde_json::json;
     use vacs_protocol::http::auth::InitVatsimLogin;
+    use vacs_protocol::vatsim::ClientId;
 
     pub async fn vatsim(auth_session: AuthSession, session: Session) -> ApiResult<InitVatsimLogin> {
         let (url, csrf_token) = auth_session.backend.authorize_url();
@@ -41,18 +74,101 @@ mod get {
     pub async fn user_info(auth: AuthenticatedUser) -> ApiResult<UserInfo> {
         Ok(Json(UserInfo { cid: auth.user.cid }))
     }
+
+    pub async fn api_tokens(
+        auth: AuthenticatedUser,
+        State(state): State<Arc<AppState>>,
+    ) -> ApiResult<Vec<ApiToken>> {
+        let tokens = state.list_api_tokens(auth.cid()).await?;
+        Ok(Json(tokens))
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct DevAuthorizeParams {
+        pub state: String,
+        /// CID to log in as. Renders a page listing the configured CIDs if omitted.
+        pub cid: Option<ClientId>,
+    }
+
+    pub async fn dev_authorize(
+        auth_session: AuthSession,
+        Query(params): Query<DevAuthorizeParams>,
+    ) -> Result<Response, AppError> {
+        let provider = dev_provider(&auth_session)?;
+
+        let Some(cid) = params.cid else {
+            let links = provider
+                .cids()
+                .iter()
+                .map(|cid| {
+                    let url = provider.login_url(cid, &params.state);
+                    format!(
+                        r#"<li><a href="{}">{cid}</a></li>"#,
+                        url.as_str().replace('&', "&amp;")
+                    )
+                })
+                .collect::<String>();
+            return Ok(Html(format!(
+                "<!DOCTYPE html><html><head><title>vacs dev login</title></head>\
+                 <body><h1>Log in as</h1><ul>{links}</ul></body></html>"
+            ))
+            .into_response());
+        };
+
+        let url = provider
+            .authorize(&cid, &params.state)
+            .ok_or_else(|| AppError::Forbidden(format!("Unknown dev CID {cid}")))?;
+        tracing::debug!(%cid, "Issued dev authorization code");
+        Ok(Redirect::to(url.as_str()).into_response())
+    }
+
+    /// Mirrors the VATSIM Connect user details endpoint.
+    pub async fn dev_user(
+        auth_session: AuthSession,
+        parts: http::request::Parts,
+    ) -> Result<Json<serde_json::Value>, AppError> {
+        let provider = dev_provider(&auth_session)?;
+
+        let cid = extract_bearer_token(&parts)
+            .and_then(|token| provider.user(&token))
+            .ok_or_else(|| AppError::Unauthorized("Invalid access token".to_string()))?;
+        Ok(Json(json!({ "data": { "cid": cid } })))
+    }
 }
 
 mod post {
     use super::*;
+    use crate::auth::scopes::Scopes;
     use crate::http::StatusCodeResult;
+    use crate::state::tokens::ApiToken;
+    use axum::Form;
     use axum::extract::{FromRequestParts, State};
     use axum::http::StatusCode;
     use axum_client_ip::ClientIp;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
     use vacs_protocol::http::auth::AuthExchangeToken;
 
+    #[derive(Debug, Deserialize)]
+    pub struct CreateApiTokenRequest {
+        pub name: String,
+        /// Scopes to restrict the token to. The token grants full access if omitted.
+        #[serde(default)]
+        pub scopes: Option<Scopes>,
+    }
+
+    /// A newly issued API token. The token itself is only ever returned once.
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreatedApiToken {
+        pub token: String,
+        #[serde(flatten)]
+        pub info: ApiToken,
+    }
+
     pub async fn vatsim_callback(
         mut auth_session: AuthSession,
+        State(state): State<Arc<AppState>>,
         session: Session,
         Json(AuthExchangeToken { code, state }): Json<AuthExchangeToken>,
     ) -> ApiResult<UserInfo> {
@@ -71,7 +187,20 @@ mod post {
         tracing::debug!("Authenticating with VATSIM");
         let user = match auth_session.authenticate(creds).await {
             Ok(Some(user)) => user,
-            Ok(None) => return Err(AppError::Unauthorized("Invalid credentials".to_string())),
+            Ok(None) => {
+                state
+                    .audit
+                    .record(
+                        None,
+                        None,
+                        AuditEvent::LoginFailed {
+                            method: AuthMethod::OAuth,
+                            ip: None,
+                        },
+                    )
+                    .await;
+                return Err(AppError::Unauthorized("Invalid credentials".to_string()));
+            }
             Err(err) => return Err(err.into()),
         };
 
@@ -80,6 +209,17 @@ mod post {
             .await
             .context("Failed to login user")?;
 
+        state
+            .audit
+            .record(
+                Some(&user.cid),
+                None,
+                AuditEvent::Login {
+                    method: AuthMethod::OAuth,
+                },
+            )
+            .await;
+
         Ok(Json(UserInfo { cid: user.cid }))
     }
 
@@ -109,6 +249,11 @@ mod post {
                 .context("Failed to destroy session")?;
         }
 
+        state
+            .audit
+            .record(Some(&auth.user.cid), None, AuditEvent::Logout)
+            .await;
+
         Ok(StatusCode::NO_CONTENT)
     }
 
@@ -118,7 +263,7 @@ mod post {
         ClientIp(client_ip): ClientIp,
         headers: http::HeaderMap,
     ) -> ApiResult<vacs_protocol::http::auth::AuthTokenResponse> {
-        if let Err(until) = state.rate_limiters().check_vatsim_token(client_ip) {
+        if let Err(until) = state.rate_limiters().check_vatsim_token(client_ip).await {
             tracing::debug!(
                 ?client_ip,
                 ?until,
@@ -139,12 +284,43 @@ mod post {
         tracing::debug!("Authenticating with VATSIM access token");
         let user = match auth_session.authenticate(creds).await {
             Ok(Some(user)) => user,
-            Ok(None) => return Err(AppError::Unauthorized("Invalid credentials".to_string())),
+            Ok(None) => {
+                state
+                    .audit
+                    .record(
+                        None,
+                        None,
+                        AuditEvent::LoginFailed {
+                            method: AuthMethod::AccessToken,
+                            ip: Some(client_ip),
+                        },
+                    )
+                    .await;
+                return Err(AppError::Unauthorized("Invalid credentials".to_string()));
+            }
             Err(err) => return Err(err.into()),
         };
 
-        let token = state
-            .generate_api_token(user.cid.as_str())
+        state
+            .audit
+            .record(
+                Some(&user.cid),
+                None,
+                AuditEvent::Login {
+                    method: AuthMethod::AccessToken,
+                },
+            )
+            .await;
+
+        let name = headers
+            .get(http::header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(api_token_name)
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| DEFAULT_API_TOKEN_NAME.to_string());
+
+        let (token, _) = state
+            .generate_api_token(user.cid.as_str(), &name, None)
             .await
             .context("Failed to generate API token")?;
 
@@ -153,4 +329,68 @@ mod post {
             token,
         }))
     }
+
+    pub async fn create_api_token(
+        auth: AuthenticatedUser,
+        State(state): State<Arc<AppState>>,
+        Json(body): Json<CreateApiTokenRequest>,
+    ) -> ApiResult<CreatedApiToken> {
+        let name = api_token_name(&body.name);
+        if name.is_empty() {
+            return Err(AppError::BadRequest(
+                "Token name must not be empty".to_string(),
+            ));
+        }
+        if body.scopes.as_ref().is_some_and(Scopes::is_empty) {
+            return Err(AppError::BadRequest(
+                "Token scopes must not be empty".to_string(),
+            ));
+        }
+
+        let (token, info) = state
+            .generate_api_token(auth.cid().as_str(), &name, body.scopes)
+            .await
+            .context("Failed to generate API token")?;
+        Ok(Json(CreatedApiToken { token, info }))
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct DevTokenRequest {
+        pub code: String,
+    }
+
+    /// Mirrors the VATSIM Connect token endpoint, exchanging an authorization code issued by
+    /// the dev identity provider for an access token.
+    pub async fn dev_token(
+        auth_session: AuthSession,
+        Form(DevTokenRequest { code }): Form<DevTokenRequest>,
+    ) -> Result<Json<serde_json::Value>, AppError> {
+        let provider = dev_provider(&auth_session)?;
+
+        let access_token = provider
+            .exchange_code(&code)
+            .ok_or_else(|| AppError::BadRequest("Invalid code".to_string()))?;
+        Ok(Json(json!({
+            "access_token": access_token,
+            "token_type": "Bearer",
+        })))
+    }
+}
+
+mod delete {
+    use super::*;
+    use crate::http::StatusCodeResult;
+    use axum::extract::{Path, State};
+    use axum::http::StatusCode;
+
+    pub async fn revoke_api_token(
+        auth: AuthenticatedUser,
+        State(state): State<Arc<AppState>>,
+        Path(id): Path<String>,
+    ) -> StatusCodeResult {
+        if !state.revoke_api_token_by_id(auth.cid(), &id).await? {
+            return Err(AppError::NotFound);
+        }
+        Ok(StatusCode::NO_CONTENT)
+    }
 }
@@ -0,0 +1,33 @@
+use crate::state::AppState;
+use axum::Router;
+use axum::routing::get;
+use std::sync::Arc;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/stations", get(get::stations))
+}
+
+mod get {
+    use super::*;
+    use crate::auth::extractor::Scoped;
+    use crate::auth::scopes::require;
+    use crate::http::ApiResult;
+    use axum::Json;
+    use axum::extract::State;
+    use vacs_protocol::profile::ActiveProfile;
+    use vacs_protocol::ws::server::StationList;
+
+    /// Lists all stations currently covered by a connected controller.
+    pub async fn stations(
+        auth: Scoped<require::CoverageRead>,
+        State(state): State<Arc<AppState>>,
+    ) -> ApiResult<StationList> {
+        tracing::debug!(user = ?auth.user.user, "Listing covered stations for user");
+        let stations = state
+            .clients
+            .list_stations(&ActiveProfile::Custom, None)
+            .await;
+
+        Ok(Json(StationList { stations }))
+    }
+}
@@ -0,0 +1,207 @@
+use crate::auth::extractor::Authorized;
+use crate::auth::roles::require;
+use crate::http::ApiResult;
+use crate::http::error::AppError;
+use crate::state::AppState;
+use axum::Json;
+use axum::Router;
+use axum::extract::{Path, Query, State};
+use axum::routing::{delete, get, post};
+use std::sync::Arc;
+use vacs_protocol::vatsim::ClientId;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/kick", post(post::kick))
+        .route("/bans", post(post::ban))
+        .route("/bans/{cid}", get(get::ban).delete(delete::unban))
+        .route("/mutes", post(post::mute))
+        .route("/mutes/{cid}", get(get::mute).delete(delete::unmute))
+        .route("/log", get(get::log))
+}
+
+fn validate_reason(reason: &str) -> Result<(), AppError> {
+    if reason.trim().is_empty() {
+        return Err(AppError::BadRequest("Reason must not be empty".to_string()));
+    }
+    Ok(())
+}
+
+mod get {
+    use super::*;
+    use crate::audit::{AuditQuery, AuditRecord};
+    use crate::state::moderation::{Ban, Mute};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    pub struct LogQuery {
+        /// Only return actions performed on or by this CID.
+        pub cid: Option<ClientId>,
+    }
+
+    pub async fn ban(
+        _auth: Authorized<require::Supervisor>,
+        State(state): State<Arc<AppState>>,
+        Path(cid): Path<String>,
+    ) -> ApiResult<Ban> {
+        let ban = state
+            .get_ban(&ClientId::from(cid))
+            .await?
+            .ok_or(AppError::NotFound)?;
+        Ok(Json(ban))
+    }
+
+    pub async fn mute(
+        _auth: Authorized<require::Supervisor>,
+        State(state): State<Arc<AppState>>,
+        Path(cid): Path<String>,
+    ) -> ApiResult<Mute> {
+        let mute = state
+            .get_mute(&ClientId::from(cid))
+            .await?
+            .ok_or(AppError::NotFound)?;
+        Ok(Json(mute))
+    }
+
+    pub async fn log(
+        _auth: Authorized<require::Supervisor>,
+        State(state): State<Arc<AppState>>,
+        Query(query): Query<LogQuery>,
+    ) -> ApiResult<Vec<AuditRecord>> {
+        let mut records = state
+            .audit
+            .query(&AuditQuery {
+                cid: query.cid,
+                ..Default::default()
+            })
+            .await?;
+        records.retain(|record| record.event.is_moderation());
+        Ok(Json(records))
+    }
+}
+
+mod post {
+    use super::*;
+    use crate::state::moderation::{Ban, Mute};
+    use serde::{Deserialize, Serialize};
+    use std::time::Duration;
+    use tracing::instrument;
+
+    #[derive(Debug, Deserialize)]
+    pub struct KickRequest {
+        pub cid: ClientId,
+        pub reason: String,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct KickResponse {
+        /// Whether the client was connected and has been disconnected.
+        pub disconnected: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BanRequest {
+        pub cid: ClientId,
+        pub reason: String,
+        pub duration_secs: u64,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MuteRequest {
+        pub cid: ClientId,
+        pub reason: String,
+        pub duration_secs: u64,
+    }
+
+    #[instrument(level = "info", skip(auth, state, body), fields(supervisor = %auth.cid(), cid = %body.cid))]
+    pub async fn kick(
+        auth: Authorized<require::Supervisor>,
+        State(state): State<Arc<AppState>>,
+        Json(body): Json<KickRequest>,
+    ) -> ApiResult<KickResponse> {
+        validate_reason(&body.reason)?;
+
+        let disconnected = state.kick_client(auth.cid(), &body.cid, body.reason).await;
+        Ok(Json(KickResponse { disconnected }))
+    }
+
+    #[instrument(level = "info", skip(auth, state, body), fields(supervisor = %auth.cid(), cid = %body.cid))]
+    pub async fn ban(
+        auth: Authorized<require::Supervisor>,
+        State(state): State<Arc<AppState>>,
+        Json(body): Json<BanRequest>,
+    ) -> ApiResult<Ban> {
+        validate_reason(&body.reason)?;
+        if body.duration_secs == 0 {
+            return Err(AppError::BadRequest(
+                "Ban duration must be greater than zero".to_string(),
+            ));
+        }
+
+        let ban = state
+            .ban_client(
+                auth.cid(),
+                &body.cid,
+                body.reason,
+                Duration::from_secs(body.duration_secs),
+            )
+            .await?;
+        Ok(Json(ban))
+    }
+
+    #[instrument(level = "info", skip(auth, state, body), fields(supervisor = %auth.cid(), cid = %body.cid))]
+    pub async fn mute(
+        auth: Authorized<require::Supervisor>,
+        State(state): State<Arc<AppState>>,
+        Json(body): Json<MuteRequest>,
+    ) -> ApiResult<Mute> {
+        validate_reason(&body.reason)?;
+        if body.duration_secs == 0 {
+            return Err(AppError::BadRequest(
+                "Mute duration must be greater than zero".to_string(),
+            ));
+        }
+
+        let mute = state
+            .mute_client(
+                auth.cid(),
+                &body.cid,
+                body.reason,
+                Duration::from_secs(body.duration_secs),
+            )
+            .await?;
+        Ok(Json(mute))
+    }
+}
+
+mod delete {
+    use super::*;
+    use crate::http::StatusCodeResult;
+    use axum::http::StatusCode;
+    use tracing::instrument;
+
+    #[instrument(level = "info", skip(auth, state), fields(supervisor = %auth.cid()))]
+    pub async fn unban(
+        auth: Authorized<require::Supervisor>,
+        State(state): State<Arc<AppState>>,
+        Path(cid): Path<String>,
+    ) -> StatusCodeResult {
+        state.unban_client(auth.cid(), &ClientId::from(cid)).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    #[instrument(level = "info", skip(auth, state), fields(supervisor = %auth.cid()))]
+    pub async fn unmute(
+        auth: Authorized<require::Supervisor>,
+        State(state): State<Arc<AppState>>,
+        Path(cid): Path<String>,
+    ) -> StatusCodeResult {
+        state
+            .unmute_client(auth.cid(), &ClientId::from(cid))
+            .await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
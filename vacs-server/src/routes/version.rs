@@ -35,7 +35,7 @@ mod get {
         State(state): State<Arc<AppState>>,
         ClientIp(client_ip): ClientIp,
     ) -> ApiMaybe<Release> {
-        if let Err(until) = state.rate_limiters().check_version_update(client_ip) {
+        if let Err(until) = state.rate_limiters().check_version_update(client_ip).await {
             tracing::debug!(
                 ?client_ip,
                 ?until,
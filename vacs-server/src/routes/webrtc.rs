@@ -9,21 +9,19 @@ pub fn routes() -> Router<Arc<AppState>> {
 
 mod get {
     use super::*;
-    use crate::auth::extractor::AuthenticatedUser;
+    use crate::auth::extractor::Scoped;
+    use crate::auth::scopes::require;
     use crate::http::ApiResult;
     use axum::Json;
     use axum::extract::State;
     use vacs_protocol::http::webrtc::IceConfig;
 
     pub async fn ice_config(
-        auth: AuthenticatedUser,
+        auth: Scoped<require::CallsWrite>,
         State(state): State<Arc<AppState>>,
     ) -> ApiResult<IceConfig> {
-        tracing::debug!(user = ?auth.user, "Retrieving ICE config for user");
-        let config = state
-            .ice_config_provider
-            .get_ice_config(&auth.user.cid)
-            .await?;
+        tracing::debug!(user = ?auth.user.user, "Retrieving ICE config for user");
+        let config = state.ice_config_provider.get_ice_config(auth.cid()).await?;
 
         Ok(Json(config))
     }
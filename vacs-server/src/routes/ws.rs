@@ -1,4 +1,5 @@
-use crate::auth::extractor::AuthenticatedUser;
+use crate::auth::extractor::Scoped;
+use crate::auth::scopes::require;
 use crate::http::ApiResult;
 use crate::state::AppState;
 use axum::Json;
@@ -18,11 +19,13 @@ mod get {
     use vacs_protocol::http::ws::WebSocketToken;
 
     pub async fn token(
-        auth: AuthenticatedUser,
+        auth: Scoped<require::WsLogin>,
         State(state): State<Arc<AppState>>,
     ) -> ApiResult<WebSocketToken> {
-        tracing::debug!(user = ?auth.user, "Generating websocket token");
-        let token = state.generate_ws_auth_token(auth.user.cid.as_str()).await?;
+        tracing::debug!(user = ?auth.user.user, "Generating websocket token");
+        let token = state
+            .generate_ws_auth_token(auth.cid().as_str(), auth.user.scopes)
+            .await?;
 
         Ok(Json(WebSocketToken { token }))
     }
@@ -35,12 +38,12 @@ mod delete {
     use vacs_protocol::ws::server::DisconnectReason;
 
     pub async fn terminate_connection(
-        auth: AuthenticatedUser,
+        auth: Scoped<require::WsLogin>,
         State(state): State<Arc<AppState>>,
     ) -> StatusCodeResult {
-        tracing::debug!(user = ?auth.user, "Terminating existing web socket connection");
+        tracing::debug!(user = ?auth.user.user, "Terminating existing web socket connection");
         state
-            .unregister_client(&auth.user.cid, Some(DisconnectReason::Terminated))
+            .disconnect_client(auth.cid(), DisconnectReason::Terminated)
             .await;
 
         Ok(StatusCode::NO_CONTENT)
@@ -0,0 +1,309 @@
+mod session;
+
+pub use session::SfuSession;
+
+use crate::metrics::SfuMetrics;
+use crate::state::clients::session::ClientSession;
+use anyhow::Context;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::instrument;
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::shared::CallId;
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::MediaEngine;
+use webrtc::api::setting_engine::SettingEngine;
+use webrtc::api::{API, APIBuilder};
+use webrtc::ice::udp_network::{EphemeralUDP, UDPNetwork};
+use webrtc::ice_transport::ice_candidate_type::RTCIceCandidateType;
+use webrtc::ice_transport::ice_server::RTCIceServer;
+use webrtc::interceptor::registry::Registry;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+
+/// Policy deciding whether the media of a call is routed through the server's selective
+/// forwarding unit or exchanged directly between the two clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SfuPolicy {
+    /// All calls use peer-to-peer media.
+    #[default]
+    Disabled,
+    /// All calls are routed through the server.
+    Always,
+    /// Only calls involving one of the configured [`SfuConfig::clients`] are routed through the
+    /// server, e.g. users known to be behind symmetric NATs.
+    Clients,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SfuConfig {
+    pub policy: SfuPolicy,
+    /// Clients whose calls are routed through the server if [`SfuPolicy::Clients`] is used.
+    pub clients: HashSet<ClientId>,
+    /// Public IP addresses advertised as host candidates. Required if the server is running
+    /// behind a 1:1 NAT, e.g. inside a container without host networking.
+    pub public_ips: Vec<String>,
+    /// Inclusive range of UDP ports used for media. Any ephemeral port is used if omitted.
+    pub udp_port_range: Option<(u16, u16)>,
+    /// STUN server URLs used to discover server reflexive candidates.
+    pub stun_urls: Vec<String>,
+}
+
+/// Selective forwarding unit terminating the WebRTC connections of both clients of a call and
+/// forwarding their audio between them.
+///
+/// Clients are unaware of the server being part of the media path, as all signaling still flows
+/// through `WebrtcOffer`, `WebrtcAnswer` and `WebrtcIceCandidate` messages, with the server
+/// answering on behalf of the respective remote peer.
+pub struct Sfu {
+    config: SfuConfig,
+    sessions: RwLock<HashMap<CallId, Arc<SfuSession>>>,
+}
+
+impl std::fmt::Debug for Sfu {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sfu")
+            .field("policy", &self.config.policy)
+            .field("sessions", &self.sessions.read().len())
+            .finish()
+    }
+}
+
+impl Sfu {
+    pub fn new(config: SfuConfig) -> Self {
+        Self {
+            config,
+            sessions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns whether the media of a call between the given clients should be routed through
+    /// the server according to the configured [`SfuPolicy`].
+    pub fn should_route(&self, caller_id: &ClientId, callee_id: &ClientId) -> bool {
+        match self.config.policy {
+            SfuPolicy::Disabled => false,
+            SfuPolicy::Always => true,
+            SfuPolicy::Clients => {
+                self.config.clients.contains(caller_id) || self.config.clients.contains(callee_id)
+            }
+        }
+    }
+
+    pub fn session(&self, call_id: &CallId) -> Option<Arc<SfuSession>> {
+        self.sessions.read().get(call_id).cloned()
+    }
+
+    /// Creates the server side peer connections for an accepted call. Subsequent WebRTC
+    /// signaling messages for the call have to be passed to the returned [`SfuSession`] instead
+    /// of being forwarded to the remote client.
+    #[instrument(level = "debug", skip(self, caller, callee), fields(caller_id = %caller.id(), callee_id = %callee.id()), err)]
+    pub async fn open_session(
+        &self,
+        call_id: CallId,
+        caller: ClientSession,
+        callee: ClientSession,
+    ) -> anyhow::Result<Arc<SfuSession>> {
+        tracing::debug!("Opening SFU session");
+
+        let api = self.build_api()?;
+        let session =
+            match SfuSession::new(&api, self.rtc_configuration(), call_id, caller, callee).await {
+                Ok(session) => Arc::new(session),
+                Err(err) => {
+                    SfuMetrics::session_failed();
+                    return Err(err);
+                }
+            };
+
+        let previous = self.sessions.write().insert(call_id, session.clone());
+        if let Some(previous) = previous {
+            tracing::warn!("Replacing existing SFU session for call");
+            previous.close().await;
+        } else {
+            SfuMetrics::session_opened();
+        }
+
+        tracing::debug!("SFU session opened");
+        Ok(session)
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn close_session(&self, call_id: &CallId) {
+        let session = self.sessions.write().remove(call_id);
+        if let Some(session) = session {
+            tracing::debug!("Closing SFU session");
+            session.close().await;
+            SfuMetrics::session_closed();
+        }
+    }
+
+    fn build_api(&self) -> anyhow::Result<API> {
+        let mut media_engine = MediaEngine::default();
+        media_engine
+            .register_default_codecs()
+            .context("Failed to register default codecs")?;
+
+        let mut registry = Registry::new();
+        registry = register_default_interceptors(registry, &mut media_engine)
+            .context("Failed to register default interceptors")?;
+
+        let mut setting_engine = SettingEngine::default();
+        if !self.config.public_ips.is_empty() {
+            setting_engine
+                .set_nat_1to1_ips(self.config.public_ips.clone(), RTCIceCandidateType::Host);
+        }
+        if let Some((min, max)) = self.config.udp_port_range {
+            setting_engine.set_udp_network(UDPNetwork::Ephemeral(
+                EphemeralUDP::new(min, max).context("Invalid SFU UDP port range")?,
+            ));
+        }
+
+        Ok(APIBuilder::new()
+            .with_media_engine(media_engine)
+            .with_interceptor_registry(registry)
+            .with_setting_engine(setting_engine)
+            .build())
+    }
+
+    fn rtc_configuration(&self) -> RTCConfiguration {
+        RTCConfiguration {
+            ice_servers: if self.config.stun_urls.is_empty() {
+                Vec::new()
+            } else {
+                vec![RTCIceServer {
+                    urls: self.config.stun_urls.clone(),
+                    ..Default::default()
+                }]
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ws::test_util::{TestSetup, create_client_info};
+    use pretty_assertions::{assert_eq, assert_matches};
+    use test_log::test;
+    use vacs_protocol::ws::server::ServerMessage;
+    use vacs_protocol::ws::shared::WebrtcOffer;
+    use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
+
+    fn sfu(policy: SfuPolicy, clients: &[&str]) -> Sfu {
+        Sfu::new(SfuConfig {
+            policy,
+            clients: clients.iter().map(|c| ClientId::from(*c)).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn policy_disabled() {
+        let sfu = sfu(SfuPolicy::Disabled, &["client1"]);
+        assert!(!sfu.should_route(&ClientId::from("client1"), &ClientId::from("client2")));
+    }
+
+    #[test]
+    fn policy_always() {
+        let sfu = sfu(SfuPolicy::Always, &[]);
+        assert!(sfu.should_route(&ClientId::from("client1"), &ClientId::from("client2")));
+    }
+
+    #[test]
+    fn policy_clients() {
+        let sfu = sfu(SfuPolicy::Clients, &["client2"]);
+        assert!(sfu.should_route(&ClientId::from("client1"), &ClientId::from("client2")));
+        assert!(sfu.should_route(&ClientId::from("client2"), &ClientId::from("client3")));
+        assert!(!sfu.should_route(&ClientId::from("client1"), &ClientId::from("client3")));
+    }
+
+    #[test]
+    fn config_deserialize() {
+        let config: SfuConfig = toml::from_str(
+            r#"
+            policy = "clients"
+            clients = ["123456"]
+            public_ips = ["203.0.113.1"]
+            udp_port_range = [50000, 50100]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.policy, SfuPolicy::Clients);
+        assert!(config.clients.contains(&ClientId::from("123456")));
+        assert_eq!(config.udp_port_range, Some((50000, 50100)));
+        assert!(config.stun_urls.is_empty());
+    }
+
+    async fn client_offer() -> String {
+        let mut media_engine = MediaEngine::default();
+        media_engine.register_default_codecs().unwrap();
+        let api = APIBuilder::new().with_media_engine(media_engine).build();
+        let peer_connection = api
+            .new_peer_connection(RTCConfiguration::default())
+            .await
+            .unwrap();
+        peer_connection
+            .add_transceiver_from_kind(RTPCodecType::Audio, None)
+            .await
+            .unwrap();
+        let offer = peer_connection.create_offer(None).await.unwrap();
+        let sdp = serde_json::to_string(&offer).unwrap();
+        peer_connection.close().await.unwrap();
+        sdp
+    }
+
+    #[test(tokio::test)]
+    async fn session_answers_offer_on_behalf_of_peer() {
+        let setup = TestSetup::new();
+        let (caller, mut caller_rx) = setup.register_client(create_client_info(1)).await;
+        let (callee, mut callee_rx) = setup.register_client(create_client_info(2)).await;
+
+        let sfu = sfu(SfuPolicy::Always, &[]);
+        let call_id = CallId::new();
+        let session = sfu
+            .open_session(call_id, caller.clone(), callee.clone())
+            .await
+            .unwrap();
+        assert!(sfu.session(&call_id).is_some());
+
+        session
+            .handle_offer(WebrtcOffer {
+                call_id,
+                from_client_id: caller.id().clone(),
+                to_client_id: callee.id().clone(),
+                sdp: client_offer().await,
+            })
+            .await
+            .unwrap();
+
+        let answer = loop {
+            match caller_rx.recv().await.expect("No message received") {
+                ServerMessage::WebrtcAnswer(answer) => break answer,
+                _ => continue,
+            }
+        };
+        assert_eq!(answer.call_id, call_id);
+        assert_eq!(answer.from_client_id, *callee.id());
+        assert_eq!(answer.to_client_id, *caller.id());
+
+        let offer = loop {
+            match callee_rx.recv().await.expect("No message received") {
+                ServerMessage::WebrtcOffer(offer) => break offer,
+                _ => continue,
+            }
+        };
+        assert_eq!(offer.call_id, call_id);
+        assert_eq!(offer.from_client_id, *caller.id());
+        assert_matches!(
+            serde_json::from_str::<serde_json::Value>(&offer.sdp),
+            Ok(serde_json::Value::Object(_))
+        );
+
+        sfu.close_session(&call_id).await;
+        assert!(sfu.session(&call_id).is_none());
+    }
+}
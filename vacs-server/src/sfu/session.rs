@@ -0,0 +1,357 @@
+use crate::state::clients::session::ClientSession;
+use anyhow::{Context, bail};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tracing::Instrument;
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::shared::{CallId, WebrtcAnswer, WebrtcIceCandidate, WebrtcOffer};
+use webrtc::api::API;
+use webrtc::api::media_engine::MIME_TYPE_OPUS;
+use webrtc::ice_transport::ice_candidate::{RTCIceCandidate, RTCIceCandidateInit};
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::rtp_transceiver::RTCRtpTransceiver;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::rtp_transceiver::rtp_receiver::RTCRtpReceiver;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
+use webrtc::track::track_local::{TrackLocal, TrackLocalWriter};
+use webrtc::track::track_remote::TrackRemote;
+
+const SFU_TRACK_ID: &str = "audio";
+const SFU_CLOCK_RATE: u32 = 48_000;
+const SFU_CHANNELS: u16 = 1;
+const RTCP_BUFFER_SIZE: usize = 1500;
+
+/// Server side media session of a single call, consisting of one peer connection ("leg") per
+/// client. Audio received on one leg is forwarded as-is to the other leg.
+pub struct SfuSession {
+    call_id: CallId,
+    caller: SfuLeg,
+    callee: SfuLeg,
+}
+
+impl SfuSession {
+    pub(super) async fn new(
+        api: &API,
+        rtc_config: RTCConfiguration,
+        call_id: CallId,
+        caller: ClientSession,
+        callee: ClientSession,
+    ) -> anyhow::Result<Self> {
+        let caller_id = caller.id().clone();
+        let callee_id = callee.id().clone();
+
+        let caller = SfuLeg::new(api, rtc_config.clone(), call_id, caller, callee_id).await?;
+        let callee = match SfuLeg::new(api, rtc_config, call_id, callee, caller_id).await {
+            Ok(callee) => callee,
+            Err(err) => {
+                caller.close().await;
+                return Err(err);
+            }
+        };
+
+        caller.forward_to(callee.track.clone());
+        callee.forward_to(caller.track.clone());
+
+        Ok(Self {
+            call_id,
+            caller,
+            callee,
+        })
+    }
+
+    pub fn call_id(&self) -> &CallId {
+        &self.call_id
+    }
+
+    /// Accepts an offer of one client, answering it on behalf of the remote client, and starts
+    /// the negotiation with the remote client by sending it the server's offer.
+    #[tracing::instrument(level = "debug", skip(self, offer), fields(call_id = %self.call_id, from_client_id = %offer.from_client_id), err)]
+    pub async fn handle_offer(&self, offer: WebrtcOffer) -> anyhow::Result<()> {
+        let (leg, other) = self.legs(&offer.from_client_id)?;
+
+        tracing::trace!("Answering client offer");
+        let answer = leg.accept_offer(&offer.sdp).await?;
+        leg.client
+            .send_message(WebrtcAnswer {
+                call_id: self.call_id,
+                from_client_id: leg.peer_id.clone(),
+                to_client_id: leg.client.id().clone(),
+                sdp: answer,
+            })
+            .await
+            .context("Failed to send WebRTC answer to client")?;
+
+        tracing::trace!("Sending offer to remote client");
+        let offer = other.create_offer().await?;
+        other
+            .client
+            .send_message(WebrtcOffer {
+                call_id: self.call_id,
+                from_client_id: other.peer_id.clone(),
+                to_client_id: other.client.id().clone(),
+                sdp: offer,
+            })
+            .await
+            .context("Failed to send WebRTC offer to remote client")?;
+
+        Ok(())
+    }
+
+    /// Applies the answer of a client to the server's offer sent in [`SfuSession::handle_offer`].
+    #[tracing::instrument(level = "debug", skip(self, answer), fields(call_id = %self.call_id, from_client_id = %answer.from_client_id), err)]
+    pub async fn handle_answer(&self, answer: WebrtcAnswer) -> anyhow::Result<()> {
+        let (leg, _) = self.legs(&answer.from_client_id)?;
+        leg.accept_answer(&answer.sdp).await
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, candidate), fields(call_id = %self.call_id, from_client_id = %candidate.from_client_id), err)]
+    pub async fn handle_ice_candidate(&self, candidate: WebrtcIceCandidate) -> anyhow::Result<()> {
+        let (leg, _) = self.legs(&candidate.from_client_id)?;
+        leg.add_remote_ice_candidate(&candidate.candidate).await
+    }
+
+    pub(super) async fn close(&self) {
+        self.caller.close().await;
+        self.callee.close().await;
+    }
+
+    fn legs(&self, client_id: &ClientId) -> anyhow::Result<(&SfuLeg, &SfuLeg)> {
+        if self.caller.client.id() == client_id {
+            Ok((&self.caller, &self.callee))
+        } else if self.callee.client.id() == client_id {
+            Ok((&self.callee, &self.caller))
+        } else {
+            bail!("Client {client_id} is not part of this SFU session")
+        }
+    }
+}
+
+/// Peer connection between the server and a single client, impersonating the client's remote
+/// peer (`peer_id`) during signaling.
+struct SfuLeg {
+    client: ClientSession,
+    peer_id: ClientId,
+    peer_connection: Arc<RTCPeerConnection>,
+    /// Track carrying the audio forwarded to this leg's client.
+    track: Arc<TrackLocalStaticRTP>,
+    /// Remote ICE candidates received before the remote description was set. `None` once the
+    /// remote description is available and candidates can be added directly.
+    pending_candidates: Mutex<Option<Vec<RTCIceCandidateInit>>>,
+}
+
+impl SfuLeg {
+    async fn new(
+        api: &API,
+        rtc_config: RTCConfiguration,
+        call_id: CallId,
+        client: ClientSession,
+        peer_id: ClientId,
+    ) -> anyhow::Result<Self> {
+        let peer_connection = Arc::new(
+            api.new_peer_connection(rtc_config)
+                .await
+                .context("Failed to create SFU peer connection")?,
+        );
+
+        let track = Arc::new(TrackLocalStaticRTP::new(
+            RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_OPUS.to_owned(),
+                clock_rate: SFU_CLOCK_RATE,
+                channels: SFU_CHANNELS,
+                ..Default::default()
+            },
+            SFU_TRACK_ID.to_owned(),
+            format!("sfu-{call_id}"),
+        ));
+
+        let rtp_sender = peer_connection
+            .add_track(Arc::clone(&track) as Arc<dyn TrackLocal + Send + Sync>)
+            .await
+            .context("Failed to add track to SFU peer connection")?;
+
+        // RTCP packets have to be read for interceptors (e.g. NACK) to work
+        tokio::spawn(
+            async move {
+                let mut buf = vec![0u8; RTCP_BUFFER_SIZE];
+                while rtp_sender.read(&mut buf).await.is_ok() {}
+                tracing::trace!("SFU RTCP reader finished");
+            }
+            .in_current_span(),
+        );
+
+        {
+            let client = client.clone();
+            let peer_id = peer_id.clone();
+            peer_connection.on_ice_candidate(Box::new(
+                move |candidate: Option<RTCIceCandidate>| {
+                    let client = client.clone();
+                    let peer_id = peer_id.clone();
+                    Box::pin(async move {
+                        let Some(candidate) = candidate else {
+                            return;
+                        };
+                        let candidate = match candidate
+                            .to_json()
+                            .map_err(anyhow::Error::from)
+                            .and_then(|init| serde_json::to_string(&init).map_err(Into::into))
+                        {
+                            Ok(candidate) => candidate,
+                            Err(err) => {
+                                tracing::warn!(?err, "Failed to serialize SFU ICE candidate");
+                                return;
+                            }
+                        };
+
+                        let message = WebrtcIceCandidate {
+                            call_id,
+                            from_client_id: peer_id,
+                            to_client_id: client.id().clone(),
+                            candidate,
+                        };
+                        if let Err(err) = client.send_message(message).await {
+                            tracing::warn!(?err, "Failed to send SFU ICE candidate to client");
+                        }
+                    })
+                },
+            ));
+        }
+
+        {
+            let client_id = client.id().clone();
+            peer_connection.on_peer_connection_state_change(Box::new(
+                move |state: RTCPeerConnectionState| {
+                    match state {
+                        RTCPeerConnectionState::Failed => {
+                            tracing::warn!(?client_id, %call_id, "SFU peer connection failed");
+                        }
+                        state => {
+                            tracing::trace!(?client_id, %call_id, ?state, "SFU peer connection state changed");
+                        }
+                    }
+                    Box::pin(async {})
+                },
+            ));
+        }
+
+        Ok(Self {
+            client,
+            peer_id,
+            peer_connection,
+            track,
+            pending_candidates: Mutex::new(Some(Vec::new())),
+        })
+    }
+
+    /// Forwards all RTP packets received from this leg's client to the given track of the other leg.
+    fn forward_to(&self, target: Arc<TrackLocalStaticRTP>) {
+        let client_id = self.client.id().clone();
+        self.peer_connection.on_track(Box::new(
+            move |remote: Arc<TrackRemote>,
+                  _receiver: Arc<RTCRtpReceiver>,
+                  _transceiver: Arc<RTCRtpTransceiver>| {
+                let target = target.clone();
+                let client_id = client_id.clone();
+                tokio::spawn(async move {
+                    tracing::debug!(?client_id, codec = ?remote.codec().capability.mime_type, "Forwarding SFU track");
+                    while let Ok((packet, _)) = remote.read_rtp().await {
+                        if let Err(err) = target.write_rtp(&packet).await {
+                            tracing::trace!(?err, ?client_id, "Failed to forward RTP packet");
+                        }
+                    }
+                    tracing::debug!(?client_id, "SFU track forwarding finished");
+                });
+                Box::pin(async {})
+            },
+        ));
+    }
+
+    async fn accept_offer(&self, sdp: &str) -> anyhow::Result<String> {
+        let offer = serde_json::from_str::<RTCSessionDescription>(sdp)
+            .context("Failed to deserialize SDP")?;
+        self.peer_connection
+            .set_remote_description(offer)
+            .await
+            .context("Failed to set offer as remote description")?;
+        self.flush_pending_candidates().await;
+
+        let answer = self
+            .peer_connection
+            .create_answer(None)
+            .await
+            .context("Failed to create answer")?;
+        self.peer_connection
+            .set_local_description(answer)
+            .await
+            .context("Failed to set answer as local description")?;
+
+        self.local_description().await
+    }
+
+    async fn create_offer(&self) -> anyhow::Result<String> {
+        let offer = self
+            .peer_connection
+            .create_offer(None)
+            .await
+            .context("Failed to create offer")?;
+        self.peer_connection
+            .set_local_description(offer)
+            .await
+            .context("Failed to set offer as local description")?;
+
+        self.local_description().await
+    }
+
+    async fn accept_answer(&self, sdp: &str) -> anyhow::Result<()> {
+        let answer = serde_json::from_str::<RTCSessionDescription>(sdp)
+            .context("Failed to deserialize SDP")?;
+        self.peer_connection
+            .set_remote_description(answer)
+            .await
+            .context("Failed to set answer as remote description")?;
+        self.flush_pending_candidates().await;
+        Ok(())
+    }
+
+    async fn add_remote_ice_candidate(&self, candidate: &str) -> anyhow::Result<()> {
+        let candidate = serde_json::from_str::<RTCIceCandidateInit>(candidate)
+            .context("Failed to deserialize candidate")?;
+
+        if let Some(pending) = self.pending_candidates.lock().as_mut() {
+            tracing::trace!("Remote description not set yet, queueing ICE candidate");
+            pending.push(candidate);
+            return Ok(());
+        }
+
+        self.peer_connection
+            .add_ice_candidate(candidate)
+            .await
+            .context("Failed to add remote ICE candidate")
+    }
+
+    async fn flush_pending_candidates(&self) {
+        let pending = self.pending_candidates.lock().take().unwrap_or_default();
+        for candidate in pending {
+            if let Err(err) = self.peer_connection.add_ice_candidate(candidate).await {
+                tracing::warn!(?err, "Failed to add queued remote ICE candidate");
+            }
+        }
+    }
+
+    async fn local_description(&self) -> anyhow::Result<String> {
+        let description = self
+            .peer_connection
+            .local_description()
+            .await
+            .context("Failed to get local description")?;
+        serde_json::to_string(&description).context("Failed to serialize local description")
+    }
+
+    async fn close(&self) {
+        if let Err(err) = self.peer_connection.close().await {
+            tracing::warn!(?err, client_id = ?self.client.id(), "Failed to close SFU peer connection");
+        }
+    }
+}
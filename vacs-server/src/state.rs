@@ -1,30 +1,47 @@
 pub mod calls;
 pub mod clients;
+pub mod moderation;
+pub mod tokens;
 
+use crate::audit::{AuditEvent, AuditLog, AuditSink, unix_now};
+use crate::auth::roles::{Role, Roles};
+use crate::auth::scopes::Scopes;
+use crate::cluster::{Cluster, ClusterEnvelope, ClusterInstances, ClusterMessage};
 use crate::config;
 use crate::config::AppConfig;
 use crate::dataset::DatasetManager;
 use crate::ice::provider::IceConfigProvider;
 use crate::metrics::guards::ClientConnectionGuard;
-use crate::metrics::{ErrorMetrics, VatsimSyncMetrics};
+use crate::metrics::{ClientMetrics, ErrorMetrics, VatsimSyncMetrics};
 use crate::ratelimit::RateLimiters;
 use crate::release::UpdateChecker;
+use crate::sfu::Sfu;
 use crate::state::calls::CallManager;
-use crate::state::clients::{ClientManager, ClientSession};
+use crate::state::clients::{
+    ClientManager, ClientManagerError, ClientSession, DetachedClients, ResumedClient,
+};
+use crate::state::moderation::{Ban, Mute};
+use crate::state::tokens::{ApiToken, ApiTokenGrant, StoredApiToken, WsAuthToken};
 use crate::store::{Store, StoreBackend};
+use crate::ws::application_message::handle_call_message;
 use anyhow::Context;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
-use tokio::sync::{broadcast, mpsc, watch};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
 use tokio::task::JoinHandle;
 use tokio::time;
+use tokio::time::MissedTickBehavior;
 use tracing::{Instrument, instrument};
 use uuid::Uuid;
 use vacs_protocol::profile::{ActiveProfile, ProfileId};
 use vacs_protocol::vatsim::{ClientId, PositionId};
-use vacs_protocol::ws::server::{ClientInfo, DisconnectReason, ServerMessage, StationInfo};
-use vacs_protocol::ws::shared::{Error, ErrorReason};
+use vacs_protocol::ws::client::ClientMessage;
+use vacs_protocol::ws::server::{
+    ClientChange, ClientInfo, ClientList, DisconnectReason, ServerMessage, StationInfo,
+};
+use vacs_protocol::ws::shared::{CallId, Error, ErrorReason};
 use vacs_vatsim::ControllerInfo;
 use vacs_vatsim::coverage::network::Network;
 use vacs_vatsim::data_feed::DataFeed;
@@ -37,12 +54,21 @@ pub struct AppState {
     pub clients: ClientManager,
     pub dataset: Option<DatasetManager>,
     pub ice_config_provider: Arc<dyn IceConfigProvider>,
+    pub sfu: Sfu,
+    pub audit: AuditLog,
+    detached_clients: DetachedClients,
     store: Store,
     broadcast_tx: broadcast::Sender<ServerMessage>,
     slurper: SlurperClient,
     data_feed: Arc<dyn DataFeed>,
     rate_limiters: RateLimiters,
     shutdown_rx: watch::Receiver<()>,
+    draining: AtomicBool,
+    cluster: Option<Arc<Cluster>>,
+    /// Resumptions of sessions detached on other instances of the cluster, awaiting the
+    /// messages queued by the owning instance.
+    remote_resumes:
+        parking_lot::Mutex<HashMap<ClientId, oneshot::Sender<Option<Vec<ServerMessage>>>>>,
 }
 
 impl AppState {
@@ -58,14 +84,20 @@ impl AppState {
         shutdown_rx: watch::Receiver<()>,
         ice_config_provider: Arc<dyn IceConfigProvider>,
         dataset: Option<DatasetManager>,
+        audit_sink: Arc<dyn AuditSink>,
     ) -> Self {
         let (broadcast_tx, _) = broadcast::channel(config::BROADCAST_CHANNEL_CAPACITY);
         Self {
+            sfu: Sfu::new(config.sfu.clone()),
+            detached_clients: DetachedClients::new(Duration::from_secs(
+                config.auth.session_resume_grace_period_secs,
+            )),
             config,
             updates,
             ice_config_provider,
             store,
             calls: CallManager::new(),
+            audit: AuditLog::new(audit_sink),
             clients: ClientManager::new(broadcast_tx.clone(), network),
             dataset,
             broadcast_tx,
@@ -73,22 +105,44 @@ impl AppState {
             data_feed,
             rate_limiters,
             shutdown_rx,
+            draining: AtomicBool::new(false),
+            cluster: None,
+            remote_resumes: parking_lot::Mutex::new(HashMap::new()),
         }
     }
 
+    /// Runs the app as instance of a cluster. Messages are only exchanged with the other
+    /// instances once [`AppState::start_cluster_task`] is started.
+    pub fn with_cluster(mut self, cluster: Cluster) -> Self {
+        self.cluster = Some(Arc::new(cluster));
+        self
+    }
+
+    pub fn cluster(&self) -> Option<&Cluster> {
+        self.cluster.as_deref()
+    }
+
     pub fn get_client_receivers(
         &self,
     ) -> (broadcast::Receiver<ServerMessage>, watch::Receiver<()>) {
         (self.broadcast_tx.subscribe(), self.shutdown_rx.clone())
     }
 
-    #[instrument(level = "debug", skip(self, client_connection_guard), err)]
+    /// Registers a new session of a client. If the client resumes a session detached on another
+    /// instance of the cluster, the session is handed over to this instance along with the
+    /// messages queued while it was detached.
+    #[instrument(
+        level = "debug",
+        skip(self, resume_token, client_connection_guard),
+        err
+    )]
     pub async fn register_client(
         &self,
         client_info: ClientInfo,
         active_profile: ActiveProfile<ProfileId>,
+        resume_token: Option<&str>,
         client_connection_guard: ClientConnectionGuard,
-    ) -> anyhow::Result<(ClientSession, mpsc::Receiver<ServerMessage>)> {
+    ) -> anyhow::Result<ResumedClient> {
         tracing::trace!("Registering client");
 
         if self.clients.is_empty().await {
@@ -101,13 +155,67 @@ impl AppState {
             }
         }
 
+        if let Some(resume_token) = resume_token
+            && let Some(pending) = self
+                .resume_remote_client(&client_info.id, resume_token)
+                .await
+        {
+            let (client, rx) = self
+                .clients
+                .take_over_client(
+                    client_info,
+                    active_profile,
+                    resume_token.to_string(),
+                    client_connection_guard,
+                )
+                .await?;
+
+            tracing::debug!("Client session taken over from other instance");
+            return Ok(ResumedClient {
+                client,
+                rx,
+                pending,
+            });
+        }
+
+        // Ownership is claimed atomically, so a client logging in to two instances at once is
+        // only accepted by one of them. Records of instances no longer in the cluster expire
+        // after the instance timeout, allowing their clients to log in again.
+        if let Some(cluster) = &self.cluster {
+            match self
+                .store
+                .set_if_absent(
+                    format!("cluster.client.{}", client_info.id).as_str(),
+                    cluster.instance_id(),
+                    Some(self.config.cluster.instance_timeout),
+                )
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    if let Some(owner) = self.client_owner(&client_info.id).await {
+                        tracing::trace!(?owner, "Client already connected to another instance");
+                        return Err(ClientManagerError::DuplicateClient(
+                            client_info.id.to_string(),
+                        )
+                        .into());
+                    }
+                }
+                Err(err) => tracing::warn!(?err, "Failed to record client ownership"),
+            }
+        }
+
         let (client, rx) = self
             .clients
             .add_client(client_info, active_profile, client_connection_guard)
             .await?;
 
         tracing::trace!("Client registered");
-        Ok((client, rx))
+        Ok(ResumedClient {
+            client,
+            rx,
+            pending: Vec::new(),
+        })
     }
 
     #[instrument(level = "debug", skip(self))]
@@ -122,12 +230,238 @@ impl AppState {
             .remove_client(client_id.clone(), disconnect_reason)
             .await;
 
+        if self.cluster.is_some()
+            && self.client_owner(client_id).await.is_none()
+            && let Err(err) = self
+                .store
+                .remove(format!("cluster.client.{client_id}").as_str())
+                .await
+        {
+            tracing::warn!(?err, "Failed to remove client ownership");
+        }
+
         self.calls.cleanup_client_calls(self, client_id).await;
 
         tracing::debug!("Client unregistered");
     }
 
-    pub async fn list_clients(&self, self_client_id: Option<&ClientId>) -> Vec<ClientInfo> {
+    /// Keeps the session of a client whose connection was lost for the configured grace period,
+    /// allowing it to be resumed by [`AppState::resume_client`]. Messages sent or broadcast to the
+    /// client in the meantime are queued. If queued messages had to be dropped, a full client list
+    /// is sent on resumption instead. The client is unregistered once the grace period expires.
+    #[instrument(level = "debug", skip_all, fields(client_id = %client.id()))]
+    pub async fn detach_client(
+        self: &Arc<Self>,
+        client: ClientSession,
+        mut rx: mpsc::Receiver<ServerMessage>,
+    ) {
+        let grace_period = self.detached_clients.grace_period();
+        if grace_period.is_zero() || !self.clients.is_client_connected(client.id()).await {
+            tracing::trace!("Session cannot be resumed, unregistering client");
+            self.unregister_client(client.id(), None).await;
+            return;
+        }
+
+        tracing::debug!(?grace_period, "Detaching client session");
+        let mut resume_rx = self
+            .detached_clients
+            .insert(client.id().clone(), client.resume_token().to_string());
+
+        let mut broadcast_rx = self.broadcast_tx.subscribe();
+        let state = self.clone();
+        tokio::spawn(
+            async move {
+                let mut pending = VecDeque::new();
+                let mut dropped = false;
+                let mut broadcast_open = true;
+                let expiry = time::sleep(grace_period);
+                tokio::pin!(expiry);
+                let mut expired = false;
+
+                loop {
+                    tokio::select! {
+                        biased;
+
+                        reply = &mut resume_rx => {
+                            // If the request was dropped, the session was rejected and has
+                            // already been unregistered by the resuming connection.
+                            if let Ok(reply) = reply {
+                                if dropped {
+                                    tracing::debug!("Queued messages were dropped, sending full client list");
+                                    let list = state.list_clients(Some(client.id())).await;
+                                    pending.push_back(ServerMessage::ClientList(list));
+                                }
+                                tracing::debug!(pending = pending.len(), "Handing over detached client session");
+                                ClientMetrics::detached_session(true);
+                                let _ = reply.send(ResumedClient {
+                                    client,
+                                    rx,
+                                    pending: pending.into(),
+                                });
+                            }
+                            break;
+                        }
+
+                        _ = &mut expiry, if !expired => {
+                            expired = true;
+                            if state.detached_clients.remove(client.id(), client.resume_token()) {
+                                tracing::debug!("Grace period expired, unregistering detached client");
+                                ClientMetrics::detached_session(false);
+                                state.unregister_client(client.id(), None).await;
+                                break;
+                            }
+                            tracing::trace!("Grace period expired while session is being resumed");
+                        }
+
+                        Some(msg) = rx.recv() => {
+                            dropped |= DetachedClients::queue_pending(&mut pending, msg);
+                        }
+
+                        msg = broadcast_rx.recv(), if broadcast_open => {
+                            match msg {
+                                Ok(msg) if client.is_own_broadcast(&msg) => {}
+                                Ok(msg) => {
+                                    dropped |= DetachedClients::queue_pending(&mut pending, msg);
+                                }
+                                Err(broadcast::error::RecvError::Lagged(count)) => {
+                                    tracing::warn!(count, "Detached client lagged behind broadcasts");
+                                    dropped = true;
+                                }
+                                Err(broadcast::error::RecvError::Closed) => broadcast_open = false,
+                            }
+                        }
+                    }
+                }
+            }
+            .in_current_span(),
+        );
+    }
+
+    /// Resumes the detached session of a client if the given resume token matches. An active
+    /// session is taken over if the server has not detected the connection loss yet.
+    ///
+    /// Returns `None` if there is no session to resume, in which case a new session has to be
+    /// registered. A detached session not matching the resume token is unregistered immediately.
+    #[instrument(level = "debug", skip(self, resume_token))]
+    pub async fn resume_client(
+        &self,
+        client_id: &ClientId,
+        resume_token: Option<&str>,
+    ) -> Option<ResumedClient> {
+        let detached = match self.detached_clients.take(client_id) {
+            Some(detached) => detached,
+            None => {
+                let resume_token = resume_token?;
+                let client = self.get_client(client_id).await?;
+                if client.resume_token() != resume_token {
+                    return None;
+                }
+
+                tracing::debug!("Taking over active session of resuming client");
+                client.disconnect(None);
+                self.detached_clients
+                    .take_when_detached(client_id, config::SESSION_TAKEOVER_TIMEOUT)
+                    .await?
+            }
+        };
+
+        if resume_token != Some(detached.resume_token())
+            || !self.clients.is_client_connected(client_id).await
+        {
+            tracing::debug!("Resume token does not match detached session, unregistering client");
+            drop(detached);
+            self.unregister_client(client_id, None).await;
+            return None;
+        }
+
+        let resumed = detached.resume().await;
+        if resumed.is_some() {
+            tracing::debug!("Client session resumed");
+        }
+        resumed
+    }
+
+    /// Asks the instance of the cluster owning a client to hand over its session, as the client
+    /// reconnected to this instance. Returns the messages queued while the session was detached,
+    /// or `None` if there is no session to resume on another instance.
+    #[instrument(level = "debug", skip(self, resume_token))]
+    async fn resume_remote_client(
+        &self,
+        client_id: &ClientId,
+        resume_token: &str,
+    ) -> Option<Vec<ServerMessage>> {
+        let cluster = self.cluster.as_ref()?;
+        let instance_id = self.client_owner(client_id).await?;
+
+        tracing::debug!(
+            ?instance_id,
+            "Requesting session of client from other instance"
+        );
+        let (tx, rx) = oneshot::channel();
+        self.remote_resumes.lock().insert(client_id.clone(), tx);
+        let message = ClusterMessage::ResumeRequest {
+            client_id: client_id.clone(),
+            resume_token: resume_token.to_string(),
+        };
+        if let Err(err) = cluster.publish(Some(&instance_id), message).await {
+            tracing::warn!(?err, "Failed to request session of client");
+            self.remote_resumes.lock().remove(client_id);
+            return None;
+        }
+
+        match time::timeout(config::CLUSTER_RESUME_TIMEOUT, rx).await {
+            Ok(Ok(pending)) => pending,
+            Ok(Err(_)) => None,
+            Err(_) => {
+                tracing::warn!(
+                    ?instance_id,
+                    "Instance did not answer session request in time"
+                );
+                self.remote_resumes.lock().remove(client_id);
+                None
+            }
+        }
+    }
+
+    /// Hands the session of a client over to the instance of the cluster the client reconnected
+    /// to, recording it as new owner. Returns the messages queued while the session was
+    /// detached, or `None` if there is no session matching the resume token.
+    #[instrument(level = "debug", skip(self, resume_token))]
+    async fn hand_over_client(
+        &self,
+        instance_id: &str,
+        client_id: &ClientId,
+        resume_token: &str,
+    ) -> Option<Vec<ServerMessage>> {
+        let ResumedClient {
+            mut rx,
+            mut pending,
+            ..
+        } = self.resume_client(client_id, Some(resume_token)).await?;
+
+        // The new owner is recorded first, so messages sent to the client from now on are
+        // forwarded to its new instance.
+        if let Err(err) = self
+            .store
+            .set(
+                format!("cluster.client.{client_id}").as_str(),
+                instance_id,
+                Some(self.config.cluster.instance_timeout),
+            )
+            .await
+        {
+            tracing::warn!(?err, "Failed to hand over client ownership");
+        }
+        self.clients.release_client(client_id, instance_id).await;
+        while let Ok(msg) = rx.try_recv() {
+            pending.push(msg);
+        }
+
+        tracing::debug!(pending = pending.len(), "Handed over client session");
+        Some(pending)
+    }
+
+    pub async fn list_clients(&self, self_client_id: Option<&ClientId>) -> ClientList {
         self.clients.list_clients(self_client_id).await
     }
 
@@ -163,6 +497,25 @@ impl AppState {
                 }
             }
             None => {
+                if let Some(cluster) = &self.cluster
+                    && let Some(instance_id) = self.client_owner(client_id).await
+                {
+                    tracing::trace!(%instance_id, "Forwarding message to instance of client");
+                    let message = ClusterMessage::Direct {
+                        client_id: client_id.clone(),
+                        message: message.into(),
+                    };
+                    return cluster
+                        .publish(Some(&instance_id), message)
+                        .await
+                        .map_err(|err| {
+                            tracing::warn!(?err, "Failed to forward message to instance of client");
+                            ErrorMetrics::error(&ErrorReason::PeerConnection);
+                            Error::new(ErrorReason::PeerConnection)
+                                .with_client_id(client_id.clone())
+                        });
+                }
+
                 tracing::warn!("Client not found");
                 ErrorMetrics::peer_not_found();
                 Err(Error::new(ErrorReason::ClientNotFound).with_client_id(client_id.clone()))
@@ -170,8 +523,136 @@ impl AppState {
         }
     }
 
+    /// Disconnects a client connected to this or another instance of the cluster. Returns whether
+    /// the client was connected.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn disconnect_client(&self, client_id: &ClientId, reason: DisconnectReason) -> bool {
+        if self.clients.is_client_connected(client_id).await {
+            self.unregister_client(client_id, Some(reason)).await;
+            return true;
+        }
+
+        let (Some(cluster), Some(instance_id)) =
+            (&self.cluster, self.client_owner(client_id).await)
+        else {
+            tracing::trace!("Client not connected");
+            return false;
+        };
+        tracing::debug!(?instance_id, "Forwarding disconnect to instance of client");
+        let message = ClusterMessage::Disconnect {
+            client_id: client_id.clone(),
+            reason,
+        };
+        match cluster.publish(Some(&instance_id), message).await {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::warn!(?err, "Failed to forward disconnect to instance of client");
+                false
+            }
+        }
+    }
+
+    /// Returns the ID of the instance a client is connected to, if it is connected to another
+    /// instance of the cluster.
+    async fn client_owner(&self, client_id: &ClientId) -> Option<String> {
+        let cluster = self.cluster.as_ref()?;
+        match self
+            .store
+            .get::<String>(format!("cluster.client.{client_id}").as_str())
+            .await
+        {
+            Ok(owner) => owner.filter(|owner| owner != cluster.instance_id()),
+            Err(err) => {
+                tracing::warn!(?err, "Failed to get client ownership");
+                None
+            }
+        }
+    }
+
+    /// Records this instance as host of a call, so messages of clients connected to other
+    /// instances concerning the call are forwarded to it. The record is refreshed with each
+    /// heartbeat until [`AppState::release_call`] is called once the call ended.
+    pub async fn claim_call(&self, call_id: &CallId) {
+        let Some(cluster) = &self.cluster else {
+            return;
+        };
+        if let Err(err) = self
+            .store
+            .set(
+                format!("cluster.call.{call_id}").as_str(),
+                cluster.instance_id(),
+                Some(self.config.cluster.instance_timeout),
+            )
+            .await
+        {
+            tracing::warn!(?err, "Failed to record call ownership");
+        }
+    }
+
+    /// Removes the record of this instance hosting a call once the call ended.
+    pub async fn release_call(&self, call_id: &CallId) {
+        if self.cluster.is_none() {
+            return;
+        }
+        if let Err(err) = self
+            .store
+            .remove(format!("cluster.call.{call_id}").as_str())
+            .await
+        {
+            tracing::warn!(?err, "Failed to remove call ownership");
+        }
+    }
+
+    /// Forwards a message concerning a call hosted by another instance of the cluster to that
+    /// instance. Returns `false` if the call is not hosted by another instance, in which case the
+    /// message has to be handled locally.
+    #[instrument(level = "trace", skip(self, message))]
+    pub async fn forward_call_message(
+        &self,
+        client_id: &ClientId,
+        call_id: &CallId,
+        message: &ClientMessage,
+    ) -> bool {
+        let Some(cluster) = &self.cluster else {
+            return false;
+        };
+        if self.calls.has_call(call_id) {
+            return false;
+        }
+
+        let instance_id = match self
+            .store
+            .get::<String>(format!("cluster.call.{call_id}").as_str())
+            .await
+        {
+            Ok(Some(instance_id)) if instance_id != cluster.instance_id() => instance_id,
+            Ok(_) => return false,
+            Err(err) => {
+                tracing::warn!(?err, "Failed to get call ownership");
+                return false;
+            }
+        };
+
+        tracing::trace!(%instance_id, "Forwarding call message to instance hosting call");
+        let message = ClusterMessage::CallMessage {
+            client_id: client_id.clone(),
+            message: message.clone(),
+        };
+        match cluster.publish(Some(&instance_id), message).await {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::warn!(?err, "Failed to forward call message");
+                false
+            }
+        }
+    }
+
     #[instrument(level = "debug", skip(self), err)]
-    pub async fn generate_ws_auth_token(&self, cid: &str) -> anyhow::Result<String> {
+    pub async fn generate_ws_auth_token(
+        &self,
+        cid: &str,
+        scopes: Option<Scopes>,
+    ) -> anyhow::Result<String> {
         tracing::debug!("Generating web socket auth token");
 
         let token = Uuid::now_v7().to_string();
@@ -180,7 +661,10 @@ impl AppState {
         self.store
             .set(
                 format!("ws.token.{token}").as_str(),
-                cid,
+                WsAuthToken {
+                    cid: ClientId::from(cid),
+                    scopes,
+                },
                 Some(Duration::from_secs(30)),
             )
             .await
@@ -191,13 +675,13 @@ impl AppState {
     }
 
     #[instrument(level = "debug", skip_all, err)]
-    pub async fn verify_ws_auth_token(&self, token: &str) -> anyhow::Result<ClientId> {
+    pub async fn verify_ws_auth_token(&self, token: &str) -> anyhow::Result<WsAuthToken> {
         tracing::debug!("Verifying web socket auth token");
 
         match self.store.get(format!("ws.token.{token}").as_str()).await {
-            Ok(Some(cid)) => {
-                tracing::debug!(?cid, "Web socket auth token verified");
-                Ok(cid)
+            Ok(Some(WsAuthToken { cid, scopes })) => {
+                tracing::debug!(?cid, ?scopes, "Web socket auth token verified");
+                Ok(WsAuthToken { cid, scopes })
             }
             Ok(None) => anyhow::bail!("Web socket auth token not found"),
             Err(err) => anyhow::bail!(err),
@@ -205,23 +689,48 @@ impl AppState {
     }
 
     #[instrument(level = "debug", skip(self), err)]
-    pub async fn generate_api_token(&self, cid: &str) -> anyhow::Result<String> {
+    pub async fn generate_api_token(
+        &self,
+        cid: &str,
+        name: &str,
+        scopes: Option<Scopes>,
+    ) -> anyhow::Result<(String, ApiToken)> {
         tracing::debug!("Generating API token");
 
         let token = Uuid::now_v7().to_string();
         let expiry = Duration::from_secs(self.config.auth.api_token.expiry_secs);
+        let info = ApiToken {
+            id: Uuid::now_v7().to_string(),
+            name: name.to_string(),
+            cid: ClientId::from(cid),
+            scopes,
+            created_at: unix_now(),
+            last_used_at: None,
+        };
 
         self.store
-            .set(format!("api.token.{token}").as_str(), cid, Some(expiry))
+            .set(
+                format!("api.token.{token}").as_str(),
+                StoredApiToken::Named(info.clone()),
+                Some(expiry),
+            )
             .await
             .context("Failed to store API token")?;
+        self.store
+            .add_to_set(format!("api.tokens.{cid}").as_str(), &token)
+            .await
+            .context("Failed to index API token")?;
+
+        self.audit
+            .record(Some(&info.cid), None, AuditEvent::ApiTokenIssued)
+            .await;
 
         tracing::debug!("API token generated");
-        Ok(token)
+        Ok((token, info))
     }
 
     #[instrument(level = "debug", skip_all, err)]
-    pub async fn verify_api_token(&self, token: &str) -> anyhow::Result<Option<ClientId>> {
+    pub async fn verify_api_token(&self, token: &str) -> anyhow::Result<Option<ApiTokenGrant>> {
         tracing::debug!("Verifying API token");
 
         if Uuid::try_parse(token).is_err() {
@@ -230,15 +739,34 @@ impl AppState {
         }
 
         let key = format!("api.token.{token}");
-        match self.store.get(key.as_str()).await {
-            Ok(Some(cid)) => {
-                tracing::debug!(?cid, "API token verified");
+        match self.store.get::<StoredApiToken>(key.as_str()).await {
+            Ok(Some(stored)) => {
+                let grant = ApiTokenGrant {
+                    cid: stored.cid().clone(),
+                    scopes: stored.scopes().cloned(),
+                };
+                tracing::debug!(cid = ?grant.cid, scopes = ?grant.scopes, "API token verified");
                 // extend TTL on each use (inactivity-based expiry, like sessions)
                 let expiry = Duration::from_secs(self.config.auth.api_token.expiry_secs);
-                if let Err(err) = self.store.expire(&key, expiry).await {
+                let now = unix_now();
+                let result = match stored {
+                    StoredApiToken::Named(mut info)
+                        if info.last_used_at.is_none_or(|last_used_at| {
+                            now.saturating_sub(last_used_at)
+                                >= config::API_TOKEN_LAST_USED_RESOLUTION.as_secs()
+                        }) =>
+                    {
+                        info.last_used_at = Some(now);
+                        self.store
+                            .set(&key, StoredApiToken::Named(info), Some(expiry))
+                            .await
+                    }
+                    _ => self.store.expire(&key, expiry).await,
+                };
+                if let Err(err) = result {
                     tracing::warn!(?err, "Failed to extend API token TTL");
                 }
-                Ok(Some(cid))
+                Ok(Some(grant))
             }
             Ok(None) => Ok(None),
             Err(err) => anyhow::bail!(err),
@@ -248,10 +776,284 @@ impl AppState {
     #[instrument(level = "debug", skip(self), err)]
     pub async fn revoke_api_token(&self, token: &str) -> anyhow::Result<()> {
         tracing::debug!("Revoking API token");
+
+        let key = format!("api.token.{token}");
+        let stored: Option<StoredApiToken> = self
+            .store
+            .get(key.as_str())
+            .await
+            .context("Failed to get API token")?;
+        self.store
+            .remove(key.as_str())
+            .await
+            .context("Failed to revoke API token")?;
+
+        if let Some(stored) = stored {
+            if let StoredApiToken::Named(info) = &stored {
+                self.store
+                    .remove_from_set(format!("api.tokens.{}", info.cid).as_str(), token)
+                    .await
+                    .context("Failed to remove API token from index")?;
+            }
+            self.audit
+                .record(Some(stored.cid()), None, AuditEvent::ApiTokenRevoked)
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Returns the API tokens of a user, oldest first. Expired tokens are removed from the index.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn list_api_tokens(&self, cid: &ClientId) -> anyhow::Result<Vec<ApiToken>> {
+        Ok(self
+            .indexed_api_tokens(cid)
+            .await?
+            .into_iter()
+            .map(|(_, info)| info)
+            .collect())
+    }
+
+    /// Revokes an API token of a user by its ID. Returns whether the token was found.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn revoke_api_token_by_id(&self, cid: &ClientId, id: &str) -> anyhow::Result<bool> {
+        let Some((token, _)) = self
+            .indexed_api_tokens(cid)
+            .await?
+            .into_iter()
+            .find(|(_, info)| info.id == id)
+        else {
+            return Ok(false);
+        };
+
+        self.revoke_api_token(&token).await?;
+        Ok(true)
+    }
+
+    async fn indexed_api_tokens(&self, cid: &ClientId) -> anyhow::Result<Vec<(String, ApiToken)>> {
+        let index_key = format!("api.tokens.{cid}");
+        let members = self
+            .store
+            .set_members(index_key.as_str())
+            .await
+            .context("Failed to get API token index")?;
+
+        let mut tokens = Vec::with_capacity(members.len());
+        for token in members {
+            match self
+                .store
+                .get::<StoredApiToken>(format!("api.token.{token}").as_str())
+                .await
+                .context("Failed to get API token")?
+            {
+                Some(StoredApiToken::Named(info)) if &info.cid == cid => tokens.push((token, info)),
+                _ => {
+                    tracing::trace!("Removing expired API token from index");
+                    self.store
+                        .remove_from_set(index_key.as_str(), &token)
+                        .await
+                        .context("Failed to remove API token from index")?;
+                }
+            }
+        }
+
+        tokens.sort_by_key(|(_, info)| info.created_at);
+        Ok(tokens)
+    }
+
+    /// Disconnects a client on behalf of a supervisor. Returns whether the client was connected.
+    #[instrument(level = "info", skip(self))]
+    pub async fn kick_client(&self, actor: &ClientId, cid: &ClientId, reason: String) -> bool {
+        let connected = self
+            .disconnect_client(
+                cid,
+                DisconnectReason::Kicked {
+                    reason: reason.clone(),
+                },
+            )
+            .await;
+
+        self.audit
+            .record(
+                Some(actor),
+                Some(cid),
+                AuditEvent::ClientKicked { reason, connected },
+            )
+            .await;
+        connected
+    }
+
+    /// Bans a client for the given duration on behalf of a supervisor, disconnecting it if it is
+    /// currently connected.
+    #[instrument(level = "info", skip(self), err)]
+    pub async fn ban_client(
+        &self,
+        actor: &ClientId,
+        cid: &ClientId,
+        reason: String,
+        duration: Duration,
+    ) -> anyhow::Result<Ban> {
+        let issued_at = unix_now();
+        let ban = Ban {
+            cid: cid.clone(),
+            reason,
+            issued_by: actor.clone(),
+            issued_at,
+            expires_at: issued_at.saturating_add(duration.as_secs()),
+        };
+
+        self.store
+            .set(
+                format!("moderation.ban.{cid}").as_str(),
+                &ban,
+                Some(duration),
+            )
+            .await
+            .context("Failed to store ban")?;
+
+        self.audit
+            .record(
+                Some(actor),
+                Some(cid),
+                AuditEvent::ClientBanned {
+                    reason: ban.reason.clone(),
+                    expires_at: ban.expires_at,
+                },
+            )
+            .await;
+
+        self.disconnect_client(
+            cid,
+            DisconnectReason::Kicked {
+                reason: ban.reason.clone(),
+            },
+        )
+        .await;
+
+        Ok(ban)
+    }
+
+    /// Lifts the ban of a client on behalf of a supervisor.
+    #[instrument(level = "info", skip(self), err)]
+    pub async fn unban_client(&self, actor: &ClientId, cid: &ClientId) -> anyhow::Result<()> {
+        self.store
+            .remove(format!("moderation.ban.{cid}").as_str())
+            .await
+            .context("Failed to remove ban")?;
+
+        self.audit
+            .record(Some(actor), Some(cid), AuditEvent::ClientUnbanned)
+            .await;
+        Ok(())
+    }
+
+    /// Returns the active ban of a client, if any. Bans are removed by the store once expired.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn get_ban(&self, cid: &ClientId) -> anyhow::Result<Option<Ban>> {
         self.store
-            .remove(format!("api.token.{token}").as_str())
+            .get(format!("moderation.ban.{cid}").as_str())
             .await
-            .context("Failed to revoke API token")
+            .context("Failed to get ban")
+    }
+
+    /// Mutes a client for the given duration on behalf of a supervisor, preventing it from
+    /// placing calls and sending text messages.
+    #[instrument(level = "info", skip(self), err)]
+    pub async fn mute_client(
+        &self,
+        actor: &ClientId,
+        cid: &ClientId,
+        reason: String,
+        duration: Duration,
+    ) -> anyhow::Result<Mute> {
+        let issued_at = unix_now();
+        let mute = Mute {
+            cid: cid.clone(),
+            reason,
+            issued_by: actor.clone(),
+            issued_at,
+            expires_at: issued_at.saturating_add(duration.as_secs()),
+        };
+
+        self.store
+            .set(
+                format!("moderation.mute.{cid}").as_str(),
+                &mute,
+                Some(duration),
+            )
+            .await
+            .context("Failed to store mute")?;
+
+        self.audit
+            .record(
+                Some(actor),
+                Some(cid),
+                AuditEvent::ClientMuted {
+                    reason: mute.reason.clone(),
+                    expires_at: mute.expires_at,
+                },
+            )
+            .await;
+        Ok(mute)
+    }
+
+    /// Lifts the mute of a client on behalf of a supervisor.
+    #[instrument(level = "info", skip(self), err)]
+    pub async fn unmute_client(&self, actor: &ClientId, cid: &ClientId) -> anyhow::Result<()> {
+        self.store
+            .remove(format!("moderation.mute.{cid}").as_str())
+            .await
+            .context("Failed to remove mute")?;
+
+        self.audit
+            .record(Some(actor), Some(cid), AuditEvent::ClientUnmuted)
+            .await;
+        Ok(())
+    }
+
+    /// Returns the active mute of a client, if any. Mutes are removed by the store once expired.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn get_mute(&self, cid: &ClientId) -> anyhow::Result<Option<Mute>> {
+        self.store
+            .get(format!("moderation.mute.{cid}").as_str())
+            .await
+            .context("Failed to get mute")
+    }
+
+    /// Returns the roles of a user, combining the roles assigned in the config and the store.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn user_roles(&self, cid: &ClientId) -> anyhow::Result<Roles> {
+        let mut roles: Roles = self
+            .config
+            .auth
+            .roles
+            .get(cid)
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect();
+
+        let stored: Option<Vec<Role>> = self
+            .store
+            .get(format!("auth.roles.{cid}").as_str())
+            .await
+            .context("Failed to get user roles")?;
+        roles.extend(stored.into_iter().flatten());
+
+        Ok(roles)
+    }
+
+    /// Replaces the roles assigned to a user in the store. Roles assigned in the config are
+    /// not affected.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn set_user_roles(&self, cid: &ClientId, roles: Vec<Role>) -> anyhow::Result<()> {
+        tracing::debug!("Updating user roles");
+        let key = format!("auth.roles.{cid}");
+        if roles.is_empty() {
+            self.store.remove(key.as_str()).await
+        } else {
+            self.store.set(key.as_str(), roles, None).await
+        }
+        .context("Failed to store user roles")
     }
 
     #[instrument(level = "debug", skip(self), err)]
@@ -311,6 +1113,262 @@ impl AppState {
         )
     }
 
+    /// Joins the cluster, publishing local roster changes to and handling messages of the other
+    /// instances until the app shuts down.
+    #[instrument(level = "debug", skip(state), err)]
+    pub async fn start_cluster_task(state: Arc<AppState>) -> anyhow::Result<JoinHandle<()>> {
+        let cluster = state
+            .cluster
+            .clone()
+            .context("App is not running as cluster instance")?;
+
+        let mut inbound_rx = cluster.subscribe().await?;
+        let (cluster_tx, mut outbound_rx) = mpsc::unbounded_channel();
+        let mut heartbeat = time::interval(state.config.cluster.heartbeat_interval);
+        heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let instance_timeout = state.config.cluster.instance_timeout;
+        state.clients.connect_cluster(cluster_tx);
+        cluster
+            .publish(None, ClusterMessage::SyncRequest)
+            .await
+            .context("Failed to request cluster sync")?;
+        tracing::info!(instance_id = cluster.instance_id(), "Joined cluster");
+
+        Ok(tokio::spawn(
+            async move {
+                let mut shutdown = state.shutdown_rx.clone();
+                let mut instances = ClusterInstances::default();
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = shutdown.changed() => {
+                            tracing::info!("Leaving cluster");
+                            if let Err(err) = cluster.publish(None, ClusterMessage::Leave).await {
+                                tracing::warn!(?err, "Failed to announce leaving the cluster");
+                            }
+                            break;
+                        }
+                        _ = heartbeat.tick() => {
+                            if let Err(err) = cluster.publish(None, ClusterMessage::Heartbeat).await {
+                                tracing::warn!(?err, "Failed to publish cluster heartbeat");
+                            }
+                            state.refresh_ownership().await;
+                            for instance_id in instances.take_stale(instance_timeout) {
+                                tracing::warn!(?instance_id, "Cluster instance timed out");
+                                state.remove_remote_instance(&instance_id).await;
+                            }
+                        }
+                        Some(message) = outbound_rx.recv() => {
+                            if let Err(err) = cluster.publish(None, message).await {
+                                tracing::warn!(?err, "Failed to publish cluster message");
+                            }
+                        }
+                        Some(envelope) = inbound_rx.recv() => {
+                            match envelope.message {
+                                ClusterMessage::Leave => instances.remove(&envelope.origin),
+                                // an unknown instance sending heartbeats has been removed after
+                                // timing out, e.g. during a network partition, so its clients
+                                // have to be requested again
+                                ClusterMessage::Heartbeat => {
+                                    if instances.seen(&envelope.origin)
+                                        && let Err(err) = cluster
+                                            .publish(Some(&envelope.origin), ClusterMessage::SyncRequest)
+                                            .await
+                                    {
+                                        tracing::warn!(?err, "Failed to request cluster sync");
+                                    }
+                                }
+                                _ => {
+                                    instances.seen(&envelope.origin);
+                                }
+                            }
+                            state.handle_cluster_message(&cluster, envelope).await;
+                        }
+                        else => break,
+                    }
+                }
+            }
+            .in_current_span(),
+        ))
+    }
+
+    #[instrument(level = "trace", skip(self, cluster))]
+    async fn handle_cluster_message(
+        self: &Arc<Self>,
+        cluster: &Arc<Cluster>,
+        envelope: ClusterEnvelope,
+    ) {
+        let ClusterEnvelope { origin, message } = envelope;
+        match message {
+            ClusterMessage::Announcement { announcement } => {
+                self.clients.announce_locally(announcement);
+            }
+            ClusterMessage::RosterChange { change } => {
+                let disconnected = match &change {
+                    ClientChange::Disconnected { client_id } => Some(client_id.clone()),
+                    _ => None,
+                };
+                if self
+                    .clients
+                    .apply_remote_roster_change(&origin, change)
+                    .await
+                    && let Some(client_id) = disconnected
+                {
+                    self.calls.cleanup_client_calls(self, &client_id).await;
+                }
+            }
+            ClusterMessage::Direct { client_id, message } => {
+                match self.get_client(&client_id).await {
+                    Some(client) => {
+                        if let Err(err) = client.send_message(message).await {
+                            tracing::warn!(
+                                ?err,
+                                ?client_id,
+                                "Failed to send forwarded message to client"
+                            );
+                        }
+                    }
+                    None => {
+                        tracing::debug!(?client_id, "Client of forwarded message not connected")
+                    }
+                }
+            }
+            ClusterMessage::CallMessage { client_id, message } => {
+                self.handle_remote_call_message(cluster, origin, client_id, message)
+                    .await;
+            }
+            ClusterMessage::Disconnect { client_id, reason } => {
+                if self.clients.is_client_connected(&client_id).await {
+                    self.unregister_client(&client_id, Some(reason)).await;
+                } else {
+                    tracing::debug!(?client_id, "Client to disconnect not connected");
+                }
+            }
+            ClusterMessage::ResumeRequest {
+                client_id,
+                resume_token,
+            } => {
+                // Taking over a session may wait for its connection to be closed, so the
+                // request is handled without blocking other cluster messages.
+                let state = self.clone();
+                let cluster = cluster.clone();
+                tokio::spawn(
+                    async move {
+                        let pending = state
+                            .hand_over_client(&origin, &client_id, &resume_token)
+                            .await;
+                        let message = ClusterMessage::ResumeResponse { client_id, pending };
+                        if let Err(err) = cluster.publish(Some(&origin), message).await {
+                            tracing::warn!(?err, "Failed to answer session request");
+                        }
+                    }
+                    .in_current_span(),
+                );
+            }
+            ClusterMessage::ResumeResponse { client_id, pending } => {
+                let resume_tx = self.remote_resumes.lock().remove(&client_id);
+                match resume_tx {
+                    Some(tx) => {
+                        let _ = tx.send(pending);
+                    }
+                    None => tracing::debug!(?client_id, "Session request no longer pending"),
+                }
+            }
+            ClusterMessage::SyncRequest => {
+                for client in self.clients.local_clients().await {
+                    let message = ClusterMessage::RosterChange {
+                        change: ClientChange::Connected { client },
+                    };
+                    if let Err(err) = cluster.publish(Some(&origin), message).await {
+                        tracing::warn!(?err, "Failed to answer cluster sync request");
+                        break;
+                    }
+                }
+            }
+            ClusterMessage::Heartbeat => {}
+            ClusterMessage::Leave => {
+                self.remove_remote_instance(&origin).await;
+            }
+        }
+    }
+
+    /// Removes all clients of another instance of the cluster which left or timed out, ending
+    /// their calls.
+    async fn remove_remote_instance(&self, instance_id: &str) {
+        for client_id in self.clients.remove_remote_instance(instance_id) {
+            self.calls.cleanup_client_calls(self, &client_id).await;
+        }
+    }
+
+    /// Extends the expiry of the ownership records of all clients connected to and calls hosted
+    /// by this instance. Records of an instance which stopped sending heartbeats expire,
+    /// allowing its clients to log in to another instance.
+    async fn refresh_ownership(&self) {
+        for client in self.clients.local_clients().await {
+            if let Err(err) = self
+                .store
+                .expire(
+                    format!("cluster.client.{}", client.id).as_str(),
+                    self.config.cluster.instance_timeout,
+                )
+                .await
+            {
+                tracing::warn!(?err, client_id = ?client.id, "Failed to refresh client ownership");
+            }
+        }
+        for call_id in self.calls.call_ids() {
+            if let Err(err) = self
+                .store
+                .expire(
+                    format!("cluster.call.{call_id}").as_str(),
+                    self.config.cluster.instance_timeout,
+                )
+                .await
+            {
+                tracing::warn!(?err, ?call_id, "Failed to refresh call ownership");
+            }
+        }
+    }
+
+    /// Handles a message of a client connected to another instance concerning a call hosted by
+    /// this instance. The client is represented by a remote session forwarding all replies to
+    /// the instance it is connected to.
+    async fn handle_remote_call_message(
+        &self,
+        cluster: &Arc<Cluster>,
+        origin: String,
+        client_id: ClientId,
+        message: ClientMessage,
+    ) {
+        let Some(client_info) = self.clients.remote_client(&client_id) else {
+            tracing::debug!(
+                ?client_id,
+                "Remote client of call message not known, ignoring"
+            );
+            return;
+        };
+
+        let (tx, mut rx) = mpsc::channel(config::CLIENT_CHANNEL_CAPACITY);
+        let cluster = cluster.clone();
+        tokio::spawn(
+            async move {
+                while let Some(message) = rx.recv().await {
+                    let message = ClusterMessage::Direct {
+                        client_id: client_id.clone(),
+                        message,
+                    };
+                    if let Err(err) = cluster.publish(Some(&origin), message).await {
+                        tracing::warn!(?err, "Failed to forward message to remote client");
+                    }
+                }
+            }
+            .in_current_span(),
+        );
+
+        let client = ClientSession::remote(client_info, tx);
+        handle_call_message(self, &client, message).await;
+    }
+
     pub async fn force_update_controllers(&self) -> anyhow::Result<()> {
         self.update_vatsim_controllers(
             &mut HashSet::new(),
@@ -364,6 +1422,66 @@ impl AppState {
         Ok(())
     }
 
+    /// Whether the server is draining in preparation for a shutdown and refuses new logins.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
+
+    /// Drains the server before shutting down.
+    ///
+    /// New logins are refused, active calls are given up to the configured drain timeout to end
+    /// and all remaining clients are disconnected with [`DisconnectReason::ServerRestart`], asking
+    /// them to reconnect once the server is back.
+    #[instrument(level = "info", skip(self))]
+    pub async fn drain(&self) {
+        if self.draining.swap(true, Ordering::Relaxed) {
+            tracing::debug!("Server is already draining");
+            return;
+        }
+
+        let drain_timeout = Duration::from_secs(self.config.server.drain_timeout_secs);
+        tracing::info!(
+            ?drain_timeout,
+            active_calls = self.calls.active_calls_count(),
+            "Draining server, refusing new logins"
+        );
+
+        let calls_ended = time::timeout(drain_timeout, async {
+            while self.calls.active_calls_count() > 0 {
+                time::sleep(config::DRAIN_POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .is_ok();
+        if !calls_ended {
+            tracing::warn!(
+                active_calls = self.calls.active_calls_count(),
+                "Drain timeout elapsed, disconnecting clients with active calls"
+            );
+        }
+
+        self.clients
+            .disconnect_all(DisconnectReason::ServerRestart {
+                retry_after: self.config.server.drain_retry_after_secs,
+            })
+            .await;
+
+        // Give the clients' writer tasks a chance to deliver the disconnect before the app
+        // shutdown signal stops them.
+        if time::timeout(config::DRAIN_DISCONNECT_TIMEOUT, async {
+            while !self.clients.is_empty().await {
+                time::sleep(config::DRAIN_POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .is_err()
+        {
+            tracing::warn!("Not all clients disconnected before drain completed");
+        }
+
+        tracing::info!("Server drained");
+    }
+
     pub async fn health_check(&self) -> anyhow::Result<()> {
         self.store.is_healthy().await
     }
@@ -380,6 +1498,7 @@ impl AppState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audit::memory::MemoryAuditSink;
     use crate::ice::provider::stun::StunOnlyProvider;
     use crate::release::UpdateChecker;
     use crate::store::Store;
@@ -401,14 +1520,22 @@ mod tests {
             shutdown_rx,
             Arc::new(StunOnlyProvider::default()),
             None,
+            Arc::new(MemoryAuditSink::default()),
         ))
     }
 
     #[tokio::test]
     async fn verify_api_token_returns_cid() {
         let state = test_state();
-        let token = state.generate_api_token("123456").await.unwrap();
-        let cid = state.verify_api_token(&token).await.unwrap();
+        let (token, _) = state
+            .generate_api_token("123456", "Test", None)
+            .await
+            .unwrap();
+        let cid = state
+            .verify_api_token(&token)
+            .await
+            .unwrap()
+            .map(|grant| grant.cid);
         assert_eq!(cid, Some(ClientId::from("123456")));
     }
 
@@ -433,7 +1560,10 @@ mod tests {
     #[tokio::test]
     async fn revoke_api_token_removes_it() {
         let state = test_state();
-        let token = state.generate_api_token("123456").await.unwrap();
+        let (token, _) = state
+            .generate_api_token("123456", "Test", None)
+            .await
+            .unwrap();
 
         state.revoke_api_token(&token).await.unwrap();
 
@@ -450,21 +1580,126 @@ mod tests {
     #[tokio::test]
     async fn multiple_tokens_for_same_cid() {
         let state = test_state();
-        let token1 = state.generate_api_token("123456").await.unwrap();
-        let token2 = state.generate_api_token("123456").await.unwrap();
+        let (token1, _) = state
+            .generate_api_token("123456", "Test", None)
+            .await
+            .unwrap();
+        let (token2, _) = state
+            .generate_api_token("123456", "Test", None)
+            .await
+            .unwrap();
 
         assert_ne!(token1, token2);
 
-        let cid1 = state.verify_api_token(&token1).await.unwrap();
-        let cid2 = state.verify_api_token(&token2).await.unwrap();
+        let cid1 = state
+            .verify_api_token(&token1)
+            .await
+            .unwrap()
+            .map(|grant| grant.cid);
+        let cid2 = state
+            .verify_api_token(&token2)
+            .await
+            .unwrap()
+            .map(|grant| grant.cid);
         assert_eq!(cid1, Some(ClientId::from("123456")));
         assert_eq!(cid2, Some(ClientId::from("123456")));
 
         state.revoke_api_token(&token1).await.unwrap();
         assert_eq!(state.verify_api_token(&token1).await.unwrap(), None);
         assert_eq!(
-            state.verify_api_token(&token2).await.unwrap(),
+            state
+                .verify_api_token(&token2)
+                .await
+                .unwrap()
+                .map(|grant| grant.cid),
             Some(ClientId::from("123456"))
         );
     }
+
+    #[tokio::test]
+    async fn list_api_tokens_returns_named_tokens() {
+        let state = test_state();
+        let (_, laptop) = state
+            .generate_api_token("123456", "Laptop", None)
+            .await
+            .unwrap();
+        let (_, desktop) = state
+            .generate_api_token("123456", "Desktop", None)
+            .await
+            .unwrap();
+        state
+            .generate_api_token("654321", "Other", None)
+            .await
+            .unwrap();
+
+        let tokens = state
+            .list_api_tokens(&ClientId::from("123456"))
+            .await
+            .unwrap();
+        let mut names: Vec<_> = tokens.iter().map(|t| t.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["Desktop", "Laptop"]);
+        assert!(tokens.contains(&laptop));
+        assert!(tokens.contains(&desktop));
+    }
+
+    #[tokio::test]
+    async fn verify_api_token_updates_last_used() {
+        let state = test_state();
+        let (token, info) = state
+            .generate_api_token("123456", "Laptop", None)
+            .await
+            .unwrap();
+        assert_eq!(info.last_used_at, None);
+
+        state.verify_api_token(&token).await.unwrap();
+
+        let tokens = state
+            .list_api_tokens(&ClientId::from("123456"))
+            .await
+            .unwrap();
+        assert!(tokens[0].last_used_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn revoke_api_token_by_id() {
+        let state = test_state();
+        let cid = ClientId::from("123456");
+        let (token, info) = state
+            .generate_api_token("123456", "Laptop", None)
+            .await
+            .unwrap();
+
+        assert!(
+            !state
+                .revoke_api_token_by_id(&ClientId::from("654321"), &info.id)
+                .await
+                .unwrap()
+        );
+        assert!(state.revoke_api_token_by_id(&cid, &info.id).await.unwrap());
+        assert!(!state.revoke_api_token_by_id(&cid, &info.id).await.unwrap());
+
+        assert_eq!(state.verify_api_token(&token).await.unwrap(), None);
+        assert!(state.list_api_tokens(&cid).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn legacy_api_token_remains_valid() {
+        let state = test_state();
+        let token = MemoryStore::test_api_token(0);
+
+        let cid = state
+            .verify_api_token(&token)
+            .await
+            .unwrap()
+            .map(|grant| grant.cid);
+        assert_eq!(cid, Some(ClientId::from("cid0")));
+        assert!(
+            state
+                .list_api_tokens(&ClientId::from("cid0"))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -58,6 +58,10 @@ impl CallManager {
         }
     }
 
+    pub fn active_calls_count(&self) -> usize {
+        self.active_calls.read().len()
+    }
+
     pub fn has_outgoing_call(&self, client_id: &ClientId) -> bool {
         self.client_outgoing_calls.read().contains_key(client_id)
     }
@@ -69,6 +73,19 @@ impl CallManager {
             .is_some_and(|active| active.involves(client_id))
     }
 
+    /// Whether a ringing or active call with the given ID is hosted by this instance.
+    pub fn has_call(&self, call_id: &CallId) -> bool {
+        self.ringing_calls.read().contains_key(call_id)
+            || self.active_calls.read().contains_key(call_id)
+    }
+
+    /// IDs of all ringing and active calls hosted by this instance.
+    pub fn call_ids(&self) -> Vec<CallId> {
+        let mut call_ids: Vec<CallId> = self.ringing_calls.read().keys().copied().collect();
+        call_ids.extend(self.active_calls.read().keys().copied());
+        call_ids
+    }
+
     pub fn ringing_call(&self, call_id: &CallId) -> Option<RingingCall> {
         self.ringing_calls.read().get(call_id).map(Into::into)
     }
@@ -350,6 +367,7 @@ impl CallManager {
             self.client_outgoing_calls
                 .write()
                 .remove(&ringing.caller_id);
+            state.release_call(&ringing.call_id).await;
 
             if ringing.caller_id == *client_id {
                 let cancelled =
@@ -381,6 +399,11 @@ impl CallManager {
             }
         }
 
+        if let Some(active) = &cleaned_active_call {
+            state.sfu.close_session(&active.call_id).await;
+            state.release_call(&active.call_id).await;
+        }
+
         if let Some(active) = cleaned_active_call
             && let Some(peer_id) = active.peer(client_id)
         {
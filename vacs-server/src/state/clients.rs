@@ -1,7 +1,11 @@
+pub mod detached;
 pub mod manager;
+pub mod roster;
 pub mod session;
 
+pub use detached::*;
 pub use manager::*;
+pub use roster::*;
 pub use session::*;
 
 use thiserror::Error;
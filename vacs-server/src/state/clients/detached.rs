@@ -0,0 +1,194 @@
+use crate::state::clients::session::ClientSession;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::sync::{Notify, mpsc, oneshot};
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::server::ServerMessage;
+
+/// A client session handed over to a resuming connection, including all messages queued while
+/// the session was detached. Newly registered sessions have no pending messages.
+#[derive(Debug)]
+pub struct ResumedClient {
+    pub client: ClientSession,
+    pub rx: mpsc::Receiver<ServerMessage>,
+    pub pending: Vec<ServerMessage>,
+}
+
+#[derive(Debug)]
+pub struct DetachedClient {
+    resume_token: String,
+    resume_tx: oneshot::Sender<oneshot::Sender<ResumedClient>>,
+}
+
+impl DetachedClient {
+    #[inline]
+    pub fn resume_token(&self) -> &str {
+        &self.resume_token
+    }
+
+    /// Requests the task holding the detached session to hand it over. Returns `None` if the
+    /// task already finished, e.g. because the grace period expired.
+    pub async fn resume(self) -> Option<ResumedClient> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.resume_tx.send(reply_tx).ok()?;
+        reply_rx.await.ok()
+    }
+}
+
+/// Client sessions whose connection was lost, kept until they are either resumed by a
+/// reconnecting client or their grace period expires.
+///
+/// While detached, the session stays registered with the
+/// [`ClientManager`](crate::state::clients::ClientManager), so other clients are not notified
+/// about the disconnect and ongoing calls are kept.
+#[derive(Debug)]
+pub struct DetachedClients {
+    grace_period: Duration,
+    clients: Mutex<HashMap<ClientId, DetachedClient>>,
+    detached: Notify,
+}
+
+impl DetachedClients {
+    /// Maximum number of messages queued for a detached client. Older messages are dropped once
+    /// exceeded.
+    pub const MAX_PENDING_MESSAGES: usize = crate::config::CLIENT_CHANNEL_CAPACITY;
+
+    /// Queues a message for a detached client, dropping the oldest message if the queue is full.
+    /// Returns whether a message was dropped.
+    pub fn queue_pending(pending: &mut VecDeque<ServerMessage>, msg: ServerMessage) -> bool {
+        let full = pending.len() >= Self::MAX_PENDING_MESSAGES;
+        if full {
+            tracing::warn!(
+                "Pending message queue of detached client full, dropping oldest message"
+            );
+            pending.pop_front();
+        }
+        pending.push_back(msg);
+        full
+    }
+
+    pub fn new(grace_period: Duration) -> Self {
+        Self {
+            grace_period,
+            clients: Mutex::new(HashMap::new()),
+            detached: Notify::new(),
+        }
+    }
+
+    #[inline]
+    pub fn grace_period(&self) -> Duration {
+        self.grace_period
+    }
+
+    /// Registers a detached session, returning the receiver used by the holding task to await a
+    /// hand-over request.
+    pub fn insert(
+        &self,
+        client_id: ClientId,
+        resume_token: String,
+    ) -> oneshot::Receiver<oneshot::Sender<ResumedClient>> {
+        let (resume_tx, resume_rx) = oneshot::channel();
+        self.clients.lock().insert(
+            client_id,
+            DetachedClient {
+                resume_token,
+                resume_tx,
+            },
+        );
+        self.detached.notify_waiters();
+        resume_rx
+    }
+
+    pub fn take(&self, client_id: &ClientId) -> Option<DetachedClient> {
+        self.clients.lock().remove(client_id)
+    }
+
+    /// Removes the detached session if it is still registered with the given resume token.
+    /// Returns `false` if the session was already taken by a resuming connection.
+    pub fn remove(&self, client_id: &ClientId, resume_token: &str) -> bool {
+        let mut clients = self.clients.lock();
+        if clients
+            .get(client_id)
+            .is_some_and(|c| c.resume_token == resume_token)
+        {
+            clients.remove(client_id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Waits for the session of the given client to be detached, taking it once available.
+    pub async fn take_when_detached(
+        &self,
+        client_id: &ClientId,
+        timeout: Duration,
+    ) -> Option<DetachedClient> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let notified = self.detached.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                if let Some(client) = self.take(client_id) {
+                    return client;
+                }
+                notified.await;
+            }
+        })
+        .await
+        .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use test_log::test;
+
+    #[test]
+    fn remove_requires_matching_token() {
+        let detached = DetachedClients::new(Duration::from_secs(30));
+        let client_id = ClientId::from("client1");
+        let _resume_rx = detached.insert(client_id.clone(), "token1".to_string());
+
+        assert!(!detached.remove(&client_id, "token2"));
+        assert!(detached.remove(&client_id, "token1"));
+        assert!(detached.take(&client_id).is_none());
+    }
+
+    #[test(tokio::test)]
+    async fn take_when_detached() {
+        let detached = std::sync::Arc::new(DetachedClients::new(Duration::from_secs(30)));
+        let client_id = ClientId::from("client1");
+
+        let waiter = tokio::spawn({
+            let detached = detached.clone();
+            let client_id = client_id.clone();
+            async move {
+                detached
+                    .take_when_detached(&client_id, Duration::from_secs(1))
+                    .await
+                    .map(|c| c.resume_token().to_string())
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let _resume_rx = detached.insert(client_id, "token1".to_string());
+
+        assert_eq!(waiter.await.unwrap(), Some("token1".to_string()));
+    }
+
+    #[test(tokio::test)]
+    async fn take_when_detached_timeout() {
+        let detached = DetachedClients::new(Duration::from_secs(30));
+        assert!(
+            detached
+                .take_when_detached(&ClientId::from("client1"), Duration::from_millis(10))
+                .await
+                .is_none()
+        );
+    }
+}
@@ -1,18 +1,23 @@
+use crate::cluster::ClusterMessage;
 use crate::metrics::guards::ClientConnectionGuard;
 use crate::metrics::{CoverageMetrics, NetworkDatasetMetrics};
+use crate::state::clients::roster::Roster;
 use crate::state::clients::session::ClientSession;
 use crate::state::clients::{ClientManagerError, Result};
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
 use tokio::sync::broadcast::error::SendError;
 use tokio::sync::{RwLock, broadcast, mpsc};
 use tracing::instrument;
 use vacs_protocol::profile::{ActiveProfile, ProfileId};
 use vacs_protocol::vatsim::{ClientId, PositionId, StationChange, StationId};
+use vacs_protocol::ws::Encoding;
 use vacs_protocol::ws::server;
 use vacs_protocol::ws::server::{
-    ClientInfo, DisconnectReason, ServerMessage, SessionProfile, StationInfo,
+    ClientChange, ClientInfo, DisconnectReason, ServerMessage, SessionProfile, StationInfo,
 };
+use vacs_protocol::ws::shared::{Capabilities, Presence};
 use vacs_vatsim::coverage::network::{Network, RelevantStations};
 use vacs_vatsim::coverage::position::Position;
 use vacs_vatsim::coverage::profile::Profile;
@@ -29,6 +34,10 @@ use vacs_vatsim::{ControllerInfo, FacilityType};
 /// Read-only methods that only need a subset may skip unused locks but
 /// must never invert this order. Note that this strict order does not
 /// apply if a lock is dropped immediately again.
+///
+/// The `roster` lock is only held while recording and broadcasting a single
+/// roster change and must not be held while acquiring any other lock. The
+/// `remote_clients` lock must not be held while acquiring any other lock.
 #[derive(Debug)]
 pub struct ClientManager {
     broadcast_tx: broadcast::Sender<ServerMessage>,
@@ -37,6 +46,14 @@ pub struct ClientManager {
     online_positions: RwLock<HashMap<PositionId, HashSet<ClientId>>>,
     vatsim_only_positions: RwLock<HashMap<PositionId, HashSet<ClientId>>>,
     online_stations: RwLock<HashMap<StationId, PositionId>>,
+    roster: parking_lot::Mutex<Roster>,
+    /// Announcements sent to clients connecting before their expiry.
+    announcements: parking_lot::Mutex<Vec<server::Announcement>>,
+    /// Clients connected to other instances of the cluster, along with the ID of their instance.
+    remote_clients: parking_lot::RwLock<HashMap<ClientId, (String, ClientInfo)>>,
+    /// Publishes roster changes and announcements to the other instances of the cluster, if
+    /// clustering is enabled.
+    cluster_tx: OnceLock<mpsc::UnboundedSender<ClusterMessage>>,
 }
 
 /// Intermediate results from syncing vacs client positions against the VATSIM datafeed.
@@ -46,7 +63,7 @@ struct ClientPositionSync {
     /// Session info messages to send to clients whose position changed.
     session_info_updates: Vec<(ClientSession, server::SessionInfo)>,
     /// Client info updates to broadcast to all clients.
-    client_info_updates: Vec<ServerMessage>,
+    client_info_updates: Vec<ClientInfo>,
     /// Clients that joined an already-online position and need self-handoff events.
     self_handoff_clients: Vec<(ClientSession, PositionId)>,
     /// Clients that left a still-online position and need departure handoff events.
@@ -64,6 +81,18 @@ impl ClientManager {
             online_positions: RwLock::new(HashMap::new()),
             vatsim_only_positions: RwLock::new(HashMap::new()),
             online_stations: RwLock::new(HashMap::new()),
+            roster: parking_lot::Mutex::new(Roster::new()),
+            announcements: parking_lot::Mutex::new(Vec::new()),
+            remote_clients: parking_lot::RwLock::new(HashMap::new()),
+            cluster_tx: OnceLock::new(),
+        }
+    }
+
+    /// Publishes all further roster changes and announcements to the other instances of the
+    /// cluster via the given channel.
+    pub fn connect_cluster(&self, cluster_tx: mpsc::UnboundedSender<ClusterMessage>) {
+        if self.cluster_tx.set(cluster_tx).is_err() {
+            tracing::warn!("Client manager is already connected to a cluster");
         }
     }
 
@@ -89,13 +118,24 @@ impl ClientManager {
         position_id.and_then(|position_id| self.network.read().get_position(position_id).cloned())
     }
 
+    /// Returns all clients on the given position, including clients connected to other
+    /// instances of the cluster.
     pub async fn clients_for_position(&self, position_id: &PositionId) -> HashSet<ClientId> {
-        self.online_positions
+        let mut clients = self
+            .online_positions
             .read()
             .await
             .get(position_id)
             .cloned()
-            .unwrap_or_default()
+            .unwrap_or_default();
+        clients.extend(
+            self.remote_clients
+                .read()
+                .iter()
+                .filter(|(_, (_, client))| client.position_id.as_ref() == Some(position_id))
+                .map(|(client_id, _)| client_id.clone()),
+        );
+        clients
     }
 
     pub async fn clients_for_station(&self, station_id: &StationId) -> HashSet<ClientId> {
@@ -111,6 +151,40 @@ impl ClientManager {
         client_info: ClientInfo,
         active_profile: ActiveProfile<ProfileId>,
         client_connection_guard: ClientConnectionGuard,
+    ) -> Result<(ClientSession, mpsc::Receiver<ServerMessage>)> {
+        self.insert_client(client_info, active_profile, None, client_connection_guard)
+            .await
+    }
+
+    /// Adds a client whose session was handed over by another instance of the cluster, keeping
+    /// its resume token so the client recognizes the session as resumed.
+    #[instrument(
+        level = "debug",
+        skip(self, resume_token, client_connection_guard),
+        err
+    )]
+    pub async fn take_over_client(
+        &self,
+        client_info: ClientInfo,
+        active_profile: ActiveProfile<ProfileId>,
+        resume_token: String,
+        client_connection_guard: ClientConnectionGuard,
+    ) -> Result<(ClientSession, mpsc::Receiver<ServerMessage>)> {
+        self.insert_client(
+            client_info,
+            active_profile,
+            Some(resume_token),
+            client_connection_guard,
+        )
+        .await
+    }
+
+    async fn insert_client(
+        &self,
+        client_info: ClientInfo,
+        active_profile: ActiveProfile<ProfileId>,
+        resume_token: Option<String>,
+        client_connection_guard: ClientConnectionGuard,
     ) -> Result<(ClientSession, mpsc::Receiver<ServerMessage>)> {
         tracing::trace!("Adding client");
 
@@ -124,12 +198,15 @@ impl ClientManager {
 
         let (tx, rx) = mpsc::channel(crate::config::CLIENT_CHANNEL_CAPACITY);
 
-        let client = ClientSession::new(
+        let mut client = ClientSession::new(
             client_info.clone(),
             active_profile,
             tx,
             client_connection_guard,
         );
+        if let Some(resume_token) = resume_token {
+            client = client.with_resume_token(resume_token);
+        }
         clients.insert(client_info.id.clone(), client.clone());
         drop(clients);
 
@@ -209,10 +286,28 @@ impl ClientManager {
             Vec::new()
         };
 
-        if let Err(err) = self.broadcast(server::ClientConnected {
-            client: client_info,
-        }) {
-            tracing::warn!(?err, "Failed to broadcast client connected message");
+        // A client previously connected to another instance is already known to all clients,
+        // e.g. when its session was handed over after reconnecting to this instance. Other
+        // instances still have to learn about its new instance.
+        let previous = self
+            .remote_clients
+            .write()
+            .remove(&client_info.id)
+            .map(|(_, previous)| previous);
+        match previous {
+            Some(previous) if previous == client_info => {
+                self.publish_to_cluster(ClusterMessage::RosterChange {
+                    change: ClientChange::Updated {
+                        client: client_info,
+                    },
+                });
+            }
+            Some(_) => self.broadcast_roster_change(ClientChange::Updated {
+                client: client_info,
+            }),
+            None => self.broadcast_roster_change(ClientChange::Connected {
+                client: client_info,
+            }),
         }
 
         self.broadcast_station_changes(&changes).await;
@@ -235,7 +330,41 @@ impl ClientManager {
             return;
         };
 
-        let changes = if let Some(position_id) = client.position_id() {
+        let changes = self.remove_client_position(&client).await;
+        client.disconnect(disconnect_reason);
+
+        self.broadcast_roster_change(ClientChange::Disconnected { client_id });
+
+        self.broadcast_removal_changes(&changes).await;
+        tracing::debug!("Client removed");
+    }
+
+    /// Hands a client over to another instance of the cluster after its session was resumed
+    /// there. Unlike [`ClientManager::remove_client`], no disconnect is broadcast, as the client
+    /// stays connected to the cluster.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn release_client(&self, client_id: &ClientId, instance_id: &str) {
+        tracing::trace!("Releasing client");
+
+        let Some(client) = self.clients.write().await.remove(client_id) else {
+            tracing::debug!("Client not found in client list, skipping release");
+            return;
+        };
+
+        let changes = self.remove_client_position(&client).await;
+        self.remote_clients.write().insert(
+            client_id.clone(),
+            (instance_id.to_string(), client.client_info().clone()),
+        );
+
+        self.broadcast_removal_changes(&changes).await;
+        tracing::debug!("Client released");
+    }
+
+    /// Removes a client no longer connected to this instance from its position, returning the
+    /// resulting station changes visible to clients.
+    async fn remove_client_position(&self, client: &ClientSession) -> Vec<StationChange> {
+        if let Some(position_id) = client.position_id() {
             let mut online_positions = self.online_positions.write().await;
 
             if online_positions.contains_key(position_id) {
@@ -280,7 +409,7 @@ impl ClientManager {
                     online_positions
                         .get_mut(position_id)
                         .unwrap()
-                        .remove(&client_id);
+                        .remove(client.id());
                 }
 
                 changes
@@ -296,13 +425,12 @@ impl ClientManager {
                 "Client has no position, skipping online positions list removal and station changes broadcast"
             );
             Vec::new()
-        };
-        client.disconnect(disconnect_reason);
-
-        if let Err(err) = self.broadcast(server::ClientDisconnected { client_id }) {
-            tracing::warn!(?err, "Failed to broadcast client disconnected message");
         }
+    }
 
+    /// Broadcasts the station changes caused by removing a client, clearing all coverage state
+    /// once the last client is gone.
+    async fn broadcast_removal_changes(&self, changes: &[StationChange]) {
         if self.clients.read().await.is_empty() {
             tracing::debug!(
                 "Last client disconnected, clearing VATSIM-only positions and online stations"
@@ -312,24 +440,68 @@ impl ClientManager {
             return;
         }
 
-        self.broadcast_station_changes(&changes).await;
+        self.broadcast_station_changes(changes).await;
         self.emit_coverage_gauges().await;
-
-        tracing::debug!("Client removed");
     }
 
-    pub async fn list_clients(&self, self_client_id: Option<&ClientId>) -> Vec<ClientInfo> {
+    /// Lists all connected clients except the given one, along with the roster revision the list
+    /// is up to date with.
+    pub async fn list_clients(&self, self_client_id: Option<&ClientId>) -> server::ClientList {
+        // The revision is read before the clients, so changes happening in between are included
+        // again when requesting changes since the returned revision.
+        let revision = self.roster_revision();
+
         let mut clients: Vec<ClientInfo> = self
             .clients
             .read()
             .await
             .values()
-            .filter(|c| self_client_id.map(|s| s != c.id()).unwrap_or(true))
             .map(|c| c.client_info().clone())
+            .chain(
+                self.remote_clients
+                    .read()
+                    .values()
+                    .map(|(_, client)| client.clone()),
+            )
+            .filter(|c| self_client_id.map(|s| *s != c.id).unwrap_or(true))
             .collect();
 
         clients.sort_by(|a, b| a.id.cmp(&b.id));
-        clients
+        server::ClientList { clients, revision }
+    }
+
+    /// Returns all roster changes since the given revision, excluding changes of the given
+    /// client. Falls back to the full client list if the revision is not covered by the roster
+    /// history.
+    pub async fn list_client_changes(
+        &self,
+        since_revision: u64,
+        self_client_id: Option<&ClientId>,
+    ) -> ServerMessage {
+        let changes = {
+            let roster = self.roster.lock();
+            roster
+                .changes_since(since_revision, self_client_id)
+                .map(|changes| server::ClientChanges {
+                    revision: roster.revision(),
+                    changes,
+                })
+        };
+
+        match changes {
+            Some(changes) => changes.into(),
+            None => {
+                tracing::debug!(
+                    since_revision,
+                    "Revision not covered by roster history, returning full client list"
+                );
+                self.list_clients(self_client_id).await.into()
+            }
+        }
+    }
+
+    pub fn roster_revision(&self) -> u64 {
+        self.roster.lock().revision()
     }
 
     pub async fn list_stations(
@@ -390,14 +562,80 @@ impl ClientManager {
         self.clients.read().await.get(client_id).cloned()
     }
 
+    /// Returns the presence of a client connected to this or another instance of the cluster.
+    pub async fn client_presence(&self, client_id: &ClientId) -> Option<Presence> {
+        let presence = self
+            .clients
+            .read()
+            .await
+            .get(client_id)
+            .map(ClientSession::presence);
+        presence.or_else(|| {
+            self.remote_clients
+                .read()
+                .get(client_id)
+                .map(|(_, client)| client.presence)
+        })
+    }
+
+    /// Sets the presence of a connected client and broadcasts it to all other clients. Returns
+    /// `false` if the client is not connected.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_client_presence(&self, client_id: &ClientId, presence: Presence) -> bool {
+        let client = {
+            let mut clients = self.clients.write().await;
+            let Some(session) = clients.get_mut(client_id) else {
+                tracing::debug!("Client not found, skipping presence update");
+                return false;
+            };
+            if session.presence() == presence {
+                tracing::trace!("Presence unchanged, skipping broadcast");
+                return true;
+            }
+            session.set_presence(presence);
+            session.client_info().clone()
+        };
+
+        self.broadcast_presence_change(client);
+        true
+    }
+
     pub async fn is_client_connected(&self, client_id: &ClientId) -> bool {
         self.clients.read().await.contains_key(client_id)
     }
 
+    /// Returns a client connected to another instance of the cluster.
+    pub fn remote_client(&self, client_id: &ClientId) -> Option<ClientInfo> {
+        self.remote_clients
+            .read()
+            .get(client_id)
+            .map(|(_, client)| client.clone())
+    }
+
+    /// Returns all clients connected to this instance.
+    pub async fn local_clients(&self) -> Vec<ClientInfo> {
+        self.clients
+            .read()
+            .await
+            .values()
+            .map(|c| c.client_info().clone())
+            .collect()
+    }
+
     pub async fn is_empty(&self) -> bool {
         self.clients.read().await.is_empty()
     }
 
+    /// Disconnects all connected clients with the given reason.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn disconnect_all(&self, reason: DisconnectReason) {
+        let clients = self.clients.read().await;
+        tracing::debug!(count = clients.len(), "Disconnecting all clients");
+        for client in clients.values() {
+            client.disconnect(Some(reason.clone()));
+        }
+    }
+
     /// Returns coverage info for a single station, or `None` if the station
     /// is not currently online.
     pub async fn station_coverage(&self, station_id: &StationId) -> Option<StationCoverage> {
@@ -484,6 +722,174 @@ impl ClientManager {
         }
     }
 
+    /// Records a roster change of a local client and broadcasts it to all clients, including
+    /// the clients of other instances of the cluster.
+    fn broadcast_roster_change(&self, change: ClientChange) {
+        self.record_roster_change(change.clone());
+        self.publish_to_cluster(ClusterMessage::RosterChange { change });
+    }
+
+    /// Applies a roster change of a client connected to another instance of the cluster and
+    /// broadcasts it to the clients of this instance. Changes of clients which have since
+    /// connected to this instance are ignored. Returns whether the change was applied.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn apply_remote_roster_change(
+        &self,
+        instance_id: &str,
+        change: ClientChange,
+    ) -> bool {
+        let client_id = match &change {
+            ClientChange::Connected { client } | ClientChange::Updated { client } => &client.id,
+            ClientChange::Disconnected { client_id } => client_id,
+        };
+        if self.clients.read().await.contains_key(client_id) {
+            tracing::trace!("Client connected to this instance, ignoring remote roster change");
+            self.remote_clients.write().remove(client_id);
+            return false;
+        }
+
+        let change = {
+            let mut remote_clients = self.remote_clients.write();
+            match change {
+                ClientChange::Connected { client } | ClientChange::Updated { client } => {
+                    match remote_clients
+                        .insert(client.id.clone(), (instance_id.to_string(), client.clone()))
+                    {
+                        Some((_, previous)) if previous == client => {
+                            tracing::trace!("Remote client unchanged, skipping broadcast");
+                            return false;
+                        }
+                        Some(_) => ClientChange::Updated { client },
+                        None => ClientChange::Connected { client },
+                    }
+                }
+                ClientChange::Disconnected { client_id } => {
+                    if remote_clients.remove(&client_id).is_none() {
+                        tracing::trace!("Remote client not found, skipping broadcast");
+                        return false;
+                    }
+                    ClientChange::Disconnected { client_id }
+                }
+            }
+        };
+        self.record_roster_change(change);
+        true
+    }
+
+    /// Removes all clients of another instance of the cluster, returning their IDs.
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_remote_instance(&self, instance_id: &str) -> Vec<ClientId> {
+        let mut client_ids = Vec::new();
+        self.remote_clients.write().retain(|client_id, (id, _)| {
+            if id == instance_id {
+                client_ids.push(client_id.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        tracing::debug!(
+            count = client_ids.len(),
+            "Removing clients of remote instance"
+        );
+        for client_id in &client_ids {
+            self.record_roster_change(ClientChange::Disconnected {
+                client_id: client_id.clone(),
+            });
+        }
+        client_ids
+    }
+
+    /// Records a roster change and broadcasts it to all clients of this instance. The roster
+    /// lock is held while broadcasting, so broadcasts are sent in the order of their revisions.
+    fn record_roster_change(&self, change: ClientChange) {
+        let mut roster = self.roster.lock();
+        let revision = roster.record(change.clone());
+
+        let message = match change {
+            ClientChange::Connected { client } => {
+                ServerMessage::from(server::ClientConnected { client, revision })
+            }
+            ClientChange::Updated { client } => {
+                ServerMessage::from(server::ClientInfoUpdate { client, revision })
+            }
+            ClientChange::Disconnected { client_id } => {
+                ServerMessage::from(server::ClientDisconnected {
+                    client_id,
+                    revision,
+                })
+            }
+        };
+        if let Err(err) = self.broadcast(message) {
+            tracing::warn!(?err, revision, "Failed to broadcast roster change");
+        }
+    }
+
+    /// Broadcasts an announcement to all clients, including the clients of other instances of
+    /// the cluster. Announcements with an expiry are kept until they expire, so clients
+    /// connecting later receive them as well.
+    #[instrument(level = "info", skip(self))]
+    pub fn announce(&self, announcement: server::Announcement) {
+        self.announce_locally(announcement.clone());
+        self.publish_to_cluster(ClusterMessage::Announcement { announcement });
+    }
+
+    /// Broadcasts an announcement to the clients of this instance only.
+    pub fn announce_locally(&self, announcement: server::Announcement) {
+        {
+            let mut announcements = self.announcements.lock();
+            announcements.retain(|a| !a.is_expired());
+            if announcement.expires_at.is_some() && !announcement.is_expired() {
+                announcements.push(announcement.clone());
+            }
+        }
+
+        if let Err(err) = self.broadcast(announcement) {
+            tracing::warn!(?err, "Failed to broadcast announcement");
+        }
+    }
+
+    /// Returns all announcements which have not expired yet.
+    pub fn active_announcements(&self) -> Vec<server::Announcement> {
+        let mut announcements = self.announcements.lock();
+        announcements.retain(|a| !a.is_expired());
+        announcements.clone()
+    }
+
+    /// Records a presence change as roster update and broadcasts it as
+    /// [`server::ClientPresence`], which is only delivered to clients supporting presence.
+    /// Other instances of the cluster receive it as regular roster update.
+    fn broadcast_presence_change(&self, client: ClientInfo) {
+        self.publish_to_cluster(ClusterMessage::RosterChange {
+            change: ClientChange::Updated {
+                client: client.clone(),
+            },
+        });
+
+        let mut roster = self.roster.lock();
+        let client_id = client.id.clone();
+        let presence = client.presence;
+        let revision = roster.record(ClientChange::Updated { client });
+
+        let message = server::ClientPresence {
+            client_id,
+            presence,
+            revision,
+        };
+        if let Err(err) = self.broadcast(message) {
+            tracing::warn!(?err, revision, "Failed to broadcast presence change");
+        }
+    }
+
+    fn publish_to_cluster(&self, message: ClusterMessage) {
+        if let Some(cluster_tx) = self.cluster_tx.get()
+            && cluster_tx.send(message).is_err()
+        {
+            tracing::warn!("Cluster publisher stopped, dropping cluster message");
+        }
+    }
+
     pub async fn replace_network(&self, network: Network) {
         tracing::info!(?network, "Replacing network coverage data");
         *self.network.write() = network;
@@ -527,6 +933,10 @@ impl ClientManager {
                                 server::SessionInfo {
                                     client: session.client_info().clone(),
                                     profile: session_profile,
+                                    resume_token: None,
+                                    encoding: Encoding::Json,
+                                    compression: false,
+                                    capabilities: Capabilities::default(),
                                 },
                             ));
                         }
@@ -598,6 +1008,10 @@ impl ClientManager {
                             server::SessionInfo {
                                 client: session.client_info().clone(),
                                 profile: session_profile,
+                                resume_token: None,
+                                encoding: Encoding::Json,
+                                compression: false,
+                                capabilities: Capabilities::default(),
                             },
                         ));
                     }
@@ -750,12 +1164,8 @@ impl ClientManager {
             }
         }
 
-        if self.broadcast_tx.receiver_count() > 0 {
-            for msg in sync.client_info_updates {
-                if let Err(err) = self.broadcast(msg) {
-                    tracing::warn!(?err, "Failed to broadcast client info update");
-                }
-            }
+        for client in sync.client_info_updates {
+            self.broadcast_roster_change(ClientChange::Updated { client });
         }
 
         self.broadcast_station_changes(&coverage_changes).await;
@@ -949,6 +1359,10 @@ impl ClientManager {
                                 server::SessionInfo {
                                     client: session.client_info().clone(),
                                     profile: session_profile,
+                                    resume_token: None,
+                                    encoding: Encoding::Json,
+                                    compression: false,
+                                    capabilities: Capabilities::default(),
                                 },
                             ));
                         }
@@ -956,7 +1370,7 @@ impl ClientManager {
                         tracing::trace!(?cid, ?session, "Client info updated, broadcasting");
                         result
                             .client_info_updates
-                            .push(ServerMessage::from(session.client_info().clone()));
+                            .push(session.client_info().clone());
                     }
                 }
             }
@@ -1333,6 +1747,7 @@ mod tests {
             position_id: Some(PositionId::from(position_id)),
             display_name: id.to_string(),
             frequency: freq.to_string(),
+            presence: Presence::default(),
         }
     }
 
@@ -1342,6 +1757,7 @@ mod tests {
             position_id: None,
             display_name: id.to_string(),
             frequency: String::new(),
+            presence: Presence::default(),
         }
     }
 
@@ -3394,6 +3810,24 @@ controlled_by = ["LOWW_DEL"]
         );
     }
 
+    #[test]
+    fn active_announcements_keeps_unexpired_with_expiry() {
+        let (_dir, network) = create_lovv_network();
+        let manager = client_manager(network);
+
+        let transient = server::Announcement::new(server::AnnouncementSeverity::Info, "transient");
+        let expired =
+            server::Announcement::new(server::AnnouncementSeverity::Info, "expired").with_expiry(1);
+        let active = server::Announcement::new(server::AnnouncementSeverity::Warning, "active")
+            .with_expiry(u64::MAX);
+
+        manager.announce(transient);
+        manager.announce(expired);
+        manager.announce(active.clone());
+
+        assert_eq!(manager.active_announcements(), vec![active]);
+    }
+
     /// Base builder for the standard LOVV FIR used by most tests.
     fn lovv_fir() -> TestFirBuilder {
         TestFirBuilder::new("LOVV")
@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::server::ClientChange;
+
+/// Sequence-numbered history of changes to the roster of connected clients.
+///
+/// Every recorded change increments the roster revision. The most recent changes are kept, so
+/// clients that missed some broadcasts, e.g. during a brief reconnect, can catch up on all changes
+/// since their last known revision instead of requesting the full client list.
+///
+/// The initial revision is derived from the current time, so revisions keep increasing across
+/// server restarts and revisions known from before a restart are not mistaken for current ones.
+#[derive(Debug)]
+pub struct Roster {
+    revision: u64,
+    history: VecDeque<(u64, ClientChange)>,
+}
+
+impl Roster {
+    /// Maximum number of changes kept in the history.
+    pub const MAX_HISTORY: usize = crate::config::ROSTER_HISTORY_CAPACITY;
+
+    pub fn new() -> Self {
+        let revision = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        Self::with_revision(revision)
+    }
+
+    pub fn with_revision(revision: u64) -> Self {
+        Self {
+            revision,
+            history: VecDeque::with_capacity(Self::MAX_HISTORY),
+        }
+    }
+
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Records a change, returning its revision.
+    pub fn record(&mut self, change: ClientChange) -> u64 {
+        self.revision += 1;
+        if self.history.len() == Self::MAX_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back((self.revision, change));
+        self.revision
+    }
+
+    /// Returns all changes since the given revision, excluding changes of the given client.
+    /// Returns `None` if the revision is not covered by the history, in which case the full client
+    /// list has to be sent instead.
+    pub fn changes_since(
+        &self,
+        revision: u64,
+        self_client_id: Option<&ClientId>,
+    ) -> Option<Vec<ClientChange>> {
+        let oldest_known = self.revision - self.history.len() as u64;
+        if revision < oldest_known || revision > self.revision {
+            return None;
+        }
+
+        Some(
+            self.history
+                .iter()
+                .filter(|(rev, _)| *rev > revision)
+                .map(|(_, change)| change)
+                .filter(|change| self_client_id.is_none_or(|id| change_client_id(change) != id))
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+impl Default for Roster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn change_client_id(change: &ClientChange) -> &ClientId {
+    match change {
+        ClientChange::Connected { client } | ClientChange::Updated { client } => &client.id,
+        ClientChange::Disconnected { client_id } => client_id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use vacs_protocol::ws::server::ClientInfo;
+    use vacs_protocol::ws::shared::Presence;
+
+    fn connected(id: &str) -> ClientChange {
+        ClientChange::Connected {
+            client: ClientInfo {
+                id: ClientId::from(id),
+                display_name: id.to_string(),
+                frequency: "100.000".to_string(),
+                position_id: None,
+                presence: Presence::default(),
+            },
+        }
+    }
+
+    fn disconnected(id: &str) -> ClientChange {
+        ClientChange::Disconnected {
+            client_id: ClientId::from(id),
+        }
+    }
+
+    #[test]
+    fn record_increments_revision() {
+        let mut roster = Roster::with_revision(10);
+        assert_eq!(roster.record(connected("client1")), 11);
+        assert_eq!(roster.record(disconnected("client1")), 12);
+        assert_eq!(roster.revision(), 12);
+    }
+
+    #[test]
+    fn changes_since() {
+        let mut roster = Roster::with_revision(0);
+        roster.record(connected("client1"));
+        roster.record(connected("client2"));
+        roster.record(disconnected("client1"));
+
+        assert_eq!(
+            roster.changes_since(1, None),
+            Some(vec![connected("client2"), disconnected("client1")])
+        );
+        assert_eq!(roster.changes_since(3, None), Some(vec![]));
+        assert_eq!(
+            roster.changes_since(0, None),
+            Some(vec![
+                connected("client1"),
+                connected("client2"),
+                disconnected("client1")
+            ])
+        );
+    }
+
+    #[test]
+    fn changes_since_excludes_own_changes() {
+        let mut roster = Roster::with_revision(0);
+        roster.record(connected("client1"));
+        roster.record(connected("client2"));
+
+        assert_eq!(
+            roster.changes_since(0, Some(&ClientId::from("client1"))),
+            Some(vec![connected("client2")])
+        );
+    }
+
+    #[test]
+    fn changes_since_unknown_revision() {
+        let mut roster = Roster::with_revision(100);
+        roster.record(connected("client1"));
+
+        // Revision from a previous server run or not yet reached.
+        assert_eq!(roster.changes_since(50, None), None);
+        assert_eq!(roster.changes_since(102, None), None);
+    }
+
+    #[test]
+    fn changes_since_truncated_history() {
+        let mut roster = Roster::with_revision(0);
+        for n in 0..Roster::MAX_HISTORY + 5 {
+            roster.record(connected(&format!("client{n}")));
+        }
+
+        assert_eq!(roster.changes_since(4, None), None);
+        assert_eq!(
+            roster.changes_since(5, None).map(|c| c.len()),
+            Some(Roster::MAX_HISTORY)
+        );
+    }
+}
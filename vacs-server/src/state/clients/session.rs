@@ -1,9 +1,12 @@
+use crate::auth::scopes::Scopes;
 use crate::config;
 use crate::metrics::guards::ClientConnectionGuard;
 use crate::state::AppState;
 use crate::state::clients::{ClientManagerError, Result};
 use crate::ws::application_message::handle_application_message;
-use crate::ws::message::{MessageResult, receive_message, send_message};
+use crate::ws::message::{
+    MessageFormat, MessageResult, encode_message, receive_message, send_message,
+};
 use crate::ws::traits::{WebSocketSink, WebSocketStream};
 use axum::extract::ws;
 use futures_util::SinkExt;
@@ -15,21 +18,37 @@ use tokio::sync::{broadcast, mpsc, oneshot, watch};
 use tokio::task::JoinHandle;
 use tokio::time::Instant;
 use tracing::{Instrument, instrument};
+use uuid::Uuid;
 use vacs_protocol::profile::{ActiveProfile, ProfileId};
 use vacs_protocol::vatsim::{ClientId, PositionId};
 use vacs_protocol::ws::client::ClientMessage;
 use vacs_protocol::ws::server::{ClientInfo, DisconnectReason, ServerMessage, SessionProfile};
+use vacs_protocol::ws::shared::{Capabilities, Presence};
 use vacs_protocol::ws::{server, shared};
 use vacs_vatsim::ControllerInfo;
 use vacs_vatsim::coverage::network::Network;
 
+/// Describes how the interaction with a client ended, deciding whether its session can be
+/// resumed by a reconnecting client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionEnd {
+    /// The client logged out, closed the websocket or the server is shutting down.
+    Closed,
+    /// The connection was lost unexpectedly, e.g. due to a transport error or missing pong
+    /// responses.
+    ConnectionLost,
+}
+
 #[derive(Clone)]
 pub struct ClientSession {
     client_info: ClientInfo,
     active_profile: ActiveProfile<ProfileId>,
+    resume_token: String,
     tx: mpsc::Sender<ServerMessage>,
     client_shutdown_tx: watch::Sender<Option<DisconnectReason>>,
-    client_connection_guard: Arc<Mutex<ClientConnectionGuard>>,
+    client_connection_guard: Option<Arc<Mutex<ClientConnectionGuard>>>,
+    capabilities: Capabilities,
+    scopes: Option<Scopes>,
 }
 
 impl ClientSession {
@@ -43,12 +62,38 @@ impl ClientSession {
         Self {
             client_info,
             active_profile,
+            resume_token: Uuid::now_v7().to_string(),
             tx,
             client_shutdown_tx,
-            client_connection_guard: Arc::new(Mutex::new(client_connection_guard)),
+            client_connection_guard: Some(Arc::new(Mutex::new(client_connection_guard))),
+            capabilities: Capabilities::default(),
+            scopes: None,
         }
     }
 
+    /// Creates a session standing in for a client connected to another instance of the
+    /// cluster. Messages sent to the session are delivered to `tx`, which is expected to forward
+    /// them to the instance the client is connected to.
+    pub fn remote(client_info: ClientInfo, tx: mpsc::Sender<ServerMessage>) -> Self {
+        let (client_shutdown_tx, _) = watch::channel(None);
+        Self {
+            client_info,
+            active_profile: ActiveProfile::None,
+            resume_token: Uuid::now_v7().to_string(),
+            tx,
+            client_shutdown_tx,
+            client_connection_guard: None,
+            capabilities: Capabilities::default(),
+            scopes: None,
+        }
+    }
+
+    /// Keeps the resume token of a session handed over by another instance of the cluster.
+    pub fn with_resume_token(mut self, resume_token: String) -> Self {
+        self.resume_token = resume_token;
+        self
+    }
+
     #[inline]
     pub fn id(&self) -> &ClientId {
         &self.client_info.id
@@ -69,6 +114,43 @@ impl ClientSession {
         &self.active_profile
     }
 
+    #[inline]
+    pub fn resume_token(&self) -> &str {
+        &self.resume_token
+    }
+
+    /// Capabilities negotiated with the client during its most recent login.
+    #[inline]
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    #[inline]
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Scopes of the API token used for the most recent login. `None` grants full access.
+    #[inline]
+    pub fn scopes(&self) -> Option<&Scopes> {
+        self.scopes.as_ref()
+    }
+
+    #[inline]
+    pub fn set_scopes(&mut self, scopes: Option<Scopes>) {
+        self.scopes = scopes;
+    }
+
+    /// Whether a broadcast message only concerns this client itself and is therefore not
+    /// forwarded to it.
+    pub fn is_own_broadcast(&self, msg: &ServerMessage) -> bool {
+        match msg {
+            ServerMessage::ClientInfo(info) => info.client.id == self.client_info.id,
+            ServerMessage::ClientPresence(presence) => presence.client_id == self.client_info.id,
+            _ => false,
+        }
+    }
+
     #[tracing::instrument(level = "trace")]
     pub fn update_client_info(&mut self, controller_info: &ControllerInfo) -> bool {
         let mut changed = false;
@@ -95,6 +177,16 @@ impl ClientSession {
         changed
     }
 
+    #[inline]
+    pub fn presence(&self) -> Presence {
+        self.client_info.presence
+    }
+
+    #[inline]
+    pub fn set_presence(&mut self, presence: Presence) {
+        self.client_info.presence = presence;
+    }
+
     #[inline]
     pub fn set_position_id(&mut self, position_id: Option<PositionId>) {
         self.client_info.position_id = position_id;
@@ -138,10 +230,8 @@ impl ClientSession {
     #[instrument(level = "debug", skip(self))]
     pub fn disconnect(&self, disconnect_reason: Option<DisconnectReason>) {
         tracing::trace!("Disconnecting client");
-        if let Some(reason) = &disconnect_reason {
-            self.client_connection_guard
-                .lock()
-                .set_disconnect_reason(reason.clone());
+        if let (Some(reason), Some(guard)) = (&disconnect_reason, &self.client_connection_guard) {
+            guard.lock().set_disconnect_reason(reason.clone());
         }
         let _ = self.client_shutdown_tx.send(disconnect_reason);
     }
@@ -163,6 +253,10 @@ impl ClientSession {
         }
     }
 
+    /// Handles the interaction with a connected client until it disconnects. `pending` contains
+    /// messages queued while a resumed session was detached, which are sent after the initial
+    /// session info. All messages after the initial session info are sent in the `format`
+    /// negotiated during login.
     #[allow(clippy::too_many_arguments)]
     #[instrument(level = "debug", skip_all, fields(client_id = ?self.client_info.id))]
     pub async fn handle_interaction<R: WebSocketStream + 'static, T: WebSocketSink + 'static>(
@@ -173,7 +267,9 @@ impl ClientSession {
         broadcast_rx: &mut broadcast::Receiver<ServerMessage>,
         rx: &mut mpsc::Receiver<ServerMessage>,
         app_shutdown_rx: &mut watch::Receiver<()>,
-    ) {
+        pending: Vec<ServerMessage>,
+        format: MessageFormat,
+    ) -> InteractionEnd {
         tracing::debug!("Starting to handle client interaction");
 
         let (pong_update_tx, pong_update_rx) = watch::channel(Instant::now());
@@ -182,9 +278,10 @@ impl ClientSession {
             websocket_tx,
             app_shutdown_rx.clone(),
             self.client_shutdown_tx.subscribe(),
+            format,
         )
         .await;
-        let (reader_handle, mut ws_inbound_rx) = ClientSession::spawn_reader(
+        let (mut reader_handle, mut ws_inbound_rx) = ClientSession::spawn_reader(
             websocket_rx,
             app_shutdown_rx.clone(),
             self.client_shutdown_tx.subscribe(),
@@ -195,8 +292,14 @@ impl ClientSession {
             ClientSession::spawn_ping_task(&ws_outbound_tx, pong_update_rx);
 
         tracing::trace!("Sending initial session info");
+        // Sent uncompressed, as the client only knows whether compression was accepted after
+        // receiving it.
         if let Err(err) = send_message(
             &ws_outbound_tx,
+            MessageFormat {
+                compressed: false,
+                ..format
+            },
             server::SessionInfo {
                 client: self.client_info.clone(),
                 profile: match &self.active_profile {
@@ -209,6 +312,10 @@ impl ClientSession {
                     ActiveProfile::Custom => SessionProfile::Changed(ActiveProfile::Custom),
                     ActiveProfile::None => SessionProfile::Changed(ActiveProfile::None),
                 },
+                resume_token: Some(self.resume_token.clone()),
+                encoding: format.encoding,
+                compression: format.compressed,
+                capabilities: self.capabilities.clone(),
             },
         )
         .await
@@ -218,7 +325,7 @@ impl ClientSession {
 
         tracing::trace!("Sending initial client list");
         let clients = app_state.list_clients(Some(&self.client_info.id)).await;
-        if let Err(err) = send_message(&ws_outbound_tx, server::ClientList { clients }).await {
+        if let Err(err) = send_message(&ws_outbound_tx, format, clients).await {
             tracing::warn!(?err, "Failed to send initial client list");
         }
 
@@ -226,22 +333,57 @@ impl ClientSession {
         let stations = app_state
             .list_stations(&self.active_profile, self.client_info.position_id.as_ref())
             .await;
-        if let Err(err) = send_message(&ws_outbound_tx, server::StationList { stations }).await {
+        if let Err(err) =
+            send_message(&ws_outbound_tx, format, server::StationList { stations }).await
+        {
             tracing::warn!(?err, "Failed to send initial stations list");
         }
 
-        loop {
+        let announcements = app_state.clients.active_announcements();
+        if !announcements.is_empty()
+            && self
+                .capabilities
+                .contains(shared::Capability::Announcements)
+        {
+            tracing::trace!(count = announcements.len(), "Sending active announcements");
+            for announcement in announcements {
+                if let Err(err) = send_message(&ws_outbound_tx, format, announcement).await {
+                    tracing::warn!(?err, "Failed to send announcement");
+                }
+            }
+        }
+
+        if !pending.is_empty() {
+            tracing::trace!(
+                count = pending.len(),
+                "Sending messages queued while session was detached"
+            );
+            for msg in pending {
+                if !self.capabilities.allows(msg.required_capability()) {
+                    tracing::trace!(
+                        ?msg,
+                        "Dropping queued message requiring capability not negotiated"
+                    );
+                    continue;
+                }
+                if let Err(err) = send_message(&ws_outbound_tx, format, msg).await {
+                    tracing::warn!(?err, "Failed to send queued message");
+                }
+            }
+        }
+
+        let end = loop {
             tokio::select! {
                 biased;
 
                 _ = app_shutdown_rx.changed() => {
                     tracing::trace!("Shutdown signal received, disconnecting client");
-                    break;
+                    break InteractionEnd::Closed;
                 }
 
                 _ = &mut ping_shutdown_rx => {
                     tracing::debug!("Ping task reported client disconnect");
-                    break;
+                    break InteractionEnd::ConnectionLost;
                 }
 
                 msg = ws_inbound_rx.recv() => {
@@ -251,13 +393,13 @@ impl ClientSession {
                                 ControlFlow::Continue(()) => continue,
                                 ControlFlow::Break(()) => {
                                     tracing::debug!("Breaking interaction loop");
-                                    break;
+                                    break InteractionEnd::Closed;
                                 },
                             }
                         }
                         None => {
                             tracing::debug!("Application receiver closed, disconnecting client");
-                            break;
+                            break (&mut reader_handle).await.unwrap_or(InteractionEnd::Closed);
                         }
                     }
                 }
@@ -266,13 +408,17 @@ impl ClientSession {
                     match msg {
                         Some(msg) => {
                             tracing::trace!("Received direct message");
-                            if let Err(err) = send_message(&ws_outbound_tx, msg).await {
+                            if !self.capabilities.allows(msg.required_capability()) {
+                                tracing::trace!(?msg, "Dropping direct message requiring capability not negotiated");
+                                continue;
+                            }
+                            if let Err(err) = send_message(&ws_outbound_tx, format, msg).await {
                                 tracing::warn!(?err, "Failed to send direct message");
                             }
                         }
                         None => {
                             tracing::debug!("Client receiver closed, disconnecting client");
-                            break;
+                            break InteractionEnd::Closed;
                         }
                     }
                 }
@@ -281,13 +427,16 @@ impl ClientSession {
                     match msg {
                         Ok(msg) => {
                             tracing::trace!("Received broadcast message");
-                            if let ServerMessage::ClientInfo(info) = &msg
-                                && info.id == self.client_info.id {
-                                tracing::trace!("Dropping client info update broadcast for own client");
+                            if self.is_own_broadcast(&msg) {
+                                tracing::trace!(?msg, "Dropping broadcast message for own client");
+                                continue;
+                            }
+                            if !self.capabilities.allows(msg.required_capability()) {
+                                tracing::trace!(?msg, "Dropping broadcast message requiring capability not negotiated");
                                 continue;
                             }
 
-                            if let Err(err) = send_message(&ws_outbound_tx, msg).await {
+                            if let Err(err) = send_message(&ws_outbound_tx, format, msg).await {
                                 tracing::warn!(?err, "Failed to send broadcast message");
                             }
                         }
@@ -297,13 +446,14 @@ impl ClientSession {
                     }
                 }
             }
-        }
+        };
 
         writer_handle.abort();
         reader_handle.abort();
         ping_handle.abort();
 
-        tracing::debug!("Finished handling client interaction");
+        tracing::debug!(?end, "Finished handling client interaction");
+        end
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -311,6 +461,7 @@ impl ClientSession {
         mut websocket_tx: T,
         mut app_shutdown_rx: watch::Receiver<()>,
         mut client_shutdown_rx: watch::Receiver<Option<DisconnectReason>>,
+        format: MessageFormat,
     ) -> (JoinHandle<()>, mpsc::Sender<ws::Message>) {
         let (ws_outbound_tx, mut ws_outbound_rx) =
             mpsc::channel::<ws::Message>(config::CLIENT_WEBSOCKET_TASK_CHANNEL_CAPACITY);
@@ -335,9 +486,9 @@ impl ClientSession {
 
                         if let Some(reason) = reason_opt {
                             tracing::trace!(?reason, "Sending Disconnect message before stopping WebSocket writer task");
-                            match ServerMessage::serialize(&ServerMessage::from(server::Disconnected {reason})) {
+                            match encode_message(&ServerMessage::from(server::Disconnected {reason}), format) {
                                 Ok(msg) => {
-                                    if let Err(err) = websocket_tx.send(ws::Message::from(msg)).await {
+                                    if let Err(err) = websocket_tx.send(msg).await {
                                         tracing::warn!(?err, "Failed to send Disconnect message");
                                     }
                                 },
@@ -385,7 +536,7 @@ impl ClientSession {
         mut app_shutdown_rx: watch::Receiver<()>,
         mut client_shutdown_rx: watch::Receiver<Option<DisconnectReason>>,
         pong_update_tx: watch::Sender<Instant>,
-    ) -> (JoinHandle<()>, mpsc::Receiver<ClientMessage>) {
+    ) -> (JoinHandle<InteractionEnd>, mpsc::Receiver<ClientMessage>) {
         let (ws_inbound_tx, ws_inbound_rx) =
             mpsc::channel::<ClientMessage>(config::CLIENT_WEBSOCKET_TASK_CHANNEL_CAPACITY);
 
@@ -393,18 +544,23 @@ impl ClientSession {
             tracing::trace!("WebSocket reader task started");
             let _guard = TaskDropLogger::new("reader");
 
-            loop {
+            let end = loop {
                 tokio::select! {
                     biased;
 
                     _ = app_shutdown_rx.changed() => {
                         tracing::trace!("App shutdown signal received, stopping WebSocket reader task");
-                        break;
+                        break InteractionEnd::Closed;
                     }
 
                     _ = client_shutdown_rx.changed() => {
                         tracing::trace!("Client shutdown signal received, stopping WebSocket reader task");
-                        break;
+                        // A shutdown without reason is used to detach the session if it is taken
+                        // over by a resuming connection.
+                        if client_shutdown_rx.borrow().is_some() {
+                            break InteractionEnd::Closed;
+                        }
+                        break InteractionEnd::ConnectionLost;
                     }
 
                     msg = receive_message(&mut websocket_rx) => {
@@ -412,7 +568,7 @@ impl ClientSession {
                             MessageResult::ApplicationMessage(message) => {
                                 if let Err(err) = ws_inbound_tx.send(message).await {
                                     tracing::warn!(?err, "Failed to forward message to application");
-                                    break;
+                                    break InteractionEnd::Closed;
                                 }
                             }
                             MessageResult::ControlMessage => {
@@ -423,17 +579,18 @@ impl ClientSession {
                             },
                             MessageResult::Disconnected => {
                                 tracing::debug!("Client disconnected");
-                                break;
+                                break InteractionEnd::Closed;
                             }
                             MessageResult::Error(err) => {
                                 tracing::warn!(?err, "Error while receiving message from client");
-                                break;
+                                break InteractionEnd::ConnectionLost;
                             }
                         }
                     }
                 }
-            }
-            tracing::trace!("WebSocket reader task finished");
+            };
+            tracing::trace!(?end, "WebSocket reader task finished");
+            end
         }.instrument(tracing::Span::current()));
 
         (join_handle, ws_inbound_rx)
@@ -487,6 +644,8 @@ impl Debug for ClientSession {
         f.debug_struct("ClientSession")
             .field("client_info", &self.client_info)
             .field("active_profile", &self.active_profile)
+            .field("capabilities", &self.capabilities)
+            .field("scopes", &self.scopes)
             .finish_non_exhaustive()
     }
 }
@@ -548,6 +707,7 @@ mod tests {
         let client_info_2 = create_client_info(2);
         let message = ServerMessage::ClientList(server::ClientList {
             clients: vec![client_info_2],
+            revision: 0,
         });
         let result = session.send_message(message.clone()).await;
 
@@ -571,6 +731,7 @@ mod tests {
         let client_info_2 = create_client_info(2);
         let message = ServerMessage::ClientList(server::ClientList {
             clients: vec![client_info_2],
+            revision: 0,
         });
         let result = session.send_message(message.clone()).await;
 
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use vacs_protocol::vatsim::ClientId;
+
+/// Time-limited ban preventing a client from logging in, issued by a supervisor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ban {
+    pub cid: ClientId,
+    pub reason: String,
+    pub issued_by: ClientId,
+    /// Unix timestamp in seconds.
+    pub issued_at: u64,
+    /// Unix timestamp in seconds.
+    pub expires_at: u64,
+}
+
+/// Time-limited mute preventing a client from placing calls and sending text messages, issued by
+/// a supervisor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mute {
+    pub cid: ClientId,
+    pub reason: String,
+    pub issued_by: ClientId,
+    /// Unix timestamp in seconds.
+    pub issued_at: u64,
+    /// Unix timestamp in seconds.
+    pub expires_at: u64,
+}
@@ -0,0 +1,88 @@
+use crate::auth::scopes::Scopes;
+use serde::{Deserialize, Serialize};
+use vacs_protocol::vatsim::ClientId;
+
+/// Metadata of an API token. Never contains the token itself, which is only returned once when
+/// the token is issued.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    /// Stable identifier used to reference the token, e.g. when revoking it.
+    pub id: String,
+    pub name: String,
+    pub cid: ClientId,
+    /// Scopes the token is restricted to. `None` grants full access.
+    #[serde(default)]
+    pub scopes: Option<Scopes>,
+    /// Unix timestamp in seconds.
+    pub created_at: u64,
+    /// Unix timestamp in seconds, updated with a resolution of
+    /// [`API_TOKEN_LAST_USED_RESOLUTION`](crate::config::API_TOKEN_LAST_USED_RESOLUTION).
+    pub last_used_at: Option<u64>,
+}
+
+/// Value stored under `api.token.{token}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum StoredApiToken {
+    Named(ApiToken),
+    /// Tokens issued before token metadata was stored only map to the CID. They are not listed,
+    /// but remain valid until they expire.
+    Legacy(ClientId),
+}
+
+impl StoredApiToken {
+    pub fn cid(&self) -> &ClientId {
+        match self {
+            StoredApiToken::Named(token) => &token.cid,
+            StoredApiToken::Legacy(cid) => cid,
+        }
+    }
+
+    pub fn scopes(&self) -> Option<&Scopes> {
+        match self {
+            StoredApiToken::Named(token) => token.scopes.as_ref(),
+            StoredApiToken::Legacy(_) => None,
+        }
+    }
+}
+
+/// Identity and access granted by a verified API token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiTokenGrant {
+    pub cid: ClientId,
+    /// Scopes the token is restricted to. `None` grants full access.
+    pub scopes: Option<Scopes>,
+}
+
+/// Value stored under `ws.token.{token}`, carrying over the scopes of the API token used to
+/// request it to the websocket session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WsAuthToken {
+    pub cid: ClientId,
+    #[serde(default)]
+    pub scopes: Option<Scopes>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::{assert_eq, assert_matches};
+
+    #[test]
+    fn deserialize_legacy_token() {
+        let stored: StoredApiToken = serde_json::from_str(r#""123456""#).unwrap();
+        assert_matches!(stored, StoredApiToken::Legacy(_));
+        assert_eq!(stored.cid(), &ClientId::from("123456"));
+    }
+
+    #[test]
+    fn deserialize_named_token() {
+        let stored: StoredApiToken = serde_json::from_str(
+            r#"{"id":"id1","name":"Laptop","cid":"123456","createdAt":1,"lastUsedAt":null}"#,
+        )
+        .unwrap();
+        assert_matches!(stored, StoredApiToken::Named(ApiToken { ref name, .. }) if name == "Laptop");
+        assert_eq!(stored.cid(), &ClientId::from("123456"));
+    }
+}
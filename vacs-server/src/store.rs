@@ -1,10 +1,13 @@
 pub mod memory;
 pub mod redis;
+pub mod sqlite;
 
 use crate::store::memory::MemoryStore;
 use crate::store::redis::RedisStore;
+use crate::store::sqlite::SqliteStore;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use std::num::NonZeroU32;
 use std::time::Duration;
 
 #[async_trait::async_trait]
@@ -16,14 +19,41 @@ pub trait StoreBackend {
         value: V,
         expiry: Option<Duration>,
     ) -> anyhow::Result<()>;
+    /// Sets the value at `key` only if no value is stored yet. Returns whether the value was set,
+    /// so instances can claim a key atomically.
+    async fn set_if_absent<V: Serialize + Send>(
+        &self,
+        key: &str,
+        value: V,
+        expiry: Option<Duration>,
+    ) -> anyhow::Result<bool>;
     async fn remove(&self, key: &str) -> anyhow::Result<()>;
     async fn expire(&self, key: &str, duration: Duration) -> anyhow::Result<()>;
+    /// Adds a member to the set stored at `key`, creating the set if it does not exist.
+    async fn add_to_set(&self, key: &str, member: &str) -> anyhow::Result<()>;
+    /// Removes a member from the set stored at `key`.
+    async fn remove_from_set(&self, key: &str, member: &str) -> anyhow::Result<()>;
+    /// Returns all members of the set stored at `key`, in no particular order.
+    async fn set_members(&self, key: &str) -> anyhow::Result<Vec<String>>;
+    /// Checks a request against the rate limit stored at `key` using the generic cell rate
+    /// algorithm (GCRA), allowing `burst` requests at once and replenishing one request per
+    /// `interval`. Returns the time to wait until the request would be allowed if the limit is
+    /// exceeded. Allowed requests are recorded atomically, so limits can be shared between
+    /// instances.
+    async fn check_rate_limit(
+        &self,
+        key: &str,
+        interval: Duration,
+        burst: NonZeroU32,
+    ) -> anyhow::Result<Option<Duration>>;
     async fn is_healthy(&self) -> anyhow::Result<()>;
 }
 
+#[derive(Debug, Clone)]
 pub enum Store {
     Redis(RedisStore),
     Memory(MemoryStore),
+    Sqlite(SqliteStore),
 }
 
 #[async_trait::async_trait]
@@ -32,6 +62,7 @@ impl StoreBackend for Store {
         match self {
             Store::Redis(store) => store.get(key).await,
             Store::Memory(store) => store.get(key).await,
+            Store::Sqlite(store) => store.get(key).await,
         }
     }
 
@@ -44,6 +75,20 @@ impl StoreBackend for Store {
         match self {
             Store::Redis(store) => store.set(key, value, expiry).await,
             Store::Memory(store) => store.set(key, value, expiry).await,
+            Store::Sqlite(store) => store.set(key, value, expiry).await,
+        }
+    }
+
+    async fn set_if_absent<V: Serialize + Send>(
+        &self,
+        key: &str,
+        value: V,
+        expiry: Option<Duration>,
+    ) -> anyhow::Result<bool> {
+        match self {
+            Store::Redis(store) => store.set_if_absent(key, value, expiry).await,
+            Store::Memory(store) => store.set_if_absent(key, value, expiry).await,
+            Store::Sqlite(store) => store.set_if_absent(key, value, expiry).await,
         }
     }
 
@@ -51,6 +96,7 @@ impl StoreBackend for Store {
         match self {
             Store::Redis(store) => store.remove(key).await,
             Store::Memory(store) => store.remove(key).await,
+            Store::Sqlite(store) => store.remove(key).await,
         }
     }
 
@@ -58,6 +104,44 @@ impl StoreBackend for Store {
         match self {
             Store::Redis(store) => store.expire(key, duration).await,
             Store::Memory(store) => store.expire(key, duration).await,
+            Store::Sqlite(store) => store.expire(key, duration).await,
+        }
+    }
+
+    async fn add_to_set(&self, key: &str, member: &str) -> anyhow::Result<()> {
+        match self {
+            Store::Redis(store) => store.add_to_set(key, member).await,
+            Store::Memory(store) => store.add_to_set(key, member).await,
+            Store::Sqlite(store) => store.add_to_set(key, member).await,
+        }
+    }
+
+    async fn remove_from_set(&self, key: &str, member: &str) -> anyhow::Result<()> {
+        match self {
+            Store::Redis(store) => store.remove_from_set(key, member).await,
+            Store::Memory(store) => store.remove_from_set(key, member).await,
+            Store::Sqlite(store) => store.remove_from_set(key, member).await,
+        }
+    }
+
+    async fn set_members(&self, key: &str) -> anyhow::Result<Vec<String>> {
+        match self {
+            Store::Redis(store) => store.set_members(key).await,
+            Store::Memory(store) => store.set_members(key).await,
+            Store::Sqlite(store) => store.set_members(key).await,
+        }
+    }
+
+    async fn check_rate_limit(
+        &self,
+        key: &str,
+        interval: Duration,
+        burst: NonZeroU32,
+    ) -> anyhow::Result<Option<Duration>> {
+        match self {
+            Store::Redis(store) => store.check_rate_limit(key, interval, burst).await,
+            Store::Memory(store) => store.check_rate_limit(key, interval, burst).await,
+            Store::Sqlite(store) => store.check_rate_limit(key, interval, burst).await,
         }
     }
 
@@ -65,6 +149,278 @@ impl StoreBackend for Store {
         match self {
             Store::Redis(store) => store.is_healthy().await,
             Store::Memory(store) => store.is_healthy().await,
+            Store::Sqlite(store) => store.is_healthy().await,
         }
     }
 }
+
+/// Applies the GCRA to a rate limit whose theoretical arrival time lies `tat` in the future,
+/// zero if it has passed. Returns the new time until the theoretical arrival time if the
+/// request is allowed, or the time to wait until it would be allowed otherwise.
+fn gcra(tat: Duration, interval: Duration, burst: NonZeroU32) -> Result<Duration, Duration> {
+    let tolerance = interval * (burst.get() - 1);
+    if tat > tolerance {
+        Err(tat - tolerance)
+    } else {
+        Ok(tat + interval)
+    }
+}
+
+/// Tests shared by all store backends, run against each backend via
+/// [`store_backend_tests`](tests::store_backend_tests).
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    /// Returns a unique key, so tests can run against a shared Redis instance.
+    fn key(name: &str) -> String {
+        format!("test.{}.{name}", Uuid::now_v7())
+    }
+
+    pub async fn get_set_remove(store: &impl StoreBackend) {
+        let key = key("value");
+        assert_eq!(store.get::<String>(&key).await.unwrap(), None);
+
+        store.set(&key, "a", None).await.unwrap();
+        assert_eq!(
+            store.get::<String>(&key).await.unwrap().as_deref(),
+            Some("a")
+        );
+        store.set(&key, "b", None).await.unwrap();
+        assert_eq!(
+            store.get::<String>(&key).await.unwrap().as_deref(),
+            Some("b")
+        );
+
+        store.remove(&key).await.unwrap();
+        assert_eq!(store.get::<String>(&key).await.unwrap(), None);
+        store.remove(&key).await.unwrap();
+    }
+
+    pub async fn set_if_absent(store: &impl StoreBackend) {
+        let key = key("claim");
+        assert!(store.set_if_absent(&key, "a", None).await.unwrap());
+        assert!(!store.set_if_absent(&key, "b", None).await.unwrap());
+        assert_eq!(
+            store.get::<String>(&key).await.unwrap().as_deref(),
+            Some("a")
+        );
+
+        // Expired values are absent
+        store.expire(&key, Duration::ZERO).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        assert!(
+            store
+                .set_if_absent(&key, "c", Some(Duration::from_secs(60)))
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            store.get::<String>(&key).await.unwrap().as_deref(),
+            Some("c")
+        );
+        store.remove(&key).await.unwrap();
+    }
+
+    pub async fn expiry(store: &impl StoreBackend) {
+        let expiring = key("expiring");
+        let expired = key("expired");
+        let persisted = key("persisted");
+        let missing = key("missing");
+
+        store
+            .set(&expiring, "a", Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        store.set(&expired, "b", None).await.unwrap();
+        store
+            .expire(&expired, Duration::from_secs(1))
+            .await
+            .unwrap();
+        // Setting a value again without expiry removes the expiry
+        store
+            .set(&persisted, "c", Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        store.set(&persisted, "c", None).await.unwrap();
+        store
+            .expire(&missing, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.get::<String>(&expiring).await.unwrap().as_deref(),
+            Some("a")
+        );
+        assert_eq!(
+            store.get::<String>(&expired).await.unwrap().as_deref(),
+            Some("b")
+        );
+        assert_eq!(store.get::<String>(&missing).await.unwrap(), None);
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert_eq!(store.get::<String>(&expiring).await.unwrap(), None);
+        assert_eq!(store.get::<String>(&expired).await.unwrap(), None);
+        assert_eq!(
+            store.get::<String>(&persisted).await.unwrap().as_deref(),
+            Some("c")
+        );
+
+        // Expired keys are not revived by setting a new expiry
+        store
+            .expire(&expired, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(store.get::<String>(&expired).await.unwrap(), None);
+
+        store.remove(&persisted).await.unwrap();
+    }
+
+    pub async fn set_operations(store: &impl StoreBackend) {
+        let key = key("set");
+        store.add_to_set(&key, "a").await.unwrap();
+        store.add_to_set(&key, "b").await.unwrap();
+        store.add_to_set(&key, "a").await.unwrap();
+
+        let mut members = store.set_members(&key).await.unwrap();
+        members.sort();
+        assert_eq!(members, vec!["a", "b"]);
+
+        store.remove_from_set(&key, "a").await.unwrap();
+        store.remove_from_set("missing", "a").await.unwrap();
+        assert_eq!(store.set_members(&key).await.unwrap(), vec!["b"]);
+        assert!(store.set_members("missing").await.unwrap().is_empty());
+
+        store.remove(&key).await.unwrap();
+        assert!(store.set_members(&key).await.unwrap().is_empty());
+    }
+
+    pub async fn expired_set_is_empty(store: &impl StoreBackend) {
+        let key = key("set");
+        store.add_to_set(&key, "a").await.unwrap();
+        store.expire(&key, Duration::ZERO).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        assert!(store.set_members(&key).await.unwrap().is_empty());
+
+        store.add_to_set(&key, "b").await.unwrap();
+        assert_eq!(store.set_members(&key).await.unwrap(), vec!["b"]);
+        store.remove(&key).await.unwrap();
+    }
+
+    pub async fn rate_limit(store: &impl StoreBackend) {
+        let key = key("ratelimit");
+        let interval = Duration::from_secs(60);
+        let burst = NonZeroU32::new(2).unwrap();
+        assert_eq!(
+            store.check_rate_limit(&key, interval, burst).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            store.check_rate_limit(&key, interval, burst).await.unwrap(),
+            None
+        );
+
+        let wait = store
+            .check_rate_limit(&key, interval, burst)
+            .await
+            .unwrap()
+            .expect("rate limit should be exceeded");
+        assert!(wait > Duration::from_secs(58) && wait <= interval);
+        // Rejected requests are not recorded
+        let retry_wait = store
+            .check_rate_limit(&key, interval, burst)
+            .await
+            .unwrap()
+            .expect("rate limit should be exceeded");
+        assert!(retry_wait <= wait);
+
+        assert_eq!(
+            store
+                .check_rate_limit(&format!("{key}.other"), interval, burst)
+                .await
+                .unwrap(),
+            None
+        );
+        store.remove(&key).await.unwrap();
+        store.remove(&format!("{key}.other")).await.unwrap();
+    }
+
+    pub async fn rate_limit_replenishes(store: &impl StoreBackend) {
+        let key = key("ratelimit");
+        let interval = Duration::from_millis(500);
+        let burst = NonZeroU32::new(1).unwrap();
+        assert_eq!(
+            store.check_rate_limit(&key, interval, burst).await.unwrap(),
+            None
+        );
+        assert!(
+            store
+                .check_rate_limit(&key, interval, burst)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert_eq!(
+            store.check_rate_limit(&key, interval, burst).await.unwrap(),
+            None
+        );
+        store.remove(&key).await.unwrap();
+    }
+
+    /// Generates tests running the shared store tests against the store returned by `$store`,
+    /// applying the given attributes to each test.
+    macro_rules! store_backend_tests {
+        ($(#[$attr:meta])* $store:expr) => {
+            mod backend {
+                use super::*;
+
+                #[tokio::test]
+                $(#[$attr])*
+                async fn get_set_remove() {
+                    crate::store::tests::get_set_remove(&$store).await;
+                }
+
+                #[tokio::test]
+                $(#[$attr])*
+                async fn set_if_absent() {
+                    crate::store::tests::set_if_absent(&$store).await;
+                }
+
+                #[tokio::test]
+                $(#[$attr])*
+                async fn expiry() {
+                    crate::store::tests::expiry(&$store).await;
+                }
+
+                #[tokio::test]
+                $(#[$attr])*
+                async fn set_operations() {
+                    crate::store::tests::set_operations(&$store).await;
+                }
+
+                #[tokio::test]
+                $(#[$attr])*
+                async fn expired_set_is_empty() {
+                    crate::store::tests::expired_set_is_empty(&$store).await;
+                }
+
+                #[tokio::test]
+                $(#[$attr])*
+                async fn rate_limit() {
+                    crate::store::tests::rate_limit(&$store).await;
+                }
+
+                #[tokio::test]
+                $(#[$attr])*
+                async fn rate_limit_replenishes() {
+                    crate::store::tests::rate_limit_replenishes(&$store).await;
+                }
+            }
+        };
+    }
+    pub(crate) use store_backend_tests;
+}
@@ -1,10 +1,13 @@
-use crate::store::StoreBackend;
+use crate::store::{StoreBackend, gcra};
 use anyhow::Context;
 use bytes::Bytes;
 use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use std::fmt::Debug;
+use std::num::NonZeroU32;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::instrument;
 use uuid::Uuid;
@@ -15,9 +18,11 @@ struct StoredValue {
     expires_at: Option<Instant>,
 }
 
-#[derive(Debug)]
+/// In-memory store. Clones share the same data, allowing multiple in-process app instances to
+/// use one store.
+#[derive(Debug, Clone)]
 pub struct MemoryStore {
-    map: DashMap<String, StoredValue>,
+    map: Arc<DashMap<String, StoredValue>>,
 }
 
 impl MemoryStore {
@@ -34,7 +39,7 @@ impl Default for MemoryStore {
             map.insert(
                 format!("ws.token.token{i}"),
                 StoredValue {
-                    value: Bytes::from(format!("\"client{i}\"")),
+                    value: Bytes::from(format!(r#"{{"cid":"client{i}"}}"#)),
                     expires_at: None,
                 },
             );
@@ -47,7 +52,7 @@ impl Default for MemoryStore {
             );
         }
 
-        Self { map }
+        Self { map: Arc::new(map) }
     }
 }
 
@@ -61,6 +66,8 @@ impl StoreBackend for MemoryStore {
                 && Instant::now() > expires_at
             {
                 tracing::trace!("Value expired, removing from memory store and returning None");
+                // Release the read lock on the entry first, removing it would deadlock otherwise
+                drop(stored_value);
                 self.map.remove(key);
                 return Ok(None);
             }
@@ -100,6 +107,47 @@ impl StoreBackend for MemoryStore {
         Ok(())
     }
 
+    #[instrument(level = "trace", skip(self, value), err)]
+    async fn set_if_absent<V: Serialize + Send>(
+        &self,
+        key: &str,
+        value: V,
+        expiry: Option<Duration>,
+    ) -> anyhow::Result<bool> {
+        tracing::trace!("Serializing value for memory store");
+        let serialized = serde_json::to_vec(&value).context("Failed to serialize value")?;
+
+        tracing::trace!("Storing value in memory store if absent");
+        let stored_value = StoredValue {
+            value: Bytes::from(serialized),
+            expires_at: expiry.map(|expiry| Instant::now() + expiry),
+        };
+        let stored = match self.map.entry(key.to_string()) {
+            Entry::Occupied(mut entry) => {
+                if entry
+                    .get()
+                    .expires_at
+                    .is_some_and(|expires_at| Instant::now() > expires_at)
+                {
+                    entry.insert(stored_value);
+                    true
+                } else {
+                    false
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(stored_value);
+                true
+            }
+        };
+
+        tracing::trace!(
+            ?stored,
+            "Successfully stored value in memory store if absent"
+        );
+        Ok(stored)
+    }
+
     #[instrument(level = "trace", skip(self), err)]
     async fn remove(&self, key: &str) -> anyhow::Result<()> {
         tracing::trace!("Removing value from memory store");
@@ -112,13 +160,111 @@ impl StoreBackend for MemoryStore {
     #[instrument(level = "trace", skip(self), err)]
     async fn expire(&self, key: &str, duration: Duration) -> anyhow::Result<()> {
         tracing::trace!("Setting expiry on memory store key");
-        if let Some(mut entry) = self.map.get_mut(key) {
+        if let Some(mut entry) = self.map.get_mut(key)
+            && entry
+                .expires_at
+                .is_none_or(|expires_at| Instant::now() <= expires_at)
+        {
             entry.expires_at = Some(Instant::now() + duration);
         }
         Ok(())
     }
 
+    #[instrument(level = "trace", skip(self), err)]
+    async fn add_to_set(&self, key: &str, member: &str) -> anyhow::Result<()> {
+        tracing::trace!("Adding member to set in memory store");
+        let mut entry = self
+            .map
+            .entry(key.to_string())
+            .or_insert_with(|| StoredValue {
+                value: Bytes::from_static(b"[]"),
+                expires_at: None,
+            });
+
+        let mut members = live_set_members(&entry)?;
+        if !members.iter().any(|m| m == member) {
+            members.push(member.to_string());
+        }
+        entry.value = Bytes::from(serde_json::to_vec(&members).context("Failed to serialize set")?);
+        entry.expires_at = entry
+            .expires_at
+            .filter(|expires_at| Instant::now() <= *expires_at);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn remove_from_set(&self, key: &str, member: &str) -> anyhow::Result<()> {
+        tracing::trace!("Removing member from set in memory store");
+        if let Some(mut entry) = self.map.get_mut(key) {
+            let mut members = live_set_members(&entry)?;
+            members.retain(|m| m != member);
+            entry.value =
+                Bytes::from(serde_json::to_vec(&members).context("Failed to serialize set")?);
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn set_members(&self, key: &str) -> anyhow::Result<Vec<String>> {
+        tracing::trace!("Getting set members from memory store");
+        match self.map.get(key) {
+            Some(entry) => live_set_members(&entry),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn check_rate_limit(
+        &self,
+        key: &str,
+        interval: Duration,
+        burst: NonZeroU32,
+    ) -> anyhow::Result<Option<Duration>> {
+        tracing::trace!("Checking rate limit in memory store");
+        let now = Instant::now();
+        // The theoretical arrival time is kept as expiry, as the limit is reset once it passed
+        let mut entry = self
+            .map
+            .entry(key.to_string())
+            .or_insert_with(|| StoredValue {
+                value: Bytes::from_static(b"null"),
+                expires_at: Some(now),
+            });
+        let tat = entry
+            .expires_at
+            .map(|tat| tat.saturating_duration_since(now))
+            .unwrap_or_default();
+
+        match gcra(tat, interval, burst) {
+            Ok(tat) => {
+                entry.expires_at = Some(now + tat);
+                Ok(None)
+            }
+            Err(wait) => Ok(Some(wait)),
+        }
+    }
+
     async fn is_healthy(&self) -> anyhow::Result<()> {
         Ok(())
     }
 }
+
+/// Deserializes the members of a set, treating an expired set as empty.
+fn live_set_members(stored_value: &StoredValue) -> anyhow::Result<Vec<String>> {
+    if stored_value
+        .expires_at
+        .is_some_and(|expires_at| Instant::now() > expires_at)
+    {
+        return Ok(Vec::new());
+    }
+    serde_json::from_slice(&stored_value.value)
+        .context("Failed to deserialize set from memory store")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::store_backend_tests;
+
+    store_backend_tests!(MemoryStore::default());
+}
@@ -5,14 +5,36 @@ use bytes::Bytes;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use std::fmt::Debug;
+use std::num::NonZeroU32;
 use std::time::Duration;
-use tower_sessions_redis_store::fred::interfaces::ClientLike;
-use tower_sessions_redis_store::fred::prelude::Expiration::EX;
+use tower_sessions_redis_store::fred::interfaces::{ClientLike, LuaInterface, SetsInterface};
+use tower_sessions_redis_store::fred::prelude::Expiration::{EX, PX};
 use tower_sessions_redis_store::fred::prelude::{Config, KeysInterface, Pool};
-use tower_sessions_redis_store::fred::types::Builder;
+use tower_sessions_redis_store::fred::types::{Builder, SetOptions};
 use tracing::instrument;
 
-#[derive(Debug)]
+/// Applies the GCRA to the rate limit stored at `KEYS[1]`, with the interval in milliseconds and
+/// the burst as arguments. The theoretical arrival time is stored as value expiring once it
+/// passed, using the time of the Redis server so limits are consistent between instances.
+/// Returns the time to wait in milliseconds, or 0 if the request is allowed.
+const GCRA_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local interval = tonumber(ARGV[1])
+local tolerance = interval * (tonumber(ARGV[2]) - 1)
+local tat = now
+local stored = redis.call('GET', KEYS[1])
+if stored then
+    tat = math.max(tonumber(stored), now)
+end
+if tat - now > tolerance then
+    return tat - now - tolerance
+end
+redis.call('SET', KEYS[1], tat + interval, 'PX', tat + interval - now)
+return 0
+"#;
+
+#[derive(Debug, Clone)]
 pub struct RedisStore {
     pool: Pool,
 }
@@ -97,6 +119,38 @@ impl StoreBackend for RedisStore {
         Ok(())
     }
 
+    #[instrument(level = "trace", skip(self, value), err)]
+    async fn set_if_absent<V: Serialize + Send>(
+        &self,
+        key: &str,
+        value: V,
+        expiry: Option<Duration>,
+    ) -> anyhow::Result<bool> {
+        tracing::trace!("Serializing value for redis store");
+        let serialized = serde_json::to_vec(&value).context("Failed to serialize value")?;
+
+        tracing::trace!("Storing value in redis store if absent");
+        // SET NX replies with nil if the key already exists
+        let stored = self
+            .pool
+            .set::<Option<String>, _, _>(
+                key,
+                serialized,
+                expiry.map(|d| PX(d.as_millis() as i64)),
+                Some(SetOptions::NX),
+                false,
+            )
+            .await
+            .context("Failed to store value in redis")?
+            .is_some();
+
+        tracing::trace!(
+            ?stored,
+            "Successfully stored value in redis store if absent"
+        );
+        Ok(stored)
+    }
+
     #[instrument(level = "trace", skip(self), err)]
     async fn remove(&self, key: &str) -> anyhow::Result<()> {
         tracing::trace!("Removing value from redis store");
@@ -120,7 +174,84 @@ impl StoreBackend for RedisStore {
         Ok(())
     }
 
+    #[instrument(level = "trace", skip(self), err)]
+    async fn add_to_set(&self, key: &str, member: &str) -> anyhow::Result<()> {
+        tracing::trace!("Adding member to redis set");
+        self.pool
+            .sadd::<i64, _, _>(key, member)
+            .await
+            .context("Failed to add member to redis set")?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn remove_from_set(&self, key: &str, member: &str) -> anyhow::Result<()> {
+        tracing::trace!("Removing member from redis set");
+        self.pool
+            .srem::<i64, _, _>(key, member)
+            .await
+            .context("Failed to remove member from redis set")?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn set_members(&self, key: &str) -> anyhow::Result<Vec<String>> {
+        tracing::trace!("Getting redis set members");
+        self.pool
+            .smembers::<Vec<String>, _>(key)
+            .await
+            .context("Failed to get redis set members")
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn check_rate_limit(
+        &self,
+        key: &str,
+        interval: Duration,
+        burst: NonZeroU32,
+    ) -> anyhow::Result<Option<Duration>> {
+        tracing::trace!("Checking rate limit in redis");
+        let wait = self
+            .pool
+            .next()
+            .eval::<i64, _, _, _>(
+                GCRA_SCRIPT,
+                key,
+                vec![interval.as_millis() as i64, i64::from(burst.get())],
+            )
+            .await
+            .context("Failed to check rate limit in redis")?;
+        Ok((wait > 0).then(|| Duration::from_millis(wait as u64)))
+    }
+
     async fn is_healthy(&self) -> anyhow::Result<()> {
         self.pool.ping(None).await.context("Failed to ping redis")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::store_backend_tests;
+
+    /// Connects to the Redis instance at `VACS_TEST_REDIS_ADDR`, defaulting to a local instance.
+    async fn test_store() -> RedisStore {
+        let mut config = RedisConfig::default();
+        if let Ok(addr) = std::env::var("VACS_TEST_REDIS_ADDR") {
+            config.addr = addr;
+        }
+        RedisStore::new(&config).await.unwrap_or_else(|err| {
+            panic!(
+                "Failed to connect to Redis at {}, set VACS_TEST_REDIS_ADDR: {err:?}",
+                config.addr
+            )
+        })
+    }
+
+    // run with `cargo test -p vacs-server store::redis -- --include-ignored`, e.g. against the
+    // instance from docker-compose.yml
+    store_backend_tests!(
+        #[ignore = "requires a running Redis instance, see VACS_TEST_REDIS_ADDR"]
+        test_store().await
+    );
+}
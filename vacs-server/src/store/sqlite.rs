@@ -0,0 +1,411 @@
+use crate::store::{StoreBackend, gcra};
+use anyhow::Context;
+use parking_lot::Mutex;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tower_sessions::cookie::time::OffsetDateTime;
+use tower_sessions::session::{Id, Record};
+use tower_sessions::{SessionStore, session_store};
+use tracing::instrument;
+
+/// Persistent single-node store backed by an SQLite database, for deployments without Redis.
+///
+/// Expiring keys are hidden from reads once they expire and purged from the database on
+/// startup and when writing values. Sets are stored as JSON array, like in the memory store.
+/// Clones share the same connection.
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Opens the database at the given path, creating it if it does not exist.
+    #[instrument(level = "trace", err)]
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        tracing::trace!("Opening SQLite database");
+        let conn = Connection::open(path).context("Failed to open SQLite database")?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .context("Failed to enable SQLite write-ahead logging")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")
+            .context("Failed to set SQLite synchronous mode")?;
+
+        let store = Self::init(conn)?;
+        tracing::info!("SQLite store opened");
+        Ok(store)
+    }
+
+    /// Opens a private in-memory database, which is discarded once the store is dropped.
+    pub fn open_in_memory() -> anyhow::Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open SQLite database")?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> anyhow::Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS kv (
+                key TEXT PRIMARY KEY NOT NULL,
+                value BLOB NOT NULL,
+                expires_at INTEGER
+            );
+            CREATE INDEX IF NOT EXISTS kv_expires_at ON kv (expires_at);",
+        )
+        .context("Failed to create SQLite schema")?;
+
+        let purged = conn
+            .execute(
+                "DELETE FROM kv WHERE expires_at <= ?1",
+                params![unix_millis()],
+            )
+            .context("Failed to purge expired values")?;
+        tracing::debug!(?purged, "Purged expired values from SQLite store");
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Runs a closure with the connection on the blocking thread pool.
+    async fn call<T, F>(&self, f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&Connection) -> anyhow::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || f(&conn.lock()))
+            .await
+            .context("SQLite task panicked")?
+    }
+}
+
+/// Returns the value and expiry of a key, unless it has expired.
+fn get_live(conn: &Connection, key: &str) -> anyhow::Result<Option<(Vec<u8>, Option<i64>)>> {
+    conn.query_row(
+        "SELECT value, expires_at FROM kv WHERE key = ?1 AND (expires_at IS NULL OR expires_at > ?2)",
+        params![key, unix_millis()],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .context("Failed to get value from SQLite")
+}
+
+fn upsert(
+    conn: &Connection,
+    key: &str,
+    value: &[u8],
+    expires_at: Option<i64>,
+) -> anyhow::Result<()> {
+    conn.execute(
+        "INSERT INTO kv (key, value, expires_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT (key) DO UPDATE SET value = excluded.value, expires_at = excluded.expires_at",
+        params![key, value, expires_at],
+    )
+    .context("Failed to store value in SQLite")?;
+    Ok(())
+}
+
+fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+fn expires_at(duration: Duration) -> i64 {
+    unix_millis().saturating_add(duration.as_millis() as i64)
+}
+
+/// Applies a change to the members of a live set, keeping its expiry. Returns without storing
+/// the set if the change returns `false`.
+fn update_set(
+    conn: &Connection,
+    key: &str,
+    f: impl FnOnce(&mut Vec<String>) -> bool,
+) -> anyhow::Result<()> {
+    let (mut members, expires_at) = match get_live(conn, key)? {
+        Some((value, expires_at)) => (parse_set(&value)?, expires_at),
+        None => (Vec::new(), None),
+    };
+    if f(&mut members) {
+        let serialized = serde_json::to_vec(&members).context("Failed to serialize set")?;
+        upsert(conn, key, &serialized, expires_at)?;
+    }
+    Ok(())
+}
+
+fn parse_set(value: &[u8]) -> anyhow::Result<Vec<String>> {
+    serde_json::from_slice(value).context("Failed to deserialize set from SQLite store")
+}
+
+#[async_trait::async_trait]
+impl StoreBackend for SqliteStore {
+    #[instrument(level = "trace", skip(self), err)]
+    async fn get<V: DeserializeOwned + Send>(&self, key: &str) -> anyhow::Result<Option<V>> {
+        tracing::trace!("Getting value from SQLite store");
+        let key = key.to_string();
+        match self.call(move |conn| get_live(conn, &key)).await? {
+            Some((value, _)) => {
+                tracing::trace!("Deserializing value from SQLite store");
+                let value = serde_json::from_slice(&value)
+                    .context("Failed to deserialize value from SQLite store")?;
+
+                tracing::trace!("Successfully retrieved value from SQLite store");
+                Ok(Some(value))
+            }
+            None => {
+                tracing::trace!("Value not found in SQLite store");
+                Ok(None)
+            }
+        }
+    }
+
+    #[instrument(level = "trace", skip(self, value), err)]
+    async fn set<V: Serialize + Send>(
+        &self,
+        key: &str,
+        value: V,
+        expiry: Option<Duration>,
+    ) -> anyhow::Result<()> {
+        tracing::trace!("Serializing value for SQLite store");
+        let serialized = serde_json::to_vec(&value).context("Failed to serialize value")?;
+
+        tracing::trace!("Storing value in SQLite store");
+        let key = key.to_string();
+        self.call(move |conn| {
+            conn.execute(
+                "DELETE FROM kv WHERE expires_at <= ?1",
+                params![unix_millis()],
+            )
+            .context("Failed to purge expired values")?;
+            upsert(conn, &key, &serialized, expiry.map(expires_at))
+        })
+        .await?;
+
+        tracing::trace!("Successfully stored value in SQLite store");
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self, value), err)]
+    async fn set_if_absent<V: Serialize + Send>(
+        &self,
+        key: &str,
+        value: V,
+        expiry: Option<Duration>,
+    ) -> anyhow::Result<bool> {
+        tracing::trace!("Serializing value for SQLite store");
+        let serialized = serde_json::to_vec(&value).context("Failed to serialize value")?;
+
+        tracing::trace!("Storing value in SQLite store if absent");
+        let key = key.to_string();
+        // The connection is locked for the whole closure, so no other write can happen in between
+        let stored = self
+            .call(move |conn| {
+                if get_live(conn, &key)?.is_some() {
+                    return Ok(false);
+                }
+                upsert(conn, &key, &serialized, expiry.map(expires_at))?;
+                Ok(true)
+            })
+            .await?;
+
+        tracing::trace!(
+            ?stored,
+            "Successfully stored value in SQLite store if absent"
+        );
+        Ok(stored)
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn remove(&self, key: &str) -> anyhow::Result<()> {
+        tracing::trace!("Removing value from SQLite store");
+        let key = key.to_string();
+        let removed = self
+            .call(move |conn| {
+                conn.execute("DELETE FROM kv WHERE key = ?1", params![key])
+                    .context("Failed to remove value from SQLite")
+            })
+            .await?;
+
+        tracing::trace!(?removed, "Successfully removed value from SQLite store");
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn expire(&self, key: &str, duration: Duration) -> anyhow::Result<()> {
+        tracing::trace!("Setting expiry on SQLite store key");
+        let key = key.to_string();
+        self.call(move |conn| {
+            conn.execute(
+                "UPDATE kv SET expires_at = ?2
+                 WHERE key = ?1 AND (expires_at IS NULL OR expires_at > ?3)",
+                params![key, expires_at(duration), unix_millis()],
+            )
+            .context("Failed to set expiry on SQLite key")
+        })
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn add_to_set(&self, key: &str, member: &str) -> anyhow::Result<()> {
+        tracing::trace!("Adding member to set in SQLite store");
+        let (key, member) = (key.to_string(), member.to_string());
+        self.call(move |conn| {
+            update_set(conn, &key, |members| {
+                if members.contains(&member) {
+                    return false;
+                }
+                members.push(member);
+                true
+            })
+        })
+        .await
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn remove_from_set(&self, key: &str, member: &str) -> anyhow::Result<()> {
+        tracing::trace!("Removing member from set in SQLite store");
+        let (key, member) = (key.to_string(), member.to_string());
+        self.call(move |conn| {
+            update_set(conn, &key, |members| {
+                let len = members.len();
+                members.retain(|m| *m != member);
+                members.len() != len
+            })
+        })
+        .await
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn set_members(&self, key: &str) -> anyhow::Result<Vec<String>> {
+        tracing::trace!("Getting set members from SQLite store");
+        let key = key.to_string();
+        match self.call(move |conn| get_live(conn, &key)).await? {
+            Some((value, _)) => parse_set(&value),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    #[instrument(level = "trace", skip(self), err)]
+    async fn check_rate_limit(
+        &self,
+        key: &str,
+        interval: Duration,
+        burst: NonZeroU32,
+    ) -> anyhow::Result<Option<Duration>> {
+        tracing::trace!("Checking rate limit in SQLite store");
+        let key = key.to_string();
+        self.call(move |conn| {
+            // The theoretical arrival time is kept as expiry, as the limit is reset once it passed
+            let now = unix_millis();
+            let tat = get_live(conn, &key)?
+                .and_then(|(_, expires_at)| expires_at)
+                .map(|tat| Duration::from_millis(tat.saturating_sub(now).max(0) as u64))
+                .unwrap_or_default();
+
+            match gcra(tat, interval, burst) {
+                Ok(tat) => {
+                    upsert(conn, &key, b"null", Some(now + tat.as_millis() as i64))?;
+                    Ok(None)
+                }
+                Err(wait) => Ok(Some(wait)),
+            }
+        })
+        .await
+    }
+
+    async fn is_healthy(&self) -> anyhow::Result<()> {
+        self.call(|conn| {
+            conn.query_row("SELECT 1", [], |_| Ok(()))
+                .context("Failed to query SQLite")
+        })
+        .await
+    }
+}
+
+/// Stores HTTP sessions alongside all other values, so they survive restarts as well.
+#[async_trait::async_trait]
+impl SessionStore for SqliteStore {
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        let expiry = Duration::try_from(record.expiry_date - OffsetDateTime::now_utc())
+            .unwrap_or(Duration::ZERO);
+        self.set(&session_key(&record.id), record, Some(expiry))
+            .await
+            .map_err(|err| session_store::Error::Backend(format!("{err:#}")))
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        self.get(&session_key(session_id))
+            .await
+            .map_err(|err| session_store::Error::Backend(format!("{err:#}")))
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        self.remove(&session_key(session_id))
+            .await
+            .map_err(|err| session_store::Error::Backend(format!("{err:#}")))
+    }
+}
+
+fn session_key(session_id: &Id) -> String {
+    format!("session.{session_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::store_backend_tests;
+    use pretty_assertions::assert_eq;
+
+    store_backend_tests!(SqliteStore::open_in_memory().unwrap());
+
+    #[tokio::test]
+    async fn values_survive_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vacs.db");
+
+        let store = SqliteStore::open(&path).unwrap();
+        store.set("persisted", "a", None).await.unwrap();
+        store
+            .set("expiring", "b", Some(Duration::ZERO))
+            .await
+            .unwrap();
+        store.add_to_set("set", "c").await.unwrap();
+        drop(store);
+
+        let store = SqliteStore::open(&path).unwrap();
+        assert_eq!(
+            store.get::<String>("persisted").await.unwrap().as_deref(),
+            Some("a")
+        );
+        assert_eq!(store.get::<String>("expiring").await.unwrap(), None);
+        assert_eq!(store.set_members("set").await.unwrap(), vec!["c"]);
+    }
+
+    #[tokio::test]
+    async fn session_store() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let mut record = Record {
+            id: Id::default(),
+            data: [("user".to_string(), serde_json::json!("cid1"))].into(),
+            expiry_date: OffsetDateTime::now_utc()
+                + tower_sessions::cookie::time::Duration::hours(1),
+        };
+
+        store.create(&mut record).await.unwrap();
+        assert_eq!(store.load(&record.id).await.unwrap(), Some(record.clone()));
+
+        store.delete(&record.id).await.unwrap();
+        assert_eq!(store.load(&record.id).await.unwrap(), None);
+
+        record.expiry_date =
+            OffsetDateTime::now_utc() - tower_sessions::cookie::time::Duration::hours(1);
+        store.save(&record).await.unwrap();
+        assert_eq!(store.load(&record.id).await.unwrap(), None);
+    }
+}
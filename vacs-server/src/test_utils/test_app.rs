@@ -1,5 +1,9 @@
-use crate::auth::layer::setup_mock_auth_layer;
-use crate::config::{AppConfig, AuthConfig, VatsimConfig};
+use crate::auth::layer::setup_memory_auth_layer;
+use crate::cluster::memory::MemoryBroker;
+use crate::cluster::{Cluster, ClusterConfig};
+use crate::config::{
+    AppConfig, AuthConfig, DevIdentityConfig, IdentityProviderType, SessionConfig, VatsimConfig,
+};
 use crate::ice::provider::stun::StunOnlyProvider;
 use crate::ratelimit::RateLimiters;
 use crate::release::UpdateChecker;
@@ -9,8 +13,10 @@ use crate::store::Store;
 use crate::store::memory::MemoryStore;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
+use vacs_protocol::vatsim::ClientId;
 use vacs_vatsim::coverage::network::Network;
 use vacs_vatsim::data_feed::mock::MockDataFeed;
 use vacs_vatsim::slurper::SlurperClient;
@@ -22,6 +28,7 @@ pub struct TestApp {
     http_base_url: String,
     shutdown_tx: watch::Sender<()>,
     handle: JoinHandle<()>,
+    cluster_task: Option<JoinHandle<()>>,
 }
 
 impl TestApp {
@@ -30,9 +37,26 @@ impl TestApp {
     }
 
     pub async fn new_with_network(network: Network) -> Self {
-        let config = AppConfig {
+        Self::new_with_config(Self::config(), network).await
+    }
+
+    /// Default configuration used by test apps. Session resumption is disabled, so clients
+    /// losing their connection are unregistered immediately. Users log in via the dev identity
+    /// provider as `cid0` to `cid5`. Cluster instances time out after half a second.
+    pub fn config() -> AppConfig {
+        AppConfig {
             auth: AuthConfig {
                 login_flow_timeout_millis: 100,
+                session_resume_grace_period_secs: 0,
+                identity_provider: IdentityProviderType::Dev,
+                dev_identity: DevIdentityConfig {
+                    cids: (0..=5).map(|i| ClientId::from(format!("cid{i}"))).collect(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            session: SessionConfig {
+                secure: false,
                 ..Default::default()
             },
             vatsim: VatsimConfig {
@@ -42,18 +66,61 @@ impl TestApp {
                 controller_update_interval: Default::default(),
                 data_feed_url: Default::default(),
                 data_feed_timeout: Default::default(),
+                data_feed_record_dir: None,
+                data_feed_replay_dir: None,
+                data_feed_replay_speed: 1.0,
                 coverage_dir: Default::default(),
             },
+            cluster: ClusterConfig {
+                heartbeat_interval: Duration::from_millis(100),
+                instance_timeout: Duration::from_millis(500),
+                ..Default::default()
+            },
             ..Default::default()
-        };
+        }
+    }
+
+    pub async fn new_with_config(config: AppConfig, network: Network) -> Self {
+        Self::spawn(config, network, MemoryStore::default(), None).await
+    }
+
+    /// Creates an app running as cluster instance. Apps sharing the broker and store form a
+    /// cluster.
+    pub async fn new_clustered(
+        instance_id: &str,
+        broker: &MemoryBroker,
+        store: &MemoryStore,
+    ) -> Self {
+        Self::new_clustered_with_config(Self::config(), instance_id, broker, store).await
+    }
+
+    pub async fn new_clustered_with_config(
+        config: AppConfig,
+        instance_id: &str,
+        broker: &MemoryBroker,
+        store: &MemoryStore,
+    ) -> Self {
+        let cluster = Cluster::new(instance_id, Arc::new(broker.clone()));
+        Self::spawn(config, Network::default(), store.clone(), Some(cluster)).await
+    }
+
+    async fn spawn(
+        mut config: AppConfig,
+        network: Network,
+        store: MemoryStore,
+        cluster: Option<Cluster>,
+    ) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        config.auth.dev_identity.base_url = format!("http://{addr}");
 
         let mock_data_feed = Arc::new(MockDataFeed::default());
 
         let (shutdown_tx, shutdown_rx) = watch::channel(());
-        let state = Arc::new(AppState::new(
+        let mut state = AppState::new(
             config.clone(),
             UpdateChecker::default(),
-            Store::Memory(MemoryStore::default()),
+            Store::Memory(store),
             SlurperClient::new("http://localhost:12345").unwrap(),
             mock_data_feed.clone(),
             network,
@@ -61,18 +128,26 @@ impl TestApp {
             shutdown_rx,
             Arc::new(StunOnlyProvider::default()),
             None,
-        ));
+            config.audit.create_sink().unwrap(),
+        );
+        let clustered = cluster.is_some();
+        if let Some(cluster) = cluster {
+            state = state.with_cluster(cluster);
+        }
+        let state = Arc::new(state);
+        let cluster_task = if clustered {
+            Some(AppState::start_cluster_task(state.clone()).await.unwrap())
+        } else {
+            None
+        };
 
-        let auth_layer = setup_mock_auth_layer(&config).await.unwrap();
+        let auth_layer = setup_memory_auth_layer(&config).await.unwrap();
         let app = create_app(
             auth_layer,
             None,
             config.server.client_ip_source.clone(),
             config.server.debug_endpoints,
         );
-        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
-        let addr = listener.local_addr().unwrap();
-
         let state_clone = state.clone();
         let handle = tokio::spawn(async move {
             axum::serve(
@@ -91,6 +166,7 @@ impl TestApp {
             addr: format!("ws://{addr}/ws"),
             shutdown_tx,
             handle,
+            cluster_task,
         }
     }
 
@@ -105,6 +181,14 @@ impl TestApp {
     pub fn state(&self) -> Arc<AppState> {
         self.state.clone()
     }
+
+    /// Stops exchanging messages with the other instances of the cluster without leaving it, as
+    /// if the instance crashed. Its clients stay connected.
+    pub fn stop_cluster_task(&self) {
+        if let Some(cluster_task) = &self.cluster_task {
+            cluster_task.abort();
+        }
+    }
 }
 
 impl Drop for TestApp {
@@ -7,12 +7,21 @@ use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 use vacs_protocol::VACS_PROTOCOL_VERSION;
 use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::Encoding;
 use vacs_protocol::ws::client::ClientMessage;
 use vacs_protocol::ws::server::{self, ClientInfo, ServerMessage, StationInfo};
+use vacs_protocol::ws::shared::Capabilities;
 
 pub struct TestClient {
     id: ClientId,
     token: String,
+    resume_token: Option<String>,
+    requested_encoding: Encoding,
+    encoding: Encoding,
+    requested_compression: bool,
+    compressed: bool,
+    requested_capabilities: Capabilities,
+    capabilities: Capabilities,
     ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
 }
 
@@ -22,6 +31,13 @@ impl TestClient {
         Ok(Self {
             id: id.into(),
             token: token.to_string(),
+            resume_token: None,
+            requested_encoding: Encoding::Json,
+            encoding: Encoding::Json,
+            requested_compression: false,
+            compressed: false,
+            requested_capabilities: Capabilities::all(),
+            capabilities: Capabilities::default(),
             ws_stream,
         })
     }
@@ -54,6 +70,50 @@ impl TestClient {
         &self.id
     }
 
+    pub fn resume_token(&self) -> Option<&str> {
+        self.resume_token.as_deref()
+    }
+
+    /// Sets the encoding requested with the next login.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.requested_encoding = encoding;
+        self
+    }
+
+    /// Encoding confirmed by the server during the last login, used for all sent messages.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Sets whether compression is requested with the next login.
+    pub fn with_compression(mut self, compression: bool) -> Self {
+        self.requested_compression = compression;
+        self
+    }
+
+    /// Whether the server confirmed compression of its messages during the last login.
+    pub fn compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// Sets the capabilities requested with the next login.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.requested_capabilities = capabilities;
+        self
+    }
+
+    /// Capabilities negotiated with the server during the last login.
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Replaces the websocket connection with a new one without closing the previous connection,
+    /// simulating a connection loss. The resume token of the previous session is kept and sent
+    /// with the next login.
+    pub async fn reconnect(&mut self, ws_addr: &str) {
+        self.ws_stream = connect_to_websocket(ws_addr).await;
+    }
+
     pub async fn login<FI, FC, FS>(
         &mut self,
         client_info_predicate: FI,
@@ -70,9 +130,28 @@ impl TestClient {
             protocol_version: VACS_PROTOCOL_VERSION.to_string(),
             custom_profile: false,
             position_id: None,
+            resume_token: self.resume_token.clone(),
+            encoding: self.requested_encoding,
+            compression: self.requested_compression,
+            capabilities: self.requested_capabilities.clone(),
         });
+        let mut resume_token = None;
+        let mut encoding = Encoding::Json;
+        let mut compressed = false;
+        let mut capabilities = Capabilities::default();
         self.send_and_expect_with_timeout(login_msg, Duration::from_millis(100), |msg| match msg {
-            ServerMessage::SessionInfo(server::SessionInfo { client, .. }) => {
+            ServerMessage::SessionInfo(server::SessionInfo {
+                client,
+                resume_token: token,
+                encoding: session_encoding,
+                compression,
+                capabilities: session_capabilities,
+                ..
+            }) => {
+                resume_token = token;
+                encoding = session_encoding;
+                compressed = compression;
+                capabilities = session_capabilities;
                 client_info_predicate(true, client)
             }
             ServerMessage::LoginFailure(server::LoginFailure { reason }) => {
@@ -81,9 +160,13 @@ impl TestClient {
             _ => Err(anyhow::anyhow!("Unexpected response: {:?}", msg)),
         })
         .await?;
+        self.resume_token = resume_token;
+        self.encoding = encoding;
+        self.compressed = compressed;
+        self.capabilities = capabilities;
 
         self.recv_with_timeout_and_filter(Duration::from_millis(100), |msg| {
-            matches!(msg, ServerMessage::ClientList(server::ClientList { clients }) if client_list_predicate(clients).is_ok())
+            matches!(msg, ServerMessage::ClientList(server::ClientList { clients, .. }) if client_list_predicate(clients).is_ok())
         })
         .await
         .ok_or_else(|| anyhow::anyhow!("Client list not received"))?;
@@ -103,9 +186,11 @@ impl TestClient {
     }
 
     pub async fn send(&mut self, msg: ClientMessage) -> anyhow::Result<()> {
-        self.ws_stream
-            .send(Message::from(ClientMessage::serialize(&msg)?))
-            .await?;
+        let msg = match self.encoding {
+            Encoding::Json => Message::from(ClientMessage::serialize(&msg)?),
+            Encoding::MessagePack => Message::from(ClientMessage::serialize_msgpack(&msg)?),
+        };
+        self.ws_stream.send(msg).await?;
         Ok(())
     }
 
@@ -135,6 +220,18 @@ impl TestClient {
         loop {
             match self.recv_raw_with_timeout(timeout).await {
                 Some(Message::Text(text)) => return ServerMessage::deserialize(&text).ok(),
+                Some(Message::Binary(bytes)) if self.compressed => {
+                    let bytes = vacs_protocol::ws::decompress(&bytes).ok()?;
+                    return match self.encoding {
+                        Encoding::Json => std::str::from_utf8(&bytes)
+                            .ok()
+                            .and_then(|text| ServerMessage::deserialize(text).ok()),
+                        Encoding::MessagePack => ServerMessage::deserialize_msgpack(&bytes).ok(),
+                    };
+                }
+                Some(Message::Binary(bytes)) => {
+                    return ServerMessage::deserialize_msgpack(&bytes).ok();
+                }
                 Some(Message::Ping(_)) => continue,
                 _ => return None,
             }
@@ -1,3 +1,5 @@
+use crate::auth::roles::Role;
+use crate::auth::scopes::{self, Scope};
 use crate::metrics::{CallMetrics, ErrorMetrics};
 use crate::state::AppState;
 use crate::state::calls::{CallTerminationOutcome, StartCallError};
@@ -5,11 +7,16 @@ use crate::state::clients::session::ClientSession;
 use std::collections::HashSet;
 use std::ops::ControlFlow;
 use std::sync::Arc;
-use vacs_protocol::ws::client::{CallReject, ClientMessage};
+use std::time::Duration;
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::client::{
+    BanClient, CallReject, CallRejectReason, ClientMessage, KickClient, ListClientChanges,
+    MuteClient, SetPresence,
+};
 use vacs_protocol::ws::server::CallCancelReason;
 use vacs_protocol::ws::shared::{
-    CallAccept, CallEnd, CallError, CallErrorReason, CallId, CallInvite, CallTarget, ErrorReason,
-    WebrtcAnswer, WebrtcIceCandidate, WebrtcOffer,
+    CallAccept, CallEnd, CallError, CallErrorReason, CallId, CallInvite, CallTarget, Capability,
+    ErrorReason, TextMessage, WebrtcAnswer, WebrtcIceCandidate, WebrtcOffer,
 };
 use vacs_protocol::ws::{server, shared};
 
@@ -21,14 +28,65 @@ pub async fn handle_application_message(
 ) -> ControlFlow<(), ()> {
     tracing::trace!("Handling application message");
 
+    if let Some(capability) = message.required_capability()
+        && !client.capabilities().contains(capability)
+    {
+        tracing::debug!(
+            ?capability,
+            "Received message requiring capability not negotiated"
+        );
+        client
+            .send_error(ErrorReason::UnexpectedMessage(format!(
+                "capability {capability:?} not negotiated"
+            )))
+            .await;
+        return ControlFlow::Continue(());
+    }
+
+    if let Some(scope) = required_scope(&message)
+        && !scopes::grants(client.scopes(), scope)
+    {
+        tracing::debug!(%scope, "Received message requiring scope not granted");
+        client
+            .send_error(ErrorReason::UnexpectedMessage(format!(
+                "scope {scope} not granted"
+            )))
+            .await;
+        return ControlFlow::Continue(());
+    }
+
+    if let Err(until) = check_rate_limit(state, client, &message).await {
+        tracing::debug!(?until, "Rate limit exceeded, rejecting message");
+        let reason = ErrorReason::RateLimited {
+            retry_after_secs: until.as_secs(),
+        };
+        ErrorMetrics::error(&reason);
+        let mut error = shared::Error::from(reason);
+        if let Some(call_id) = call_id(&message) {
+            error = error.with_call_id(call_id);
+        }
+        client.send_error(error).await;
+        return ControlFlow::Continue(());
+    }
+
     match message {
         ClientMessage::ListClients => {
             tracing::trace!("Returning list of clients");
             let clients = state.list_clients(Some(client.id())).await;
-            if let Err(err) = client.send_message(server::ClientList { clients }).await {
+            if let Err(err) = client.send_message(clients).await {
                 tracing::warn!(?err, "Failed to send client list");
             }
         }
+        ClientMessage::ListClientChanges(ListClientChanges { since_revision }) => {
+            tracing::trace!(since_revision, "Returning client changes");
+            let changes = state
+                .clients
+                .list_client_changes(since_revision, Some(client.id()))
+                .await;
+            if let Err(err) = client.send_message(changes).await {
+                tracing::warn!(?err, "Failed to send client changes");
+            }
+        }
         ClientMessage::ListStations => {
             tracing::trace!("Returning list of stations");
             let stations = state
@@ -39,9 +97,49 @@ pub async fn handle_application_message(
                 tracing::warn!(?err, "Failed to send station list");
             }
         }
+        ClientMessage::SetPresence(SetPresence { presence }) => {
+            tracing::trace!(?presence, "Setting client presence");
+            state
+                .clients
+                .set_client_presence(client.id(), presence)
+                .await;
+        }
         ClientMessage::CallInvite(call_invite) => {
             handle_call_invite(state, client, call_invite).await;
         }
+        ClientMessage::TextMessage(text_message) => {
+            handle_text_message(state, client, text_message).await;
+        }
+        message @ (ClientMessage::KickClient(_)
+        | ClientMessage::BanClient(_)
+        | ClientMessage::MuteClient(_)) => {
+            handle_moderation_message(state, client, message).await;
+        }
+        message @ (ClientMessage::CallAccept(_)
+        | ClientMessage::CallReject(_)
+        | ClientMessage::CallEnd(_)
+        | ClientMessage::CallError(_)
+        | ClientMessage::WebrtcOffer(_)
+        | ClientMessage::WebrtcAnswer(_)
+        | ClientMessage::WebrtcIceCandidate(_)) => {
+            if let Some(call_id) = call_id(&message)
+                && state
+                    .forward_call_message(client.id(), &call_id, &message)
+                    .await
+            {
+                return ControlFlow::Continue(());
+            }
+            handle_call_message(state, client, message).await;
+        }
+        ClientMessage::Logout | ClientMessage::Disconnect => return ControlFlow::Break(()),
+        ClientMessage::Login(_) | ClientMessage::Error(_) => {}
+    };
+    ControlFlow::Continue(())
+}
+
+/// Handles a message concerning a call hosted by this instance.
+pub async fn handle_call_message(state: &AppState, client: &ClientSession, message: ClientMessage) {
+    match message {
         ClientMessage::CallAccept(call_accept) => {
             handle_call_accept(state, client, call_accept).await;
         }
@@ -63,10 +161,52 @@ pub async fn handle_application_message(
         ClientMessage::WebrtcIceCandidate(webrtc_ice_candidate) => {
             handle_webrtc_ice_candidate(state, client, webrtc_ice_candidate).await;
         }
-        ClientMessage::Logout | ClientMessage::Disconnect => return ControlFlow::Break(()),
-        ClientMessage::Login(_) | ClientMessage::Error(_) => {}
-    };
-    ControlFlow::Continue(())
+        _ => tracing::debug!(?message, "Ignoring message not concerning a call"),
+    }
+}
+
+/// ID of the existing call a message concerns.
+fn call_id(message: &ClientMessage) -> Option<CallId> {
+    match message {
+        ClientMessage::CallAccept(accept) => Some(accept.call_id),
+        ClientMessage::CallReject(reject) => Some(reject.call_id),
+        ClientMessage::CallEnd(end) => Some(end.call_id),
+        ClientMessage::CallError(error) => Some(error.call_id),
+        ClientMessage::WebrtcOffer(offer) => Some(offer.call_id),
+        ClientMessage::WebrtcAnswer(answer) => Some(answer.call_id),
+        ClientMessage::WebrtcIceCandidate(candidate) => Some(candidate.call_id),
+        _ => None,
+    }
+}
+
+/// Scope the API token used to log in must grant for the message to be handled.
+fn required_scope(message: &ClientMessage) -> Option<Scope> {
+    match message {
+        ClientMessage::ListStations => Some(Scope::CoverageRead),
+        ClientMessage::CallInvite(_) | ClientMessage::CallAccept(_) => Some(Scope::CallsWrite),
+        _ => None,
+    }
+}
+
+/// Checks the rate limit applying to the type of a message, if any. Call invites and text
+/// messages are limited by their handlers.
+async fn check_rate_limit(
+    state: &AppState,
+    client: &ClientSession,
+    message: &ClientMessage,
+) -> Result<(), Duration> {
+    let rate_limiters = state.rate_limiters();
+    match message {
+        ClientMessage::ListClients
+        | ClientMessage::ListClientChanges(_)
+        | ClientMessage::ListStations => rate_limiters.check_list_request(client.id()).await,
+        ClientMessage::WebrtcOffer(_)
+        | ClientMessage::WebrtcAnswer(_)
+        | ClientMessage::WebrtcIceCandidate(_) => {
+            rate_limiters.check_webrtc_signaling(client.id()).await
+        }
+        _ => Ok(()),
+    }
 }
 
 #[tracing::instrument(level = "trace", skip(state, client))]
@@ -75,7 +215,7 @@ async fn handle_call_invite(state: &AppState, client: &ClientSession, invite: Ca
     let caller_id = client.id();
     let call_id = &invite.call_id;
 
-    if let Err(until) = state.rate_limiters().check_call_invite(caller_id) {
+    if let Err(until) = state.rate_limiters().check_call_invite(caller_id).await {
         tracing::debug!(?until, "Rate limit exceeded, rejecting call invite");
         let reason = ErrorReason::RateLimited {
             retry_after_secs: until.as_secs(),
@@ -87,6 +227,15 @@ async fn handle_call_invite(state: &AppState, client: &ClientSession, invite: Ca
         return;
     }
 
+    if let Err(reason) = check_mute(state, client).await {
+        tracing::debug!("Client is muted, rejecting call invite");
+        ErrorMetrics::error(&reason);
+        client
+            .send_error(shared::Error::from(reason).with_call_id(invite.call_id))
+            .await;
+        return;
+    }
+
     if invite.source.client_id != *caller_id {
         tracing::debug!("Source client ID mismatch, rejecting call invite");
         send_call_error(
@@ -99,23 +248,40 @@ async fn handle_call_invite(state: &AppState, client: &ClientSession, invite: Ca
         return;
     }
 
-    let target_clients = match &invite.target {
-        CallTarget::Client(client_id) => {
-            if state.clients.is_client_connected(client_id).await {
-                HashSet::from([client_id.clone()])
-            } else {
-                HashSet::new()
+    let target_clients = resolve_target_clients(state, client, &invite.target).await;
+
+    let mut do_not_disturb = false;
+    let mut callees = HashSet::new();
+    for callee_id in target_clients {
+        match state.clients.client_presence(&callee_id).await {
+            Some(presence) if !presence.accepts_call(invite.prio) => {
+                tracing::trace!(?callee_id, ?presence, "Skipping target not accepting call");
+                do_not_disturb = true;
+            }
+            _ => {
+                callees.insert(callee_id);
             }
         }
-        CallTarget::Position(position_id) => state.clients.clients_for_position(position_id).await,
-        CallTarget::Station(station_id) => state.clients.clients_for_station(station_id).await,
     }
-    .into_iter()
-    .filter(|client_id| client_id != client.id())
-    .collect::<HashSet<_>>();
+    let target_clients = callees;
 
     CallMetrics::call_invite(&invite.source, &invite.target, invite.prio);
 
+    if target_clients.is_empty() && do_not_disturb {
+        tracing::trace!("All targets set to do not disturb, rejecting call invite");
+        // Callers unaware of presence don't know the do not disturb reason, report busy instead
+        let reason = if client.capabilities().contains(Capability::Presence) {
+            CallRejectReason::DoNotDisturb
+        } else {
+            CallRejectReason::Busy
+        };
+        let cancelled = server::CallCancelled::new(*call_id, CallCancelReason::Rejected(reason));
+        if let Err(err) = client.send_message(cancelled).await {
+            tracing::warn!(?err, "Failed to send call cancelled to source client");
+        }
+        return;
+    }
+
     if target_clients.is_empty() {
         tracing::trace!("No clients found for call invite, returning target not found error");
         send_call_error(client, call_id, CallErrorReason::TargetNotFound, None).await;
@@ -133,6 +299,7 @@ async fn handle_call_invite(state: &AppState, client: &ClientSession, invite: Ca
             return;
         }
     }
+    state.claim_call(call_id).await;
 
     for callee_id in target_clients {
         tracing::trace!(?callee_id, "Sending call invite to target");
@@ -140,6 +307,7 @@ async fn handle_call_invite(state: &AppState, client: &ClientSession, invite: Ca
             tracing::warn!(?err, ?callee_id, "Failed to send call invite to target");
             if let CallTerminationOutcome::Failed(_) = state.calls.call_error(call_id, &callee_id) {
                 tracing::trace!(?callee_id, "All call attempts failed, returning call error");
+                state.release_call(call_id).await;
                 send_call_error(client, call_id, CallErrorReason::CallFailure, None).await;
                 return;
             }
@@ -147,6 +315,206 @@ async fn handle_call_invite(state: &AppState, client: &ClientSession, invite: Ca
     }
 }
 
+/// Returns [`ErrorReason::Muted`] if the client has been muted by a supervisor. Failures to look
+/// up the mute are logged, but don't prevent the client from communicating.
+async fn check_mute(state: &AppState, client: &ClientSession) -> Result<(), ErrorReason> {
+    match state.get_mute(client.id()).await {
+        Ok(Some(mute)) => Err(ErrorReason::Muted {
+            reason: mute.reason,
+            expires_at: mute.expires_at,
+        }),
+        Ok(None) => Ok(()),
+        Err(err) => {
+            tracing::warn!(?err, "Failed to check for active mute");
+            Ok(())
+        }
+    }
+}
+
+/// Handles a kick, ban or mute sent by a supervisor.
+#[tracing::instrument(level = "trace", skip(state, client))]
+async fn handle_moderation_message(
+    state: &AppState,
+    client: &ClientSession,
+    message: ClientMessage,
+) {
+    tracing::trace!("Handling moderation message");
+
+    let is_supervisor = match state.user_roles(client.id()).await {
+        Ok(roles) => client.scopes().is_none() && roles.has(Role::Supervisor),
+        Err(err) => {
+            tracing::warn!(?err, "Failed to get user roles");
+            false
+        }
+    };
+    if !is_supervisor {
+        tracing::debug!("Client is not a supervisor, rejecting moderation message");
+        client
+            .send_error(ErrorReason::UnexpectedMessage(
+                "supervisor role required".to_string(),
+            ))
+            .await;
+        return;
+    }
+
+    let result = match message {
+        ClientMessage::KickClient(KickClient { client_id, reason }) => {
+            if !validate_moderation(client, &client_id, &reason, None).await {
+                return;
+            }
+            if !state.kick_client(client.id(), &client_id, reason).await {
+                tracing::debug!(?client_id, "Kicked client is not connected");
+                client
+                    .send_error(
+                        shared::Error::from(ErrorReason::ClientNotFound).with_client_id(client_id),
+                    )
+                    .await;
+            }
+            Ok(())
+        }
+        ClientMessage::BanClient(BanClient {
+            client_id,
+            reason,
+            duration_secs,
+        }) => {
+            if !validate_moderation(client, &client_id, &reason, Some(duration_secs)).await {
+                return;
+            }
+            let duration = Duration::from_secs(duration_secs);
+            state
+                .ban_client(client.id(), &client_id, reason, duration)
+                .await
+                .map(|_| ())
+        }
+        ClientMessage::MuteClient(MuteClient {
+            client_id,
+            reason,
+            duration_secs,
+        }) => {
+            if !validate_moderation(client, &client_id, &reason, Some(duration_secs)).await {
+                return;
+            }
+            let duration = Duration::from_secs(duration_secs);
+            state
+                .mute_client(client.id(), &client_id, reason, duration)
+                .await
+                .map(|_| ())
+        }
+        _ => Ok(()),
+    };
+    if let Err(err) = result {
+        tracing::warn!(?err, "Failed to apply moderation action");
+        client
+            .send_error(ErrorReason::Internal(
+                "failed to apply moderation action".to_string(),
+            ))
+            .await;
+    }
+}
+
+/// Checks that a moderation action has a reason and, if time-limited, a non-zero duration.
+/// Sends a malformed message error to the supervisor otherwise.
+async fn validate_moderation(
+    client: &ClientSession,
+    client_id: &ClientId,
+    reason: &str,
+    duration_secs: Option<u64>,
+) -> bool {
+    if reason.trim().is_empty() || duration_secs == Some(0) {
+        tracing::debug!("Empty reason or duration, rejecting moderation message");
+        ErrorMetrics::error(&ErrorReason::MalformedMessage);
+        client
+            .send_error(
+                shared::Error::from(ErrorReason::MalformedMessage)
+                    .with_client_id(client_id.clone()),
+            )
+            .await;
+        return false;
+    }
+    true
+}
+
+#[tracing::instrument(level = "trace", skip(state, client))]
+async fn handle_text_message(state: &AppState, client: &ClientSession, message: TextMessage) {
+    tracing::trace!("Handling text message");
+    let sender_id = client.id();
+
+    if let Err(until) = state.rate_limiters().check_text_message(sender_id).await {
+        tracing::debug!(?until, "Rate limit exceeded, rejecting text message");
+        let reason = ErrorReason::RateLimited {
+            retry_after_secs: until.as_secs(),
+        };
+        ErrorMetrics::error(&reason);
+        client.send_error(reason).await;
+        return;
+    }
+
+    if let Err(reason) = check_mute(state, client).await {
+        tracing::debug!("Client is muted, rejecting text message");
+        ErrorMetrics::error(&reason);
+        client.send_error(reason).await;
+        return;
+    }
+
+    if message.source.client_id != *sender_id {
+        tracing::debug!("Source client ID mismatch, rejecting text message");
+        client
+            .send_error(ErrorReason::UnexpectedMessage(
+                "Source client ID mismatch".to_string(),
+            ))
+            .await;
+        return;
+    }
+
+    if !message.is_valid() {
+        tracing::debug!(
+            len = message.text.len(),
+            "Invalid text, rejecting text message"
+        );
+        ErrorMetrics::error(&ErrorReason::MalformedMessage);
+        client.send_error(ErrorReason::MalformedMessage).await;
+        return;
+    }
+
+    let target_clients = resolve_target_clients(state, client, &message.target).await;
+    if target_clients.is_empty() {
+        tracing::trace!("No clients found for text message, returning client not found error");
+        client.send_error(ErrorReason::ClientNotFound).await;
+        return;
+    }
+
+    for recipient_id in target_clients {
+        tracing::trace!(?recipient_id, "Sending text message to target");
+        if let Err(err) = state.send_message(&recipient_id, message.clone()).await {
+            tracing::warn!(?err, ?recipient_id, "Failed to send text message to target");
+        }
+    }
+}
+
+/// Returns all clients covering the given target, excluding the requesting client.
+async fn resolve_target_clients(
+    state: &AppState,
+    client: &ClientSession,
+    target: &CallTarget,
+) -> HashSet<ClientId> {
+    match target {
+        CallTarget::Client(client_id) => {
+            if state.clients.is_client_connected(client_id).await
+                || state.clients.remote_client(client_id).is_some()
+            {
+                HashSet::from([client_id.clone()])
+            } else {
+                HashSet::new()
+            }
+        }
+        CallTarget::Position(position_id) => state.clients.clients_for_position(position_id).await,
+        CallTarget::Station(station_id) => state.clients.clients_for_station(station_id).await,
+    }
+    .into_iter()
+    .filter(|client_id| client_id != client.id())
+    .collect()
+}
+
 #[tracing::instrument(level = "trace", skip(state, client))]
 async fn handle_call_accept(state: &AppState, client: &ClientSession, accept: CallAccept) {
     tracing::trace!("Handling call acceptance");
@@ -171,6 +539,22 @@ async fn handle_call_accept(state: &AppState, client: &ClientSession, accept: Ca
         return;
     };
 
+    if state.sfu.should_route(&ringing.caller_id, answerer_id)
+        && let Some(caller) = state.get_client(&ringing.caller_id).await
+    {
+        tracing::trace!("Routing call media through SFU");
+        if let Err(err) = state
+            .sfu
+            .open_session(*call_id, caller, client.clone())
+            .await
+        {
+            tracing::warn!(
+                ?err,
+                "Failed to open SFU session, falling back to peer-to-peer media"
+            );
+        }
+    }
+
     tracing::trace!("Sending call accept to source client");
     if let Err(err) = state.send_message(&ringing.caller_id, accept.clone()).await {
         tracing::warn!(?err, "Failed to send call accept to source client");
@@ -238,6 +622,7 @@ async fn handle_call_reject(state: &AppState, client: &ClientSession, reject: Ca
             tracing::trace!(
                 "All notified clients either rejected or errored, call failed, sending call error to source client"
             );
+            state.release_call(call_id).await;
             // TODO send CallCancelled to all notified, just in case?
             if let Err(err) = state
                 .send_message(
@@ -273,6 +658,7 @@ async fn handle_call_end(state: &AppState, client: &ClientSession, end: CallEnd)
 
     if let Some(ringing) = state.calls.end_ringing_call(call_id, ender_id) {
         tracing::trace!("Ringing call found, canceling");
+        state.release_call(call_id).await;
         let cancelled = server::CallCancelled::new(*call_id, CallCancelReason::CallerCancelled);
 
         for callee_id in ringing.notified_clients {
@@ -287,6 +673,8 @@ async fn handle_call_end(state: &AppState, client: &ClientSession, end: CallEnd)
         }
     } else if let Some(active) = state.calls.end_active_call(call_id, ender_id) {
         tracing::trace!("Active call found, ending");
+        state.sfu.close_session(call_id).await;
+        state.release_call(call_id).await;
         if let Some(peer_id) = active.peer(ender_id) {
             tracing::trace!(?peer_id, "Sending call end to peer");
             if let Err(err) = state.send_message(peer_id, end.clone()).await {
@@ -327,6 +715,7 @@ async fn handle_call_error(state: &AppState, client: &ClientSession, error: Call
             tracing::trace!(
                 "All notified clients either rejected or errored, call failed, sending call error to source client"
             );
+            state.release_call(call_id).await;
             // TODO send CallCancelled to all notified, just in case?
             if let Err(err) = state
                 .send_message(
@@ -366,6 +755,14 @@ async fn handle_webrtc_offer(state: &AppState, client: &ClientSession, offer: We
         return;
     }
 
+    if let Some(session) = state.sfu.session(call_id) {
+        if let Err(err) = session.handle_offer(offer).await {
+            tracing::warn!(?err, "Failed to handle WebRTC offer in SFU session");
+            send_call_error(client, call_id, CallErrorReason::SignalingFailure, None).await;
+        }
+        return;
+    }
+
     if let Err(err) = state.send_message(&offer.to_client_id, offer.clone()).await {
         tracing::warn!(?err, "Failed to send WebRTC offer to peer");
         send_call_error(client, call_id, CallErrorReason::SignalingFailure, None).await;
@@ -396,6 +793,14 @@ async fn handle_webrtc_answer(state: &AppState, client: &ClientSession, answer:
         return;
     }
 
+    if let Some(session) = state.sfu.session(call_id) {
+        if let Err(err) = session.handle_answer(answer).await {
+            tracing::warn!(?err, "Failed to handle WebRTC answer in SFU session");
+            send_call_error(client, call_id, CallErrorReason::SignalingFailure, None).await;
+        }
+        return;
+    }
+
     if let Err(err) = state
         .send_message(&answer.to_client_id, answer.clone())
         .await
@@ -433,6 +838,14 @@ async fn handle_webrtc_ice_candidate(
         return;
     }
 
+    if let Some(session) = state.sfu.session(call_id) {
+        if let Err(err) = session.handle_ice_candidate(ice_candidate).await {
+            tracing::warn!(?err, "Failed to handle WebRTC ice candidate in SFU session");
+            send_call_error(client, call_id, CallErrorReason::SignalingFailure, None).await;
+        }
+        return;
+    }
+
     if let Err(err) = state
         .send_message(&ice_candidate.to_client_id, ice_candidate.clone())
         .await
@@ -469,6 +882,7 @@ mod tests {
     use test_log::test;
     use vacs_protocol::vatsim::ClientId;
     use vacs_protocol::ws::server::{self, ServerMessage};
+    use vacs_protocol::ws::shared::{CallSource, Capabilities, Presence};
 
     #[test(tokio::test)]
     async fn handle_application_message_list_clients_without_self() {
@@ -486,7 +900,7 @@ mod tests {
         let message = setup.rx.recv().await.expect("No message received");
         assert_matches!(
             message,
-            ServerMessage::ClientList(server::ClientList { clients }) if clients.is_empty()
+            ServerMessage::ClientList(server::ClientList { clients, .. }) if clients.is_empty()
         );
     }
 
@@ -528,10 +942,231 @@ mod tests {
         let message = setup.rx.recv().await.expect("No message received");
         assert_matches!(
             message,
-            ServerMessage::ClientList(server::ClientList { clients }) if clients == vec![client_2]
+            ServerMessage::ClientList(server::ClientList { clients, .. }) if clients == vec![client_2]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn handle_application_message_list_client_changes() {
+        let mut setup = TestSetup::new();
+        setup.register_client(create_client_info(1)).await;
+        let revision = setup.app_state.clients.roster_revision();
+        let client_2 = create_client_info(2);
+        setup.register_client(client_2.clone()).await;
+
+        let control_flow = handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::ListClientChanges(ListClientChanges {
+                since_revision: revision,
+            }),
+        )
+        .await;
+        assert_eq!(control_flow, ControlFlow::Continue(()));
+
+        let message = setup.rx.recv().await.expect("No message received");
+        assert_eq!(
+            message,
+            ServerMessage::ClientChanges(server::ClientChanges {
+                revision: revision + 1,
+                changes: vec![server::ClientChange::Connected { client: client_2 }],
+            })
         );
     }
 
+    #[test(tokio::test)]
+    async fn handle_application_message_list_client_changes_unknown_revision() {
+        let mut setup = TestSetup::new();
+        setup.register_client(create_client_info(1)).await;
+        let client_2 = create_client_info(2);
+        setup.register_client(client_2.clone()).await;
+
+        let control_flow = handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::ListClientChanges(ListClientChanges { since_revision: 0 }),
+        )
+        .await;
+        assert_eq!(control_flow, ControlFlow::Continue(()));
+
+        let message = setup.rx.recv().await.expect("No message received");
+        assert_eq!(
+            message,
+            ServerMessage::ClientList(server::ClientList {
+                clients: vec![client_2],
+                revision: setup.app_state.clients.roster_revision(),
+            })
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn handle_application_message_capability_not_negotiated() {
+        let mut setup = TestSetup::new();
+        setup.session.set_capabilities(Capabilities::default());
+        setup.register_client(create_client_info(1)).await;
+
+        let control_flow = handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::ListClientChanges(ListClientChanges { since_revision: 0 }),
+        )
+        .await;
+        assert_eq!(control_flow, ControlFlow::Continue(()));
+
+        let message = setup.rx.recv().await.expect("No message received");
+        assert_matches!(
+            message,
+            ServerMessage::Error(shared::Error {
+                reason: ErrorReason::UnexpectedMessage(_),
+                ..
+            })
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn handle_application_message_set_presence() {
+        let setup = TestSetup::new();
+        setup.register_client(create_client_info(1)).await;
+
+        let control_flow = handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::SetPresence(SetPresence {
+                presence: Presence::Busy,
+            }),
+        )
+        .await;
+        assert_eq!(control_flow, ControlFlow::Continue(()));
+        assert_eq!(
+            setup
+                .app_state
+                .clients
+                .client_presence(setup.session.id())
+                .await,
+            Some(Presence::Busy)
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn handle_call_invite_do_not_disturb() {
+        let mut setup = TestSetup::new();
+        setup.register_client(create_client_info(1)).await;
+        let (_client_2, mut rx_2) = setup.register_client(create_client_info(2)).await;
+        setup
+            .app_state
+            .clients
+            .set_client_presence(&ClientId::from("client2"), Presence::DoNotDisturb)
+            .await;
+
+        let invite = |prio| CallInvite {
+            call_id: CallId::new(),
+            source: CallSource::new(ClientId::from("client1")),
+            target: CallTarget::Client(ClientId::from("client2")),
+            prio,
+        };
+
+        let call_invite = invite(false);
+        handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::CallInvite(call_invite.clone()),
+        )
+        .await;
+        assert_eq!(
+            setup.rx.recv().await.expect("No message received"),
+            ServerMessage::CallCancelled(server::CallCancelled::new(
+                call_invite.call_id,
+                CallCancelReason::Rejected(CallRejectReason::DoNotDisturb),
+            ))
+        );
+        assert!(rx_2.try_recv().is_err());
+
+        let call_invite = invite(true);
+        handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::CallInvite(call_invite.clone()),
+        )
+        .await;
+        assert_eq!(
+            rx_2.recv().await.expect("No message received"),
+            ServerMessage::CallInvite(call_invite)
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn handle_call_invite_do_not_disturb_without_presence_capability() {
+        let mut setup = TestSetup::new();
+        setup.session.set_capabilities(Capabilities::default());
+        setup.register_client(create_client_info(1)).await;
+        let (_client_2, mut rx_2) = setup.register_client(create_client_info(2)).await;
+        setup
+            .app_state
+            .clients
+            .set_client_presence(&ClientId::from("client2"), Presence::DoNotDisturb)
+            .await;
+
+        let call_invite = CallInvite {
+            call_id: CallId::new(),
+            source: CallSource::new(ClientId::from("client1")),
+            target: CallTarget::Client(ClientId::from("client2")),
+            prio: false,
+        };
+        handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::CallInvite(call_invite.clone()),
+        )
+        .await;
+        assert_eq!(
+            setup.rx.recv().await.expect("No message received"),
+            ServerMessage::CallCancelled(server::CallCancelled::new(
+                call_invite.call_id,
+                CallCancelReason::Rejected(CallRejectReason::Busy),
+            ))
+        );
+        assert!(rx_2.try_recv().is_err());
+    }
+
+    #[test(tokio::test)]
+    async fn handle_call_invite_muted() {
+        let mut setup = TestSetup::new();
+        setup.register_client(create_client_info(1)).await;
+        let (_client_2, mut rx_2) = setup.register_client(create_client_info(2)).await;
+        setup
+            .app_state
+            .mute_client(
+                &ClientId::from("cid0"),
+                &ClientId::from("client1"),
+                "Spamming".to_string(),
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+
+        let call_invite = CallInvite {
+            call_id: CallId::new(),
+            source: CallSource::new(ClientId::from("client1")),
+            target: CallTarget::Client(ClientId::from("client2")),
+            prio: false,
+        };
+        handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::CallInvite(call_invite.clone()),
+        )
+        .await;
+        assert_matches!(
+            setup.rx.recv().await.expect("No message received"),
+            ServerMessage::Error(shared::Error {
+                reason: ErrorReason::Muted { reason, .. },
+                call_id: Some(call_id),
+                ..
+            }) if reason == "Spamming" && call_id == call_invite.call_id
+        );
+        assert!(rx_2.try_recv().is_err());
+    }
+
     #[test(tokio::test)]
     async fn handle_application_message_logout() {
         let setup = TestSetup::new();
@@ -543,6 +1178,56 @@ mod tests {
         assert_eq!(control_flow, ControlFlow::Break(()));
     }
 
+    #[test(tokio::test)]
+    async fn handle_text_message() {
+        let setup = TestSetup::new();
+        setup.register_client(create_client_info(1)).await;
+        let (_client_2, mut rx_2) = setup.register_client(create_client_info(2)).await;
+
+        let text_message = TextMessage::new(
+            CallSource::new(ClientId::from("client1")),
+            CallTarget::Client(ClientId::from("client2")),
+            "request release RWY 29",
+        );
+        let control_flow = handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::TextMessage(text_message.clone()),
+        )
+        .await;
+        assert_eq!(control_flow, ControlFlow::Continue(()));
+        assert_eq!(
+            rx_2.recv().await.expect("No message received"),
+            ServerMessage::TextMessage(text_message)
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn handle_text_message_too_long() {
+        let mut setup = TestSetup::new();
+        setup.register_client(create_client_info(1)).await;
+        let (_client_2, mut rx_2) = setup.register_client(create_client_info(2)).await;
+
+        handle_application_message(
+            &setup.app_state,
+            &setup.session,
+            ClientMessage::TextMessage(TextMessage::new(
+                CallSource::new(ClientId::from("client1")),
+                CallTarget::Client(ClientId::from("client2")),
+                "a".repeat(TextMessage::MAX_LENGTH + 1),
+            )),
+        )
+        .await;
+        assert_matches!(
+            setup.rx.recv().await.expect("No message received"),
+            ServerMessage::Error(shared::Error {
+                reason: ErrorReason::MalformedMessage,
+                ..
+            })
+        );
+        assert!(rx_2.try_recv().is_err());
+    }
+
     #[test(tokio::test)]
     async fn handle_application_message_call_offer() {
         let setup = TestSetup::new();
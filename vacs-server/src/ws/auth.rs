@@ -1,5 +1,7 @@
+use crate::auth::scopes::{self, Scope, Scopes};
 use crate::metrics::{ClientMetrics, ErrorMetrics, ProfileMetrics, VatsimSyncMetrics};
 use crate::state::AppState;
+use crate::state::tokens::WsAuthToken;
 use crate::ws::message::{MessageResult, receive_message, send_message_raw};
 use axum::extract::ws;
 use axum::extract::ws::WebSocket;
@@ -12,23 +14,49 @@ use vacs_protocol::profile::{ActiveProfile, ProfileId};
 use vacs_protocol::vatsim::{ClientId, PositionId};
 use vacs_protocol::ws::client::ClientMessage;
 use vacs_protocol::ws::server::{ClientInfo, LoginFailureReason};
-use vacs_protocol::ws::shared::ErrorReason;
-use vacs_protocol::ws::{server, shared};
+use vacs_protocol::ws::shared::{Capabilities, ErrorReason, Presence};
+use vacs_protocol::ws::{Encoding, server, shared};
 use vacs_vatsim::{ControllerInfo, FacilityType};
 
+/// Outcome of a successful websocket login flow.
+#[derive(Debug)]
+pub struct AuthenticatedLogin {
+    pub client_info: ClientInfo,
+    pub active_profile: ActiveProfile<ProfileId>,
+    /// Resume token of a previous session provided by the client, if any.
+    pub resume_token: Option<String>,
+    /// Encoding requested by the client for all messages after the login.
+    pub encoding: Encoding,
+    /// Whether the client requested compression of all messages after the login.
+    pub compression: bool,
+    /// Capabilities supported by the client, not yet negotiated with the server.
+    pub capabilities: Capabilities,
+    /// Scopes of the API token the websocket token was requested with. `None` grants full access.
+    pub scopes: Option<Scopes>,
+}
+
 #[instrument(level = "debug", skip_all)]
 pub async fn handle_websocket_login(
     state: Arc<AppState>,
     websocket_receiver: &mut SplitStream<WebSocket>,
     websocket_sender: &mut SplitSink<WebSocket, ws::Message>,
-) -> Option<(ClientInfo, ActiveProfile<ProfileId>)> {
+) -> Option<AuthenticatedLogin> {
     tracing::trace!("Handling websocket login flow");
 
     let result = tokio::time::timeout(Duration::from_millis(state.config.auth.login_flow_timeout_millis), async {
         loop {
             match receive_message(websocket_receiver).await {
                 MessageResult::ApplicationMessage(ClientMessage::Login (login)) => {
-                    return process_login_request(&state, &login.token, &login.protocol_version, login.custom_profile, login.position_id).await;
+                    return process_login_request(&state, &login.token, &login.protocol_version, login.custom_profile, login.position_id).await
+                        .map(|(client_info, active_profile, scopes)| AuthenticatedLogin {
+                            client_info,
+                            active_profile,
+                            scopes,
+                            resume_token: login.resume_token,
+                            encoding: login.encoding,
+                            compression: login.compression,
+                            capabilities: login.capabilities,
+                        });
                 }
                 MessageResult::ApplicationMessage(message) => {
                     tracing::debug!(msg = ?message, "Received unexpected message during websocket login flow");
@@ -51,7 +79,7 @@ pub async fn handle_websocket_login(
     }).await;
 
     match result {
-        Ok(Ok((client_info, active_profile))) => Some((client_info, active_profile)),
+        Ok(Ok(login)) => Some(login),
         Ok(Err(outcome)) => {
             handle_login_outcome(websocket_sender, outcome).await;
             None
@@ -81,7 +109,7 @@ async fn process_login_request(
     protocol_version: &str,
     custom_profile: bool,
     position_id: Option<PositionId>,
-) -> Result<(ClientInfo, ActiveProfile<ProfileId>), LoginOutcome> {
+) -> Result<(ClientInfo, ActiveProfile<ProfileId>, Option<Scopes>), LoginOutcome> {
     if !is_protocol_compatible(state, protocol_version) {
         tracing::debug!("Websocket login flow failed, due to incompatible protocol version");
         return Err(LoginOutcome::Failure(
@@ -89,11 +117,39 @@ async fn process_login_request(
         ));
     }
 
-    let cid = state.verify_ws_auth_token(token).await.map_err(|err| {
+    let WsAuthToken { cid, scopes } = state.verify_ws_auth_token(token).await.map_err(|err| {
         tracing::debug!(?err, "Websocket login flow failed");
         LoginOutcome::Failure(LoginFailureReason::InvalidCredentials)
     })?;
 
+    // websocket tokens are only issued for API tokens granting `ws:login`, verify once more in
+    // case the token was issued differently
+    if !scopes::grants(scopes.as_ref(), Scope::WsLogin) {
+        tracing::debug!(
+            ?cid,
+            ?scopes,
+            "Websocket login flow failed, missing ws:login scope"
+        );
+        return Err(LoginOutcome::Failure(LoginFailureReason::Unauthorized));
+    }
+
+    match state.get_ban(&cid).await {
+        Ok(Some(ban)) => {
+            tracing::debug!(?cid, ?ban, "Websocket login flow failed, client is banned");
+            return Err(LoginOutcome::Failure(LoginFailureReason::Banned {
+                reason: ban.reason,
+                expires_at: ban.expires_at,
+            }));
+        }
+        Ok(None) => {}
+        Err(err) => {
+            tracing::warn!(?err, ?cid, "Failed to check for active ban");
+            return Err(LoginOutcome::Error(ErrorReason::Internal(
+                "Failed to check for active ban".to_string(),
+            )));
+        }
+    }
+
     if !state.config.vatsim.require_active_connection {
         tracing::trace!(
             ?cid,
@@ -119,16 +175,19 @@ async fn process_login_request(
             position_id: position.map(|p| p.id),
             display_name: cid.to_string(),
             frequency: "".to_string(),
+            presence: Presence::default(),
         };
         ProfileMetrics::profile_activated(&active_profile);
-        return Ok((client_info, active_profile));
+        return Ok((client_info, active_profile, scopes));
     }
 
     tracing::trace!(
         ?cid,
         "Websocket token verified, checking for active VATSIM connection"
     );
-    resolve_vatsim_position(state, cid, custom_profile, position_id).await
+    resolve_vatsim_position(state, cid, custom_profile, position_id)
+        .await
+        .map(|(client_info, active_profile)| (client_info, active_profile, scopes))
 }
 
 fn is_protocol_compatible(state: &AppState, protocol_version: &str) -> bool {
@@ -212,6 +271,7 @@ async fn resolve_vatsim_position(
                     position_id: position.map(|p| p.id.clone()),
                     display_name: controller_info.callsign.clone(),
                     frequency: controller_info.frequency.clone(),
+                    presence: Presence::default(),
                 };
 
                 let active_profile = if custom_profile {
@@ -1,11 +1,13 @@
 use crate::metrics::ClientMetrics;
 use crate::metrics::guards::ClientConnectionGuard;
 use crate::state::AppState;
-use crate::ws::auth::handle_websocket_login;
-use crate::ws::message::send_message_raw;
+use crate::state::clients::{InteractionEnd, ResumedClient};
+use crate::ws::auth::{AuthenticatedLogin, handle_websocket_login};
+use crate::ws::message::{MessageFormat, send_message_raw};
 use axum::extract::ws::{CloseCode, CloseFrame, Message, Utf8Bytes, WebSocket};
 use axum::extract::{State, WebSocketUpgrade};
-use axum::response::IntoResponse;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
 use axum_client_ip::ClientIp;
 use futures_util::{SinkExt, StreamExt};
 use std::sync::Arc;
@@ -18,13 +20,26 @@ pub async fn ws_handler(
     ws: WebSocketUpgrade,
     ClientIp(ip): ClientIp,
     State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
+) -> Response {
+    if state.is_draining() {
+        tracing::debug!(client_ip = ?ip, "Refusing websocket connection while draining");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(
+                header::RETRY_AFTER,
+                state.config.server.drain_retry_after_secs.to_string(),
+            )],
+        )
+            .into_response();
+    }
+
     ws.on_upgrade(move |socket| {
         let span = tracing::trace_span!("websocket_connection", client_ip = ?ip, client_id = tracing::field::Empty);
         async move {
             handle_socket(socket, state).await;
         }.instrument(span)
     })
+    .into_response()
 }
 
 async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
@@ -33,18 +48,47 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
 
     let (mut websocket_tx, mut websocket_rx) = socket.split();
 
-    let Some((client_info, active_profile)) =
-        handle_websocket_login(state.clone(), &mut websocket_rx, &mut websocket_tx).await
+    let Some(AuthenticatedLogin {
+        client_info,
+        active_profile,
+        resume_token,
+        encoding,
+        compression,
+        capabilities,
+        scopes,
+    }) = handle_websocket_login(state.clone(), &mut websocket_rx, &mut websocket_tx).await
     else {
         return;
     };
 
     tracing::Span::current().record("client_id", tracing::field::display(&client_info.id));
 
-    let res = state
-        .register_client(client_info, active_profile, client_connection_guard)
-        .await;
-    let (mut client, mut rx) = match res {
+    let res = match state
+        .resume_client(&client_info.id, resume_token.as_deref())
+        .await
+    {
+        Some(ResumedClient {
+            client,
+            rx,
+            pending,
+        }) => Ok((client, rx, pending)),
+        None => state
+            .register_client(
+                client_info,
+                active_profile,
+                resume_token.as_deref(),
+                client_connection_guard,
+            )
+            .await
+            .map(
+                |ResumedClient {
+                     client,
+                     rx,
+                     pending,
+                 }| (client, rx, pending),
+            ),
+    };
+    let (mut client, mut rx, pending) = match res {
         Ok(client) => client,
         Err(_) => {
             ClientMetrics::login_attempt(false);
@@ -76,9 +120,19 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
 
     ClientMetrics::login_attempt(true);
 
+    let capabilities = state.config.server.capabilities().negotiate(&capabilities);
+    tracing::debug!(?capabilities, "Negotiated capabilities");
+    client.set_capabilities(capabilities);
+    client.set_scopes(scopes);
+
     let (mut broadcast_rx, mut shutdown_rx) = state.get_client_receivers();
 
-    client
+    let format = MessageFormat {
+        encoding,
+        compressed: compression && state.config.server.websocket_compression,
+    };
+
+    let end = client
         .handle_interaction(
             &state,
             websocket_rx,
@@ -86,10 +140,15 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
             &mut broadcast_rx,
             &mut rx,
             &mut shutdown_rx,
+            pending,
+            format,
         )
         .await;
 
-    state.unregister_client(client.id(), None).await;
+    match end {
+        InteractionEnd::ConnectionLost => state.detach_client(client, rx).await,
+        InteractionEnd::Closed => state.unregister_client(client.id(), None).await,
+    }
 
     tracing::trace!("Finished handling websocket connection");
 }
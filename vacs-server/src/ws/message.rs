@@ -5,6 +5,7 @@ use futures_util::{SinkExt, StreamExt};
 use tokio::sync::mpsc;
 use vacs_protocol::ws::client::ClientMessage;
 use vacs_protocol::ws::server::ServerMessage;
+use vacs_protocol::ws::{Encoding, compress};
 
 /// Represents the outcome of [`receive_message`], indicating whether the message received should be handled, skipped, or receiving errored.
 #[derive(Debug)]
@@ -34,52 +35,92 @@ impl PartialEq for MessageResult {
     }
 }
 
+/// Format of messages sent to a client, negotiated during login.
+///
+/// Compression only applies to messages sent by the server. The websocket stack doesn't support
+/// the permessage-deflate extension, so large server messages like client and station lists are
+/// compressed on the application level instead. Client messages are small and always received
+/// uncompressed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MessageFormat {
+    pub encoding: Encoding,
+    /// Whether messages sent to the client are compressed, see [`vacs_protocol::ws::compress`].
+    pub compressed: bool,
+}
+
+/// Sends a message to the client's outbound channel, encoded in the session's negotiated
+/// [`MessageFormat`].
 pub async fn send_message(
     ws_outbound_tx: &mpsc::Sender<ws::Message>,
+    format: MessageFormat,
     message: impl Into<ServerMessage>,
 ) -> anyhow::Result<()> {
-    let message = message.into();
-    let serialized_message = ServerMessage::serialize(&message)
-        .map_err(|e| anyhow::anyhow!(e).context("Failed to serialize message"))?;
-    MessageMetrics::sent(&message, serialized_message.len());
+    let message = encode_message(&message.into(), format)?;
     ws_outbound_tx
-        .send(ws::Message::from(serialized_message))
+        .send(message)
         .await
         .map_err(|e| anyhow::anyhow!(e).context("Failed to send message"))?;
     Ok(())
 }
 
+/// Sends a message directly to the websocket sink. Only used before a message format has been
+/// negotiated during login, so messages are always sent as uncompressed JSON.
 pub async fn send_message_raw<T: WebSocketSink>(
     websocket_tx: &mut T,
     message: impl Into<ServerMessage>,
 ) -> anyhow::Result<()> {
-    let message = message.into();
-    let serialized_message = ServerMessage::serialize(&message)
-        .map_err(|e| anyhow::anyhow!(e).context("Failed to serialize message"))?;
-    MessageMetrics::sent(&message, serialized_message.len());
+    let message = encode_message(&message.into(), MessageFormat::default())?;
     websocket_tx
-        .send(ws::Message::from(serialized_message))
+        .send(message)
         .await
         .map_err(|e| anyhow::anyhow!(e).context("Failed to send message"))?;
     Ok(())
 }
 
-pub async fn receive_message<R: WebSocketStream>(websocket_rx: &mut R) -> MessageResult {
-    match websocket_rx.next().await {
-        Some(Ok(ws::Message::Text(raw_message))) => {
-            match ClientMessage::deserialize(&raw_message) {
-                Ok(message) => {
-                    MessageMetrics::received(&message, raw_message.len());
-                    MessageResult::ApplicationMessage(message)
-                }
-                Err(err) => {
-                    MessageMetrics::malformed();
-                    MessageResult::Error(
-                        anyhow::anyhow!(err).context("Failed to deserialize message"),
-                    )
-                }
+/// Encodes a message in the given format. Uncompressed JSON messages are sent as text frames, all
+/// other messages as binary frames.
+pub fn encode_message(
+    message: &ServerMessage,
+    format: MessageFormat,
+) -> anyhow::Result<ws::Message> {
+    let serialized_message = match format.encoding {
+        Encoding::Json => {
+            let serialized_message = ServerMessage::serialize(message)
+                .map_err(|e| anyhow::anyhow!(e).context("Failed to serialize message"))?;
+            if !format.compressed {
+                MessageMetrics::sent(message, serialized_message.len());
+                return Ok(ws::Message::from(serialized_message));
             }
+            serialized_message.into_bytes()
         }
+        Encoding::MessagePack => ServerMessage::serialize_msgpack(message)
+            .map_err(|e| anyhow::anyhow!(e).context("Failed to serialize message"))?,
+    };
+
+    if format.compressed {
+        let compressed_message = compress(&serialized_message)
+            .map_err(|e| anyhow::anyhow!(e).context("Failed to compress message"))?;
+        MessageMetrics::compressed(serialized_message.len(), compressed_message.len());
+        MessageMetrics::sent(message, compressed_message.len());
+        return Ok(ws::Message::from(compressed_message));
+    }
+
+    MessageMetrics::sent(message, serialized_message.len());
+    Ok(ws::Message::from(serialized_message))
+}
+
+/// Receives the next message from the websocket stream. Text frames are decoded as JSON and
+/// binary frames as MessagePack, independent of the negotiated [`Encoding`].
+pub async fn receive_message<R: WebSocketStream>(websocket_rx: &mut R) -> MessageResult {
+    match websocket_rx.next().await {
+        Some(Ok(ws::Message::Text(raw_message))) => decode_message(
+            ClientMessage::deserialize(&raw_message).map_err(anyhow::Error::from),
+            raw_message.len(),
+        ),
+        Some(Ok(ws::Message::Binary(raw_message))) => decode_message(
+            ClientMessage::deserialize_msgpack(&raw_message).map_err(anyhow::Error::from),
+            raw_message.len(),
+        ),
         Some(Ok(ws::Message::Ping(_))) => MessageResult::ControlMessage,
         Some(Ok(ws::Message::Pong(_))) => MessageResult::ControlMessage,
         Some(Ok(ws::Message::Close(reason))) => {
@@ -101,6 +142,19 @@ pub async fn receive_message<R: WebSocketStream>(websocket_rx: &mut R) -> Messag
     }
 }
 
+fn decode_message(result: anyhow::Result<ClientMessage>, len: usize) -> MessageResult {
+    match result {
+        Ok(message) => {
+            MessageMetrics::received(&message, len);
+            MessageResult::ApplicationMessage(message)
+        }
+        Err(err) => {
+            MessageMetrics::malformed();
+            MessageResult::Error(err.context("Failed to deserialize message"))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,7 +167,7 @@ mod tests {
     use uuid::Uuid;
     use vacs_protocol::vatsim::{ClientId, PositionId};
     use vacs_protocol::ws::server::{self, ClientInfo, ServerMessage};
-    use vacs_protocol::ws::shared::CallId;
+    use vacs_protocol::ws::shared::{CallEnd, CallId, Capabilities, Presence};
 
     #[test(tokio::test)]
     async fn send_single_message_raw() {
@@ -126,7 +180,9 @@ mod tests {
                 position_id: Some(PositionId::from("position1")),
                 display_name: "Client 1".to_string(),
                 frequency: "100.000".to_string(),
+                presence: Presence::default(),
             },
+            revision: 0,
         });
 
         assert!(
@@ -154,7 +210,10 @@ mod tests {
         let mut mock_sink = MockSink::new(tx);
 
         let messages = vec![
-            ServerMessage::ClientList(server::ClientList { clients: vec![] }),
+            ServerMessage::ClientList(server::ClientList {
+                clients: vec![],
+                revision: 0,
+            }),
             ServerMessage::StationList(server::StationList { stations: vec![] }),
             ServerMessage::Disconnected(server::Disconnected {
                 reason: server::DisconnectReason::Terminated,
@@ -187,7 +246,10 @@ mod tests {
         let mock_sink = Arc::new(Mutex::new(MockSink::new(tx)));
 
         let messages = vec![
-            ServerMessage::ClientList(server::ClientList { clients: vec![] }),
+            ServerMessage::ClientList(server::ClientList {
+                clients: vec![],
+                revision: 0,
+            }),
             ServerMessage::StationList(server::StationList { stations: vec![] }),
             ServerMessage::Disconnected(server::Disconnected {
                 reason: server::DisconnectReason::Terminated,
@@ -227,6 +289,96 @@ mod tests {
         assert_eq!(sent.len(), messages.len());
     }
 
+    #[test(tokio::test)]
+    async fn send_message_encoding() {
+        let (tx, mut rx) = mpsc::channel(100);
+        let message = ServerMessage::ClientDisconnected(server::ClientDisconnected {
+            client_id: ClientId::from("client1"),
+            revision: 0,
+        });
+
+        for encoding in [Encoding::Json, Encoding::MessagePack] {
+            send_message(
+                &tx,
+                MessageFormat {
+                    encoding,
+                    compressed: false,
+                },
+                message.clone(),
+            )
+            .await
+            .unwrap();
+        }
+
+        match rx.recv().await {
+            Some(ws::Message::Text(raw_message)) => {
+                assert_eq!(ServerMessage::deserialize(&raw_message).unwrap(), message);
+            }
+            other => panic!("Expected a Text message, got: {:?}", other),
+        }
+        match rx.recv().await {
+            Some(ws::Message::Binary(raw_message)) => {
+                assert_eq!(
+                    ServerMessage::deserialize_msgpack(&raw_message).unwrap(),
+                    message
+                );
+            }
+            other => panic!("Expected a Binary message, got: {:?}", other),
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn send_compressed_message() {
+        let (tx, mut rx) = mpsc::channel(100);
+        let message = ServerMessage::ClientDisconnected(server::ClientDisconnected {
+            client_id: ClientId::from("client1"),
+            revision: 0,
+        });
+
+        send_message(
+            &tx,
+            MessageFormat {
+                encoding: Encoding::Json,
+                compressed: true,
+            },
+            message.clone(),
+        )
+        .await
+        .unwrap();
+        send_message(
+            &tx,
+            MessageFormat {
+                encoding: Encoding::MessagePack,
+                compressed: true,
+            },
+            message.clone(),
+        )
+        .await
+        .unwrap();
+
+        match rx.recv().await {
+            Some(ws::Message::Binary(raw_message)) => {
+                let decompressed = vacs_protocol::ws::decompress(&raw_message).unwrap();
+                assert_eq!(
+                    ServerMessage::deserialize(std::str::from_utf8(&decompressed).unwrap())
+                        .unwrap(),
+                    message
+                );
+            }
+            other => panic!("Expected a Binary message, got: {:?}", other),
+        }
+        match rx.recv().await {
+            Some(ws::Message::Binary(raw_message)) => {
+                let decompressed = vacs_protocol::ws::decompress(&raw_message).unwrap();
+                assert_eq!(
+                    ServerMessage::deserialize_msgpack(&decompressed).unwrap(),
+                    message
+                );
+            }
+            other => panic!("Expected a Binary message, got: {:?}", other),
+        }
+    }
+
     #[test(tokio::test)]
     async fn send_message_sink_disconnected_raw() {
         let (tx, rx) = mpsc::channel(100);
@@ -239,7 +391,9 @@ mod tests {
                 position_id: Some(PositionId::from("position1")),
                 display_name: "Client 1".to_string(),
                 frequency: "100.000".to_string(),
+                presence: Presence::default(),
             },
+            revision: 0,
         });
 
         assert!(
@@ -265,6 +419,10 @@ mod tests {
                     protocol_version: "0.0.0".to_string(),
                     custom_profile: false,
                     position_id: None,
+                    resume_token: None,
+                    encoding: Encoding::Json,
+                    compression: false,
+                    capabilities: Capabilities::default(),
                 }
             ))
         );
@@ -290,6 +448,10 @@ mod tests {
                     protocol_version: "0.0.0".to_string(),
                     custom_profile: false,
                     position_id: None,
+                    resume_token: None,
+                    encoding: Encoding::Json,
+                    compression: false,
+                    capabilities: Capabilities::default(),
                 }
             ))
         );
@@ -355,6 +517,10 @@ mod tests {
                         protocol_version: "0.0.0".to_string(),
                         custom_profile: false,
                         position_id: None,
+                        resume_token: None,
+                        encoding: Encoding::Json,
+                        compression: false,
+                        capabilities: Capabilities::default(),
                     }
                 ))
             );
@@ -478,14 +644,28 @@ mod tests {
     }
 
     #[test(tokio::test)]
-    async fn receive_unexpected_message() {
+    async fn receive_msgpack_message() {
+        let message =
+            ClientMessage::CallEnd(CallEnd::new(CallId::new(), ClientId::from("client1")));
+        let mut mock_stream = MockStream::new(vec![Ok(ws::Message::Binary(
+            tungstenite::Bytes::from(message.serialize_msgpack().unwrap()),
+        ))]);
+
+        assert_eq!(
+            receive_message(&mut mock_stream).await,
+            MessageResult::ApplicationMessage(message)
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn receive_malformed_binary_message() {
         let mut mock_stream = MockStream::new(vec![Ok(ws::Message::Binary(
             tungstenite::Bytes::from("binary"),
         ))]);
 
         assert_eq!(
             receive_message(&mut mock_stream).await,
-            MessageResult::Error(anyhow::anyhow!("Received unexpected websocket message"))
+            MessageResult::Error(anyhow::anyhow!("Failed to deserialize message"))
         );
     }
 
@@ -1,3 +1,4 @@
+use crate::audit::memory::MemoryAuditSink;
 use crate::config::{AppConfig, VatsimConfig};
 use crate::ice::provider::stun::StunOnlyProvider;
 use crate::metrics::guards::ClientConnectionGuard;
@@ -7,6 +8,7 @@ use crate::state::AppState;
 use crate::state::clients::session::ClientSession;
 use crate::store::Store;
 use crate::store::memory::MemoryStore;
+use crate::ws::message::MessageFormat;
 use axum::extract::ws;
 use futures_util::{Sink, Stream};
 use std::collections::HashMap;
@@ -17,6 +19,7 @@ use tokio::sync::{Mutex, broadcast, mpsc, watch};
 use vacs_protocol::profile::{ActiveProfile, ProfileId};
 use vacs_protocol::vatsim::{ClientId, PositionId};
 use vacs_protocol::ws::server::{ClientInfo, ServerMessage};
+use vacs_protocol::ws::shared::{Capabilities, Presence};
 use vacs_vatsim::coverage::network::Network;
 use vacs_vatsim::data_feed::mock::MockDataFeed;
 use vacs_vatsim::slurper::SlurperClient;
@@ -108,6 +111,9 @@ impl TestSetup {
                 controller_update_interval: Default::default(),
                 data_feed_url: Default::default(),
                 data_feed_timeout: Default::default(),
+                data_feed_record_dir: None,
+                data_feed_replay_dir: None,
+                data_feed_replay_speed: 1.0,
                 coverage_dir: coverage_dir.path().to_str().unwrap().to_string(),
             },
             ..Default::default()
@@ -124,20 +130,23 @@ impl TestSetup {
             shutdown_rx,
             Arc::new(StunOnlyProvider::default()),
             None,
+            Arc::new(MemoryAuditSink::default()),
         ));
         let client_info = ClientInfo {
             id: ClientId::from("client1"),
             position_id: Some(PositionId::from("position1")),
             display_name: "Client 1".to_string(),
             frequency: "100.000".to_string(),
+            presence: Presence::default(),
         };
         let (tx, rx) = mpsc::channel(10);
-        let session = ClientSession::new(
+        let mut session = ClientSession::new(
             client_info,
             ActiveProfile::Specific(ProfileId::from("profile1")),
             tx,
             ClientConnectionGuard::default(),
         );
+        session.set_capabilities(Capabilities::all());
         let (websocket_tx, websocket_rx) = mpsc::channel(100);
         let mock_stream = MockStream::new(vec![]);
         let mock_sink = MockSink::new(websocket_tx.clone());
@@ -170,9 +179,11 @@ impl TestSetup {
             .register_client(
                 client_info,
                 ActiveProfile::Specific(ProfileId::from("profile1")),
+                None,
                 ClientConnectionGuard::default(),
             )
             .await
+            .map(|resumed| (resumed.client, resumed.rx))
             .expect("Failed to register client")
     }
 
@@ -185,9 +196,11 @@ impl TestSetup {
             .register_client(
                 client_info,
                 active_profile,
+                None,
                 ClientConnectionGuard::default(),
             )
             .await
+            .map(|resumed| (resumed.client, resumed.rx))
             .expect("Failed to register client")
     }
 
@@ -235,6 +248,8 @@ impl TestSetup {
                     &mut broadcast_rx,
                     &mut rx,
                     &mut shutdown_rx,
+                    Vec::new(),
+                    MessageFormat::default(),
                 )
                 .await;
         });
@@ -249,5 +264,6 @@ pub fn create_client_info(id: u8) -> ClientInfo {
         position_id: Some(PositionId::from(format!("position{id}"))),
         display_name: format!("Client {id}"),
         frequency: format!("{id}00.000"),
+        presence: Presence::default(),
     }
 }
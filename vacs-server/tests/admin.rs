@@ -0,0 +1,137 @@
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use pretty_assertions::{assert_eq, assert_matches};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use test_log::test;
+use vacs_protocol::ws::server::{Announcement, AnnouncementSeverity, ServerMessage};
+use vacs_server::store::memory::MemoryStore;
+use vacs_server::test_utils::{TestApp, setup_n_test_clients};
+use vacs_vatsim::coverage::network::Network;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const OIDC_ISSUER: &str = "https://token.actions.githubusercontent.com";
+const OIDC_SUBJECT: &str = "repo:vacs-project/vacs-data:environment:production";
+const PRIVATE_KEY: &[u8] = include_bytes!("fixtures/oidc/private_key.pem");
+const JWKS: &str = include_str!("fixtures/oidc/jwks.json");
+
+async fn test_app(jwks_server: &MockServer) -> TestApp {
+    let mut config = TestApp::config();
+    config.admin.oidc_allowed_sub = OIDC_SUBJECT.to_string();
+    config.admin.oidc_jwks_url = jwks_server.uri();
+    TestApp::new_with_config(config, Network::default()).await
+}
+
+async fn jwks_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(JWKS, "application/json"))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn oidc_token(subject: &str) -> String {
+    let exp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 300;
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some("test-key".to_string());
+    encode(
+        &header,
+        &json!({
+            "sub": subject,
+            "iss": OIDC_ISSUER,
+            "aud": "https://vacs.network",
+            "exp": exp,
+        }),
+        &EncodingKey::from_rsa_pem(PRIVATE_KEY).unwrap(),
+    )
+    .unwrap()
+}
+
+#[test(tokio::test)]
+async fn announce_broadcasts_to_clients() {
+    let jwks_server = jwks_server().await;
+    let app = test_app(&jwks_server).await;
+    let mut clients = setup_n_test_clients(app.addr(), 1).await;
+    let mut client1 = clients.pop().unwrap();
+    client1.recv_until_timeout(Duration::from_millis(100)).await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/admin/announcements", app.http_base_url()))
+        .bearer_auth(oidc_token(OIDC_SUBJECT))
+        .json(&json!({"message": "Server restart in 5 minutes", "severity": "warning"}))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let announcement: Announcement = resp.json().await.unwrap();
+    assert_eq!(announcement.severity, AnnouncementSeverity::Warning);
+    assert_eq!(announcement.message, "Server restart in 5 minutes");
+
+    let received = client1
+        .recv_with_timeout_and_filter(Duration::from_millis(100), |m| {
+            matches!(m, ServerMessage::Announcement(_))
+        })
+        .await;
+    assert_matches!(received, Some(ServerMessage::Announcement(a)) if a == announcement);
+}
+
+#[test(tokio::test)]
+async fn announce_without_auth() {
+    let jwks_server = jwks_server().await;
+    let app = test_app(&jwks_server).await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/admin/announcements", app.http_base_url()))
+        .json(&json!({"message": "Server restart in 5 minutes"}))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[test(tokio::test)]
+async fn announce_with_wrong_subject() {
+    let jwks_server = jwks_server().await;
+    let app = test_app(&jwks_server).await;
+    let mut clients = setup_n_test_clients(app.addr(), 1).await;
+    let mut client1 = clients.pop().unwrap();
+    client1.recv_until_timeout(Duration::from_millis(100)).await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/admin/announcements", app.http_base_url()))
+        .bearer_auth(oidc_token("repo:someone/else:environment:production"))
+        .json(&json!({"message": "Server restart in 5 minutes"}))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        client1.recv_until_timeout(Duration::from_millis(100)).await,
+        vec![]
+    );
+}
+
+#[test(tokio::test)]
+async fn announce_with_api_token() {
+    let jwks_server = jwks_server().await;
+    let app = test_app(&jwks_server).await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/admin/announcements", app.http_base_url()))
+        .bearer_auth(MemoryStore::test_api_token(0))
+        .json(&json!({"message": "Server restart in 5 minutes"}))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
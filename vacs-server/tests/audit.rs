@@ -0,0 +1,129 @@
+use pretty_assertions::assert_eq;
+use reqwest::StatusCode;
+use serde_json::json;
+use test_log::test;
+use vacs_protocol::vatsim::ClientId;
+use vacs_server::audit::{AuditEvent, AuditRecord, AuditSinkType};
+use vacs_server::auth::roles::Role;
+use vacs_server::store::memory::MemoryStore;
+use vacs_server::test_utils::TestApp;
+use vacs_vatsim::coverage::network::Network;
+
+async fn test_app(config: Option<vacs_server::config::AppConfig>) -> TestApp {
+    let mut config = config.unwrap_or_else(TestApp::config);
+    config
+        .auth
+        .roles
+        .insert(ClientId::from("cid0"), vec![Role::Admin]);
+    TestApp::new_with_config(config, Network::default()).await
+}
+
+#[test(tokio::test)]
+async fn admin_queries_audit_log() {
+    let app = test_app(None).await;
+    let client = reqwest::Client::new();
+
+    let (token, _) = app
+        .state()
+        .generate_api_token("cid2", "Test", None)
+        .await
+        .unwrap();
+    app.state().revoke_api_token(&token).await.unwrap();
+
+    let resp = client
+        .put(format!("{}/admin/roles/cid1", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .json(&json!({"roles": ["supervisor"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = client
+        .get(format!("{}/admin/audit", app.http_base_url()))
+        .query(&[("cid", "cid1")])
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let records: Vec<AuditRecord> = resp.json().await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].actor, Some(ClientId::from("cid0")));
+    assert_eq!(
+        records[0].event,
+        AuditEvent::RolesAssigned {
+            roles: vec![Role::Supervisor]
+        }
+    );
+
+    let resp = client
+        .get(format!("{}/admin/audit", app.http_base_url()))
+        .query(&[("cid", "cid2")])
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .send()
+        .await
+        .unwrap();
+    let events: Vec<AuditEvent> = resp
+        .json::<Vec<AuditRecord>>()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|record| record.event)
+        .collect();
+    assert_eq!(
+        events,
+        vec![AuditEvent::ApiTokenRevoked, AuditEvent::ApiTokenIssued]
+    );
+}
+
+#[test(tokio::test)]
+async fn audit_log_without_admin_role() {
+    let app = test_app(None).await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/admin/audit", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(1)),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[test(tokio::test)]
+async fn file_sink_writes_json_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.log");
+
+    let mut config = TestApp::config();
+    config.audit.sink = AuditSinkType::File;
+    config.audit.path = Some(path.clone());
+    let app = test_app(Some(config)).await;
+
+    app.state()
+        .generate_api_token("cid1", "Test", None)
+        .await
+        .unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let records: Vec<AuditRecord> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].actor, Some(ClientId::from("cid1")));
+    assert_eq!(records[0].event, AuditEvent::ApiTokenIssued);
+}
@@ -5,8 +5,10 @@ use test_log::test;
 use tokio_tungstenite::tungstenite;
 use vacs_protocol::VACS_PROTOCOL_VERSION;
 use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::Encoding;
 use vacs_protocol::ws::client::ClientMessage;
 use vacs_protocol::ws::server::{self, ServerMessage};
+use vacs_protocol::ws::shared::Capabilities;
 use vacs_server::test_utils::{
     TestApp, TestClient, assert_message_matches, assert_raw_message_matches, connect_to_websocket,
     setup_test_clients,
@@ -193,6 +195,10 @@ async fn login_timeout() {
                 protocol_version: VACS_PROTOCOL_VERSION.to_string(),
                 custom_profile: false,
                 position_id: None,
+                resume_token: None,
+                encoding: Encoding::Json,
+                compression: false,
+                capabilities: Capabilities::default(),
             }))
             .unwrap(),
         ))
@@ -225,7 +231,7 @@ async fn client_connected() {
     let client1 = clients.get_mut(&ClientId::from("client1")).unwrap();
     let client_connected = client1.recv_with_timeout(Duration::from_millis(100)).await;
     assert_message_matches(client_connected, |message| match message {
-        ServerMessage::ClientConnected(server::ClientConnected { client }) => {
+        ServerMessage::ClientConnected(server::ClientConnected { client, .. }) => {
             assert_eq!(client.id, ClientId::from("client2"));
             assert_eq!(client.display_name, "client2");
         }
@@ -254,7 +260,7 @@ async fn client_disconnected() {
     let client1 = clients.get_mut(&ClientId::from("client1")).unwrap();
     let client_connected = client1.recv_with_timeout(Duration::from_millis(100)).await;
     assert_message_matches(client_connected, |message| match message {
-        ServerMessage::ClientConnected(server::ClientConnected { client }) => {
+        ServerMessage::ClientConnected(server::ClientConnected { client, .. }) => {
             assert_eq!(client.id, ClientId::from("client2"));
             assert_eq!(client.display_name, "client2");
         }
@@ -266,7 +272,7 @@ async fn client_disconnected() {
     let client2 = clients.get_mut(&ClientId::from("client2")).unwrap();
     let client_disconnected = client2.recv_with_timeout(Duration::from_millis(100)).await;
     assert_message_matches(client_disconnected, |message| match message {
-        ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id }) => {
+        ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id, .. }) => {
             assert_eq!(client_id, ClientId::from("client1"));
         }
         _ => panic!("Unexpected message: {message:?}"),
@@ -337,7 +343,7 @@ async fn logout() {
     let client1 = clients.get_mut(&ClientId::from("client1")).unwrap();
     let client_connected = client1.recv_with_timeout(Duration::from_millis(100)).await;
     assert_message_matches(client_connected, |message| match message {
-        ServerMessage::ClientConnected(server::ClientConnected { client }) => {
+        ServerMessage::ClientConnected(server::ClientConnected { client, .. }) => {
             assert_eq!(client.id, ClientId::from("client2"));
             assert_eq!(client.display_name, "client2");
         }
@@ -355,7 +361,7 @@ async fn logout() {
     let client2 = clients.get_mut(&ClientId::from("client2")).unwrap();
     let client_disconnected = client2.recv_with_timeout(Duration::from_millis(100)).await;
     assert_message_matches(client_disconnected, |message| match message {
-        ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id }) => {
+        ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id, .. }) => {
             assert_eq!(client_id, ClientId::from("client1"));
         }
         _ => panic!("Unexpected message: {message:?}"),
@@ -4,8 +4,9 @@ use test_log::test;
 use tokio_tungstenite::tungstenite;
 use tokio_tungstenite::tungstenite::Bytes;
 use vacs_protocol::vatsim::ClientId;
-use vacs_protocol::ws::client::ClientMessage;
+use vacs_protocol::ws::client::{ClientMessage, ListClientChanges};
 use vacs_protocol::ws::server::{self, ServerMessage};
+use vacs_protocol::ws::shared::{self, Capabilities};
 use vacs_server::test_utils::{TestApp, TestClient, setup_n_test_clients};
 
 #[test(tokio::test)]
@@ -30,7 +31,8 @@ async fn client_connected() -> anyhow::Result<()> {
             .collect();
 
         for message in messages {
-            if let ServerMessage::ClientConnected(server::ClientConnected { client }) = message {
+            if let ServerMessage::ClientConnected(server::ClientConnected { client, .. }) = message
+            {
                 assert!(
                     expected_ids.contains(&client.id),
                     "Unexpected client ID: {:?}, expected one of: {:?}",
@@ -81,7 +83,7 @@ async fn client_disconnected() -> anyhow::Result<()> {
 
         for message in messages {
             match message {
-                ServerMessage::ClientConnected(server::ClientConnected { client }) => {
+                ServerMessage::ClientConnected(server::ClientConnected { client, .. }) => {
                     assert!(
                         expected_ids.contains(&client.id),
                         "Unexpected client ID: {:?}, expected one of: {:?}",
@@ -89,7 +91,9 @@ async fn client_disconnected() -> anyhow::Result<()> {
                         expected_ids
                     );
                 }
-                ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id }) => {
+                ServerMessage::ClientDisconnected(server::ClientDisconnected {
+                    client_id, ..
+                }) => {
                     assert_eq!(
                         client_id,
                         ClientId::from(format!("client{initial_client_count}")),
@@ -131,7 +135,7 @@ async fn client_dropped() -> anyhow::Result<()> {
 
         for message in messages {
             match message {
-                ServerMessage::ClientConnected(server::ClientConnected { client }) => {
+                ServerMessage::ClientConnected(server::ClientConnected { client, .. }) => {
                     assert!(
                         expected_ids.contains(&client.id),
                         "Unexpected client ID: {:?}, expected one of: {:?}",
@@ -139,7 +143,9 @@ async fn client_dropped() -> anyhow::Result<()> {
                         expected_ids
                     );
                 }
-                ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id }) => {
+                ServerMessage::ClientDisconnected(server::ClientDisconnected {
+                    client_id, ..
+                }) => {
                     assert_eq!(
                         client_id,
                         ClientId::from(format!("client{initial_client_count}")),
@@ -188,3 +194,98 @@ async fn control_messages() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn client_changes_since_revision() -> anyhow::Result<()> {
+    let test_app = TestApp::new().await;
+    let mut clients = setup_n_test_clients(test_app.addr(), 2).await;
+
+    let revision = match clients[0]
+        .recv_with_timeout(Duration::from_millis(100))
+        .await
+    {
+        Some(ServerMessage::ClientConnected(server::ClientConnected { client, revision })) => {
+            assert_eq!(client.id, ClientId::from("client2"));
+            revision
+        }
+        other => panic!("Unexpected message: {other:?}"),
+    };
+
+    // Changes missed by client1, e.g. during a brief reconnect.
+    clients[1].send(ClientMessage::Logout).await?;
+    assert!(matches!(
+        clients[0]
+            .recv_with_timeout(Duration::from_millis(100))
+            .await,
+        Some(ServerMessage::ClientDisconnected(_))
+    ));
+    let _client3 = TestClient::new_with_login(
+        test_app.addr(),
+        "client3",
+        "token3",
+        |_, _| Ok(()),
+        |_| Ok(()),
+        |_| Ok(()),
+    )
+    .await?;
+    assert!(matches!(
+        clients[0]
+            .recv_with_timeout(Duration::from_millis(100))
+            .await,
+        Some(ServerMessage::ClientConnected(_))
+    ));
+
+    clients[0]
+        .send_and_expect_with_timeout(
+            ClientMessage::ListClientChanges(ListClientChanges {
+                since_revision: revision,
+            }),
+            Duration::from_millis(100),
+            |msg| match msg {
+                ServerMessage::ClientChanges(server::ClientChanges {
+                    revision: current,
+                    changes,
+                }) => {
+                    assert_eq!(current, revision + 2);
+                    assert_eq!(changes.len(), 2);
+                    assert!(matches!(
+                        &changes[0],
+                        server::ClientChange::Disconnected { client_id }
+                            if client_id == &ClientId::from("client2")
+                    ));
+                    assert!(matches!(
+                        &changes[1],
+                        server::ClientChange::Connected { client }
+                            if client.id == ClientId::from("client3")
+                    ));
+                    Ok(())
+                }
+                other => Err(anyhow::anyhow!("Unexpected response: {other:?}")),
+            },
+        )
+        .await
+}
+
+#[test(tokio::test)]
+async fn client_changes_without_capability() -> anyhow::Result<()> {
+    let test_app = TestApp::new().await;
+    let mut client = TestClient::new(test_app.addr(), "client1", "token1")
+        .await?
+        .with_capabilities(Capabilities::default());
+    client.login(|_, _| Ok(()), |_| Ok(()), |_| Ok(())).await?;
+    assert!(client.capabilities().is_empty());
+
+    client
+        .send_and_expect_with_timeout(
+            ClientMessage::ListClientChanges(ListClientChanges { since_revision: 0 }),
+            Duration::from_millis(100),
+            |msg| match msg {
+                ServerMessage::Error(shared::Error {
+                    reason: shared::ErrorReason::UnexpectedMessage(_),
+                    ..
+                }) => Ok(()),
+                other => Err(anyhow::anyhow!("Unexpected response: {other:?}")),
+            },
+        )
+        .await
+}
@@ -0,0 +1,470 @@
+use pretty_assertions::{assert_eq, assert_matches};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::time::Duration;
+use test_log::test;
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::client::ClientMessage;
+use vacs_protocol::ws::server::{self, AnnouncementSeverity, DisconnectReason, ServerMessage};
+use vacs_protocol::ws::shared::{
+    CallAccept, CallEnd, CallId, CallInvite, CallSource, CallTarget, WebrtcAnswer, WebrtcOffer,
+};
+use vacs_server::auth::roles::Role;
+use vacs_server::cluster::memory::MemoryBroker;
+use vacs_server::config::AppConfig;
+use vacs_server::store::StoreBackend;
+use vacs_server::store::memory::MemoryStore;
+use vacs_server::test_utils::{TestApp, TestClient};
+
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+async fn login(app: &TestApp, n: usize) -> TestClient {
+    TestClient::new_with_login(
+        app.addr(),
+        format!("client{n}"),
+        &format!("token{n}"),
+        |_, _| Ok(()),
+        |_| Ok(()),
+        |_| Ok(()),
+    )
+    .await
+    .unwrap()
+}
+
+/// Starts two apps forming a cluster, with `client1` connected to the first and `client2`
+/// connected to the second app.
+async fn setup() -> (TestApp, TestApp, TestClient, TestClient) {
+    setup_with(TestApp::config(), &MemoryStore::default()).await
+}
+
+async fn setup_with(
+    config: AppConfig,
+    store: &MemoryStore,
+) -> (TestApp, TestApp, TestClient, TestClient) {
+    let broker = MemoryBroker::default();
+    let app_a = TestApp::new_clustered_with_config(config.clone(), "a", &broker, store).await;
+    let app_b = TestApp::new_clustered_with_config(config, "b", &broker, store).await;
+
+    let client1 = login(&app_a, 1).await;
+    let mut client2 = login(&app_b, 2).await;
+    // client2 may log in before the roster change of client1 reached the second app
+    if app_b.state().clients.remote_client(client1.id()).is_none() {
+        client2
+            .recv_with_timeout_and_filter(TIMEOUT, |m| {
+                matches!(m, ServerMessage::ClientConnected(_))
+            })
+            .await
+            .expect("client2 should learn about client1");
+    }
+
+    (app_a, app_b, client1, client2)
+}
+
+#[test(tokio::test)]
+async fn clients_are_visible_across_instances() {
+    let (_app_a, _app_b, mut client1, mut client2) = setup().await;
+
+    let connected = client1
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::ClientConnected(_)))
+        .await;
+    assert!(
+        matches!(connected, Some(ServerMessage::ClientConnected(server::ClientConnected { client, .. })) if client.id == *client2.id())
+    );
+
+    client2.send(ClientMessage::ListClients).await.unwrap();
+    let list = client2
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::ClientList(_)))
+        .await;
+    let Some(ServerMessage::ClientList(list)) = list else {
+        panic!("Expected client list, got {list:?}");
+    };
+    let ids: Vec<&ClientId> = list.clients.iter().map(|c| &c.id).collect();
+    assert_eq!(ids, vec![client1.id()]);
+
+    client2.close().await;
+    let disconnected = client1
+        .recv_with_timeout_and_filter(TIMEOUT, |m| {
+            matches!(m, ServerMessage::ClientDisconnected(_))
+        })
+        .await;
+    assert!(
+        matches!(disconnected, Some(ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id, .. })) if client_id == *client2.id())
+    );
+}
+
+#[test(tokio::test)]
+async fn client_ownership_is_recorded_in_store() {
+    let broker = MemoryBroker::default();
+    let store = MemoryStore::default();
+    let app_a = TestApp::new_clustered("a", &broker, &store).await;
+    let _app_b = TestApp::new_clustered("b", &broker, &store).await;
+
+    let mut client1 = login(&app_a, 1).await;
+    assert_eq!(
+        store
+            .get::<String>("cluster.client.client1")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("a")
+    );
+
+    client1.close().await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(
+        store.get::<String>("cluster.client.client1").await.unwrap(),
+        None
+    );
+}
+
+#[test(tokio::test)]
+async fn duplicate_login_on_other_instance_is_rejected() {
+    let (_app_a, app_b, client1, _client2) = setup().await;
+
+    let mut duplicate = TestClient::new(app_b.addr(), client1.id().clone(), "token1")
+        .await
+        .unwrap();
+    let result = duplicate.login(|_, _| Ok(()), |_| Ok(()), |_| Ok(())).await;
+    assert!(result.is_err());
+}
+
+#[test(tokio::test)]
+async fn concurrent_logins_on_different_instances_are_exclusive() {
+    let broker = MemoryBroker::default();
+    let store = MemoryStore::default();
+    let app_a = TestApp::new_clustered("a", &broker, &store).await;
+    let app_b = TestApp::new_clustered("b", &broker, &store).await;
+
+    let mut client_a = TestClient::new(app_a.addr(), "client1", "token1")
+        .await
+        .unwrap();
+    let mut client_b = TestClient::new(app_b.addr(), "client1", "token1")
+        .await
+        .unwrap();
+    let (result_a, result_b) = tokio::join!(
+        client_a.login(|_, _| Ok(()), |_| Ok(()), |_| Ok(())),
+        client_b.login(|_, _| Ok(()), |_| Ok(()), |_| Ok(()))
+    );
+    assert!(
+        result_a.is_ok() != result_b.is_ok(),
+        "Exactly one login must succeed: {result_a:?}, {result_b:?}"
+    );
+
+    let owner = store.get::<String>("cluster.client.client1").await.unwrap();
+    let expected = if result_a.is_ok() { "a" } else { "b" };
+    assert_eq!(owner.as_deref(), Some(expected));
+}
+
+#[test(tokio::test)]
+async fn client_ownership_expires_without_heartbeats() {
+    let broker = MemoryBroker::default();
+    let store = MemoryStore::default();
+    let app_a = TestApp::new_clustered("a", &broker, &store).await;
+
+    let _client1 = login(&app_a, 1).await;
+    // ownership is refreshed beyond the instance timeout while heartbeats are sent
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(
+        store
+            .get::<String>("cluster.client.client1")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("a")
+    );
+
+    app_a.stop_cluster_task();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(
+        store.get::<String>("cluster.client.client1").await.unwrap(),
+        None
+    );
+}
+
+#[test(tokio::test)]
+async fn clients_of_timed_out_instance_are_removed() {
+    let (app_a, app_b, client1, mut client2) = setup().await;
+
+    app_a.stop_cluster_task();
+    let disconnected = client2
+        .recv_with_timeout_and_filter(Duration::from_secs(2), |m| {
+            matches!(m, ServerMessage::ClientDisconnected(_))
+        })
+        .await;
+    assert!(
+        matches!(disconnected, Some(ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id, .. })) if client_id == *client1.id())
+    );
+    assert!(app_b.state().clients.remote_client(client1.id()).is_none());
+
+    // the client can log in to the remaining instance again once the ownership record of the
+    // timed out instance expired
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let mut takeover = TestClient::new(app_b.addr(), client1.id().clone(), "token1")
+        .await
+        .unwrap();
+    takeover
+        .login(|_, _| Ok(()), |_| Ok(()), |_| Ok(()))
+        .await
+        .unwrap();
+}
+
+#[test(tokio::test)]
+async fn supervisor_kicks_client_of_other_instance() {
+    let (app_a, app_b, _client1, mut client2) = setup().await;
+    app_a
+        .state()
+        .set_user_roles(&ClientId::from("cid0"), vec![Role::Supervisor])
+        .await
+        .unwrap();
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/moderation/kick", app_a.http_base_url()))
+        .bearer_auth(MemoryStore::test_api_token(0))
+        .json(&json!({"cid": "client2", "reason": "Spamming"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let disconnected = client2
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::Disconnected(_)))
+        .await;
+    assert_matches!(
+        disconnected,
+        Some(ServerMessage::Disconnected(server::Disconnected {
+            reason: DisconnectReason::Kicked { reason }
+        })) if reason == "Spamming"
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(
+        !app_b
+            .state()
+            .clients
+            .is_client_connected(client2.id())
+            .await
+    );
+}
+
+#[test(tokio::test)]
+async fn terminate_connection_on_other_instance() {
+    let (app_a, _app_b, _client1, mut client2) = setup().await;
+    let (token, _) = app_a
+        .state()
+        .generate_api_token(client2.id().as_str(), "Test", None)
+        .await
+        .unwrap();
+
+    let resp = reqwest::Client::new()
+        .delete(format!("{}/ws", app_a.http_base_url()))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let disconnected = client2
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::Disconnected(_)))
+        .await;
+    assert_matches!(
+        disconnected,
+        Some(ServerMessage::Disconnected(server::Disconnected {
+            reason: DisconnectReason::Terminated
+        }))
+    );
+}
+
+#[test(tokio::test)]
+async fn call_across_instances() {
+    let store = MemoryStore::default();
+    let (_app_a, _app_b, mut client1, mut client2) = setup_with(TestApp::config(), &store).await;
+    let call_key = |call_id: &CallId| format!("cluster.call.{call_id}");
+
+    let call_id = CallId::new();
+    client1
+        .send(ClientMessage::CallInvite(CallInvite {
+            call_id,
+            source: CallSource {
+                client_id: client1.id().clone(),
+                position_id: None,
+                station_id: None,
+            },
+            target: CallTarget::Client(client2.id().clone()),
+            prio: false,
+        }))
+        .await
+        .unwrap();
+    client2
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::CallInvite(_)))
+        .await
+        .expect("client2 should receive call invite");
+    assert_eq!(
+        store
+            .get::<String>(&call_key(&call_id))
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("a")
+    );
+
+    client2
+        .send(ClientMessage::CallAccept(CallAccept {
+            call_id,
+            accepting_client_id: client2.id().clone(),
+        }))
+        .await
+        .unwrap();
+    client1
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::CallAccept(_)))
+        .await
+        .expect("client1 should receive call accept");
+
+    client1
+        .send(ClientMessage::WebrtcOffer(WebrtcOffer {
+            call_id,
+            from_client_id: client1.id().clone(),
+            to_client_id: client2.id().clone(),
+            sdp: "offer".to_string(),
+        }))
+        .await
+        .unwrap();
+    let offer = client2
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::WebrtcOffer(_)))
+        .await;
+    assert!(matches!(offer, Some(ServerMessage::WebrtcOffer(offer)) if offer.sdp == "offer"));
+
+    client2
+        .send(ClientMessage::WebrtcAnswer(WebrtcAnswer {
+            call_id,
+            from_client_id: client2.id().clone(),
+            to_client_id: client1.id().clone(),
+            sdp: "answer".to_string(),
+        }))
+        .await
+        .unwrap();
+    let answer = client1
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::WebrtcAnswer(_)))
+        .await;
+    assert!(matches!(answer, Some(ServerMessage::WebrtcAnswer(answer)) if answer.sdp == "answer"));
+
+    client2
+        .send(ClientMessage::CallEnd(CallEnd {
+            call_id,
+            ending_client_id: client2.id().clone(),
+        }))
+        .await
+        .unwrap();
+    client1
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::CallEnd(_)))
+        .await
+        .expect("client1 should receive call end");
+    assert_eq!(
+        store.get::<String>(&call_key(&call_id)).await.unwrap(),
+        None
+    );
+
+    let errors = client2
+        .recv_until_timeout_with_filter(Duration::from_millis(100), |m| {
+            matches!(m, ServerMessage::CallError(_) | ServerMessage::Error(_))
+        })
+        .await;
+    assert!(errors.is_empty(), "Unexpected errors: {errors:?}");
+}
+
+#[test(tokio::test)]
+async fn announcement_reaches_all_instances() {
+    let (app_a, _app_b, _client1, mut client2) = setup().await;
+
+    app_a.state().clients.announce(server::Announcement::new(
+        AnnouncementSeverity::Info,
+        "Maintenance",
+    ));
+
+    let announcement = client2
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::Announcement(_)))
+        .await;
+    assert!(
+        matches!(announcement, Some(ServerMessage::Announcement(a)) if a.message == "Maintenance")
+    );
+}
+
+#[test(tokio::test)]
+async fn session_is_resumed_on_other_instance() {
+    let mut config = TestApp::config();
+    config.auth.session_resume_grace_period_secs = 5;
+    let store = MemoryStore::default();
+    let (app_a, app_b, mut client1, mut client2) = setup_with(config, &store).await;
+    client1.recv_until_timeout(Duration::from_millis(100)).await;
+    client2.recv_until_timeout(Duration::from_millis(100)).await;
+    let resume_token = client1.resume_token().map(str::to_string);
+
+    client1.reconnect(app_b.addr()).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // the invite is forwarded to the first app and queued while the session is detached
+    let call_id = CallId::new();
+    client2
+        .send(ClientMessage::CallInvite(CallInvite {
+            call_id,
+            source: CallSource {
+                client_id: client2.id().clone(),
+                position_id: None,
+                station_id: None,
+            },
+            target: CallTarget::Client(client1.id().clone()),
+            prio: false,
+        }))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    client1
+        .login(|_, _| Ok(()), |_| Ok(()), |_| Ok(()))
+        .await
+        .unwrap();
+    assert_eq!(client1.resume_token(), resume_token.as_deref());
+    let invite = client1
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::CallInvite(_)))
+        .await;
+    assert_matches!(invite, Some(ServerMessage::CallInvite(invite)) if invite.call_id == call_id);
+
+    assert_eq!(
+        store
+            .get::<String>("cluster.client.client1")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("b")
+    );
+    assert!(
+        !app_a
+            .state()
+            .clients
+            .is_client_connected(client1.id())
+            .await
+    );
+    assert!(app_a.state().clients.remote_client(client1.id()).is_some());
+
+    let messages = client2.recv_until_timeout(Duration::from_millis(100)).await;
+    assert!(
+        messages.iter().all(|m| !matches!(
+            m,
+            ServerMessage::ClientConnected(_)
+                | ServerMessage::ClientDisconnected(_)
+                | ServerMessage::ClientInfoUpdate(_)
+        )),
+        "Resumption on other instance must not be broadcast: {messages:?}"
+    );
+
+    // the client is now connected to the second app, along with the caller
+    client1
+        .send(ClientMessage::CallAccept(CallAccept {
+            call_id,
+            accepting_client_id: client1.id().clone(),
+        }))
+        .await
+        .unwrap();
+    client2
+        .recv_with_timeout_and_filter(TIMEOUT, |m| matches!(m, ServerMessage::CallAccept(_)))
+        .await
+        .expect("client2 should receive call accept");
+}
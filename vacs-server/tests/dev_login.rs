@@ -0,0 +1,134 @@
+use pretty_assertions::assert_eq;
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use reqwest::{StatusCode, Url, redirect};
+use serde_json::{Value, json};
+use test_log::test;
+use vacs_protocol::http::auth::{AuthTokenResponse, InitVatsimLogin, UserInfo};
+use vacs_protocol::vatsim::ClientId;
+use vacs_server::test_utils::TestApp;
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .cookie_store(true)
+        .redirect(redirect::Policy::none())
+        .build()
+        .unwrap()
+}
+
+fn query_param(url: &Url, key: &str) -> String {
+    url.query_pairs()
+        .find_map(|(k, v)| (k == key).then(|| v.to_string()))
+        .unwrap_or_else(|| panic!("Missing query parameter {key} in {url}"))
+}
+
+/// Logs in as `cid` via the dev authorization endpoint, returning the deep link the user is
+/// redirected to.
+async fn authorize(client: &reqwest::Client, app: &TestApp, cid: &str) -> Url {
+    let init: InitVatsimLogin = client
+        .get(format!("{}/auth/vatsim", app.http_base_url()))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let mut url = Url::parse(&init.url).unwrap();
+    assert!(url.as_str().starts_with(app.http_base_url()));
+
+    let page = client.get(url.clone()).send().await.unwrap();
+    assert_eq!(page.status(), StatusCode::OK);
+    assert!(page.text().await.unwrap().contains(cid));
+
+    url.query_pairs_mut().append_pair("cid", cid);
+    let resp = client.get(url).send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+    Url::parse(resp.headers()[LOCATION].to_str().unwrap()).unwrap()
+}
+
+#[test(tokio::test)]
+async fn oauth_login_flow() {
+    let app = TestApp::new().await;
+    let client = http_client();
+
+    let callback = authorize(&client, &app, "cid1").await;
+    assert_eq!(callback.scheme(), "vacs");
+
+    let resp = client
+        .post(format!("{}/auth/vatsim/callback", app.http_base_url()))
+        .json(&json!({
+            "code": query_param(&callback, "code"),
+            "state": query_param(&callback, "state"),
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let user: UserInfo = resp.json().await.unwrap();
+    assert_eq!(user.cid, ClientId::from("cid1"));
+
+    let resp = client
+        .get(format!("{}/auth/user", app.http_base_url()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let user: UserInfo = resp.json().await.unwrap();
+    assert_eq!(user.cid, ClientId::from("cid1"));
+}
+
+#[test(tokio::test)]
+async fn access_token_exchange() {
+    let app = TestApp::new().await;
+    let client = http_client();
+
+    let callback = authorize(&client, &app, "cid2").await;
+    let resp = client
+        .post(format!("{}/auth/dev/token", app.http_base_url()))
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(format!(
+            "grant_type=authorization_code&code={}",
+            query_param(&callback, "code")
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let token: Value = resp.json().await.unwrap();
+    let access_token = token["access_token"].as_str().unwrap();
+
+    let resp = client
+        .get(format!("{}/auth/dev/user", app.http_base_url()))
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        resp.json::<Value>().await.unwrap(),
+        json!({"data": {"cid": "cid2"}})
+    );
+
+    let resp = client
+        .post(format!("{}/auth/vatsim/token", app.http_base_url()))
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let token: AuthTokenResponse = resp.json().await.unwrap();
+    assert_eq!(token.cid, ClientId::from("cid2"));
+}
+
+#[test(tokio::test)]
+async fn authorize_unknown_cid() {
+    let app = TestApp::new().await;
+
+    let resp = http_client()
+        .get(format!(
+            "{}/auth/dev/authorize?state=state&cid=cid9",
+            app.http_base_url()
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
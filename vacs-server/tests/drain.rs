@@ -0,0 +1,62 @@
+use pretty_assertions::assert_matches;
+use std::time::Duration;
+use test_log::test;
+use tokio_tungstenite::tungstenite;
+use vacs_protocol::ws::server::{self, DisconnectReason, ServerMessage};
+use vacs_server::test_utils::{TestApp, setup_n_test_clients};
+use vacs_vatsim::coverage::network::Network;
+
+async fn test_app() -> TestApp {
+    let mut config = TestApp::config();
+    config.server.drain_timeout_secs = 1;
+    config.server.drain_retry_after_secs = 3;
+    TestApp::new_with_config(config, Network::default()).await
+}
+
+#[test(tokio::test)]
+async fn drain_disconnects_clients_with_server_restart() -> anyhow::Result<()> {
+    let test_app = test_app().await;
+    let mut clients = setup_n_test_clients(test_app.addr(), 2).await;
+
+    let state = test_app.state();
+    tokio::spawn(async move { state.drain().await });
+
+    for client in clients.iter_mut() {
+        let disconnected = client
+            .recv_with_timeout_and_filter(Duration::from_secs(2), |m| {
+                matches!(m, ServerMessage::Disconnected(_))
+            })
+            .await;
+        assert_matches!(
+            disconnected,
+            Some(ServerMessage::Disconnected(server::Disconnected {
+                reason: DisconnectReason::ServerRestart { retry_after: 3 }
+            }))
+        );
+    }
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn drain_refuses_new_connections() -> anyhow::Result<()> {
+    let test_app = test_app().await;
+    test_app.state().drain().await;
+    assert!(test_app.state().is_draining());
+
+    match tokio_tungstenite::connect_async(test_app.addr()).await {
+        Err(tungstenite::Error::Http(response)) => {
+            assert_eq!(response.status(), 503);
+            assert_eq!(
+                response
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|v| v.to_str().ok()),
+                Some("3")
+            );
+        }
+        other => panic!("Expected connection to be refused, got {other:?}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use pretty_assertions::assert_eq;
+use std::time::Duration;
+use test_log::test;
+use tokio_tungstenite::tungstenite::Message;
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::client::ClientMessage;
+use vacs_protocol::ws::server::{self, ServerMessage};
+use vacs_protocol::ws::shared::{CallId, CallInvite, CallSource, CallTarget};
+use vacs_protocol::ws::{Encoding, decompress};
+use vacs_server::test_utils::{TestApp, TestClient};
+
+#[test(tokio::test)]
+async fn msgpack_session() -> anyhow::Result<()> {
+    let test_app = TestApp::new().await;
+
+    let mut client1 = TestClient::new(test_app.addr(), "client1", "token1")
+        .await?
+        .with_encoding(Encoding::MessagePack);
+    client1.login(|_, _| Ok(()), |_| Ok(()), |_| Ok(())).await?;
+    assert_eq!(client1.encoding(), Encoding::MessagePack);
+
+    let mut client2 = TestClient::new_with_login(
+        test_app.addr(),
+        "client2",
+        "token2",
+        |_, _| Ok(()),
+        |_| Ok(()),
+        |_| Ok(()),
+    )
+    .await?;
+    assert_eq!(client2.encoding(), Encoding::Json);
+
+    match client1
+        .recv_raw_with_timeout(Duration::from_millis(100))
+        .await
+    {
+        Some(Message::Binary(bytes)) => match ServerMessage::deserialize_msgpack(&bytes)? {
+            ServerMessage::ClientConnected(server::ClientConnected { client, .. }) => {
+                assert_eq!(client.id, ClientId::from("client2"));
+            }
+            other => panic!("Unexpected message: {other:?}"),
+        },
+        other => panic!("Expected binary ClientConnected, got {other:?}"),
+    }
+
+    let invite = CallInvite {
+        call_id: CallId::new(),
+        source: CallSource::new(ClientId::from("client1")),
+        target: CallTarget::Client(ClientId::from("client2")),
+        prio: false,
+    };
+    client1
+        .send(ClientMessage::CallInvite(invite.clone()))
+        .await?;
+
+    match client2
+        .recv_raw_with_timeout(Duration::from_millis(100))
+        .await
+    {
+        Some(Message::Text(text)) => {
+            assert_eq!(
+                ServerMessage::deserialize(&text)?,
+                ServerMessage::CallInvite(invite)
+            );
+        }
+        other => panic!("Expected text CallInvite, got {other:?}"),
+    }
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn compressed_session() -> anyhow::Result<()> {
+    let test_app = TestApp::new().await;
+
+    let mut client1 = TestClient::new(test_app.addr(), "client1", "token1")
+        .await?
+        .with_compression(true);
+    client1.login(|_, _| Ok(()), |_| Ok(()), |_| Ok(())).await?;
+    assert!(client1.compressed());
+
+    let _client2 = TestClient::new_with_login(
+        test_app.addr(),
+        "client2",
+        "token2",
+        |_, _| Ok(()),
+        |_| Ok(()),
+        |_| Ok(()),
+    )
+    .await?;
+
+    match client1
+        .recv_raw_with_timeout(Duration::from_millis(100))
+        .await
+    {
+        Some(Message::Binary(bytes)) => {
+            let json = String::from_utf8(decompress(&bytes)?)?;
+            match ServerMessage::deserialize(&json)? {
+                ServerMessage::ClientConnected(server::ClientConnected { client, .. }) => {
+                    assert_eq!(client.id, ClientId::from("client2"));
+                }
+                other => panic!("Unexpected message: {other:?}"),
+            }
+        }
+        other => panic!("Expected compressed ClientConnected, got {other:?}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use pretty_assertions::{assert_eq, assert_matches};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::time::Duration;
+use test_log::test;
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::client::{ClientMessage, KickClient};
+use vacs_protocol::ws::server::{self, DisconnectReason, ServerMessage};
+use vacs_protocol::ws::shared::{self, CallSource, CallTarget, ErrorReason, TextMessage};
+use vacs_server::audit::{AuditEvent, AuditQuery};
+use vacs_server::auth::roles::Role;
+use vacs_server::store::memory::MemoryStore;
+use vacs_server::test_utils::{TestApp, TestClient, setup_n_test_clients};
+use vacs_vatsim::coverage::network::Network;
+
+async fn test_app() -> TestApp {
+    let mut config = TestApp::config();
+    config
+        .auth
+        .roles
+        .insert(ClientId::from("cid0"), vec![Role::Supervisor]);
+    config
+        .auth
+        .roles
+        .insert(ClientId::from("client2"), vec![Role::Supervisor]);
+    TestApp::new_with_config(config, Network::default()).await
+}
+
+#[test(tokio::test)]
+async fn supervisor_kicks_client() -> anyhow::Result<()> {
+    let app = test_app().await;
+    let mut clients = setup_n_test_clients(app.addr(), 1).await;
+    let client1 = &mut clients[0];
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/moderation/kick", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .json(&json!({"cid": "client1", "reason": "Spamming"}))
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let disconnected = client1
+        .recv_with_timeout_and_filter(Duration::from_millis(200), |m| {
+            matches!(m, ServerMessage::Disconnected(_))
+        })
+        .await;
+    assert_matches!(
+        disconnected,
+        Some(ServerMessage::Disconnected(server::Disconnected {
+            reason: DisconnectReason::Kicked { reason }
+        })) if reason == "Spamming"
+    );
+
+    let records = app.state().audit.query(&AuditQuery::default()).await?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].actor, Some(ClientId::from("cid0")));
+    assert_eq!(records[0].target, Some(ClientId::from("client1")));
+    assert_eq!(
+        records[0].event,
+        AuditEvent::ClientKicked {
+            reason: "Spamming".to_string(),
+            connected: true,
+        }
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn kick_without_supervisor_role() -> anyhow::Result<()> {
+    let app = test_app().await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/moderation/kick", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(1)),
+        )
+        .json(&json!({"cid": "client1", "reason": "Spamming"}))
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert!(
+        app.state()
+            .audit
+            .query(&AuditQuery::default())
+            .await?
+            .is_empty()
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn banned_client_cannot_login() -> anyhow::Result<()> {
+    let app = test_app().await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/moderation/bans", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .json(&json!({"cid": "client1", "reason": "Abuse", "durationSecs": 60}))
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let mut client1 = TestClient::new(app.addr(), "client1", "token1").await?;
+    let err = client1
+        .login(|_, _| Ok(()), |_| Ok(()), |_| Ok(()))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Banned"), "{err}");
+
+    app.state()
+        .unban_client(&ClientId::from("cid0"), &ClientId::from("client1"))
+        .await?;
+    let mut client1 = TestClient::new(app.addr(), "client1", "token1").await?;
+    client1.login(|_, _| Ok(()), |_| Ok(()), |_| Ok(())).await?;
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn muted_client_cannot_send_text_messages() -> anyhow::Result<()> {
+    let app = test_app().await;
+    let mut clients = setup_n_test_clients(app.addr(), 2).await;
+    let client1 = &mut clients[0];
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/moderation/mutes", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .json(&json!({"cid": "client1", "reason": "Spamming", "durationSecs": 60}))
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    client1
+        .send(ClientMessage::TextMessage(TextMessage::new(
+            CallSource::new(ClientId::from("client1")),
+            CallTarget::Client(ClientId::from("client2")),
+            "request release RWY 29",
+        )))
+        .await?;
+    let error = client1
+        .recv_with_timeout_and_filter(Duration::from_millis(200), |m| {
+            matches!(m, ServerMessage::Error(_))
+        })
+        .await;
+    assert_matches!(
+        error,
+        Some(ServerMessage::Error(shared::Error {
+            reason: ErrorReason::Muted { reason, .. },
+            ..
+        })) if reason == "Spamming"
+    );
+
+    let records = app.state().audit.query(&AuditQuery::default()).await?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].target, Some(ClientId::from("client1")));
+    assert_matches!(
+        &records[0].event,
+        AuditEvent::ClientMuted { reason, .. } if reason == "Spamming"
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn supervisor_kicks_client_over_websocket() -> anyhow::Result<()> {
+    let app = test_app().await;
+    let mut clients = setup_n_test_clients(app.addr(), 2).await;
+
+    clients[1]
+        .send(ClientMessage::KickClient(KickClient {
+            client_id: ClientId::from("client1"),
+            reason: "Spamming".to_string(),
+        }))
+        .await?;
+
+    let disconnected = clients[0]
+        .recv_with_timeout_and_filter(Duration::from_millis(200), |m| {
+            matches!(m, ServerMessage::Disconnected(_))
+        })
+        .await;
+    assert_matches!(
+        disconnected,
+        Some(ServerMessage::Disconnected(server::Disconnected {
+            reason: DisconnectReason::Kicked { reason }
+        })) if reason == "Spamming"
+    );
+
+    let records = app.state().audit.query(&AuditQuery::default()).await?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].actor, Some(ClientId::from("client2")));
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn kick_over_websocket_without_supervisor_role() -> anyhow::Result<()> {
+    let app = test_app().await;
+    let mut clients = setup_n_test_clients(app.addr(), 2).await;
+
+    clients[0]
+        .send(ClientMessage::KickClient(KickClient {
+            client_id: ClientId::from("client2"),
+            reason: "Spamming".to_string(),
+        }))
+        .await?;
+
+    let error = clients[0]
+        .recv_with_timeout_and_filter(Duration::from_millis(200), |m| {
+            matches!(m, ServerMessage::Error(_))
+        })
+        .await;
+    assert_matches!(
+        error,
+        Some(ServerMessage::Error(shared::Error {
+            reason: ErrorReason::UnexpectedMessage(_),
+            ..
+        }))
+    );
+    assert!(
+        clients[1]
+            .recv_until_timeout_with_filter(Duration::from_millis(100), |m| {
+                matches!(m, ServerMessage::Disconnected(_))
+            })
+            .await
+            .is_empty()
+    );
+
+    Ok(())
+}
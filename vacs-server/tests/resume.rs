@@ -0,0 +1,153 @@
+use pretty_assertions::{assert_eq, assert_matches};
+use std::time::Duration;
+use test_log::test;
+use vacs_protocol::vatsim::ClientId;
+use vacs_protocol::ws::client::ClientMessage;
+use vacs_protocol::ws::server::{self, ServerMessage};
+use vacs_protocol::ws::shared::{CallId, CallInvite, CallSource, CallTarget};
+use vacs_server::test_utils::{TestApp, TestClient, setup_n_test_clients};
+use vacs_vatsim::coverage::network::Network;
+
+async fn test_app(grace_period_secs: u64) -> TestApp {
+    let mut config = TestApp::config();
+    config.auth.session_resume_grace_period_secs = grace_period_secs;
+    TestApp::new_with_config(config, Network::default()).await
+}
+
+#[test(tokio::test)]
+async fn resume_session_after_connection_loss() -> anyhow::Result<()> {
+    let test_app = test_app(5).await;
+    let mut clients = setup_n_test_clients(test_app.addr(), 2).await;
+    let mut client2 = clients.pop().unwrap();
+    let mut client1 = clients.pop().unwrap();
+    client1.recv_until_timeout(Duration::from_millis(100)).await;
+
+    let resume_token = client1.resume_token().map(str::to_string);
+    assert!(resume_token.is_some());
+
+    client1.reconnect(test_app.addr()).await;
+
+    let messages = client2.recv_until_timeout(Duration::from_millis(200)).await;
+    assert_eq!(messages, vec![], "Connection loss must not be broadcast");
+
+    let call_id = CallId::new();
+    client2
+        .send(ClientMessage::CallInvite(CallInvite {
+            call_id,
+            source: CallSource {
+                client_id: client2.id().clone(),
+                position_id: None,
+                station_id: None,
+            },
+            target: CallTarget::Client(client1.id().clone()),
+            prio: false,
+        }))
+        .await?;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    client1
+        .login(
+            |_, info| {
+                assert_eq!(info.id, ClientId::from("client1"));
+                Ok(())
+            },
+            |_| Ok(()),
+            |_| Ok(()),
+        )
+        .await?;
+    assert_eq!(client1.resume_token(), resume_token.as_deref());
+
+    let invite = client1
+        .recv_with_timeout_and_filter(Duration::from_millis(100), |m| {
+            matches!(m, ServerMessage::CallInvite(_))
+        })
+        .await;
+    match invite {
+        Some(ServerMessage::CallInvite(invite)) => assert_eq!(invite.call_id, call_id),
+        other => panic!("Expected queued call invite, got {other:?}"),
+    }
+
+    let messages = client2.recv_until_timeout(Duration::from_millis(100)).await;
+    assert!(
+        messages.iter().all(|m| !matches!(
+            m,
+            ServerMessage::ClientConnected(_) | ServerMessage::ClientDisconnected(_)
+        )),
+        "Resumption must not be broadcast: {messages:?}"
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn resumed_session_receives_queued_broadcasts() -> anyhow::Result<()> {
+    let test_app = test_app(5).await;
+    let mut clients = setup_n_test_clients(test_app.addr(), 1).await;
+    let mut client1 = clients.pop().unwrap();
+    client1.recv_until_timeout(Duration::from_millis(100)).await;
+
+    client1.reconnect(test_app.addr()).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let _client2 = TestClient::new_with_login(
+        test_app.addr(),
+        "client2",
+        "token2",
+        |_, _| Ok(()),
+        |_| Ok(()),
+        |_| Ok(()),
+    )
+    .await?;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    client1.login(|_, _| Ok(()), |_| Ok(()), |_| Ok(())).await?;
+
+    let connected = client1
+        .recv_with_timeout_and_filter(Duration::from_millis(100), |m| {
+            matches!(m, ServerMessage::ClientConnected(_))
+        })
+        .await;
+    assert_matches!(
+        connected,
+        Some(ServerMessage::ClientConnected(server::ClientConnected { client, .. }))
+            if client.id == ClientId::from("client2")
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn detached_session_expires() -> anyhow::Result<()> {
+    let test_app = test_app(1).await;
+    let mut clients = setup_n_test_clients(test_app.addr(), 2).await;
+    let mut client2 = clients.pop().unwrap();
+    let mut client1 = clients.pop().unwrap();
+    client1.recv_until_timeout(Duration::from_millis(100)).await;
+
+    let resume_token = client1.resume_token().map(str::to_string);
+    client1.reconnect(test_app.addr()).await;
+
+    let disconnected = client2
+        .recv_with_timeout_and_filter(Duration::from_secs(2), |m| {
+            matches!(m, ServerMessage::ClientDisconnected(_))
+        })
+        .await;
+    assert_matches!(
+        disconnected,
+        Some(ServerMessage::ClientDisconnected(server::ClientDisconnected { client_id, .. }))
+            if client_id == ClientId::from("client1")
+    );
+
+    client1.login(|_, _| Ok(()), |_| Ok(()), |_| Ok(())).await?;
+    assert!(client1.resume_token().is_some());
+    assert_ne!(client1.resume_token(), resume_token.as_deref());
+
+    let connected = client2
+        .recv_with_timeout_and_filter(Duration::from_millis(100), |m| {
+            matches!(m, ServerMessage::ClientConnected(_))
+        })
+        .await;
+    assert!(connected.is_some());
+
+    Ok(())
+}
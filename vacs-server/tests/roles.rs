@@ -0,0 +1,78 @@
+use reqwest::StatusCode;
+use serde_json::json;
+use test_log::test;
+use vacs_protocol::vatsim::ClientId;
+use vacs_server::auth::roles::Role;
+use vacs_server::store::memory::MemoryStore;
+use vacs_server::test_utils::TestApp;
+use vacs_vatsim::coverage::network::Network;
+
+async fn test_app() -> TestApp {
+    let mut config = TestApp::config();
+    config
+        .auth
+        .roles
+        .insert(ClientId::from("cid0"), vec![Role::Admin]);
+    TestApp::new_with_config(config, Network::default()).await
+}
+
+#[test(tokio::test)]
+async fn admin_assigns_roles() {
+    let app = test_app().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .put(format!("{}/admin/roles/cid1", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .json(&json!({"roles": ["supervisor"]}))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let roles: Vec<Role> = resp.json().await.unwrap();
+    assert_eq!(roles, vec![Role::Supervisor]);
+
+    let roles = app
+        .state()
+        .user_roles(&ClientId::from("cid1"))
+        .await
+        .unwrap();
+    assert!(roles.has(Role::Supervisor));
+    assert!(!roles.has(Role::Admin));
+}
+
+#[test(tokio::test)]
+async fn roles_without_admin_role() {
+    let app = test_app().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .get(format!("{}/admin/roles/cid0", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(1)),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[test(tokio::test)]
+async fn roles_without_auth() {
+    let app = test_app().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .get(format!("{}/admin/roles/cid0", app.http_base_url()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
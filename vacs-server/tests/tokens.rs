@@ -0,0 +1,194 @@
+use pretty_assertions::assert_eq;
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use test_log::test;
+use vacs_server::auth::scopes::{Scope, Scopes};
+use vacs_server::store::memory::MemoryStore;
+use vacs_server::test_utils::TestApp;
+
+#[test(tokio::test)]
+async fn create_list_and_revoke_tokens() {
+    let app = TestApp::new().await;
+    let client = reqwest::Client::new();
+    let auth = format!("Bearer {}", MemoryStore::test_api_token(0));
+
+    let resp = client
+        .post(format!("{}/auth/tokens", app.http_base_url()))
+        .header("Authorization", &auth)
+        .json(&json!({"name": "Laptop"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let created: Value = resp.json().await.unwrap();
+    assert_eq!(created["name"], "Laptop");
+    assert_eq!(created["cid"], "cid0");
+    let id = created["id"].as_str().unwrap().to_string();
+    let token = created["token"].as_str().unwrap().to_string();
+
+    // the new token authenticates as the same user
+    let resp = client
+        .get(format!("{}/auth/tokens", app.http_base_url()))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let tokens: Vec<Value> = resp.json().await.unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0]["id"], id.as_str());
+    assert!(tokens[0].get("token").is_none());
+    assert!(tokens[0]["lastUsedAt"].is_u64());
+
+    let resp = client
+        .delete(format!("{}/auth/tokens/{id}", app.http_base_url()))
+        .header("Authorization", &auth)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = client
+        .get(format!("{}/auth/tokens", app.http_base_url()))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[test(tokio::test)]
+async fn revoke_token_of_other_user() {
+    let app = TestApp::new().await;
+    let client = reqwest::Client::new();
+
+    let (_, info) = app
+        .state()
+        .generate_api_token("cid1", "Laptop", None)
+        .await
+        .unwrap();
+
+    let resp = client
+        .delete(format!("{}/auth/tokens/{}", app.http_base_url(), info.id))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn create_token_with_empty_name() {
+    let app = TestApp::new().await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/auth/tokens", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .json(&json!({"name": "  "}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test(tokio::test)]
+async fn list_tokens_without_auth() {
+    let app = TestApp::new().await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/auth/tokens", app.http_base_url()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[test(tokio::test)]
+async fn scoped_token_only_grants_its_scopes() {
+    let app = TestApp::new().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{}/auth/tokens", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .json(&json!({"name": "Overlay", "scopes": ["ws:login"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let created: Value = resp.json().await.unwrap();
+    assert_eq!(created["scopes"], json!(["ws:login"]));
+    let auth = format!("Bearer {}", created["token"].as_str().unwrap());
+
+    let status = |path: &'static str| {
+        let req = client
+            .get(format!("{}{path}", app.http_base_url()))
+            .header("Authorization", &auth);
+        async move { req.send().await.unwrap().status() }
+    };
+    assert_eq!(status("/ws/token").await, StatusCode::OK);
+    assert_eq!(status("/webrtc/ice-config").await, StatusCode::FORBIDDEN);
+    assert_eq!(status("/coverage/stations").await, StatusCode::FORBIDDEN);
+    // routes not requiring a scope only accept unrestricted tokens
+    assert_eq!(status("/auth/user").await, StatusCode::FORBIDDEN);
+    assert_eq!(status("/auth/tokens").await, StatusCode::FORBIDDEN);
+}
+
+#[test(tokio::test)]
+async fn coverage_read_token_lists_stations() {
+    let app = TestApp::new().await;
+    let client = reqwest::Client::new();
+
+    let (token, _) = app
+        .state()
+        .generate_api_token(
+            "cid0",
+            "Overlay",
+            Some(Scopes::from_iter([Scope::CoverageRead])),
+        )
+        .await
+        .unwrap();
+
+    let resp = client
+        .get(format!("{}/coverage/stations", app.http_base_url()))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let stations: Value = resp.json().await.unwrap();
+    assert_eq!(stations, json!({"stations": []}));
+
+    let resp = client
+        .get(format!("{}/coverage/stations", app.http_base_url()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[test(tokio::test)]
+async fn create_token_with_empty_scopes() {
+    let app = TestApp::new().await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/auth/tokens", app.http_base_url()))
+        .header(
+            "Authorization",
+            format!("Bearer {}", MemoryStore::test_api_token(0)),
+        )
+        .json(&json!({"name": "Overlay", "scopes": []}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
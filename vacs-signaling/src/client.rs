@@ -1,7 +1,7 @@
 use crate::auth::TokenProvider;
 use crate::error::{SignalingError, SignalingRuntimeError, UntilInstant};
 use crate::matcher::ResponseMatcher;
-use crate::transport::{SignalingReceiver, SignalingSender, SignalingTransport};
+use crate::transport::{SignalingReceiver, SignalingSender, SignalingTransport, encode_message};
 use parking_lot::{Mutex, RwLock};
 use rand::RngExt;
 use std::collections::VecDeque;
@@ -18,7 +18,8 @@ use vacs_protocol::profile::{ActiveProfile, Profile};
 use vacs_protocol::vatsim::PositionId;
 use vacs_protocol::ws::client::ClientMessage;
 use vacs_protocol::ws::server::{ClientInfo, ServerMessage, SessionProfile};
-use vacs_protocol::ws::{client, server};
+use vacs_protocol::ws::shared::Capabilities;
+use vacs_protocol::ws::{Encoding, client, server};
 
 const BROADCAST_CHANNEL_SIZE: usize = 100;
 const SEND_CHANNEL_SIZE: usize = 100;
@@ -51,6 +52,11 @@ pub enum SignalingEvent {
         client_info: ClientInfo,
         /// The profile associated with the current session.
         profile: ActiveProfile<Profile>,
+        /// Whether the previous session was resumed after a connection loss, keeping its calls.
+        /// If `false`, a new session was created and all previous calls have been ended.
+        resumed: bool,
+        /// Capabilities negotiated with the server for this session.
+        capabilities: Capabilities,
     },
     /// Emitted for every [`ServerMessage`] received by a connected and authenticated [`SignalingClient`].
     Message(ServerMessage),
@@ -122,6 +128,32 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClient<ST, TP> {
         self.inner.state()
     }
 
+    /// Whether the current session is resumed after a connection loss, as the server issued a
+    /// resume token and reconnecting is enabled. The server keeps the calls of a resumable session
+    /// for its grace period, they are only ended if the reconnect fails or creates a new session,
+    /// see [`SignalingEvent::Connected`].
+    pub fn is_resumable(&self) -> bool {
+        self.inner.reconnect_max_tries > 0 && self.inner.resume_token.lock().is_some()
+    }
+
+    /// Sets the encoding requested at the next login. The negotiated encoding is used for all
+    /// messages once confirmed by the server, otherwise messages are sent as JSON.
+    pub fn set_encoding(&self, encoding: Encoding) {
+        *self.inner.requested_encoding.write() = encoding;
+    }
+
+    /// Sets whether compression of server messages is requested at the next login. Compression is
+    /// requested by default and only used if enabled on the server.
+    pub fn set_compression(&self, compression: bool) {
+        *self.inner.requested_compression.write() = compression;
+    }
+
+    /// Capabilities negotiated with the server during the last login. Messages requiring any other
+    /// capability are rejected by [`SignalingClient::send`].
+    pub fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities.read().clone()
+    }
+
     pub async fn connect(&self, position_id: Option<PositionId>) -> Result<(), SignalingError> {
         self.inner.set_position_id(position_id);
         self.inner.connect().await
@@ -178,6 +210,11 @@ struct SignalingClientInner<ST: SignalingTransport, TP: TokenProvider> {
 
     custom_profile: bool,
     position_id: Arc<RwLock<Option<PositionId>>>,
+    resume_token: Arc<Mutex<Option<String>>>,
+    requested_encoding: Arc<RwLock<Encoding>>,
+    encoding: Arc<RwLock<Encoding>>,
+    requested_compression: Arc<RwLock<bool>>,
+    capabilities: Arc<RwLock<Capabilities>>,
 
     login_timeout: Duration,
     reconnect_max_tries: u8,
@@ -220,6 +257,11 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClientInner<ST, TP> {
 
             custom_profile,
             position_id: Arc::new(RwLock::new(None)),
+            resume_token: Arc::new(Mutex::new(None)),
+            requested_encoding: Arc::new(RwLock::new(Encoding::Json)),
+            encoding: Arc::new(RwLock::new(Encoding::Json)),
+            requested_compression: Arc::new(RwLock::new(true)),
+            capabilities: Arc::new(RwLock::new(Capabilities::default())),
 
             login_timeout,
             reconnect_max_tries,
@@ -261,6 +303,7 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClientInner<ST, TP> {
         self.cleanup().await;
         if requested {
             self.reconnect_gate.lock().clear();
+            self.resume_token.lock().take();
         }
     }
 
@@ -282,6 +325,18 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClientInner<ST, TP> {
             _ => {}
         };
 
+        if let Some(capability) = msg.required_capability()
+            && !self.capabilities.read().contains(capability)
+        {
+            tracing::warn!(
+                ?capability,
+                "Tried to send message without negotiated capability"
+            );
+            return Err(SignalingError::Runtime(
+                SignalingRuntimeError::CapabilityNotNegotiated(capability),
+            ));
+        }
+
         let send_tx = {
             self.send_tx.lock().as_ref().cloned().ok_or_else(|| {
                 tracing::error!("Client is connected, but send_tx is not initialized");
@@ -289,13 +344,14 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClientInner<ST, TP> {
             })?
         };
 
-        let serialized = ClientMessage::serialize(&msg).map_err(|err| {
+        let encoding = *self.encoding.read();
+        let serialized = encode_message(&msg, encoding).map_err(|err| {
             tracing::warn!(?err, "Failed to serialize message");
-            SignalingError::Runtime(SignalingRuntimeError::SerializationError(err.to_string()))
+            SignalingError::Runtime(err)
         })?;
 
         send_tx
-            .send(tungstenite::Message::from(serialized))
+            .send(serialized)
             .await
             .map_err(|_| SignalingError::Runtime(SignalingRuntimeError::Disconnected(None)))
     }
@@ -350,19 +406,37 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClientInner<ST, TP> {
         *self.position_id.write() = position_id;
     }
 
+    /// Performs the login, returning the session's client info and profile as well as whether a
+    /// previous session was resumed.
     #[instrument(level = "debug", skip(self), err)]
-    async fn login(&self) -> Result<(ClientInfo, ActiveProfile<Profile>), SignalingError> {
+    async fn login(&self) -> Result<(ClientInfo, ActiveProfile<Profile>, bool), SignalingError> {
         tracing::trace!("Retrieving auth token from token provider");
         let token = self.token_provider.get_token().await?;
 
         let position_id = self.position_id.read().clone();
-        tracing::debug!("Sending Login message to server");
+        let resume_token = self.resume_token.lock().clone();
+        let requested_encoding = *self.requested_encoding.read();
+        let requested_compression = *self.requested_compression.read();
+        // The login itself is always sent as JSON, the requested encoding is only used once
+        // confirmed by the server.
+        *self.encoding.write() = Encoding::Json;
+        *self.capabilities.write() = Capabilities::default();
+        tracing::debug!(
+            resume = resume_token.is_some(),
+            ?requested_encoding,
+            requested_compression,
+            "Sending Login message to server"
+        );
         self.send(
             client::Login {
                 token: token.to_string(),
                 protocol_version: VACS_PROTOCOL_VERSION.to_string(),
                 custom_profile: self.custom_profile,
                 position_id,
+                resume_token: resume_token.clone(),
+                encoding: requested_encoding,
+                compression: requested_compression,
+                capabilities: Capabilities::all(),
             }
             .into(),
         )
@@ -370,10 +444,21 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClientInner<ST, TP> {
 
         tracing::debug!("Awaiting authentication response from server");
         match self.recv_with_timeout(self.login_timeout).await? {
-            ServerMessage::SessionInfo(server::SessionInfo { client, profile }) => {
+            ServerMessage::SessionInfo(server::SessionInfo {
+                client,
+                profile,
+                resume_token: new_resume_token,
+                encoding,
+                compression,
+                capabilities,
+            }) => {
                 if let SessionProfile::Changed(profile) = profile {
-                    tracing::info!(?client, %profile, "Login successful, received session info");
-                    Ok((client, profile))
+                    let resumed = resume_token.is_some() && resume_token == new_resume_token;
+                    tracing::info!(?client, %profile, ?resumed, ?encoding, compression, ?capabilities, "Login successful, received session info");
+                    *self.resume_token.lock() = new_resume_token;
+                    *self.encoding.write() = encoding;
+                    *self.capabilities.write() = capabilities;
+                    Ok((client, profile, resumed))
                 } else {
                     tracing::error!(
                         ?client,
@@ -447,13 +532,15 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClientInner<ST, TP> {
 
         tracing::trace!("Successfully started worker tasks, logging in");
         match self.login().await {
-            Ok((client_info, profile)) => {
+            Ok((client_info, profile, resumed)) => {
                 tracing::trace!("Successfully logged in to server");
 
                 self.set_state(State::LoggedIn);
                 if let Err(err) = self.broadcast_tx.send(SignalingEvent::Connected {
                     client_info,
                     profile,
+                    resumed,
+                    capabilities: self.capabilities.read().clone(),
                 }) {
                     tracing::warn!(?err, "Failed to broadcast connected event");
                 }
@@ -533,6 +620,19 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClientInner<ST, TP> {
                                         gate.on_reconnect(Instant::now());
                                     }
 
+                                    if let Some(delay) = err.reconnect_delay() {
+                                        tracing::info!(?delay, "Server requested delay before reconnecting");
+                                        tokio::select! {
+                                            biased;
+                                            _ = self.shutdown_token.cancelled() => {
+                                                tracing::debug!("Shutdown signal received, aborting reconnect");
+                                                self.set_state(State::Disconnected);
+                                                break;
+                                            }
+                                            _ = tokio::time::sleep(delay) => {}
+                                        }
+                                    }
+
                                     let terminate = err.needs_session_terminate();
                                     tracing::info!(?terminate, "Reconnecting after error");
                                     if let Err(err) = self.reconnect(terminate).await {
@@ -576,7 +676,13 @@ impl<ST: SignalingTransport, TP: TokenProvider> SignalingClientInner<ST, TP> {
 
         let mut reconnect_error = SignalingError::Other("Unknown".to_string());
         for attempt in 1..=self.reconnect_max_tries {
-            if terminate && let Some(ref cb) = self.on_terminate_session {
+            // Terminating the session would prevent resuming it, the server takes over the
+            // existing session instead if it has not detected the connection loss yet.
+            let resumable = self.resume_token.lock().is_some();
+            if terminate
+                && !resumable
+                && let Some(ref cb) = self.on_terminate_session
+            {
                 tracing::debug!(?attempt, "Terminating session before reconnect");
                 cb().await;
             }
@@ -857,8 +963,9 @@ mod tests {
     use test_log::test;
     use tokio::sync::Notify;
     use vacs_protocol::vatsim::{ClientId, PositionId};
+    use vacs_protocol::ws::client::ListClientChanges;
     use vacs_protocol::ws::server::LoginFailureReason;
-    use vacs_protocol::ws::shared::ErrorReason;
+    use vacs_protocol::ws::shared::{Capability, ErrorReason, Presence};
 
     async fn setup_test_client(
         transport: MockTransport,
@@ -883,11 +990,16 @@ mod tests {
                         position_id: Some(PositionId::from("position1")),
                         display_name: "Client 1".into(),
                         frequency: "100.000".into(),
+                        presence: Presence::default(),
                     },
                     profile: SessionProfile::Changed(ActiveProfile::Specific(Profile {
                         id: vacs_protocol::profile::ProfileId::from("1"),
                         profile_type: vacs_protocol::profile::ProfileType::Tabbed(vec![]),
                     })),
+                    resume_token: None,
+                    encoding: Encoding::Json,
+                    compression: false,
+                    capabilities: Capabilities::default(),
                 }))
                 .unwrap()
                 .into(),
@@ -966,6 +1078,93 @@ mod tests {
         assert!(sent_msg.is_ok());
     }
 
+    #[test(tokio::test)]
+    async fn send_without_negotiated_capability() {
+        let transport = MockTransport::default();
+        let mut outgoing_rx = transport.outgoing_tx.subscribe();
+        let (client, _shutdown_token) = setup_test_client(transport, false, 0).await;
+        assert!(client.capabilities().is_empty());
+
+        let msg = ClientMessage::ListClientChanges(ListClientChanges { since_revision: 1 });
+        let serialized = tungstenite::Message::from(ClientMessage::serialize(&msg).unwrap());
+
+        let result = client.send(msg).await;
+        assert_matches!(
+            result,
+            Err(SignalingError::Runtime(
+                SignalingRuntimeError::CapabilityNotNegotiated(Capability::ClientChanges)
+            ))
+        );
+
+        let sent_msg = outgoing_rx
+            .recv_with_timeout(Duration::from_millis(100), |m| m == &serialized)
+            .await;
+        assert!(sent_msg.is_err());
+    }
+
+    #[test(tokio::test)]
+    async fn send_msgpack_after_negotiation() {
+        let transport = MockTransport::default();
+        let mock_tx = transport.incoming_tx.clone();
+        let mut outgoing_rx = transport.outgoing_tx.subscribe();
+        let mut login_rx = transport.outgoing_tx.subscribe();
+        let shutdown_token = CancellationToken::new();
+
+        tokio::spawn(async move {
+            loop {
+                if let Ok(tungstenite::Message::Text(text)) = login_rx.recv().await
+                    && text.contains("\"encoding\":\"messagePack\"")
+                {
+                    let session_info = ServerMessage::SessionInfo(server::SessionInfo {
+                        client: ClientInfo {
+                            id: ClientId::from("client1"),
+                            position_id: None,
+                            display_name: "Client 1".into(),
+                            frequency: "100.000".into(),
+                            presence: Presence::default(),
+                        },
+                        profile: SessionProfile::Changed(ActiveProfile::None),
+                        resume_token: None,
+                        encoding: Encoding::MessagePack,
+                        compression: false,
+                        capabilities: Capabilities::default(),
+                    });
+                    let _ = mock_tx.send(tungstenite::Message::from(
+                        session_info.serialize_msgpack().unwrap(),
+                    ));
+                    break;
+                }
+            }
+        });
+
+        let client = SignalingClient::new(
+            transport,
+            MockTokenProvider::new(1, None),
+            |_| async {},
+            shutdown_token.clone(),
+            false,
+            Duration::from_millis(100),
+            0,
+            None,
+            &tokio::runtime::Handle::current(),
+        );
+        client.set_encoding(Encoding::MessagePack);
+
+        assert!(client.connect(None).await.is_ok());
+        assert_matches!(client.state(), State::LoggedIn);
+
+        let msg = ClientMessage::ListClients;
+        let serialized = tungstenite::Message::from(msg.serialize_msgpack().unwrap());
+        assert!(client.send(msg).await.is_ok());
+
+        let sent_msg = outgoing_rx
+            .recv_with_timeout(Duration::from_millis(100), |m| m == &serialized)
+            .await;
+        assert!(sent_msg.is_ok());
+
+        shutdown_token.cancel();
+    }
+
     #[test(tokio::test)]
     async fn send_without_start() {
         let shutdown_token = CancellationToken::new();
@@ -988,6 +1187,10 @@ mod tests {
             protocol_version: VACS_PROTOCOL_VERSION.to_string(),
             custom_profile: false,
             position_id: None,
+            resume_token: None,
+            encoding: Encoding::Json,
+            compression: false,
+            capabilities: Capabilities::default(),
         });
 
         let result = client.send(msg.clone()).await;
@@ -1059,6 +1262,10 @@ mod tests {
             protocol_version: VACS_PROTOCOL_VERSION.to_string(),
             custom_profile: false,
             position_id: None,
+            resume_token: None,
+            encoding: Encoding::Json,
+            compression: false,
+            capabilities: Capabilities::default(),
         });
 
         let result = client.send(msg.clone()).await;
@@ -1085,6 +1292,10 @@ mod tests {
             protocol_version: VACS_PROTOCOL_VERSION.to_string(),
             custom_profile: false,
             position_id: None,
+            resume_token: None,
+            encoding: Encoding::Json,
+            compression: false,
+            capabilities: Capabilities::default(),
         });
 
         let result = client.send(msg.clone()).await;
@@ -1534,11 +1745,16 @@ mod tests {
                         position_id: Some(PositionId::from("position1")),
                         display_name: "Client 1".into(),
                         frequency: "100.000".into(),
+                        presence: Presence::default(),
                     },
                     profile: SessionProfile::Changed(ActiveProfile::Specific(Profile {
                         id: vacs_protocol::profile::ProfileId::from("1"),
                         profile_type: vacs_protocol::profile::ProfileType::Tabbed(vec![]),
                     })),
+                    resume_token: None,
+                    encoding: Encoding::Json,
+                    compression: false,
+                    capabilities: Capabilities::default(),
                 }))
                 .unwrap()
                 .into(),
@@ -1624,11 +1840,16 @@ mod tests {
                         position_id: Some(PositionId::from("position1")),
                         display_name: "Client 1".into(),
                         frequency: "100.000".into(),
+                        presence: Presence::default(),
                     },
                     profile: SessionProfile::Changed(ActiveProfile::Specific(Profile {
                         id: vacs_protocol::profile::ProfileId::from("1"),
                         profile_type: vacs_protocol::profile::ProfileType::Tabbed(vec![]),
                     })),
+                    resume_token: None,
+                    encoding: Encoding::Json,
+                    compression: false,
+                    capabilities: Capabilities::default(),
                 }))
                 .unwrap()
                 .into(),
@@ -1676,6 +1897,106 @@ mod tests {
         shutdown_token.cancel();
     }
 
+    #[test(tokio::test)]
+    async fn reconnect_after_server_restart() {
+        let transport = MockTransport::default();
+        let incoming_tx = transport.incoming_tx.clone();
+        let outgoing_tx = transport.outgoing_tx.clone();
+
+        let shutdown_token = CancellationToken::new();
+        let token_provider = MockTokenProvider::new(1, None);
+
+        let mock_tx = transport.incoming_tx.clone();
+        let ready = transport.ready.clone();
+
+        let session_info_msg = || {
+            tungstenite::Message::Text(
+                ServerMessage::serialize(&ServerMessage::SessionInfo(server::SessionInfo {
+                    client: ClientInfo {
+                        id: ClientId::from("client1"),
+                        position_id: Some(PositionId::from("position1")),
+                        display_name: "Client 1".into(),
+                        frequency: "100.000".into(),
+                        presence: Presence::default(),
+                    },
+                    profile: SessionProfile::Changed(ActiveProfile::Specific(Profile {
+                        id: vacs_protocol::profile::ProfileId::from("1"),
+                        profile_type: vacs_protocol::profile::ProfileType::Tabbed(vec![]),
+                    })),
+                    resume_token: None,
+                    encoding: Encoding::Json,
+                    compression: false,
+                    capabilities: Capabilities::default(),
+                }))
+                .unwrap()
+                .into(),
+            )
+        };
+
+        let session_info = session_info_msg();
+        tokio::spawn(async move {
+            ready.notified().await;
+            let _ = mock_tx.send(session_info);
+        });
+
+        let hook_called = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let hook_called_clone = hook_called.clone();
+
+        let on_terminate_session: OnTerminateSessionCb = Arc::new(move || {
+            let called = hook_called_clone.clone();
+            Box::pin(async move {
+                called.store(true, std::sync::atomic::Ordering::SeqCst);
+            })
+        });
+
+        let client = SignalingClient::new(
+            transport,
+            token_provider,
+            |_| async {},
+            shutdown_token.clone(),
+            false,
+            Duration::from_millis(500),
+            1,
+            Some(on_terminate_session),
+            &tokio::runtime::Handle::current(),
+        );
+
+        let res = client.connect(None).await;
+        assert!(res.is_ok());
+        assert_matches!(client.state(), State::LoggedIn);
+
+        let session_info = session_info_msg();
+        let reconnect_incoming_tx = incoming_tx.clone();
+        let mut outgoing_rx = outgoing_tx.subscribe();
+        tokio::spawn(async move {
+            loop {
+                if let Ok(tungstenite::Message::Text(text)) = outgoing_rx.recv().await
+                    && text.contains("\"login\"")
+                {
+                    let _ = reconnect_incoming_tx.send(session_info);
+                    break;
+                }
+            }
+        });
+
+        let _ = incoming_tx.send(tungstenite::Message::Text(
+            ServerMessage::serialize(&ServerMessage::Disconnected(
+                server::DisconnectReason::ServerRestart { retry_after: 0 }.into(),
+            ))
+            .unwrap()
+            .into(),
+        ));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert!(
+            !hook_called.load(std::sync::atomic::Ordering::SeqCst),
+            "on_terminate_session hook should not be called after a server restart"
+        );
+        assert_matches!(client.state(), State::LoggedIn);
+
+        shutdown_token.cancel();
+    }
+
     mod reconnect_gate {
         use super::super::*;
         use pretty_assertions::assert_eq;
@@ -1771,4 +2092,109 @@ mod tests {
             assert!(g.suppressed_until.is_none());
         }
     }
+
+    #[test(tokio::test)]
+    async fn resume_session_after_connection_loss() {
+        let transport = MockTransport::default();
+        let incoming_tx = transport.incoming_tx.clone();
+        let outgoing_tx = transport.outgoing_tx.clone();
+
+        let shutdown_token = CancellationToken::new();
+        let token_provider = MockTokenProvider::new(1, None);
+
+        let mock_tx = transport.incoming_tx.clone();
+        let ready = transport.ready.clone();
+
+        let session_info_msg = || {
+            tungstenite::Message::Text(
+                ServerMessage::serialize(&ServerMessage::SessionInfo(server::SessionInfo {
+                    client: ClientInfo {
+                        id: ClientId::from("client1"),
+                        position_id: Some(PositionId::from("position1")),
+                        display_name: "Client 1".into(),
+                        frequency: "100.000".into(),
+                        presence: Presence::default(),
+                    },
+                    profile: SessionProfile::Changed(ActiveProfile::Specific(Profile {
+                        id: vacs_protocol::profile::ProfileId::from("1"),
+                        profile_type: vacs_protocol::profile::ProfileType::Tabbed(vec![]),
+                    })),
+                    resume_token: Some("resume1".to_string()),
+                    encoding: Encoding::Json,
+                    compression: false,
+                    capabilities: Capabilities::default(),
+                }))
+                .unwrap()
+                .into(),
+            )
+        };
+
+        let session_info = session_info_msg();
+        tokio::spawn(async move {
+            ready.notified().await;
+            let _ = mock_tx.send(session_info);
+        });
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        let client = SignalingClient::new(
+            transport,
+            token_provider,
+            move |event| {
+                if !matches!(event, SignalingEvent::Message(_)) {
+                    events_clone.lock().push(event);
+                }
+                async {}
+            },
+            shutdown_token.clone(),
+            false,
+            Duration::from_millis(500),
+            1,
+            None,
+            &tokio::runtime::Handle::current(),
+        );
+
+        let res = client.connect(None).await;
+        assert!(res.is_ok());
+        assert!(client.is_resumable());
+
+        let session_info = session_info_msg();
+        let reconnect_incoming_tx = incoming_tx.clone();
+        let mut outgoing_rx = outgoing_tx.subscribe();
+        let resume_requested = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let resume_requested_clone = resume_requested.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Ok(tungstenite::Message::Text(text)) = outgoing_rx.recv().await
+                    && text.contains("\"login\"")
+                {
+                    resume_requested_clone.store(
+                        text.contains("\"resume1\""),
+                        std::sync::atomic::Ordering::SeqCst,
+                    );
+                    let _ = reconnect_incoming_tx.send(session_info);
+                    break;
+                }
+            }
+        });
+
+        let _ = incoming_tx.send(tungstenite::Message::Close(None));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert!(resume_requested.load(std::sync::atomic::Ordering::SeqCst));
+        assert_matches!(client.state(), State::LoggedIn);
+        assert!(client.is_resumable());
+
+        let events = events.lock();
+        assert_matches!(
+            events.as_slice(),
+            [
+                SignalingEvent::Connected { resumed: false, .. },
+                SignalingEvent::Error(SignalingRuntimeError::Disconnected(None)),
+                SignalingEvent::Connected { resumed: true, .. },
+            ]
+        );
+
+        shutdown_token.cancel();
+    }
 }
@@ -4,7 +4,7 @@ use std::time::{Duration, Instant};
 use thiserror::Error;
 use tokio_tungstenite::tungstenite;
 use vacs_protocol::ws::server::{DisconnectReason, LoginFailureReason};
-use vacs_protocol::ws::shared::ErrorReason;
+use vacs_protocol::ws::shared::{Capability, ErrorReason};
 
 #[derive(Debug, Error)]
 pub enum SignalingError {
@@ -38,17 +38,33 @@ pub enum SignalingRuntimeError {
     SerializationError(String),
     #[error("rate limited for {0}")]
     RateLimited(UntilInstant),
+    #[error("capability not negotiated: {0:?}")]
+    CapabilityNotNegotiated(Capability),
 }
 
 impl SignalingRuntimeError {
     pub fn can_reconnect(&self) -> bool {
-        matches!(self, SignalingRuntimeError::Disconnected(reason) if reason.is_none())
-            || matches!(
-                self,
-                SignalingRuntimeError::ServerError(_)
-                    | SignalingRuntimeError::Transport(_)
-                    | SignalingRuntimeError::SerializationError(_)
+        matches!(
+            self,
+            SignalingRuntimeError::Disconnected(
+                None | Some(DisconnectReason::ServerRestart { .. })
             )
+        ) || matches!(
+            self,
+            SignalingRuntimeError::ServerError(_)
+                | SignalingRuntimeError::Transport(_)
+                | SignalingRuntimeError::SerializationError(_)
+        )
+    }
+
+    /// Delay requested by the server before attempting to reconnect, if any.
+    pub fn reconnect_delay(&self) -> Option<Duration> {
+        match self {
+            SignalingRuntimeError::Disconnected(Some(DisconnectReason::ServerRestart {
+                retry_after,
+            })) => Some(Duration::from_secs(*retry_after)),
+            _ => None,
+        }
     }
 
     /// Whether this error indicates a connection loss where the server may not have detected
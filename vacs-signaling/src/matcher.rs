@@ -111,6 +111,7 @@ mod tests {
     use vacs_protocol::vatsim::{ClientId, PositionId};
     use vacs_protocol::ws::server;
     use vacs_protocol::ws::server::ClientInfo;
+    use vacs_protocol::ws::shared::Presence;
 
     #[test(tokio::test)]
     async fn wait_for() {
@@ -141,7 +142,9 @@ mod tests {
                 position_id: Some(PositionId::from("position1")),
                 display_name: "Client 1".to_string(),
                 frequency: "100.000".to_string(),
+                presence: Presence::default(),
             }],
+            revision: 0,
         });
 
         let matcher_clone = matcher.clone();
@@ -381,7 +384,9 @@ mod tests {
                 position_id: Some(PositionId::from("position1")),
                 display_name: "Client 1".into(),
                 frequency: "100.000".into(),
+                presence: Presence::default(),
             }],
+            revision: 0,
         }));
         matcher.try_match(&ServerMessage::WebrtcAnswer(
             vacs_protocol::ws::shared::WebrtcAnswer {
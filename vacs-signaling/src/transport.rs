@@ -6,7 +6,9 @@ use crate::error::{SignalingError, SignalingRuntimeError};
 use ::tokio::sync::mpsc;
 use async_trait::async_trait;
 use tokio_tungstenite::tungstenite;
+use vacs_protocol::ws::client::ClientMessage;
 use vacs_protocol::ws::server::ServerMessage;
+use vacs_protocol::ws::{Encoding, decompress};
 
 #[async_trait]
 pub trait SignalingTransport: Send + Sync + 'static {
@@ -29,3 +31,155 @@ pub trait SignalingReceiver: Send + Sync + 'static {
         send_tx: &mpsc::Sender<tungstenite::Message>,
     ) -> Result<ServerMessage, SignalingRuntimeError>;
 }
+
+/// Encodes a message as a text frame for JSON or a binary frame for MessagePack.
+pub(crate) fn encode_message(
+    msg: &ClientMessage,
+    encoding: Encoding,
+) -> Result<tungstenite::Message, SignalingRuntimeError> {
+    match encoding {
+        Encoding::Json => ClientMessage::serialize(msg)
+            .map(tungstenite::Message::from)
+            .map_err(|err| SignalingRuntimeError::SerializationError(err.to_string())),
+        Encoding::MessagePack => ClientMessage::serialize_msgpack(msg)
+            .map(tungstenite::Message::from)
+            .map_err(|err| SignalingRuntimeError::SerializationError(err.to_string())),
+    }
+}
+
+/// Decodes server messages received on a single connection.
+///
+/// The encoding and compression negotiated during login are taken from the first session info
+/// received on the connection. Before that, text frames are decoded as JSON and binary frames as
+/// MessagePack. Once compression has been negotiated, binary frames are decompressed before being
+/// decoded using the negotiated encoding.
+#[derive(Debug, Default)]
+pub(crate) struct MessageDecoder {
+    negotiated: Option<(Encoding, bool)>,
+}
+
+impl MessageDecoder {
+    pub(crate) fn decode(
+        &mut self,
+        msg: &tungstenite::Message,
+    ) -> Result<ServerMessage, SignalingRuntimeError> {
+        let decoded = match (msg, self.negotiated) {
+            (tungstenite::Message::Text(text), _) => ServerMessage::deserialize(text)
+                .map_err(|err| SignalingRuntimeError::SerializationError(err.to_string())),
+            (tungstenite::Message::Binary(bytes), Some((encoding, true))) => {
+                let bytes = decompress(bytes)
+                    .map_err(|err| SignalingRuntimeError::SerializationError(err.to_string()))?;
+                match encoding {
+                    Encoding::Json => std::str::from_utf8(&bytes)
+                        .map_err(|err| SignalingRuntimeError::SerializationError(err.to_string()))
+                        .and_then(|text| {
+                            ServerMessage::deserialize(text).map_err(|err| {
+                                SignalingRuntimeError::SerializationError(err.to_string())
+                            })
+                        }),
+                    Encoding::MessagePack => ServerMessage::deserialize_msgpack(&bytes)
+                        .map_err(|err| SignalingRuntimeError::SerializationError(err.to_string())),
+                }
+            }
+            (tungstenite::Message::Binary(bytes), _) => ServerMessage::deserialize_msgpack(bytes)
+                .map_err(|err| SignalingRuntimeError::SerializationError(err.to_string())),
+            (other, _) => Err(SignalingRuntimeError::SerializationError(format!(
+                "Unexpected websocket frame: {other:?}"
+            ))),
+        }?;
+
+        if self.negotiated.is_none()
+            && let ServerMessage::SessionInfo(info) = &decoded
+        {
+            self.negotiated = Some((info.encoding, info.compression));
+        }
+
+        Ok(decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use vacs_protocol::profile::ActiveProfile;
+    use vacs_protocol::vatsim::ClientId;
+    use vacs_protocol::ws::compress;
+    use vacs_protocol::ws::server::{self, ClientInfo, SessionProfile};
+    use vacs_protocol::ws::shared::{Capabilities, Presence};
+
+    fn session_info(encoding: Encoding, compression: bool) -> ServerMessage {
+        ServerMessage::SessionInfo(server::SessionInfo {
+            client: ClientInfo {
+                id: ClientId::from("client1"),
+                position_id: None,
+                display_name: "Client 1".into(),
+                frequency: "100.000".into(),
+                presence: Presence::default(),
+            },
+            profile: SessionProfile::Changed(ActiveProfile::None),
+            resume_token: None,
+            encoding,
+            compression,
+            capabilities: Capabilities::default(),
+        })
+    }
+
+    fn client_list() -> ServerMessage {
+        ServerMessage::ClientList(server::ClientList {
+            clients: vec![],
+            revision: 0,
+        })
+    }
+
+    #[test]
+    fn decode_compressed_after_negotiation() {
+        let mut decoder = MessageDecoder::default();
+
+        let msg = session_info(Encoding::Json, true);
+        let frame = tungstenite::Message::from(ServerMessage::serialize(&msg).unwrap());
+        assert_eq!(decoder.decode(&frame).unwrap(), msg);
+
+        let json = ServerMessage::serialize(&client_list()).unwrap();
+        let frame = tungstenite::Message::from(compress(json.as_bytes()).unwrap());
+        assert_eq!(decoder.decode(&frame).unwrap(), client_list());
+    }
+
+    #[test]
+    fn decode_compressed_msgpack_after_negotiation() {
+        let mut decoder = MessageDecoder::default();
+
+        let msg = session_info(Encoding::MessagePack, true);
+        let frame = tungstenite::Message::from(ServerMessage::serialize(&msg).unwrap());
+        assert_eq!(decoder.decode(&frame).unwrap(), msg);
+
+        let bytes = client_list().serialize_msgpack().unwrap();
+        let frame = tungstenite::Message::from(compress(&bytes).unwrap());
+        assert_eq!(decoder.decode(&frame).unwrap(), client_list());
+    }
+
+    #[test]
+    fn decode_ignores_later_session_info() {
+        let mut decoder = MessageDecoder::default();
+
+        let frame = tungstenite::Message::from(
+            ServerMessage::serialize(&session_info(Encoding::Json, true)).unwrap(),
+        );
+        decoder.decode(&frame).unwrap();
+
+        // Session infos sent after login (e.g. profile updates) do not carry negotiated options.
+        let frame = tungstenite::Message::from(
+            compress(
+                ServerMessage::serialize(&session_info(Encoding::Json, false))
+                    .unwrap()
+                    .as_bytes(),
+            )
+            .unwrap(),
+        );
+        decoder.decode(&frame).unwrap();
+
+        let json = ServerMessage::serialize(&client_list()).unwrap();
+        let frame = tungstenite::Message::from(compress(json.as_bytes()).unwrap());
+        assert_eq!(decoder.decode(&frame).unwrap(), client_list());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::{SignalingError, SignalingRuntimeError, TransportFailureReason};
-use crate::transport::{SignalingReceiver, SignalingSender, SignalingTransport};
+use crate::transport::{MessageDecoder, SignalingReceiver, SignalingSender, SignalingTransport};
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc};
@@ -50,6 +50,7 @@ impl SignalingTransport for MockTransport {
         let receiver = MockReceiver {
             rx: self.incoming_tx.subscribe(),
             disconnect_token: self.disconnect_token.child_token(),
+            decoder: MessageDecoder::default(),
         };
 
         self.ready.notify_one();
@@ -66,6 +67,7 @@ pub struct MockSender {
 pub struct MockReceiver {
     rx: broadcast::Receiver<tungstenite::Message>,
     disconnect_token: CancellationToken,
+    decoder: MessageDecoder,
 }
 
 #[async_trait]
@@ -117,11 +119,10 @@ impl SignalingReceiver for MockReceiver {
                 msg = self.rx.recv() => {
                     tracing::debug!(?msg, "Received tungstenite::Message");
                     match msg {
-                        Ok(tungstenite::Message::Text(text)) => {
+                        Ok(msg @ (tungstenite::Message::Text(_) | tungstenite::Message::Binary(_))) => {
                             tracing::debug!("Received message");
-                            return ServerMessage::deserialize(&text).map_err(|err| {
+                            return self.decoder.decode(&msg).inspect_err(|err| {
                                 tracing::warn!(?err, "Failed to deserialize message");
-                                SignalingRuntimeError::SerializationError(err.to_string())
                             });
                         }
                         Ok(tungstenite::Message::Close(reason)) => {
@@ -135,7 +136,7 @@ impl SignalingReceiver for MockReceiver {
                             }
                         }
                         Ok(other) => {
-                            tracing::debug!(?other, "Skipping non-text WebSocket frame");
+                            tracing::debug!(?other, "Skipping non-data WebSocket frame");
                         }
                         Err(_) => {
                             tracing::warn!("Channel closed");
@@ -1,5 +1,5 @@
 use crate::error::{SignalingError, SignalingRuntimeError, TransportFailureReason};
-use crate::transport::{SignalingReceiver, SignalingSender, SignalingTransport};
+use crate::transport::{MessageDecoder, SignalingReceiver, SignalingSender, SignalingTransport};
 use async_trait::async_trait;
 use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
@@ -64,6 +64,7 @@ pub struct TokioReceiver {
     cancel: CancellationToken,
     heartbeat_state: Arc<HeartbeatState>,
     heartbeat_handle: Option<JoinHandle<()>>,
+    decoder: MessageDecoder,
 }
 
 #[async_trait]
@@ -124,9 +125,9 @@ impl SignalingReceiver for TokioReceiver {
                 msg = self.websocket_rx.next() => {
                     let Some(msg) = msg else { break; };
                     match msg {
-                        Ok(tungstenite::Message::Text(text)) => {
+                        Ok(msg @ (tungstenite::Message::Text(_) | tungstenite::Message::Binary(_))) => {
                             self.heartbeat_state.mark_rx();
-                            return match ServerMessage::deserialize(&text) {
+                            return match self.decoder.decode(&msg) {
                                 Ok(ServerMessage::Disconnected(disconnected)) => {
                                     tracing::debug!(
                                         reason = ?disconnected.reason,
@@ -137,7 +138,7 @@ impl SignalingReceiver for TokioReceiver {
                                 Ok(msg) => Ok(msg),
                                 Err(err) => {
                                     tracing::warn!(?err, "Failed to deserialize message");
-                                    Err(SignalingRuntimeError::SerializationError(err.to_string()))
+                                    Err(err)
                                 }
                             };
                         }
@@ -156,7 +157,7 @@ impl SignalingReceiver for TokioReceiver {
                             self.heartbeat_state.mark_pong();
                         }
                         Ok(other) => {
-                            tracing::debug!(?other, "Skipping non-text WebSocket frame");
+                            tracing::debug!(?other, "Skipping non-data WebSocket frame");
                         }
                         Err(err) => {
                             tracing::warn!(?err, "Failed to receive message");
@@ -188,6 +189,7 @@ impl TokioReceiver {
             cancel: CancellationToken::new(),
             heartbeat_state: HeartbeatState::new(),
             heartbeat_handle: None,
+            decoder: MessageDecoder::default(),
         }
     }
 
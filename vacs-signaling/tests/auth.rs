@@ -39,7 +39,7 @@ async fn login_without_self() {
     let connected_event = broadcast_rx.recv_with_timeout(Duration::from_millis(100), |event|
         matches!(event, SignalingEvent::Connected{ client_info, .. } if client_info.id == ClientId::from("client1") && client_info.display_name == "client1" && client_info.frequency.is_empty()),
     ).await;
-    let client_info_event = broadcast_rx.recv_with_timeout(Duration::from_millis(100), |event| matches!(event, SignalingEvent::Message(ServerMessage::ClientList(vacs_protocol::ws::server::ClientList { clients })) if clients.is_empty())).await;
+    let client_info_event = broadcast_rx.recv_with_timeout(Duration::from_millis(100), |event| matches!(event, SignalingEvent::Message(ServerMessage::ClientList(vacs_protocol::ws::server::ClientList { clients, .. })) if clients.is_empty())).await;
 
     assert!(res.is_ok());
     assert!(connected_event.is_ok());
@@ -75,7 +75,7 @@ async fn login() {
     let connected_event1 = broadcast_rx1.recv_with_timeout(Duration::from_millis(100), |event|
         matches!(event, SignalingEvent::Connected{ client_info, .. } if client_info.id == ClientId::from("client1") && client_info.display_name == "client1" && client_info.frequency.is_empty()),
     ).await;
-    let client_list_event1 = broadcast_rx1.recv_with_timeout(Duration::from_millis(100), |event| matches!(event, SignalingEvent::Message(ServerMessage::ClientList(vacs_protocol::ws::server::ClientList { clients })) if clients.is_empty())).await;
+    let client_list_event1 = broadcast_rx1.recv_with_timeout(Duration::from_millis(100), |event| matches!(event, SignalingEvent::Message(ServerMessage::ClientList(vacs_protocol::ws::server::ClientList { clients, .. })) if clients.is_empty())).await;
 
     assert!(res1.is_ok());
     assert!(connected_event1.is_ok());
@@ -102,7 +102,7 @@ async fn login() {
     let connected_event2 = broadcast_rx2.recv_with_timeout(Duration::from_millis(100), |event|
         matches!(event, SignalingEvent::Connected{ client_info, .. } if client_info.id == ClientId::from("client2") && client_info.display_name == "client2" && client_info.frequency.is_empty()),
     ).await;
-    let client_list_event2 = broadcast_rx2.recv_with_timeout(Duration::from_millis(100), |event| matches!(event, SignalingEvent::Message(ServerMessage::ClientList(vacs_protocol::ws::server::ClientList { clients })) if clients.len() == 1 && clients[0].id == ClientId::from("client1"))).await;
+    let client_list_event2 = broadcast_rx2.recv_with_timeout(Duration::from_millis(100), |event| matches!(event, SignalingEvent::Message(ServerMessage::ClientList(vacs_protocol::ws::server::ClientList { clients, .. })) if clients.len() == 1 && clients[0].id == ClientId::from("client1"))).await;
 
     assert!(res2.is_ok());
     assert!(connected_event2.is_ok());
@@ -241,7 +241,7 @@ async fn client_disconnects() {
         .client_mut(1)
         .recv_with_timeout_and_filter(
             Duration::from_millis(300),
-            |e| matches!(e, SignalingEvent::Message(ServerMessage::ClientDisconnected(vacs_protocol::ws::server::ClientDisconnected { client_id })) if client_id.as_str() == "client0"),
+            |e| matches!(e, SignalingEvent::Message(ServerMessage::ClientDisconnected(vacs_protocol::ws::server::ClientDisconnected { client_id, .. })) if client_id.as_str() == "client0"),
         )
         .await;
     assert!(event.is_some());
@@ -260,7 +260,7 @@ async fn client_list_synchronization() {
         .client_mut(2)
         .recv_with_timeout_and_filter(
             Duration::from_millis(300),
-            |e| matches!(e, SignalingEvent::Message(ServerMessage::ClientDisconnected(vacs_protocol::ws::server::ClientDisconnected { client_id })) if client_id.as_str() == "client0"),
+            |e| matches!(e, SignalingEvent::Message(ServerMessage::ClientDisconnected(vacs_protocol::ws::server::ClientDisconnected { client_id, .. })) if client_id.as_str() == "client0"),
         )
         .await;
     assert!(event.is_some());
@@ -276,7 +276,7 @@ async fn client_list_synchronization() {
         .client_mut(2)
         .recv_with_timeout_and_filter(
             Duration::from_millis(300),
-            |e| matches!(e, SignalingEvent::Message(ServerMessage::ClientList(vacs_protocol::ws::server::ClientList { clients })) if clients.len() == 1 && clients[0].id == ClientId::from("client1")),
+            |e| matches!(e, SignalingEvent::Message(ServerMessage::ClientList(vacs_protocol::ws::server::ClientList { clients, .. })) if clients.len() == 1 && clients[0].id == ClientId::from("client1")),
         )
         .await;
     assert!(event.is_some());
@@ -310,7 +310,7 @@ async fn client_connected_broadcast() {
         {
             match msg {
                 SignalingEvent::Message(ServerMessage::ClientConnected(
-                    vacs_protocol::ws::server::ClientConnected { client },
+                    vacs_protocol::ws::server::ClientConnected { client, .. },
                 )) => {
                     received_client_ids.push(client.id);
                 }
@@ -1,7 +1,11 @@
 #[cfg(feature = "test-utils")]
 pub mod mock;
+mod recorder;
+mod replay;
 mod vatsim;
 
+pub use recorder::DataFeedRecorder;
+pub use replay::ReplayDataFeed;
 pub use vatsim::VatsimDataFeed;
 
 use crate::ControllerInfo;
@@ -12,6 +16,12 @@ use thiserror::Error;
 pub enum DataFeedError {
     #[error("Request failed: {0}")]
     Request(#[from] reqwest::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid data feed snapshot: {0}")]
+    Snapshot(#[from] serde_json::Error),
+    #[error("No data feed snapshots found in {0}")]
+    EmptyRecording(String),
 }
 
 #[async_trait]
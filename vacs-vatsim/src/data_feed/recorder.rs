@@ -0,0 +1,44 @@
+use crate::Result;
+use crate::data_feed::DataFeedError;
+use crate::data_feed::vatsim::VatsimDataFeedResponse;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File extension of recorded data feed snapshots.
+pub(super) const SNAPSHOT_EXTENSION: &str = "json";
+
+/// Records data feed snapshots fetched by a [`VatsimDataFeed`](super::VatsimDataFeed) for later
+/// replay with a [`ReplayDataFeed`](super::ReplayDataFeed).
+///
+/// Each snapshot is written to `{dir}/{unix_millis}.json` in the format of the VATSIM data feed,
+/// only containing the fields used by vacs. Downloaded data feed files can therefore be added to
+/// a recording by naming them accordingly.
+#[derive(Debug, Clone)]
+pub struct DataFeedRecorder {
+    dir: PathBuf,
+}
+
+impl DataFeedRecorder {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(DataFeedError::from)?;
+        tracing::info!(?dir, "Recording VATSIM data feed snapshots");
+        Ok(Self { dir })
+    }
+
+    pub(super) async fn record(&self, data_feed: &VatsimDataFeedResponse) -> Result<PathBuf> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let path = self.dir.join(format!("{timestamp}.{SNAPSHOT_EXTENSION}"));
+
+        let json = serde_json::to_vec(data_feed).map_err(DataFeedError::from)?;
+        tokio::fs::write(&path, json)
+            .await
+            .map_err(DataFeedError::from)?;
+
+        tracing::trace!(?path, controllers = ?data_feed.controllers.len(), "Recorded data feed snapshot");
+        Ok(path)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::data_feed::recorder::SNAPSHOT_EXTENSION;
+use crate::data_feed::vatsim::VatsimDataFeedResponse;
+use crate::data_feed::{DataFeed, DataFeedError};
+use crate::{ControllerInfo, Result};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::instrument;
+
+/// Data feed replaying a recording of data feed snapshots, as written by a
+/// [`DataFeedRecorder`](super::DataFeedRecorder).
+///
+/// Playback starts with the first fetch and returns the most recent snapshot at the current
+/// playback position. Once the end of the recording is reached, the last snapshot is returned
+/// indefinitely.
+#[derive(Debug)]
+pub struct ReplayDataFeed {
+    snapshots: Vec<Snapshot>,
+    speed: f64,
+    started_at: Mutex<Option<Instant>>,
+}
+
+#[derive(Debug)]
+struct Snapshot {
+    /// Offset from the first snapshot of the recording.
+    offset: Duration,
+    controllers: Vec<ControllerInfo>,
+}
+
+impl ReplayDataFeed {
+    /// Loads all snapshots from a recording directory.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+
+        let mut recorded = Vec::new();
+        for entry in std::fs::read_dir(dir).map_err(DataFeedError::from)? {
+            let path = entry.map_err(DataFeedError::from)?.path();
+            if path.extension().is_none_or(|ext| ext != SNAPSHOT_EXTENSION) {
+                continue;
+            }
+            let Some(timestamp) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok())
+            else {
+                tracing::warn!(?path, "Skipping data feed snapshot without timestamp");
+                continue;
+            };
+
+            let file = std::fs::File::open(&path).map_err(DataFeedError::from)?;
+            let data_feed: VatsimDataFeedResponse =
+                serde_json::from_reader(std::io::BufReader::new(file))
+                    .map_err(DataFeedError::from)?;
+            recorded.push((timestamp, data_feed));
+        }
+        recorded.sort_by_key(|(timestamp, _)| *timestamp);
+
+        let Some(&(start, _)) = recorded.first() else {
+            return Err(DataFeedError::EmptyRecording(dir.display().to_string()).into());
+        };
+        let snapshots: Vec<Snapshot> = recorded
+            .into_iter()
+            .map(|(timestamp, data_feed)| Snapshot {
+                offset: Duration::from_millis(timestamp - start),
+                controllers: data_feed.controllers.into_iter().map(Into::into).collect(),
+            })
+            .collect();
+
+        tracing::info!(
+            ?dir,
+            snapshots = snapshots.len(),
+            duration = ?snapshots.last().map(|s| s.offset),
+            "Loaded data feed recording"
+        );
+        Ok(Self {
+            snapshots,
+            speed: 1.0,
+            started_at: Mutex::new(None),
+        })
+    }
+
+    /// Sets the playback speed, e.g. `10.0` to replay the recording ten times faster than it was
+    /// recorded. Defaults to real-time playback.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is not finite or not greater than zero.
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        assert!(
+            speed.is_finite() && speed > 0.0,
+            "Replay speed must be a positive number, got {speed}"
+        );
+        self.speed = speed;
+        self
+    }
+
+    /// Duration between the first and last snapshot of the recording.
+    pub fn duration(&self) -> Duration {
+        self.snapshots.last().map(|s| s.offset).unwrap_or_default()
+    }
+
+    /// Returns the controllers of the most recent snapshot at the given playback position.
+    pub fn controllers_at(&self, position: Duration) -> &[ControllerInfo] {
+        let index = self
+            .snapshots
+            .partition_point(|s| s.offset <= position)
+            .saturating_sub(1);
+        &self.snapshots[index].controllers
+    }
+
+    fn position(&self) -> Duration {
+        let started_at = *self.started_at.lock().get_or_insert_with(Instant::now);
+        started_at.elapsed().mul_f64(self.speed)
+    }
+}
+
+#[async_trait]
+impl DataFeed for ReplayDataFeed {
+    #[instrument(level = "debug", skip(self), err)]
+    async fn fetch_controller_info(&self) -> Result<Vec<ControllerInfo>> {
+        let position = self.position();
+        let controllers = self.controllers_at(position).to_vec();
+        tracing::debug!(?position, controllers = ?controllers.len(), "Returning replayed controller info");
+        Ok(controllers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_feed::DataFeedRecorder;
+    use crate::data_feed::vatsim::VatsimDataFeedController;
+    use pretty_assertions::assert_eq;
+    use vacs_protocol::vatsim::ClientId;
+
+    fn write_snapshot(dir: &Path, timestamp: u64, cids: &[i32]) {
+        let controllers = cids
+            .iter()
+            .map(|cid| format!(r#"{{"cid":{cid},"callsign":"LOVV_CTR","frequency":"134.440"}}"#))
+            .collect::<Vec<_>>()
+            .join(",");
+        std::fs::write(
+            dir.join(format!("{timestamp}.json")),
+            format!(r#"{{"general":{{}},"pilots":[],"controllers":[{controllers}]}}"#),
+        )
+        .unwrap();
+    }
+
+    fn cids(controllers: &[ControllerInfo]) -> Vec<ClientId> {
+        controllers.iter().map(|c| c.cid.clone()).collect()
+    }
+
+    #[test]
+    fn replays_snapshots_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        write_snapshot(dir.path(), 1_000_030_000, &[2, 3]);
+        write_snapshot(dir.path(), 1_000_000_000, &[1]);
+        write_snapshot(dir.path(), 1_000_015_000, &[1, 2]);
+        std::fs::write(dir.path().join("README.md"), "not a snapshot").unwrap();
+
+        let feed = ReplayDataFeed::load(dir.path()).unwrap();
+        assert_eq!(feed.duration(), Duration::from_secs(30));
+        assert_eq!(
+            cids(feed.controllers_at(Duration::ZERO)),
+            vec![ClientId::from(1)]
+        );
+        assert_eq!(
+            cids(feed.controllers_at(Duration::from_secs(20))),
+            vec![ClientId::from(1), ClientId::from(2)]
+        );
+        assert_eq!(
+            cids(feed.controllers_at(Duration::from_secs(3600))),
+            vec![ClientId::from(2), ClientId::from(3)]
+        );
+    }
+
+    #[test]
+    fn load_empty_recording() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            ReplayDataFeed::load(dir.path()),
+            Err(crate::Error::DataFeed(DataFeedError::EmptyRecording(_)))
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Replay speed must be a positive number")]
+    fn rejects_non_positive_speed() {
+        let dir = tempfile::tempdir().unwrap();
+        write_snapshot(dir.path(), 1_000_000_000, &[1]);
+        let _ = ReplayDataFeed::load(dir.path()).unwrap().with_speed(0.0);
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = DataFeedRecorder::new(dir.path().join("recording")).unwrap();
+        recorder
+            .record(&VatsimDataFeedResponse {
+                controllers: vec![VatsimDataFeedController {
+                    cid: 1234567,
+                    callsign: "LOWW_TWR".to_string(),
+                    frequency: "119.400".to_string(),
+                }],
+            })
+            .await
+            .unwrap();
+
+        let feed = ReplayDataFeed::load(dir.path().join("recording")).unwrap();
+        let controllers = feed.fetch_controller_info().await.unwrap();
+        assert_eq!(cids(&controllers), vec![ClientId::from(1234567)]);
+        assert_eq!(controllers[0].callsign, "LOWW_TWR");
+    }
+}
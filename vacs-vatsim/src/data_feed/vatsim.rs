@@ -1,9 +1,10 @@
-use crate::data_feed::{DataFeed, DataFeedError};
+use crate::data_feed::{DataFeed, DataFeedError, DataFeedRecorder};
 use crate::{ControllerInfo, FacilityType, Result};
 use async_trait::async_trait;
 use parking_lot::RwLock;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Formatter};
+use std::path::Path;
 use std::time::{Duration, Instant};
 use tracing::instrument;
 use vacs_protocol::vatsim::ClientId;
@@ -16,6 +17,7 @@ pub struct VatsimDataFeed {
     client: reqwest::Client,
     cache_ttl: Duration,
     cache: RwLock<Option<Cache>>,
+    recorder: Option<DataFeedRecorder>,
 }
 
 impl VatsimDataFeed {
@@ -31,6 +33,7 @@ impl VatsimDataFeed {
             client,
             cache_ttl: DATA_FEED_DEFAULT_CACHE_TTL,
             cache: Default::default(),
+            recorder: None,
         })
     }
 
@@ -49,6 +52,13 @@ impl VatsimDataFeed {
         self
     }
 
+    /// Records every fetched data feed snapshot to the given directory, allowing it to be replayed
+    /// later using a [`ReplayDataFeed`](super::ReplayDataFeed). Cached responses are not recorded.
+    pub fn with_recorder(mut self, dir: impl AsRef<Path>) -> Result<Self> {
+        self.recorder = Some(DataFeedRecorder::new(dir)?);
+        Ok(self)
+    }
+
     #[instrument(level = "trace", skip(self), err)]
     async fn fetch_data_feed(&self) -> Result<VatsimDataFeedResponse> {
         tracing::trace!("Fetching VATSIM data feed");
@@ -80,6 +90,12 @@ impl DataFeed for VatsimDataFeed {
         }
 
         let data_feed = self.fetch_data_feed().await?;
+        if let Some(recorder) = &self.recorder
+            && let Err(err) = recorder.record(&data_feed).await
+        {
+            tracing::warn!(?err, "Failed to record data feed snapshot");
+        }
+
         let controllers: Vec<ControllerInfo> =
             data_feed.controllers.into_iter().map(Into::into).collect();
 
@@ -108,16 +124,16 @@ impl Debug for Cache {
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct VatsimDataFeedResponse {
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct VatsimDataFeedResponse {
     pub controllers: Vec<VatsimDataFeedController>,
 }
 
-#[derive(Debug, Deserialize)]
-struct VatsimDataFeedController {
-    cid: i32,
-    callsign: String,
-    frequency: String,
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct VatsimDataFeedController {
+    pub cid: i32,
+    pub callsign: String,
+    pub frequency: String,
 }
 
 impl From<VatsimDataFeedController> for ControllerInfo {